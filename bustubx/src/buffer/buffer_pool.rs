@@ -1,10 +1,11 @@
 use dashmap::DashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::RwLock;
 use std::{collections::VecDeque, sync::Arc};
 
 use crate::buffer::page::{Page, PageId};
 
-use crate::buffer::PageRef;
+use crate::buffer::{PageRef, ReadPageGuard, WritePageGuard};
 use crate::catalog::SchemaRef;
 use crate::storage::codec::{
     BPlusTreeInternalPageCodec, BPlusTreeLeafPageCodec, BPlusTreePageCodec, TablePageCodec,
@@ -23,6 +24,8 @@ pub const BUFFER_POOL_SIZE: usize = 1000;
 #[derive(Debug)]
 pub struct BufferPoolManager {
     pool: Vec<Arc<RwLock<Page>>>,
+    // 每个frame被引用次数
+    pin_counts: Vec<Arc<AtomicU32>>,
     // LRU-K置换算法
     pub replacer: Arc<RwLock<LRUKReplacer>>,
     pub disk_manager: Arc<DiskManager>,
@@ -35,13 +38,16 @@ impl BufferPoolManager {
     pub fn new(num_pages: usize, disk_manager: Arc<DiskManager>) -> Self {
         let mut free_list = VecDeque::with_capacity(num_pages);
         let mut pool = vec![];
+        let mut pin_counts = vec![];
         for i in 0..num_pages {
             free_list.push_back(i);
             pool.push(Arc::new(RwLock::new(Page::empty())));
+            pin_counts.push(Arc::new(AtomicU32::new(0)));
         }
 
         Self {
             pool,
+            pin_counts,
             replacer: Arc::new(RwLock::new(LRUKReplacer::new(num_pages, 2))),
             disk_manager,
             page_table: Arc::new(DashMap::new()),
@@ -51,71 +57,75 @@ impl BufferPoolManager {
 
     // 从缓冲池创建一个新页
     pub fn new_page(&self) -> BustubxResult<PageRef> {
+        let mut replacer = self.replacer.write().unwrap();
         // 缓冲池已满且无可替换的页
-        if self.free_list.read().unwrap().is_empty() && self.replacer.read().unwrap().size() == 0 {
+        if self.free_list.read().unwrap().is_empty() && replacer.size() == 0 {
             return Err(BustubxError::Storage(
                 "Cannot new page because buffer pool is full and no page to evict".to_string(),
             ));
         }
 
         // 分配一个frame
-        let frame_id = self.allocate_frame()?;
+        let frame_id = self.allocate_frame(&mut replacer)?;
 
         // 从磁盘分配一个页
-        let new_page_id = self.disk_manager.allocate_page().unwrap();
-        self.page_table.insert(new_page_id, frame_id);
-        let new_page = Page::new(new_page_id).with_pin_count(1u32);
-        self.pool[frame_id].write().unwrap().replace(new_page);
-
-        self.replacer.write().unwrap().record_access(frame_id)?;
-        self.replacer
+        let new_page_id = match self.disk_manager.allocate_page() {
+            Ok(page_id) => page_id,
+            Err(e) => {
+                self.free_list.write().unwrap().push_back(frame_id);
+                return Err(e);
+            }
+        };
+        self.pool[frame_id]
             .write()
             .unwrap()
-            .set_evictable(frame_id, false)?;
-
-        Ok(PageRef {
-            page: self.pool[frame_id].clone(),
-            page_table: self.page_table.clone(),
-            replacer: self.replacer.clone(),
-        })
+            .replace(Page::new(new_page_id));
+        self.pin_frame(&mut replacer, new_page_id, frame_id)?;
+        Ok(self.page_ref(frame_id))
     }
 
     pub fn fetch_page(&self, page_id: PageId) -> BustubxResult<PageRef> {
-        if let Some(frame_id) = self.page_table.get(&page_id) {
-            let page = self.pool[*frame_id].clone();
-            page.write().unwrap().pin_count += 1;
-            self.replacer
-                .write()
-                .unwrap()
-                .set_evictable(*frame_id, false)?;
-            Ok(PageRef {
-                page,
-                page_table: self.page_table.clone(),
-                replacer: self.replacer.clone(),
-            })
-        } else {
-            // 分配一个frame
-            let frame_id = self.allocate_frame()?;
-
-            // 从磁盘读取页
-            self.page_table.insert(page_id, frame_id);
-            let new_page = Page::new(page_id)
-                .with_pin_count(1u32)
-                .with_data(self.disk_manager.read_page(page_id)?);
-            self.pool[frame_id].write().unwrap().replace(new_page);
-
-            self.replacer.write().unwrap().record_access(frame_id)?;
-            self.replacer
-                .write()
-                .unwrap()
-                .set_evictable(frame_id, false)?;
-
-            Ok(PageRef {
-                page: self.pool[frame_id].clone(),
-                page_table: self.page_table.clone(),
-                replacer: self.replacer.clone(),
-            })
+        // 查找、固定与加载都在replacer锁内，避免frame在固定前被置换，也避免同一页被加载到两个frame
+        let mut replacer = self.replacer.write().unwrap();
+        let frame_id = self.page_table.get(&page_id).map(|frame_id| *frame_id);
+        if let Some(frame_id) = frame_id {
+            self.pin_counts[frame_id].fetch_add(1, Ordering::SeqCst);
+            replacer.record_access(frame_id)?;
+            replacer.set_evictable(frame_id, false)?;
+            return Ok(self.page_ref(frame_id));
         }
+
+        // 分配一个frame
+        let frame_id = self.allocate_frame(&mut replacer)?;
+
+        // 从磁盘读取页
+        let data = match self.disk_manager.read_page(page_id) {
+            Ok(data) => data,
+            Err(e) => {
+                self.free_list.write().unwrap().push_back(frame_id);
+                return Err(e);
+            }
+        };
+        self.pool[frame_id]
+            .write()
+            .unwrap()
+            .replace(Page::new(page_id).with_data(data));
+        self.pin_frame(&mut replacer, page_id, frame_id)?;
+        Ok(self.page_ref(frame_id))
+    }
+
+    /// Fetches a page latched for reading until the guard is dropped.
+    pub fn fetch_page_read(&self, page_id: PageId) -> BustubxResult<ReadPageGuard<'_>> {
+        let page = self.fetch_page(page_id)?;
+        let guard = self.pool[page.frame_id].read().unwrap();
+        Ok(ReadPageGuard::new(page, guard))
+    }
+
+    /// Fetches a page latched for writing until the guard is dropped.
+    pub fn fetch_page_write(&self, page_id: PageId) -> BustubxResult<WritePageGuard<'_>> {
+        let page = self.fetch_page(page_id)?;
+        let guard = self.pool[page.frame_id].write().unwrap();
+        Ok(WritePageGuard::new(page, guard))
     }
 
     pub fn fetch_table_page(
@@ -163,11 +173,16 @@ impl BufferPoolManager {
 
     // 将缓冲池中指定页写回磁盘
     pub fn flush_page(&self, page_id: PageId) -> BustubxResult<bool> {
-        if let Some(frame_id) = self.page_table.get(&page_id) {
-            let page = self.pool[*frame_id].clone();
-            self.disk_manager
-                .write_page(page_id, page.read().unwrap().data())?;
-            page.write().unwrap().is_dirty = false;
+        let frame_id = self.page_table.get(&page_id).map(|frame_id| *frame_id);
+        if let Some(frame_id) = frame_id {
+            let page = self.pool[frame_id].clone();
+            let mut page = page.write().unwrap();
+            // frame在查找后可能已被置换给其他页
+            if page.page_id != page_id {
+                return Ok(false);
+            }
+            self.disk_manager.write_page(page_id, page.data())?;
+            page.is_dirty = false;
             Ok(true)
         } else {
             Ok(false)
@@ -185,12 +200,12 @@ impl BufferPoolManager {
 
     // 删除缓冲池中的页
     pub fn delete_page(&self, page_id: PageId) -> BustubxResult<bool> {
-        if let Some(frame_id_lock) = self.page_table.get(&page_id) {
-            let frame_id = *frame_id_lock;
-            drop(frame_id_lock);
-
+        // 在replacer锁内检查固定数，避免与fetch_page的固定交错
+        let mut replacer = self.replacer.write().unwrap();
+        let frame_id = self.page_table.get(&page_id).map(|frame_id| *frame_id);
+        if let Some(frame_id) = frame_id {
             let page = self.pool[frame_id].clone();
-            if page.read().unwrap().pin_count > 0 {
+            if self.pin_counts[frame_id].load(Ordering::SeqCst) > 0 {
                 // 页被固定，无法删除
                 return Ok(false);
             }
@@ -199,7 +214,8 @@ impl BufferPoolManager {
             page.write().unwrap().destroy();
             self.page_table.remove(&page_id);
             self.free_list.write().unwrap().push_back(frame_id);
-            self.replacer.write().unwrap().remove(frame_id);
+            replacer.remove(frame_id);
+            drop(replacer);
 
             // 从磁盘上删除
             self.disk_manager.deallocate_page(page_id)?;
@@ -209,15 +225,26 @@ impl BufferPoolManager {
         }
     }
 
-    fn allocate_frame(&self) -> BustubxResult<FrameId> {
+    fn page_ref(&self, frame_id: FrameId) -> PageRef {
+        PageRef {
+            page: self.pool[frame_id].clone(),
+            frame_id,
+            pin_count: self.pin_counts[frame_id].clone(),
+            replacer: self.replacer.clone(),
+        }
+    }
+
+    // 调用方持有replacer锁
+    fn allocate_frame(&self, replacer: &mut LRUKReplacer) -> BustubxResult<FrameId> {
         if let Some(frame_id) = self.free_list.write().unwrap().pop_front() {
             Ok(frame_id)
-        } else if let Some(frame_id) = self.replacer.write().unwrap().evict() {
-            let evicted_page = self.pool[frame_id].clone();
-            let evicted_page_id = evicted_page.read().unwrap().page_id;
-            let is_dirty = evicted_page.read().unwrap().is_dirty;
-            if is_dirty {
-                self.flush_page(evicted_page_id)?;
+        } else if let Some(frame_id) = replacer.evict() {
+            let evicted_page_id = self.pool[frame_id].read().unwrap().page_id;
+            if let Err(e) = self.flush_evicted_page(evicted_page_id, frame_id) {
+                // 写回失败时页留在缓冲池中
+                replacer.record_access(frame_id)?;
+                replacer.set_evictable(frame_id, true)?;
+                return Err(e);
             }
             self.page_table.remove(&evicted_page_id);
             Ok(frame_id)
@@ -227,6 +254,28 @@ impl BufferPoolManager {
             ))
         }
     }
+
+    fn flush_evicted_page(&self, page_id: PageId, frame_id: FrameId) -> BustubxResult<()> {
+        let mut page = self.pool[frame_id].write().unwrap();
+        if page.is_dirty {
+            self.disk_manager.write_page(page_id, page.data())?;
+            page.is_dirty = false;
+        }
+        Ok(())
+    }
+
+    // 调用方持有replacer锁，frame已装入页
+    fn pin_frame(
+        &self,
+        replacer: &mut LRUKReplacer,
+        page_id: PageId,
+        frame_id: FrameId,
+    ) -> BustubxResult<()> {
+        self.page_table.insert(page_id, frame_id);
+        self.pin_counts[frame_id].store(1, Ordering::SeqCst);
+        replacer.record_access(frame_id)?;
+        replacer.set_evictable(frame_id, false)
+    }
 }
 
 #[cfg(test)]
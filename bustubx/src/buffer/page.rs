@@ -1,11 +1,9 @@
 use crate::buffer::buffer_pool::FrameId;
 use crate::buffer::replacer::LRUKReplacer;
-use dashmap::DashMap;
 use derive_with::With;
-use log::error;
-use std::ops::Deref;
-use std::sync::atomic::AtomicU32;
-use std::sync::{Arc, RwLock};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 pub type PageId = u32;
 pub type AtomicPageId = AtomicU32;
@@ -17,8 +15,6 @@ pub const BUSTUBX_PAGE_SIZE: usize = 4096;
 pub struct Page {
     pub page_id: PageId,
     data: [u8; BUSTUBX_PAGE_SIZE],
-    // 被引用次数
-    pub pin_count: u32,
     // 是否被写过
     pub is_dirty: bool,
 }
@@ -31,14 +27,12 @@ impl Page {
         Self {
             page_id,
             data: [0; BUSTUBX_PAGE_SIZE],
-            pin_count: 0,
             is_dirty: false,
         }
     }
     pub fn destroy(&mut self) {
         self.page_id = 0;
         self.data = [0; BUSTUBX_PAGE_SIZE];
-        self.pin_count = 0;
         self.is_dirty = false;
     }
 
@@ -54,14 +48,15 @@ impl Page {
     pub fn replace(&mut self, other: Page) {
         self.page_id = other.page_id;
         self.data = other.data;
-        self.pin_count = other.pin_count;
         self.is_dirty = other.is_dirty;
     }
 }
 
 pub struct PageRef {
     pub page: Arc<RwLock<Page>>,
-    pub page_table: Arc<DashMap<PageId, FrameId>>,
+    pub frame_id: FrameId,
+    // 被引用次数，不放在页锁内，pin/unpin不需要等待页上的latch
+    pub pin_count: Arc<AtomicU32>,
     pub replacer: Arc<RwLock<LRUKReplacer>>,
 }
 
@@ -75,47 +70,84 @@ impl Deref for PageRef {
 
 impl Drop for PageRef {
     fn drop(&mut self) {
-        if self.page.read().unwrap().pin_count == 0 {
+        // 在replacer锁内unpin，避免与同一frame的pin交错
+        let mut replacer = self.replacer.write().unwrap();
+        if self.pin_count.load(Ordering::SeqCst) == 0 {
             return;
         }
-        let page_id = self.page.read().unwrap().page_id;
-        if let Some(frame_id) = self.page_table.get(&page_id) {
-            self.page.write().unwrap().pin_count -= 1;
-            if self.page.read().unwrap().pin_count == 0 {
-                if let Err(e) = self
-                    .replacer
-                    .write()
-                    .unwrap()
-                    .set_evictable(*frame_id, true)
-                {
-                    panic!(
-                        "Failed to set evictable to frame {}, err: {:?}",
-                        *frame_id, e
-                    );
-                }
+        if self.pin_count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            if let Err(e) = replacer.set_evictable(self.frame_id, true) {
+                panic!(
+                    "Failed to set evictable to frame {}, err: {:?}",
+                    self.frame_id, e
+                );
             }
-        } else {
-            error!("Cannot unpin page id {} as it is not in the pool", page_id);
         }
     }
 }
 
+/// A pinned page latched for reading, the latch is released when the guard is dropped.
+pub struct ReadPageGuard<'a> {
+    guard: RwLockReadGuard<'a, Page>,
+    _page: PageRef,
+}
+
+impl<'a> ReadPageGuard<'a> {
+    pub fn new(page: PageRef, guard: RwLockReadGuard<'a, Page>) -> Self {
+        Self { guard, _page: page }
+    }
+}
+
+impl Deref for ReadPageGuard<'_> {
+    type Target = Page;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+/// A pinned page latched for writing, the latch is released when the guard is dropped.
+pub struct WritePageGuard<'a> {
+    guard: RwLockWriteGuard<'a, Page>,
+    _page: PageRef,
+}
+
+impl<'a> WritePageGuard<'a> {
+    pub fn new(page: PageRef, guard: RwLockWriteGuard<'a, Page>) -> Self {
+        Self { guard, _page: page }
+    }
+}
+
+impl Deref for WritePageGuard<'_> {
+    type Target = Page;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl DerefMut for WritePageGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::buffer::replacer::LRUKReplacer;
     use crate::buffer::{Page, PageRef};
-    use dashmap::DashMap;
+    use std::sync::atomic::AtomicU32;
     use std::sync::{Arc, RwLock};
 
     #[test]
     fn page_ref() {
         let page = Arc::new(RwLock::new(Page::new(1)));
-        let page_table = Arc::new(DashMap::new());
         let replacer = Arc::new(RwLock::new(LRUKReplacer::new(10, 2)));
 
         let page_ref = PageRef {
             page: page.clone(),
-            page_table,
+            frame_id: 0,
+            pin_count: Arc::new(AtomicU32::new(0)),
             replacer,
         };
         assert_eq!(Arc::strong_count(&page), 2);
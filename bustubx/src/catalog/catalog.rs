@@ -1,18 +1,25 @@
 use std::collections::HashMap;
 use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use crate::catalog::{
-    key_schema_to_varchar, SchemaRef, COLUMNS_SCHMEA, INDEXES_SCHMEA, INFORMATION_SCHEMA_COLUMNS,
-    INFORMATION_SCHEMA_INDEXES, INFORMATION_SCHEMA_NAME, INFORMATION_SCHEMA_SCHEMAS,
-    INFORMATION_SCHEMA_TABLES, SCHEMAS_SCHMEA, TABLES_SCHMEA,
+    constraint_columns_to_varchar, key_schema_to_varchar, sequence_tuple, Constraint,
+    ConstraintKind, ResolvedSequenceOptions, SchemaRef, Sequence, SequenceRow, StoredExpr,
+    COLUMNS_SCHMEA, CONSTRAINTS_SCHMEA, INDEXES_SCHMEA, INFORMATION_SCHEMA_COLUMNS,
+    INFORMATION_SCHEMA_CONSTRAINTS, INFORMATION_SCHEMA_INDEXES, INFORMATION_SCHEMA_NAME,
+    INFORMATION_SCHEMA_SCHEMAS, INFORMATION_SCHEMA_SEQUENCES, INFORMATION_SCHEMA_TABLES,
+    INFORMATION_SCHEMA_VIEWS, SCHEMAS_SCHMEA, TABLES_SCHMEA, VIEWS_SCHMEA,
+};
+use crate::common::{ScalarValue, TableReference};
+use crate::expression::Expr;
+use crate::storage::{
+    TableIterator, BPLUS_DEFAULT_FILL_FACTOR, BPLUS_INTERNAL_PAGE_MAX_SIZE,
+    BPLUS_LEAF_PAGE_MAX_SIZE, EMPTY_TUPLE_META, HASH_BUCKET_PAGE_MAX_SIZE,
 };
-use crate::common::TableReference;
-use crate::storage::{BPLUS_INTERNAL_PAGE_MAX_SIZE, BPLUS_LEAF_PAGE_MAX_SIZE, EMPTY_TUPLE_META};
 use crate::{
     buffer::BufferPoolManager,
-    storage::{index::BPlusTreeIndex, TableHeap},
-    BustubxError, BustubxResult, Tuple,
+    storage::{hash_index::HashIndex, index::BPlusTreeIndex, TableHeap},
+    BustubxError, BustubxResult, Database, Tuple,
 };
 
 pub static DEFAULT_CATALOG_NAME: &str = "bustubx";
@@ -22,12 +29,23 @@ pub static DEFAULT_SCHEMA_NAME: &str = "public";
 pub struct Catalog {
     pub schemas: HashMap<String, CatalogSchema>,
     pub buffer_pool: Arc<BufferPoolManager>,
+    /// Schemas searched in order for unqualified names, set by `SET search_path`
+    pub search_path: Vec<String>,
+    /// Percentage of the page slots filled by `CREATE INDEX`, set by `SET index_fill_factor`
+    pub index_fill_factor: u8,
+    /// Catalogs of database files mounted by `ATTACH`, keyed by their name
+    pub attached: HashMap<String, Catalog>,
+    /// Sequences of all schemas share the pages of `information_schema.sequences`, writes
+    /// to them are serialized so concurrent sequences don't overwrite each other's page.
+    pub sequences_latch: Arc<Mutex<()>>,
 }
 
 #[derive(Debug)]
 pub struct CatalogSchema {
     pub name: String,
     pub tables: HashMap<String, CatalogTable>,
+    pub sequences: HashMap<String, Arc<Sequence>>,
+    pub views: HashMap<String, CatalogView>,
 }
 
 impl CatalogSchema {
@@ -35,6 +53,8 @@ impl CatalogSchema {
         Self {
             name: name.into(),
             tables: HashMap::new(),
+            sequences: HashMap::new(),
+            views: HashMap::new(),
         }
     }
 }
@@ -44,6 +64,39 @@ pub struct CatalogTable {
     pub name: String,
     pub table: Arc<TableHeap>,
     pub indexes: HashMap<String, Arc<BPlusTreeIndex>>,
+    pub hash_indexes: HashMap<String, Arc<HashIndex>>,
+    pub constraints: Vec<Constraint>,
+}
+
+/// Access method of an index, chosen by `CREATE INDEX ... USING`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexType {
+    BTree,
+    Hash,
+}
+
+impl std::fmt::Display for IndexType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexType::BTree => write!(f, "BTREE"),
+            IndexType::Hash => write!(f, "HASH"),
+        }
+    }
+}
+
+impl TryFrom<&str> for IndexType {
+    type Error = BustubxError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_uppercase().as_str() {
+            "BTREE" => Ok(IndexType::BTree),
+            "HASH" => Ok(IndexType::Hash),
+            _ => Err(BustubxError::NotSupport(format!(
+                "index access method {}",
+                value
+            ))),
+        }
+    }
 }
 
 impl CatalogTable {
@@ -52,16 +105,157 @@ impl CatalogTable {
             name: name.into(),
             table,
             indexes: HashMap::new(),
+            hash_indexes: HashMap::new(),
+            constraints: vec![],
         }
     }
 }
 
+/// A view stored as the SQL text of its query, a materialized view also owns
+/// a table of the same name holding the query result.
+#[derive(Debug, Clone)]
+pub struct CatalogView {
+    pub name: String,
+    pub definition: String,
+    /// Column names given to the view, unnamed columns keep those of the query
+    pub columns: Vec<String>,
+    pub materialized: bool,
+}
+
 impl Catalog {
     pub fn new(buffer_pool: Arc<BufferPoolManager>) -> Self {
         Self {
             schemas: HashMap::new(),
             buffer_pool,
+            search_path: vec![DEFAULT_SCHEMA_NAME.to_string()],
+            index_fill_factor: BPLUS_DEFAULT_FILL_FACTOR,
+            attached: HashMap::new(),
+            sequences_latch: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Mounts another database file as a catalog named `name`.
+    pub fn attach(&mut self, name: &str, path: &str) -> BustubxResult<()> {
+        if name == DEFAULT_CATALOG_NAME || self.attached.contains_key(name) {
+            return Err(BustubxError::Storage(format!(
+                "database \"{}\" already exists",
+                name
+            )));
+        }
+        // a file opened twice would have two buffer pools overwriting each other's pages
+        if let Ok(canonical_path) = std::fs::canonicalize(path) {
+            if canonical_path == self.buffer_pool.disk_manager.path {
+                return Err(BustubxError::Storage(format!(
+                    "database file \"{}\" is the main database",
+                    path
+                )));
+            }
+            if let Some((attached_name, _)) = self
+                .attached
+                .iter()
+                .find(|(_, catalog)| catalog.buffer_pool.disk_manager.path == canonical_path)
+            {
+                return Err(BustubxError::Storage(format!(
+                    "database file \"{}\" is already attached as \"{}\"",
+                    path, attached_name
+                )));
+            }
         }
+        let database = Database::new_on_disk(path)?;
+        self.attached.insert(name.to_string(), database.catalog);
+        Ok(())
+    }
+
+    pub fn detach(&mut self, name: &str) -> BustubxResult<()> {
+        let Some(catalog) = self.attached.remove(name) else {
+            return Err(BustubxError::Storage(format!(
+                "database \"{}\" is not attached",
+                name
+            )));
+        };
+        catalog.buffer_pool.flush_all_pages()
+    }
+
+    pub fn attached_catalog(&self, name: &str) -> BustubxResult<&Catalog> {
+        self.attached
+            .get(name)
+            .ok_or_else(|| BustubxError::Storage(format!("database \"{}\" is not attached", name)))
+    }
+
+    /// The attached catalog a reference points into, with the reference made relative to it.
+    fn route(
+        &self,
+        table_ref: &TableReference,
+    ) -> BustubxResult<Option<(&Catalog, TableReference)>> {
+        match table_ref.catalog() {
+            Some(name) if name != DEFAULT_CATALOG_NAME => Ok(Some((
+                self.attached_catalog(name)?,
+                relative_reference(table_ref),
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    fn route_mut(
+        &mut self,
+        table_ref: &TableReference,
+    ) -> BustubxResult<Option<(&mut Catalog, TableReference)>> {
+        match table_ref.catalog() {
+            Some(name) if name != DEFAULT_CATALOG_NAME => {
+                let Some(catalog) = self.attached.get_mut(name) else {
+                    return Err(BustubxError::Storage(format!(
+                        "database \"{}\" is not attached",
+                        name
+                    )));
+                };
+                Ok(Some((catalog, relative_reference(table_ref))))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The catalog entry of a table, `None` if it doesn't exist.
+    pub fn catalog_table(&self, table_ref: &TableReference) -> Option<&CatalogTable> {
+        if let Ok(Some((catalog, table_ref))) = self.route(table_ref) {
+            return catalog
+                .schemas
+                .get(table_ref.schema().unwrap_or(DEFAULT_SCHEMA_NAME))?
+                .tables
+                .get(table_ref.table());
+        }
+        self.schemas
+            .get(table_ref.schema().unwrap_or(DEFAULT_SCHEMA_NAME))?
+            .tables
+            .get(table_ref.table())
+    }
+
+    /// Qualifies an unqualified relation name with the first schema of the search path
+    /// containing it, a name found nowhere goes to the first existing schema to be created in.
+    pub fn resolve_relation(
+        &self,
+        search_path: &[String],
+        name: &str,
+    ) -> BustubxResult<TableReference> {
+        let existing_schemas = search_path
+            .iter()
+            .filter_map(|schema_name| self.schemas.get(schema_name))
+            .collect::<Vec<&CatalogSchema>>();
+        let catalog_schema = existing_schemas
+            .iter()
+            .find(|catalog_schema| {
+                catalog_schema.tables.contains_key(name)
+                    || catalog_schema.views.contains_key(name)
+                    || catalog_schema.sequences.contains_key(name)
+            })
+            .or(existing_schemas.first())
+            .ok_or_else(|| {
+                BustubxError::Plan(format!(
+                    "no schema of search path {} contains \"{}\"",
+                    search_path.join(", "),
+                    name
+                ))
+            })?;
+        Ok(TableReference::partial(catalog_schema.name.clone(), name))
     }
 
     pub fn create_schema(&mut self, schema_name: impl Into<String>) -> BustubxResult<()> {
@@ -102,11 +296,118 @@ impl Catalog {
         Ok(())
     }
 
+    /// Drops a schema, with `cascade` the objects in it go along, otherwise it must be empty.
+    pub fn drop_schema(&mut self, schema_name: &str, cascade: bool) -> BustubxResult<()> {
+        if schema_name == INFORMATION_SCHEMA_NAME {
+            return Err(BustubxError::Storage(format!(
+                "cannot drop schema {} because it is required by the database system",
+                schema_name
+            )));
+        }
+        let Some(catalog_schema) = self.schemas.get(schema_name) else {
+            return Err(BustubxError::Storage(format!(
+                "schema \"{}\" does not exist",
+                schema_name
+            )));
+        };
+        let is_empty = catalog_schema.tables.is_empty()
+            && catalog_schema.sequences.is_empty()
+            && catalog_schema.views.is_empty();
+        if !is_empty && !cascade {
+            return Err(BustubxError::Storage(format!(
+                "cannot drop schema {} because other objects depend on it",
+                schema_name
+            )));
+        }
+        // a default of another schema's table would no longer bind once its sequence is gone
+        for other_schema in self.schemas.values() {
+            if other_schema.name == schema_name {
+                continue;
+            }
+            for catalog_table in other_schema.tables.values() {
+                for col in catalog_table.table.schema.columns.iter() {
+                    if let Some(StoredExpr {
+                        expr: Expr::SequenceFunction(function),
+                        ..
+                    }) = &col.default
+                    {
+                        if function.sequence.schema_name == schema_name {
+                            return Err(BustubxError::Storage(format!(
+                                "cannot drop schema {} because default of {}.{}.{} depends on it",
+                                schema_name, other_schema.name, catalog_table.name, col.name
+                            )));
+                        }
+                    }
+                }
+            }
+        }
+
+        // foreign keys of other schemas referencing the dropped tables go along
+        for other_schema in self.schemas.values_mut() {
+            for catalog_table in other_schema.tables.values_mut() {
+                catalog_table.constraints.retain(|constraint| {
+                    !matches!(&constraint.kind, ConstraintKind::ForeignKey(foreign_key)
+                        if foreign_key.table.schema() == Some(schema_name))
+                });
+            }
+        }
+        let Some(catalog_schema) = self.schemas.remove(schema_name) else {
+            return Err(BustubxError::Internal(format!(
+                "catalog schema {} disappeared",
+                schema_name
+            )));
+        };
+        for sequence in catalog_schema.sequences.values() {
+            sequence.delete_tuple()?;
+        }
+
+        // update system tables
+        let schema_name_value = ScalarValue::from(schema_name.to_string());
+        for table_name in [
+            INFORMATION_SCHEMA_SCHEMAS,
+            INFORMATION_SCHEMA_TABLES,
+            INFORMATION_SCHEMA_COLUMNS,
+            INFORMATION_SCHEMA_INDEXES,
+            INFORMATION_SCHEMA_VIEWS,
+        ] {
+            self.delete_information_rows(table_name, |tuple| {
+                Ok(tuple.value(1)? == &schema_name_value)
+            })?;
+        }
+        self.delete_information_rows(INFORMATION_SCHEMA_CONSTRAINTS, |tuple| {
+            Ok(tuple.value(1)? == &schema_name_value || tuple.value(6)? == &schema_name_value)
+        })?;
+        Ok(())
+    }
+
+    fn delete_information_rows(
+        &self,
+        table_name: &str,
+        predicate: impl Fn(&Tuple) -> BustubxResult<bool>,
+    ) -> BustubxResult<()> {
+        let table_heap = self.table_heap(&TableReference::partial(
+            INFORMATION_SCHEMA_NAME,
+            table_name,
+        ))?;
+        let mut iterator = TableIterator::new(table_heap.clone(), ..);
+        while let Some((rid, tuple)) = iterator.next()? {
+            if predicate(&tuple)? {
+                let mut meta = table_heap.tuple_meta(rid)?;
+                meta.is_deleted = true;
+                table_heap.update_tuple_meta(meta, rid)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn create_table(
         &mut self,
         table_ref: TableReference,
         schema: SchemaRef,
     ) -> BustubxResult<Arc<TableHeap>> {
+        if let Some((catalog, table_ref)) = self.route_mut(&table_ref)? {
+            return catalog.create_table(table_ref, schema);
+        }
         let catalog_name = table_ref
             .catalog()
             .unwrap_or(DEFAULT_CATALOG_NAME)
@@ -123,7 +424,10 @@ impl Catalog {
                 catalog_schema_name
             )));
         };
-        if catalog_schema.tables.contains_key(table_ref.table()) {
+        if catalog_schema.tables.contains_key(table_ref.table())
+            || catalog_schema.sequences.contains_key(table_ref.table())
+            || catalog_schema.views.contains_key(table_ref.table())
+        {
             return Err(BustubxError::Storage(
                 "Cannot create duplicated table".to_string(),
             ));
@@ -132,11 +436,7 @@ impl Catalog {
             schema.clone(),
             self.buffer_pool.clone(),
         )?);
-        let catalog_table = CatalogTable {
-            name: table_name.clone(),
-            table: table_heap.clone(),
-            indexes: HashMap::new(),
-        };
+        let catalog_table = CatalogTable::new(table_name.clone(), table_heap.clone());
         catalog_schema
             .tables
             .insert(table_name.clone(), catalog_table);
@@ -184,7 +484,8 @@ impl Catalog {
                     col.name.clone().into(),
                     format!("{sql_type}").into(),
                     col.nullable.into(),
-                    format!("{}", col.default).into(),
+                    ScalarValue::Varchar(col.default.as_ref().map(|default| default.sql.clone())),
+                    ScalarValue::Varchar(col.identity.map(|identity| identity.to_string())),
                 ],
             );
             columns_table
@@ -196,6 +497,9 @@ impl Catalog {
     }
 
     pub fn table_heap(&self, table_ref: &TableReference) -> BustubxResult<Arc<TableHeap>> {
+        if let Some((catalog, table_ref)) = self.route(table_ref)? {
+            return catalog.table_heap(&table_ref);
+        }
         let catalog_schema_name = table_ref
             .schema()
             .unwrap_or(DEFAULT_SCHEMA_NAME)
@@ -221,6 +525,9 @@ impl Catalog {
         &self,
         table_ref: &TableReference,
     ) -> BustubxResult<Vec<Arc<BPlusTreeIndex>>> {
+        if let Some((catalog, table_ref)) = self.route(table_ref)? {
+            return catalog.table_indexes(&table_ref);
+        }
         let catalog_schema_name = table_ref
             .schema()
             .unwrap_or(DEFAULT_SCHEMA_NAME)
@@ -248,10 +555,75 @@ impl Catalog {
         table_ref: &TableReference,
         key_schema: SchemaRef,
     ) -> BustubxResult<Arc<BPlusTreeIndex>> {
-        let catalog_name = table_ref
-            .catalog()
-            .unwrap_or(DEFAULT_CATALOG_NAME)
-            .to_string();
+        if let Some((catalog, table_ref)) = self.route_mut(table_ref)? {
+            return catalog.create_index(index_name, &table_ref, key_schema);
+        }
+        self.index_table_mut(table_ref, &index_name)?;
+        let b_plus_tree_index = Arc::new(BPlusTreeIndex::try_new(
+            key_schema.clone(),
+            self.buffer_pool.clone(),
+            BPLUS_INTERNAL_PAGE_MAX_SIZE as u32,
+            BPLUS_LEAF_PAGE_MAX_SIZE as u32,
+        )?);
+        self.index_table_mut(table_ref, &index_name)?
+            .indexes
+            .insert(index_name.clone(), b_plus_tree_index.clone());
+
+        self.insert_index_row(
+            table_ref,
+            &index_name,
+            &b_plus_tree_index.key_schema,
+            vec![
+                b_plus_tree_index.internal_max_size.into(),
+                b_plus_tree_index.leaf_max_size.into(),
+                b_plus_tree_index.header_page_id.into(),
+                IndexType::BTree.to_string().into(),
+            ],
+        )?;
+        Ok(b_plus_tree_index)
+    }
+
+    pub fn create_hash_index(
+        &mut self,
+        index_name: String,
+        table_ref: &TableReference,
+        key_schema: SchemaRef,
+    ) -> BustubxResult<Arc<HashIndex>> {
+        if let Some((catalog, table_ref)) = self.route_mut(table_ref)? {
+            return catalog.create_hash_index(index_name, &table_ref, key_schema);
+        }
+        self.index_table_mut(table_ref, &index_name)?;
+        let hash_index = Arc::new(HashIndex::try_new(
+            key_schema,
+            self.buffer_pool.clone(),
+            HASH_BUCKET_PAGE_MAX_SIZE as u32,
+        )?);
+        self.index_table_mut(table_ref, &index_name)?
+            .hash_indexes
+            .insert(index_name.clone(), hash_index.clone());
+
+        // a hash index has no internal pages, its directory page takes the header page id
+        self.insert_index_row(
+            table_ref,
+            &index_name,
+            &hash_index.key_schema,
+            vec![
+                0u32.into(),
+                hash_index.bucket_max_size.into(),
+                hash_index.directory_page_id.into(),
+                IndexType::Hash.to_string().into(),
+            ],
+        )?;
+        Ok(hash_index)
+    }
+
+    /// The table to create an index named `index_name` on, which no index of the table
+    /// may already be named.
+    fn index_table_mut(
+        &mut self,
+        table_ref: &TableReference,
+        index_name: &str,
+    ) -> BustubxResult<&mut CatalogTable> {
         let catalog_schema_name = table_ref
             .schema()
             .unwrap_or(DEFAULT_SCHEMA_NAME)
@@ -270,23 +642,35 @@ impl Catalog {
                 table_name
             )));
         };
-        if catalog_table.indexes.contains_key(&index_name) {
+        if catalog_table.indexes.contains_key(index_name)
+            || catalog_table.hash_indexes.contains_key(index_name)
+        {
             return Err(BustubxError::Storage(
                 "Cannot create duplicated index".to_string(),
             ));
         }
+        Ok(catalog_table)
+    }
 
-        let b_plus_tree_index = Arc::new(BPlusTreeIndex::new(
-            key_schema.clone(),
-            self.buffer_pool.clone(),
-            BPLUS_INTERNAL_PAGE_MAX_SIZE as u32,
-            BPLUS_LEAF_PAGE_MAX_SIZE as u32,
-        ));
-        catalog_table
-            .indexes
-            .insert(index_name.clone(), b_plus_tree_index.clone());
+    /// Records an index in the system table, `storage` holds the values of the columns
+    /// after the key schema.
+    fn insert_index_row(
+        &mut self,
+        table_ref: &TableReference,
+        index_name: &str,
+        key_schema: &SchemaRef,
+        storage: Vec<ScalarValue>,
+    ) -> BustubxResult<()> {
+        let catalog_name = table_ref
+            .catalog()
+            .unwrap_or(DEFAULT_CATALOG_NAME)
+            .to_string();
+        let catalog_schema_name = table_ref
+            .schema()
+            .unwrap_or(DEFAULT_SCHEMA_NAME)
+            .to_string();
+        let table_name = table_ref.table().to_string();
 
-        // update system table
         let Some(information_schema) = self.schemas.get_mut(INFORMATION_SCHEMA_NAME) else {
             return Err(BustubxError::Internal(
                 "catalog schema information_schema not created yet".to_string(),
@@ -301,24 +685,19 @@ impl Catalog {
             ));
         };
 
-        let tuple = Tuple::new(
-            INDEXES_SCHMEA.clone(),
-            vec![
-                catalog_name.clone().into(),
-                catalog_schema_name.clone().into(),
-                table_name.clone().into(),
-                index_name.clone().into(),
-                key_schema_to_varchar(&b_plus_tree_index.key_schema).into(),
-                b_plus_tree_index.internal_max_size.into(),
-                b_plus_tree_index.leaf_max_size.into(),
-                b_plus_tree_index.root_page_id.load(Ordering::SeqCst).into(),
-            ],
-        );
+        let mut values = vec![
+            catalog_name.into(),
+            catalog_schema_name.into(),
+            table_name.into(),
+            index_name.to_string().into(),
+            key_schema_to_varchar(key_schema).into(),
+        ];
+        values.extend(storage);
+        let tuple = Tuple::new(INDEXES_SCHMEA.clone(), values);
         indexes_table
             .table
             .insert_tuple(&EMPTY_TUPLE_META, &tuple)?;
-
-        Ok(b_plus_tree_index)
+        Ok(())
     }
 
     pub fn index(
@@ -326,6 +705,9 @@ impl Catalog {
         table_ref: &TableReference,
         index_name: &str,
     ) -> BustubxResult<Option<Arc<BPlusTreeIndex>>> {
+        if let Some((catalog, table_ref)) = self.route(table_ref)? {
+            return catalog.index(&table_ref, index_name);
+        }
         let catalog_schema_name = table_ref
             .schema()
             .unwrap_or(DEFAULT_SCHEMA_NAME)
@@ -347,6 +729,339 @@ impl Catalog {
         Ok(catalog_table.indexes.get(index_name).cloned())
     }
 
+    pub fn table_hash_indexes(
+        &self,
+        table_ref: &TableReference,
+    ) -> BustubxResult<Vec<Arc<HashIndex>>> {
+        let Some(catalog_table) = self.catalog_table(table_ref) else {
+            return Err(BustubxError::Storage(format!(
+                "table {} not created yet",
+                table_ref.table()
+            )));
+        };
+        Ok(catalog_table.hash_indexes.values().cloned().collect())
+    }
+
+    pub fn hash_index(
+        &self,
+        table_ref: &TableReference,
+        index_name: &str,
+    ) -> BustubxResult<Option<Arc<HashIndex>>> {
+        let Some(catalog_table) = self.catalog_table(table_ref) else {
+            return Err(BustubxError::Storage(format!(
+                "table {} not created yet",
+                table_ref.table()
+            )));
+        };
+        Ok(catalog_table.hash_indexes.get(index_name).cloned())
+    }
+
+    /// Adds a constraint to a table, a unique constraint creates its backing index.
+    pub fn create_constraint(
+        &mut self,
+        table_ref: &TableReference,
+        constraint: Constraint,
+    ) -> BustubxResult<()> {
+        if let Some((catalog, table_ref)) = self.route_mut(table_ref)? {
+            // references stay within the attached catalog
+            let mut constraint = constraint;
+            if let ConstraintKind::ForeignKey(foreign_key) = &mut constraint.kind {
+                foreign_key.table = relative_reference(&foreign_key.table);
+            }
+            return catalog.create_constraint(&table_ref, constraint);
+        }
+        let catalog_name = table_ref
+            .catalog()
+            .unwrap_or(DEFAULT_CATALOG_NAME)
+            .to_string();
+        let catalog_schema_name = table_ref
+            .schema()
+            .unwrap_or(DEFAULT_SCHEMA_NAME)
+            .to_string();
+        let table_name = table_ref.table().to_string();
+
+        if self
+            .table_constraints(table_ref)?
+            .iter()
+            .any(|c| c.name == constraint.name)
+        {
+            return Err(BustubxError::Storage(format!(
+                "Cannot create duplicated constraint {}",
+                constraint.name
+            )));
+        }
+        if constraint.kind.is_unique() {
+            let table_schema = self.table_heap(table_ref)?.schema.clone();
+            let indices = constraint
+                .columns
+                .iter()
+                .map(|name| table_schema.index_of(None, name))
+                .collect::<BustubxResult<Vec<usize>>>()?;
+            let key_schema = Arc::new(table_schema.project(&indices)?);
+            self.create_index(constraint.name.clone(), table_ref, key_schema)?;
+        }
+
+        // update system table
+        let Some(information_schema) = self.schemas.get_mut(INFORMATION_SCHEMA_NAME) else {
+            return Err(BustubxError::Internal(
+                "catalog schema information_schema not created yet".to_string(),
+            ));
+        };
+        let Some(constraints_table) = information_schema
+            .tables
+            .get_mut(INFORMATION_SCHEMA_CONSTRAINTS)
+        else {
+            return Err(BustubxError::Internal(
+                "table information_schema.constraints not created yet".to_string(),
+            ));
+        };
+        let mut values: Vec<ScalarValue> = vec![
+            catalog_name.into(),
+            catalog_schema_name.into(),
+            table_name.into(),
+            constraint.name.clone().into(),
+            constraint.kind.to_string().into(),
+            constraint_columns_to_varchar(&constraint.columns).into(),
+        ];
+        if let ConstraintKind::ForeignKey(foreign_key) = &constraint.kind {
+            values.extend([
+                foreign_key
+                    .table
+                    .schema()
+                    .unwrap_or(DEFAULT_SCHEMA_NAME)
+                    .to_string()
+                    .into(),
+                foreign_key.table.table().to_string().into(),
+                constraint_columns_to_varchar(&foreign_key.columns).into(),
+                foreign_key.on_update.to_string().into(),
+                foreign_key.on_delete.to_string().into(),
+            ]);
+        } else {
+            values.extend(vec![ScalarValue::Varchar(None); 5]);
+        }
+        values.push(ScalarValue::Varchar(match &constraint.kind {
+            ConstraintKind::Check(check) => Some(check.sql.clone()),
+            _ => None,
+        }));
+        let tuple = Tuple::new(CONSTRAINTS_SCHMEA.clone(), values);
+        constraints_table
+            .table
+            .insert_tuple(&EMPTY_TUPLE_META, &tuple)?;
+
+        self.load_constraint(table_ref.clone(), constraint)
+    }
+
+    /// Foreign key constraints of all tables referencing the table.
+    pub fn referencing_constraints(
+        &self,
+        table_ref: &TableReference,
+    ) -> BustubxResult<Vec<(TableReference, Constraint)>> {
+        if let Some((catalog, relative_ref)) = self.route(table_ref)? {
+            let catalog_name = table_ref.catalog().unwrap_or(DEFAULT_CATALOG_NAME);
+            return Ok(catalog
+                .referencing_constraints(&relative_ref)?
+                .into_iter()
+                .map(|(table, constraint)| {
+                    (
+                        qualified_reference(catalog_name, &table),
+                        qualify_constraint(catalog_name, constraint),
+                    )
+                })
+                .collect());
+        }
+        let catalog_schema_name = table_ref.schema().unwrap_or(DEFAULT_SCHEMA_NAME);
+        let mut constraints = vec![];
+        for catalog_schema in self.schemas.values() {
+            for catalog_table in catalog_schema.tables.values() {
+                for constraint in catalog_table.constraints.iter() {
+                    let ConstraintKind::ForeignKey(foreign_key) = &constraint.kind else {
+                        continue;
+                    };
+                    if foreign_key.table.table() == table_ref.table()
+                        && foreign_key.table.schema() == Some(catalog_schema_name)
+                    {
+                        constraints.push((
+                            TableReference::partial(
+                                catalog_schema.name.clone(),
+                                catalog_table.name.clone(),
+                            ),
+                            constraint.clone(),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(constraints)
+    }
+
+    pub fn table_constraints(&self, table_ref: &TableReference) -> BustubxResult<Vec<Constraint>> {
+        if let Some((catalog, relative_ref)) = self.route(table_ref)? {
+            let catalog_name = table_ref.catalog().unwrap_or(DEFAULT_CATALOG_NAME);
+            return Ok(catalog
+                .table_constraints(&relative_ref)?
+                .into_iter()
+                .map(|constraint| qualify_constraint(catalog_name, constraint))
+                .collect());
+        }
+        let catalog_schema_name = table_ref
+            .schema()
+            .unwrap_or(DEFAULT_SCHEMA_NAME)
+            .to_string();
+        let table_name = table_ref.table().to_string();
+
+        let Some(catalog_schema) = self.schemas.get(&catalog_schema_name) else {
+            return Err(BustubxError::Storage(format!(
+                "catalog schema {} not created yet",
+                catalog_schema_name
+            )));
+        };
+        let Some(catalog_table) = catalog_schema.tables.get(&table_name) else {
+            return Err(BustubxError::Storage(format!(
+                "table {} not created yet",
+                table_name
+            )));
+        };
+        Ok(catalog_table.constraints.clone())
+    }
+
+    pub fn create_sequence(
+        &mut self,
+        sequence_ref: TableReference,
+        options: ResolvedSequenceOptions,
+    ) -> BustubxResult<Arc<Sequence>> {
+        if let Some((catalog, sequence_ref)) = self.route_mut(&sequence_ref)? {
+            return catalog.create_sequence(sequence_ref, options);
+        }
+        let catalog_schema_name = sequence_ref
+            .schema()
+            .unwrap_or(DEFAULT_SCHEMA_NAME)
+            .to_string();
+        let sequence_name = sequence_ref.table().to_string();
+
+        let Some(catalog_schema) = self.schemas.get(&catalog_schema_name) else {
+            return Err(BustubxError::Storage(format!(
+                "catalog schema {} not created yet",
+                catalog_schema_name
+            )));
+        };
+        if catalog_schema.tables.contains_key(&sequence_name)
+            || catalog_schema.sequences.contains_key(&sequence_name)
+            || catalog_schema.views.contains_key(&sequence_name)
+        {
+            return Err(BustubxError::Storage(format!(
+                "Cannot create duplicated sequence {}",
+                sequence_name
+            )));
+        }
+
+        // update system table
+        let sequences_table = self.table_heap(&TableReference::partial(
+            INFORMATION_SCHEMA_NAME,
+            INFORMATION_SCHEMA_SEQUENCES,
+        ))?;
+        let tuple = sequence_tuple(
+            &catalog_schema_name,
+            &sequence_name,
+            &options,
+            options.start,
+            false,
+        );
+        let row = SequenceRow::insert(sequences_table, self.sequences_latch.clone(), &tuple)?;
+
+        let sequence = Arc::new(Sequence::new(
+            catalog_schema_name,
+            sequence_name,
+            options,
+            row,
+            options.start,
+            false,
+        ));
+        self.load_sequence(sequence.clone())?;
+        Ok(sequence)
+    }
+
+    pub fn sequence(&self, sequence_ref: &TableReference) -> BustubxResult<Arc<Sequence>> {
+        if let Some((catalog, sequence_ref)) = self.route(sequence_ref)? {
+            return catalog.sequence(&sequence_ref);
+        }
+        let catalog_schema_name = sequence_ref.schema().unwrap_or(DEFAULT_SCHEMA_NAME);
+        self.schemas
+            .get(catalog_schema_name)
+            .and_then(|catalog_schema| catalog_schema.sequences.get(sequence_ref.table()))
+            .cloned()
+            .ok_or_else(|| {
+                BustubxError::Storage(format!("sequence {} does not exist", sequence_ref.table()))
+            })
+    }
+
+    /// Records a view, the table of a materialized view must be created before.
+    pub fn create_view(
+        &mut self,
+        view_ref: TableReference,
+        view: CatalogView,
+    ) -> BustubxResult<()> {
+        if let Some((catalog, view_ref)) = self.route_mut(&view_ref)? {
+            return catalog.create_view(view_ref, view);
+        }
+        let catalog_name = view_ref
+            .catalog()
+            .unwrap_or(DEFAULT_CATALOG_NAME)
+            .to_string();
+        let catalog_schema_name = view_ref.schema().unwrap_or(DEFAULT_SCHEMA_NAME).to_string();
+
+        let Some(catalog_schema) = self.schemas.get(&catalog_schema_name) else {
+            return Err(BustubxError::Storage(format!(
+                "catalog schema {} not created yet",
+                catalog_schema_name
+            )));
+        };
+        if catalog_schema.views.contains_key(&view.name)
+            || (!view.materialized
+                && (catalog_schema.tables.contains_key(&view.name)
+                    || catalog_schema.sequences.contains_key(&view.name)))
+        {
+            return Err(BustubxError::Storage(format!(
+                "Cannot create duplicated view {}",
+                view.name
+            )));
+        }
+
+        // update system table
+        let views_table = self.table_heap(&TableReference::partial(
+            INFORMATION_SCHEMA_NAME,
+            INFORMATION_SCHEMA_VIEWS,
+        ))?;
+        let tuple = Tuple::new(
+            VIEWS_SCHMEA.clone(),
+            vec![
+                catalog_name.into(),
+                catalog_schema_name.into(),
+                view.name.clone().into(),
+                view.definition.clone().into(),
+                ScalarValue::Varchar(if view.columns.is_empty() {
+                    None
+                } else {
+                    Some(constraint_columns_to_varchar(&view.columns))
+                }),
+                view.materialized.into(),
+            ],
+        );
+        views_table.insert_tuple(&EMPTY_TUPLE_META, &tuple)?;
+
+        self.load_view(view_ref, view)
+    }
+
+    pub fn view(&self, view_ref: &TableReference) -> Option<CatalogView> {
+        if let Ok(Some((catalog, view_ref))) = self.route(view_ref) {
+            return catalog.view(&view_ref);
+        }
+        let catalog_schema_name = view_ref.schema().unwrap_or(DEFAULT_SCHEMA_NAME);
+        self.schemas
+            .get(catalog_schema_name)
+            .and_then(|catalog_schema| catalog_schema.views.get(view_ref.table()))
+            .cloned()
+    }
+
     pub fn load_schema(&mut self, name: impl Into<String>, schema: CatalogSchema) {
         self.schemas.insert(name.into(), schema);
     }
@@ -391,16 +1106,110 @@ impl Catalog {
         catalog_table.indexes.insert(index_name.into(), index);
         Ok(())
     }
+
+    pub fn load_hash_index(
+        &mut self,
+        table_ref: TableReference,
+        index_name: impl Into<String>,
+        index: Arc<HashIndex>,
+    ) -> BustubxResult<()> {
+        let catalog_schema_name = table_ref.schema().unwrap_or(DEFAULT_SCHEMA_NAME);
+        let table_name = table_ref.table().to_string();
+        let Some(catalog_schema) = self.schemas.get_mut(catalog_schema_name) else {
+            return Err(BustubxError::Storage(format!(
+                "catalog schema {} not created yet",
+                catalog_schema_name
+            )));
+        };
+        let Some(catalog_table) = catalog_schema.tables.get_mut(&table_name) else {
+            return Err(BustubxError::Storage(format!(
+                "catalog table {} not created yet",
+                table_name
+            )));
+        };
+        catalog_table.hash_indexes.insert(index_name.into(), index);
+        Ok(())
+    }
+
+    pub fn load_constraint(
+        &mut self,
+        table_ref: TableReference,
+        constraint: Constraint,
+    ) -> BustubxResult<()> {
+        let catalog_schema_name = table_ref.schema().unwrap_or(DEFAULT_SCHEMA_NAME);
+        let table_name = table_ref.table().to_string();
+        let Some(catalog_schema) = self.schemas.get_mut(catalog_schema_name) else {
+            return Err(BustubxError::Storage(format!(
+                "catalog schema {} not created yet",
+                catalog_schema_name
+            )));
+        };
+        let Some(catalog_table) = catalog_schema.tables.get_mut(&table_name) else {
+            return Err(BustubxError::Storage(format!(
+                "catalog table {} not created yet",
+                table_name
+            )));
+        };
+        catalog_table.constraints.push(constraint);
+        Ok(())
+    }
+
+    pub fn load_sequence(&mut self, sequence: Arc<Sequence>) -> BustubxResult<()> {
+        let Some(catalog_schema) = self.schemas.get_mut(&sequence.schema_name) else {
+            return Err(BustubxError::Storage(format!(
+                "catalog schema {} not created yet",
+                sequence.schema_name
+            )));
+        };
+        catalog_schema
+            .sequences
+            .insert(sequence.name.clone(), sequence);
+        Ok(())
+    }
+
+    pub fn load_view(&mut self, view_ref: TableReference, view: CatalogView) -> BustubxResult<()> {
+        let catalog_schema_name = view_ref.schema().unwrap_or(DEFAULT_SCHEMA_NAME);
+        let Some(catalog_schema) = self.schemas.get_mut(catalog_schema_name) else {
+            return Err(BustubxError::Storage(format!(
+                "catalog schema {} not created yet",
+                catalog_schema_name
+            )));
+        };
+        catalog_schema.views.insert(view.name.clone(), view);
+        Ok(())
+    }
+}
+
+fn relative_reference(table_ref: &TableReference) -> TableReference {
+    TableReference::partial(
+        table_ref.schema().unwrap_or(DEFAULT_SCHEMA_NAME),
+        table_ref.table(),
+    )
+}
+
+fn qualified_reference(catalog_name: &str, table_ref: &TableReference) -> TableReference {
+    TableReference::full(
+        catalog_name,
+        table_ref.schema().unwrap_or(DEFAULT_SCHEMA_NAME),
+        table_ref.table(),
+    )
+}
+
+fn qualify_constraint(catalog_name: &str, mut constraint: Constraint) -> Constraint {
+    if let ConstraintKind::ForeignKey(foreign_key) = &mut constraint.kind {
+        foreign_key.table = qualified_reference(catalog_name, &foreign_key.table);
+    }
+    constraint
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
-    use crate::common::TableReference;
+    use crate::common::{ScalarValue, TableReference};
     use crate::{
         catalog::{Column, DataType, Schema},
-        Database,
+        Database, Tuple,
     };
 
     #[test]
@@ -473,4 +1282,120 @@ mod tests {
             .unwrap();
         assert_eq!(index3.key_schema, key_schema1);
     }
+
+    #[test]
+    pub fn test_catalog_create_hash_index() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db_path = db_path.to_str().unwrap();
+        {
+            let mut db = Database::new_on_disk(db_path).unwrap();
+            db.run("create table t1 (a int, b varchar)").unwrap();
+            db.run("insert into t1 values (1, 'x'), (2, 'y'), (1, 'z')")
+                .unwrap();
+            db.run("create index idx_a on t1 using hash (a)").unwrap();
+            assert!(db.run("create index idx_a on t1 (b)").is_err());
+            db.flush().unwrap();
+        }
+
+        let mut db = Database::new_on_disk(db_path).unwrap();
+        let table_ref = TableReference::bare("t1");
+        assert!(db.catalog.index(&table_ref, "idx_a").unwrap().is_none());
+        let index = db.catalog.hash_index(&table_ref, "idx_a").unwrap().unwrap();
+        let key = Tuple::new(index.key_schema.clone(), vec![1i32.into()]);
+        assert_eq!(index.get(&key).unwrap().len(), 2);
+
+        db.run("insert into t1 values (1, 'w')").unwrap();
+        assert_eq!(db.run("select b from t1 where a = 1").unwrap().len(), 3);
+    }
+
+    #[test]
+    pub fn test_catalog_reopen_index() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db_path = db_path.to_str().unwrap();
+        {
+            let mut db = Database::new_on_disk(db_path).unwrap();
+            db.run("create table t1 (a int primary key, b varchar)")
+                .unwrap();
+            db.run("insert into t1 values (0, 'x')").unwrap();
+            db.flush().unwrap();
+        }
+        {
+            // the root page moves as the tree grows after the index is recorded
+            let mut db = Database::new_on_disk(db_path).unwrap();
+            assert_eq!(db.run("select * from t1").unwrap().len(), 1);
+            for i in 1..50 {
+                db.run(&format!("insert into t1 values ({}, 'x')", i))
+                    .unwrap();
+            }
+            db.run("delete from t1 where a < 20").unwrap();
+            db.flush().unwrap();
+        }
+
+        let mut db = Database::new_on_disk(db_path).unwrap();
+        assert_eq!(db.run("select * from t1").unwrap().len(), 30);
+        assert_eq!(db.run("select b from t1 where a = 42").unwrap().len(), 1);
+        assert!(db.run("insert into t1 values (42, 'dup')").is_err());
+        db.run("insert into t1 values (7, 'y')").unwrap();
+        assert_eq!(db.run("select * from t1").unwrap().len(), 31);
+    }
+
+    #[test]
+    pub fn test_catalog_attach_database() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let other_path = temp_dir.path().join("other.db");
+        let other_path = other_path.to_str().unwrap();
+        {
+            let mut other = Database::new_on_disk(other_path).unwrap();
+            other
+                .run("create table t1 (a serial, b int check (b > 0))")
+                .unwrap();
+            other.run("insert into t1 (b) values (1), (2)").unwrap();
+            other.flush().unwrap();
+        }
+
+        let mut db = Database::new_temp().unwrap();
+        db.run("create table t1 (c int)").unwrap();
+        db.run("insert into t1 values (3), (4)").unwrap();
+        db.run(&format!("attach '{}' as other", other_path))
+            .unwrap();
+        assert!(db
+            .run(&format!("attach '{}' as other", other_path))
+            .is_err());
+        // the same file under another name or through another path
+        assert!(db
+            .run(&format!("attach '{}' as other2", other_path))
+            .is_err());
+        let other_alias = temp_dir.path().join(".").join("other.db");
+        assert!(db
+            .run(&format!("attach '{}' as other2", other_alias.display()))
+            .is_err());
+        let main_path = db.catalog.buffer_pool.disk_manager.path.clone();
+        assert!(db
+            .run(&format!("attach '{}' as main2", main_path.display()))
+            .is_err());
+
+        assert_eq!(db.run("select * from other.t1").unwrap().len(), 2);
+        db.run("insert into other.public.t1 (b) select c from t1")
+            .unwrap();
+        let tuples = db
+            .run("select other.public.t1.a from other.public.t1 where b = 4")
+            .unwrap();
+        assert_eq!(tuples[0].data, vec![ScalarValue::Int32(Some(4))]);
+        assert!(db.run("insert into other.t1 values (5, -1)").is_err());
+        assert_eq!(
+            db.run("select * from t1, other.t1 where c = b")
+                .unwrap()
+                .len(),
+            2
+        );
+
+        db.run("detach other").unwrap();
+        assert!(db.run("select * from other.t1").is_err());
+        assert!(db.run("detach other").is_err());
+
+        let mut other = Database::new_on_disk(other_path).unwrap();
+        assert_eq!(other.run("select * from t1").unwrap().len(), 4);
+    }
 }
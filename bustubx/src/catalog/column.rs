@@ -3,6 +3,9 @@ use std::sync::Arc;
 
 use crate::catalog::DataType;
 use crate::common::{ScalarValue, TableReference};
+use crate::expression::{Expr, ExprTrait};
+use crate::storage::EMPTY_TUPLE;
+use crate::{BustubxError, BustubxResult};
 
 pub type ColumnRef = Arc<Column>;
 
@@ -12,7 +15,50 @@ pub struct Column {
     pub name: String,
     pub data_type: DataType,
     pub nullable: bool,
-    pub default: ScalarValue,
+    pub default: Option<StoredExpr>,
+    pub identity: Option<IdentityGeneration>,
+    /// Whether the column sorts in descending order, only set in index key schemas
+    pub descending: bool,
+}
+
+/// How an identity column draws values from its sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentityGeneration {
+    /// Explicit values are rejected.
+    Always,
+    /// The sequence is only used when no value is given.
+    ByDefault,
+}
+
+impl std::fmt::Display for IdentityGeneration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdentityGeneration::Always => write!(f, "ALWAYS"),
+            IdentityGeneration::ByDefault => write!(f, "BY DEFAULT"),
+        }
+    }
+}
+
+impl TryFrom<&str> for IdentityGeneration {
+    type Error = BustubxError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "ALWAYS" => Ok(IdentityGeneration::Always),
+            "BY DEFAULT" => Ok(IdentityGeneration::ByDefault),
+            _ => Err(BustubxError::Internal(format!(
+                "Unknown identity generation {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// A bound expression kept with the SQL text it is persisted as in the information schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredExpr {
+    pub sql: String,
+    pub expr: Expr,
 }
 
 impl PartialEq for Column {
@@ -30,7 +76,20 @@ impl Column {
             name: name.into(),
             data_type,
             nullable,
-            default: ScalarValue::new_empty(data_type),
+            default: None,
+            identity: None,
+            descending: false,
+        }
+    }
+
+    /// Evaluates the default expression, NULL if the column has no default.
+    pub fn default_value(&self) -> BustubxResult<ScalarValue> {
+        match &self.default {
+            Some(default) => default
+                .expr
+                .evaluate(&EMPTY_TUPLE)?
+                .cast_to(&self.data_type),
+            None => Ok(ScalarValue::new_empty(self.data_type)),
         }
     }
 }
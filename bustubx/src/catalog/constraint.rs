@@ -0,0 +1,150 @@
+use crate::catalog::StoredExpr;
+use crate::common::TableReference;
+use crate::{BustubxError, BustubxResult};
+
+/// A table constraint checked by inserts and updates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    pub name: String,
+    pub kind: ConstraintKind,
+    /// Names of the constrained columns
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintKind {
+    /// Unique and not null, backed by a unique index with the constraint name
+    PrimaryKey,
+    /// Backed by a unique index with the constraint name, NULLs are never equal
+    Unique,
+    /// Values of the constrained columns must be present in the referenced table,
+    /// unless any of them is NULL
+    ForeignKey(ForeignKey),
+    /// The expression must not be false for any row, the constrained columns are the
+    /// ones it references
+    Check(StoredExpr),
+}
+
+/// The referenced side of a foreign key constraint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKey {
+    /// Referenced table, always qualified with its schema
+    pub table: TableReference,
+    /// Referenced columns matching a unique constraint of the referenced table, in the
+    /// order of the constrained columns
+    pub columns: Vec<String>,
+    pub on_delete: ReferentialAction,
+    pub on_update: ReferentialAction,
+}
+
+/// What happens to referencing rows when a referenced row is deleted or its key updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReferentialAction {
+    /// Same as `Restrict` as constraints are never deferred
+    #[default]
+    NoAction,
+    Restrict,
+    Cascade,
+    SetNull,
+    SetDefault,
+}
+
+impl ConstraintKind {
+    /// Whether the constraint is backed by a unique index.
+    pub fn is_unique(&self) -> bool {
+        matches!(self, ConstraintKind::PrimaryKey | ConstraintKind::Unique)
+    }
+}
+
+impl std::fmt::Display for ConstraintKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstraintKind::PrimaryKey => write!(f, "PRIMARY KEY"),
+            ConstraintKind::Unique => write!(f, "UNIQUE"),
+            ConstraintKind::ForeignKey(_) => write!(f, "FOREIGN KEY"),
+            ConstraintKind::Check(_) => write!(f, "CHECK"),
+        }
+    }
+}
+
+impl std::fmt::Display for ReferentialAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferentialAction::NoAction => write!(f, "NO ACTION"),
+            ReferentialAction::Restrict => write!(f, "RESTRICT"),
+            ReferentialAction::Cascade => write!(f, "CASCADE"),
+            ReferentialAction::SetNull => write!(f, "SET NULL"),
+            ReferentialAction::SetDefault => write!(f, "SET DEFAULT"),
+        }
+    }
+}
+
+impl TryFrom<&str> for ReferentialAction {
+    type Error = BustubxError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "NO ACTION" => Ok(ReferentialAction::NoAction),
+            "RESTRICT" => Ok(ReferentialAction::Restrict),
+            "CASCADE" => Ok(ReferentialAction::Cascade),
+            "SET NULL" => Ok(ReferentialAction::SetNull),
+            "SET DEFAULT" => Ok(ReferentialAction::SetDefault),
+            _ => Err(BustubxError::Internal(format!(
+                "unknown referential action {}",
+                value
+            ))),
+        }
+    }
+}
+
+impl From<&sqlparser::ast::ReferentialAction> for ReferentialAction {
+    fn from(value: &sqlparser::ast::ReferentialAction) -> Self {
+        match value {
+            sqlparser::ast::ReferentialAction::NoAction => ReferentialAction::NoAction,
+            sqlparser::ast::ReferentialAction::Restrict => ReferentialAction::Restrict,
+            sqlparser::ast::ReferentialAction::Cascade => ReferentialAction::Cascade,
+            sqlparser::ast::ReferentialAction::SetNull => ReferentialAction::SetNull,
+            sqlparser::ast::ReferentialAction::SetDefault => ReferentialAction::SetDefault,
+        }
+    }
+}
+
+/// Parses the kind of a unique constraint, foreign keys and checks need more columns of
+/// the information schema.
+impl TryFrom<&str> for ConstraintKind {
+    type Error = BustubxError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "PRIMARY KEY" => Ok(ConstraintKind::PrimaryKey),
+            "UNIQUE" => Ok(ConstraintKind::Unique),
+            _ => Err(BustubxError::Internal(format!(
+                "unknown constraint type {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Joins constraint column names to store them in the information schema.
+pub fn constraint_columns_to_varchar(columns: &[String]) -> String {
+    columns.join(", ")
+}
+
+pub fn parse_constraint_columns_from_varchar(varchar: &str) -> BustubxResult<Vec<String>> {
+    // a check may reference no column at all
+    if varchar.is_empty() {
+        return Ok(vec![]);
+    }
+    let columns = varchar
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .collect::<Vec<String>>();
+    if columns.iter().any(|name| name.is_empty()) {
+        return Err(BustubxError::Internal(format!(
+            "invalid constraint columns {}",
+            varchar
+        )));
+    }
+    Ok(columns)
+}
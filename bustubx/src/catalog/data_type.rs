@@ -1,3 +1,6 @@
+use crate::common::decimal::{
+    DEFAULT_DECIMAL_PRECISION, DEFAULT_DECIMAL_SCALE, MAX_DECIMAL_PRECISION,
+};
 use crate::error::BustubxError;
 use crate::BustubxResult;
 use sqlparser::dialect::PostgreSqlDialect;
@@ -15,10 +18,113 @@ pub enum DataType {
     UInt64,
     Float32,
     Float64,
+    /// Fixed-point number with precision (total digits) and scale (fractional digits),
+    /// stored as an i128 scaled by 10^scale
+    Decimal(u8, u8),
     Varchar(Option<usize>),
+    /// Variable-length raw bytes
+    Binary,
+    /// A validated JSON document
+    Json,
+    /// Days since 1970-01-01
+    Date,
+    /// Microseconds since midnight
+    Time,
+    /// Microseconds since 1970-01-01 00:00:00, without time zone
+    Timestamp,
+    /// Microseconds since 1970-01-01 00:00:00 UTC
+    TimestampTz,
+    Interval,
 }
 
 impl DataType {
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            DataType::Int8
+                | DataType::Int16
+                | DataType::Int32
+                | DataType::Int64
+                | DataType::UInt8
+                | DataType::UInt16
+                | DataType::UInt32
+                | DataType::UInt64
+                | DataType::Float32
+                | DataType::Float64
+                | DataType::Decimal(_, _)
+        )
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.is_numeric()
+            && !matches!(
+                self,
+                DataType::Float32 | DataType::Float64 | DataType::Decimal(_, _)
+            )
+    }
+
+    /// The precision and scale of a decimal able to hold every value of an exact numeric type
+    pub fn decimal_precision_scale(&self) -> Option<(u8, u8)> {
+        match self {
+            DataType::Int8 | DataType::UInt8 => Some((3, 0)),
+            DataType::Int16 | DataType::UInt16 => Some((5, 0)),
+            DataType::Int32 | DataType::UInt32 => Some((10, 0)),
+            DataType::Int64 => Some((19, 0)),
+            DataType::UInt64 => Some((20, 0)),
+            DataType::Decimal(precision, scale) => Some((*precision, *scale)),
+            _ => None,
+        }
+    }
+
+    pub fn try_new_decimal(precision: u8, scale: u8) -> BustubxResult<DataType> {
+        if precision == 0 || precision > MAX_DECIMAL_PRECISION {
+            return Err(BustubxError::Plan(format!(
+                "decimal precision {} must be between 1 and {}",
+                precision, MAX_DECIMAL_PRECISION
+            )));
+        }
+        if scale > precision {
+            return Err(BustubxError::Plan(format!(
+                "decimal scale {} must be between 0 and precision {}",
+                scale, precision
+            )));
+        }
+        Ok(DataType::Decimal(precision, scale))
+    }
+
+    pub fn is_temporal(&self) -> bool {
+        matches!(
+            self,
+            DataType::Date
+                | DataType::Time
+                | DataType::Timestamp
+                | DataType::TimestampTz
+                | DataType::Interval
+        )
+    }
+
+    /// Coerce `lhs_type` and `rhs_type` to a common type for the purposes of a comparison
+    /// operation, string literals are compared as temporal values with temporal types.
+    pub fn comparison_coercion(l: &DataType, r: &DataType) -> BustubxResult<DataType> {
+        use super::DataType::*;
+        if l == r {
+            return Ok(*l);
+        }
+        match (l, r) {
+            (Varchar(_), Varchar(_)) => Ok(Varchar(None)),
+            (Varchar(_), t) | (t, Varchar(_)) if t.is_temporal() => Ok(*t),
+            (Varchar(_), Binary) | (Binary, Varchar(_)) => Ok(Binary),
+            (Varchar(_), Json) | (Json, Varchar(_)) => Ok(Json),
+            (Date, Timestamp) | (Timestamp, Date) => Ok(Timestamp),
+            (Date | Timestamp, TimestampTz) | (TimestampTz, Date | Timestamp) => Ok(TimestampTz),
+            _ if l.is_numeric() && r.is_numeric() => Self::comparison_numeric_coercion(l, r),
+            _ => Err(BustubxError::Plan(format!(
+                "Cannot compare {} and {}",
+                l, r
+            ))),
+        }
+    }
+
     /// Coerce `lhs_type` and `rhs_type` to a common type for the purposes of a comparison operation
     /// where one both are numeric
     pub fn comparison_numeric_coercion(l: &DataType, r: &DataType) -> BustubxResult<DataType> {
@@ -28,7 +134,25 @@ impl DataType {
         }
         match (l, r) {
             (Float64, _) | (_, Float64) => Ok(Float64),
+            (Decimal(_, _), Float32) | (Float32, Decimal(_, _)) => Ok(Float64),
             (_, Float32) | (Float32, _) => Ok(Float32),
+            (Decimal(_, _), _) | (_, Decimal(_, _)) => {
+                let (Some((p1, s1)), Some((p2, s2))) =
+                    (l.decimal_precision_scale(), r.decimal_precision_scale())
+                else {
+                    return Err(BustubxError::Internal(format!(
+                        "Cannot coerce {} and {} for comparison",
+                        l, r
+                    )));
+                };
+                // keep all integer and fractional digits of both sides
+                let scale = s1.max(s2);
+                let integer_digits = (p1 - s1).max(p2 - s2);
+                Ok(Decimal(
+                    (integer_digits + scale).min(MAX_DECIMAL_PRECISION),
+                    scale,
+                ))
+            }
             // The following match arms encode the following logic: Given the two
             // integral types, we choose the narrowest possible integral type that
             // accommodates all values of both types. Note that some information
@@ -68,6 +192,35 @@ impl DataType {
     }
 }
 
+impl DataType {
+    /// Coerce the types of a column in the branches of a set operation (UNION, INTERSECT
+    /// and EXCEPT) to a common type
+    pub fn set_operation_coercion(l: &DataType, r: &DataType) -> BustubxResult<DataType> {
+        use super::DataType::*;
+        if l == r {
+            return Ok(*l);
+        }
+        match (l, r) {
+            (Varchar(l_len), Varchar(r_len)) => Ok(Varchar(match (l_len, r_len) {
+                (Some(l_len), Some(r_len)) => Some(*l_len.max(r_len)),
+                _ => None,
+            })),
+            (Boolean, _)
+            | (_, Boolean)
+            | (Varchar(_), _)
+            | (_, Varchar(_))
+            | (Binary, _)
+            | (_, Binary)
+            | (Json, _)
+            | (_, Json) => Err(BustubxError::Plan(format!(
+                "{} and {} types cannot be matched",
+                l, r
+            ))),
+            _ => Self::comparison_coercion(l, r),
+        }
+    }
+}
+
 impl TryFrom<&sqlparser::ast::DataType> for DataType {
     type Error = BustubxError;
 
@@ -96,6 +249,50 @@ impl TryFrom<&sqlparser::ast::DataType> for DataType {
             sqlparser::ast::DataType::CharacterVarying(len) => {
                 Ok(DataType::Varchar(len.map(|l| l.length as usize)))
             }
+            sqlparser::ast::DataType::Decimal(info)
+            | sqlparser::ast::DataType::Numeric(info)
+            | sqlparser::ast::DataType::Dec(info) => {
+                let (precision, scale) = match info {
+                    sqlparser::ast::ExactNumberInfo::None => (
+                        DEFAULT_DECIMAL_PRECISION as u64,
+                        DEFAULT_DECIMAL_SCALE as u64,
+                    ),
+                    sqlparser::ast::ExactNumberInfo::Precision(precision) => (*precision, 0),
+                    sqlparser::ast::ExactNumberInfo::PrecisionAndScale(precision, scale) => {
+                        (*precision, *scale)
+                    }
+                };
+                let (Ok(precision), Ok(scale)) = (u8::try_from(precision), u8::try_from(scale))
+                else {
+                    return Err(BustubxError::Plan(format!("Invalid datatype {}", value)));
+                };
+                DataType::try_new_decimal(precision, scale)
+            }
+            sqlparser::ast::DataType::Bytea
+            | sqlparser::ast::DataType::Blob(_)
+            | sqlparser::ast::DataType::Binary(_)
+            | sqlparser::ast::DataType::Varbinary(_) => Ok(DataType::Binary),
+            sqlparser::ast::DataType::JSON => Ok(DataType::Json),
+            sqlparser::ast::DataType::Custom(name, modifiers)
+                if modifiers.is_empty() && name.to_string().eq_ignore_ascii_case("jsonb") =>
+            {
+                Ok(DataType::Json)
+            }
+            sqlparser::ast::DataType::Date => Ok(DataType::Date),
+            sqlparser::ast::DataType::Time(_, sqlparser::ast::TimezoneInfo::None)
+            | sqlparser::ast::DataType::Time(_, sqlparser::ast::TimezoneInfo::WithoutTimeZone) => {
+                Ok(DataType::Time)
+            }
+            sqlparser::ast::DataType::Timestamp(_, sqlparser::ast::TimezoneInfo::None)
+            | sqlparser::ast::DataType::Timestamp(
+                _,
+                sqlparser::ast::TimezoneInfo::WithoutTimeZone,
+            ) => Ok(DataType::Timestamp),
+            sqlparser::ast::DataType::Timestamp(
+                _,
+                sqlparser::ast::TimezoneInfo::WithTimeZone | sqlparser::ast::TimezoneInfo::Tz,
+            ) => Ok(DataType::TimestampTz),
+            sqlparser::ast::DataType::Interval => Ok(DataType::Interval),
             _ => Err(BustubxError::NotSupport(format!(
                 "Not support datatype {}",
                 value
@@ -118,12 +315,32 @@ impl From<&DataType> for sqlparser::ast::DataType {
             DataType::UInt64 => sqlparser::ast::DataType::UnsignedBigInt(None),
             DataType::Float32 => sqlparser::ast::DataType::Float(None),
             DataType::Float64 => sqlparser::ast::DataType::Double,
+            DataType::Decimal(precision, scale) => sqlparser::ast::DataType::Decimal(
+                sqlparser::ast::ExactNumberInfo::PrecisionAndScale(
+                    *precision as u64,
+                    *scale as u64,
+                ),
+            ),
             DataType::Varchar(len) => {
                 sqlparser::ast::DataType::Varchar(len.map(|l| sqlparser::ast::CharacterLength {
                     length: l as u64,
                     unit: None,
                 }))
             }
+            DataType::Binary => sqlparser::ast::DataType::Bytea,
+            DataType::Json => sqlparser::ast::DataType::JSON,
+            DataType::Date => sqlparser::ast::DataType::Date,
+            DataType::Time => {
+                sqlparser::ast::DataType::Time(None, sqlparser::ast::TimezoneInfo::None)
+            }
+            DataType::Timestamp => {
+                sqlparser::ast::DataType::Timestamp(None, sqlparser::ast::TimezoneInfo::None)
+            }
+            DataType::TimestampTz => sqlparser::ast::DataType::Timestamp(
+                None,
+                sqlparser::ast::TimezoneInfo::WithTimeZone,
+            ),
+            DataType::Interval => sqlparser::ast::DataType::Interval,
         }
     }
 }
@@ -141,7 +358,15 @@ impl std::fmt::Display for DataType {
             | DataType::UInt32
             | DataType::UInt64
             | DataType::Float32
-            | DataType::Float64 => write!(f, "{self:?}"),
+            | DataType::Float64
+            | DataType::Binary
+            | DataType::Json
+            | DataType::Date
+            | DataType::Time
+            | DataType::Timestamp
+            | DataType::TimestampTz
+            | DataType::Interval => write!(f, "{self:?}"),
+            DataType::Decimal(precision, scale) => write!(f, "Decimal({precision},{scale})"),
             DataType::Varchar(len_opt) => {
                 if let Some(len) = len_opt {
                     write!(f, "Varchar({len})")
@@ -181,5 +406,16 @@ mod tests {
             DataType::try_from(format!("{sql_type}").as_str()).unwrap(),
             DataType::Varchar(Some(100))
         );
+
+        let sql_type: sqlparser::ast::DataType = (&DataType::Decimal(10, 2)).into();
+        assert_eq!(
+            DataType::try_from(format!("{sql_type}").as_str()).unwrap(),
+            DataType::Decimal(10, 2)
+        );
+        assert_eq!(
+            DataType::try_from("numeric").unwrap(),
+            DataType::Decimal(38, 10)
+        );
+        assert!(DataType::try_from("decimal(3, 4)").is_err());
     }
 }
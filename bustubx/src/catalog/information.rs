@@ -1,10 +1,16 @@
 use crate::buffer::{AtomicPageId, PageId, INVALID_PAGE_ID};
-use crate::catalog::catalog::{CatalogSchema, CatalogTable};
-use crate::catalog::{Catalog, Column, DataType, Schema, SchemaRef, DEFAULT_SCHEMA_NAME};
+use crate::catalog::catalog::{CatalogSchema, CatalogTable, CatalogView};
+use crate::catalog::{
+    parse_constraint_columns_from_varchar, Catalog, Column, Constraint, ConstraintKind, DataType,
+    ForeignKey, IndexType, ResolvedSequenceOptions, Schema, SchemaRef, Sequence, SequenceRow,
+    StoredExpr, DEFAULT_SCHEMA_NAME,
+};
 use crate::common::{ScalarValue, TableReference};
-use crate::storage::TableHeap;
+use crate::planner::{LogicalPlanner, PlannerContext};
+use crate::storage::{TableHeap, TableIterator};
 use crate::{BustubxError, BustubxResult, Database};
 
+use crate::storage::hash_index::HashIndex;
 use crate::storage::index::BPlusTreeIndex;
 use std::sync::Arc;
 
@@ -13,6 +19,9 @@ pub static INFORMATION_SCHEMA_SCHEMAS: &str = "schemas";
 pub static INFORMATION_SCHEMA_TABLES: &str = "tables";
 pub static INFORMATION_SCHEMA_COLUMNS: &str = "columns";
 pub static INFORMATION_SCHEMA_INDEXES: &str = "indexes";
+pub static INFORMATION_SCHEMA_CONSTRAINTS: &str = "constraints";
+pub static INFORMATION_SCHEMA_SEQUENCES: &str = "sequences";
+pub static INFORMATION_SCHEMA_VIEWS: &str = "views";
 
 lazy_static::lazy_static! {
     pub static ref SCHEMAS_SCHMEA: SchemaRef = Arc::new(Schema::new(vec![
@@ -34,7 +43,8 @@ lazy_static::lazy_static! {
         Column::new("column_name", DataType::Varchar(None), false),
         Column::new("data_type", DataType::Varchar(None), false),
         Column::new("nullable", DataType::Boolean, false),
-        Column::new("default", DataType::Varchar(None), false),
+        Column::new("default", DataType::Varchar(None), true),
+        Column::new("identity_generation", DataType::Varchar(None), true),
     ]));
 
     pub static ref INDEXES_SCHMEA: SchemaRef = Arc::new(Schema::new(vec![
@@ -45,7 +55,45 @@ lazy_static::lazy_static! {
         Column::new("key_schema", DataType::Varchar(None), false),
         Column::new("internal_max_size", DataType::UInt32, false),
         Column::new("leaf_max_size", DataType::UInt32, false),
-        Column::new("root_page_id", DataType::UInt32, false),
+        Column::new("header_page_id", DataType::UInt32, false),
+        Column::new("index_type", DataType::Varchar(None), false),
+    ]));
+
+    pub static ref CONSTRAINTS_SCHMEA: SchemaRef = Arc::new(Schema::new(vec![
+        Column::new("table_catalog", DataType::Varchar(None), false),
+        Column::new("table_schema", DataType::Varchar(None), false),
+        Column::new("table_name", DataType::Varchar(None), false),
+        Column::new("constraint_name", DataType::Varchar(None), false),
+        Column::new("constraint_type", DataType::Varchar(None), false),
+        Column::new("column_names", DataType::Varchar(None), false),
+        Column::new("foreign_table_schema", DataType::Varchar(None), true),
+        Column::new("foreign_table_name", DataType::Varchar(None), true),
+        Column::new("foreign_column_names", DataType::Varchar(None), true),
+        Column::new("update_rule", DataType::Varchar(None), true),
+        Column::new("delete_rule", DataType::Varchar(None), true),
+        Column::new("check_clause", DataType::Varchar(None), true),
+    ]));
+
+    pub static ref SEQUENCES_SCHMEA: SchemaRef = Arc::new(Schema::new(vec![
+        Column::new("sequence_catalog", DataType::Varchar(None), false),
+        Column::new("sequence_schema", DataType::Varchar(None), false),
+        Column::new("sequence_name", DataType::Varchar(None), false),
+        Column::new("start_value", DataType::Int64, false),
+        Column::new("minimum_value", DataType::Int64, false),
+        Column::new("maximum_value", DataType::Int64, false),
+        Column::new("increment", DataType::Int64, false),
+        Column::new("cycle_option", DataType::Boolean, false),
+        Column::new("last_value", DataType::Int64, false),
+        Column::new("is_called", DataType::Boolean, false),
+    ]));
+
+    pub static ref VIEWS_SCHMEA: SchemaRef = Arc::new(Schema::new(vec![
+        Column::new("table_catalog", DataType::Varchar(None), false),
+        Column::new("table_schema", DataType::Varchar(None), false),
+        Column::new("table_name", DataType::Varchar(None), false),
+        Column::new("view_definition", DataType::Varchar(None), false),
+        Column::new("column_names", DataType::Varchar(None), true),
+        Column::new("is_materialized", DataType::Boolean, false),
     ]));
 }
 
@@ -53,8 +101,11 @@ pub fn load_catalog_data(db: &mut Database) -> BustubxResult<()> {
     load_information_schema(&mut db.catalog)?;
     load_schemas(db)?;
     create_default_schema_if_not_exists(&mut db.catalog)?;
+    load_sequences(&mut db.catalog)?;
     load_user_tables(db)?;
     load_user_indexes(db)?;
+    load_user_constraints(db)?;
+    load_views(db)?;
     Ok(())
 }
 
@@ -71,6 +122,11 @@ fn load_information_schema(catalog: &mut Catalog) -> BustubxResult<()> {
     let information_schema_tables_first_page_id = meta.information_schema_tables_first_page_id;
     let information_schema_columns_first_page_id = meta.information_schema_columns_first_page_id;
     let information_schema_indexes_first_page_id = meta.information_schema_indexes_first_page_id;
+    let information_schema_constraints_first_page_id =
+        meta.information_schema_constraints_first_page_id;
+    let information_schema_sequences_first_page_id =
+        meta.information_schema_sequences_first_page_id;
+    let information_schema_views_first_page_id = meta.information_schema_views_first_page_id;
     drop(meta);
 
     // load last page id
@@ -94,6 +150,21 @@ fn load_information_schema(catalog: &mut Catalog) -> BustubxResult<()> {
         information_schema_indexes_first_page_id,
         INDEXES_SCHMEA.clone(),
     )?;
+    let information_schema_constraints_last_page_id = load_table_last_page_id(
+        catalog,
+        information_schema_constraints_first_page_id,
+        CONSTRAINTS_SCHMEA.clone(),
+    )?;
+    let information_schema_sequences_last_page_id = load_table_last_page_id(
+        catalog,
+        information_schema_sequences_first_page_id,
+        SEQUENCES_SCHMEA.clone(),
+    )?;
+    let information_schema_views_last_page_id = load_table_last_page_id(
+        catalog,
+        information_schema_views_first_page_id,
+        VIEWS_SCHMEA.clone(),
+    )?;
 
     let mut information_schema = CatalogSchema::new(INFORMATION_SCHEMA_NAME);
 
@@ -102,6 +173,7 @@ fn load_information_schema(catalog: &mut Catalog) -> BustubxResult<()> {
         buffer_pool: catalog.buffer_pool.clone(),
         first_page_id: AtomicPageId::new(information_schema_schemas_first_page_id),
         last_page_id: AtomicPageId::new(information_schema_schemas_last_page_id),
+        visibility_map: Default::default(),
     };
     information_schema.tables.insert(
         INFORMATION_SCHEMA_SCHEMAS.to_string(),
@@ -113,6 +185,7 @@ fn load_information_schema(catalog: &mut Catalog) -> BustubxResult<()> {
         buffer_pool: catalog.buffer_pool.clone(),
         first_page_id: AtomicPageId::new(information_schema_tables_first_page_id),
         last_page_id: AtomicPageId::new(information_schema_tables_last_page_id),
+        visibility_map: Default::default(),
     };
     information_schema.tables.insert(
         INFORMATION_SCHEMA_TABLES.to_string(),
@@ -124,6 +197,7 @@ fn load_information_schema(catalog: &mut Catalog) -> BustubxResult<()> {
         buffer_pool: catalog.buffer_pool.clone(),
         first_page_id: AtomicPageId::new(information_schema_columns_first_page_id),
         last_page_id: AtomicPageId::new(information_schema_columns_last_page_id),
+        visibility_map: Default::default(),
     };
     information_schema.tables.insert(
         INFORMATION_SCHEMA_COLUMNS.to_string(),
@@ -135,12 +209,49 @@ fn load_information_schema(catalog: &mut Catalog) -> BustubxResult<()> {
         buffer_pool: catalog.buffer_pool.clone(),
         first_page_id: AtomicPageId::new(information_schema_indexes_first_page_id),
         last_page_id: AtomicPageId::new(information_schema_indexes_last_page_id),
+        visibility_map: Default::default(),
     };
     information_schema.tables.insert(
         INFORMATION_SCHEMA_INDEXES.to_string(),
         CatalogTable::new(INFORMATION_SCHEMA_INDEXES, Arc::new(indexes_table)),
     );
 
+    let constraints_table = TableHeap {
+        schema: CONSTRAINTS_SCHMEA.clone(),
+        buffer_pool: catalog.buffer_pool.clone(),
+        first_page_id: AtomicPageId::new(information_schema_constraints_first_page_id),
+        last_page_id: AtomicPageId::new(information_schema_constraints_last_page_id),
+        visibility_map: Default::default(),
+    };
+    information_schema.tables.insert(
+        INFORMATION_SCHEMA_CONSTRAINTS.to_string(),
+        CatalogTable::new(INFORMATION_SCHEMA_CONSTRAINTS, Arc::new(constraints_table)),
+    );
+
+    let sequences_table = TableHeap {
+        schema: SEQUENCES_SCHMEA.clone(),
+        buffer_pool: catalog.buffer_pool.clone(),
+        first_page_id: AtomicPageId::new(information_schema_sequences_first_page_id),
+        last_page_id: AtomicPageId::new(information_schema_sequences_last_page_id),
+        visibility_map: Default::default(),
+    };
+    information_schema.tables.insert(
+        INFORMATION_SCHEMA_SEQUENCES.to_string(),
+        CatalogTable::new(INFORMATION_SCHEMA_SEQUENCES, Arc::new(sequences_table)),
+    );
+
+    let views_table = TableHeap {
+        schema: VIEWS_SCHMEA.clone(),
+        buffer_pool: catalog.buffer_pool.clone(),
+        first_page_id: AtomicPageId::new(information_schema_views_first_page_id),
+        last_page_id: AtomicPageId::new(information_schema_views_last_page_id),
+        visibility_map: Default::default(),
+    };
+    information_schema.tables.insert(
+        INFORMATION_SCHEMA_VIEWS.to_string(),
+        CatalogTable::new(INFORMATION_SCHEMA_VIEWS, Arc::new(views_table)),
+    );
+
     catalog.load_schema(INFORMATION_SCHEMA_NAME, information_schema);
     Ok(())
 }
@@ -167,6 +278,67 @@ fn load_schemas(db: &mut Database) -> BustubxResult<()> {
     Ok(())
 }
 
+fn load_sequences(catalog: &mut Catalog) -> BustubxResult<()> {
+    // sequences are updated in place, so their rows are read with record ids
+    let sequences_table = catalog.table_heap(&TableReference::partial(
+        INFORMATION_SCHEMA_NAME,
+        INFORMATION_SCHEMA_SEQUENCES,
+    ))?;
+    let mut iterator = TableIterator::new(sequences_table.clone(), ..);
+    while let Some((rid, sequence_tuple)) = iterator.next()? {
+        let error = Err(BustubxError::Internal(format!(
+            "Failed to decode sequence tuple: {:?}",
+            sequence_tuple
+        )));
+        let (
+            ScalarValue::Varchar(Some(_catalog)),
+            ScalarValue::Varchar(Some(schema_name)),
+            ScalarValue::Varchar(Some(sequence_name)),
+            ScalarValue::Int64(Some(start)),
+            ScalarValue::Int64(Some(min_value)),
+            ScalarValue::Int64(Some(max_value)),
+            ScalarValue::Int64(Some(increment)),
+            ScalarValue::Boolean(Some(cycle)),
+            ScalarValue::Int64(Some(last_value)),
+            ScalarValue::Boolean(Some(is_called)),
+        ) = (
+            sequence_tuple.value(0)?,
+            sequence_tuple.value(1)?,
+            sequence_tuple.value(2)?,
+            sequence_tuple.value(3)?,
+            sequence_tuple.value(4)?,
+            sequence_tuple.value(5)?,
+            sequence_tuple.value(6)?,
+            sequence_tuple.value(7)?,
+            sequence_tuple.value(8)?,
+            sequence_tuple.value(9)?,
+        )
+        else {
+            return error;
+        };
+        let options = ResolvedSequenceOptions {
+            start: *start,
+            increment: *increment,
+            min_value: *min_value,
+            max_value: *max_value,
+            cycle: *cycle,
+        };
+        catalog.load_sequence(Arc::new(Sequence::new(
+            schema_name,
+            sequence_name,
+            options,
+            SequenceRow::new(
+                sequences_table.clone(),
+                catalog.sequences_latch.clone(),
+                rid,
+            ),
+            *last_value,
+            *is_called,
+        )))?;
+    }
+    Ok(())
+}
+
 fn load_user_tables(db: &mut Database) -> BustubxResult<()> {
     let table_tuples = db.run(&format!(
         "select * from {}.{}",
@@ -207,13 +379,31 @@ fn load_user_tables(db: &mut Database) -> BustubxResult<()> {
             let ScalarValue::Boolean(Some(nullable)) = column_tuple.value(5)? else {
                 return error;
             };
-            let ScalarValue::Varchar(Some(default)) = column_tuple.value(6)? else {
+            let ScalarValue::Varchar(default) = column_tuple.value(6)? else {
+                return error;
+            };
+            let ScalarValue::Varchar(identity) = column_tuple.value(7)? else {
                 return error;
             };
             let data_type: DataType = data_type_str.as_str().try_into()?;
-            let default = ScalarValue::from_string(default, data_type)?;
-            columns
-                .push(Column::new(column_name.clone(), data_type, *nullable).with_default(default));
+            let default = match default {
+                Some(sql) => Some(bind_stored_expr(&db.catalog, sql)?),
+                None => None,
+            };
+            let identity = match identity {
+                Some(identity) => Some(identity.as_str().try_into()?),
+                None => None,
+            };
+            columns.push(
+                Column::new(column_name.clone(), data_type, *nullable)
+                    .with_relation(Some(TableReference::full(
+                        catalog,
+                        table_schema,
+                        table_name,
+                    )))
+                    .with_default(default)
+                    .with_identity(identity),
+            );
         }
         let schema = Arc::new(Schema::new(columns));
 
@@ -225,6 +415,7 @@ fn load_user_tables(db: &mut Database) -> BustubxResult<()> {
             buffer_pool: db.buffer_pool.clone(),
             first_page_id: AtomicPageId::new(*first_page_id),
             last_page_id: AtomicPageId::new(last_page_id),
+            visibility_map: Default::default(),
         };
         db.catalog.load_table(
             TableReference::full(catalog, table_schema, table_name),
@@ -265,7 +456,10 @@ fn load_user_indexes(db: &mut Database) -> BustubxResult<()> {
         let ScalarValue::UInt32(Some(leaf_max_size)) = index_tuple.value(6)? else {
             return error;
         };
-        let ScalarValue::UInt32(Some(root_page_id)) = index_tuple.value(7)? else {
+        let ScalarValue::UInt32(Some(header_page_id)) = index_tuple.value(7)? else {
+            return error;
+        };
+        let ScalarValue::Varchar(Some(index_type)) = index_tuple.value(8)? else {
             return error;
         };
 
@@ -276,19 +470,159 @@ fn load_user_indexes(db: &mut Database) -> BustubxResult<()> {
             table_schema,
         )?);
 
-        let b_plus_tree_index = BPlusTreeIndex {
+        if IndexType::try_from(index_type.as_str())? == IndexType::Hash {
+            let hash_index = HashIndex::new(
+                key_schema,
+                db.buffer_pool.clone(),
+                *leaf_max_size,
+                *header_page_id,
+            );
+            db.catalog
+                .load_hash_index(table_ref, index_name, Arc::new(hash_index))?;
+            continue;
+        }
+        let b_plus_tree_index = BPlusTreeIndex::load(
             key_schema,
-            buffer_pool: db.buffer_pool.clone(),
-            internal_max_size: *internal_max_size,
-            leaf_max_size: *leaf_max_size,
-            root_page_id: AtomicPageId::new(*root_page_id),
-        };
+            db.buffer_pool.clone(),
+            *internal_max_size,
+            *leaf_max_size,
+            *header_page_id,
+        )?;
         db.catalog
             .load_index(table_ref, index_name, Arc::new(b_plus_tree_index))?;
     }
     Ok(())
 }
 
+fn load_user_constraints(db: &mut Database) -> BustubxResult<()> {
+    let constraint_tuples = db.run(&format!(
+        "select * from {}.{}",
+        INFORMATION_SCHEMA_NAME, INFORMATION_SCHEMA_CONSTRAINTS
+    ))?;
+    for constraint_tuple in constraint_tuples.into_iter() {
+        let error = Err(BustubxError::Internal(format!(
+            "Failed to decode constraint tuple: {:?}",
+            constraint_tuple
+        )));
+        let ScalarValue::Varchar(Some(catalog_name)) = constraint_tuple.value(0)? else {
+            return error;
+        };
+        let ScalarValue::Varchar(Some(table_schema_name)) = constraint_tuple.value(1)? else {
+            return error;
+        };
+        let ScalarValue::Varchar(Some(table_name)) = constraint_tuple.value(2)? else {
+            return error;
+        };
+        let ScalarValue::Varchar(Some(constraint_name)) = constraint_tuple.value(3)? else {
+            return error;
+        };
+        let ScalarValue::Varchar(Some(constraint_type)) = constraint_tuple.value(4)? else {
+            return error;
+        };
+        let ScalarValue::Varchar(Some(column_names)) = constraint_tuple.value(5)? else {
+            return error;
+        };
+
+        let kind = if constraint_type == "CHECK" {
+            let ScalarValue::Varchar(Some(check_clause)) = constraint_tuple.value(11)? else {
+                return error;
+            };
+            ConstraintKind::Check(bind_stored_expr(&db.catalog, check_clause)?)
+        } else if constraint_type == "FOREIGN KEY" {
+            let (
+                ScalarValue::Varchar(Some(foreign_table_schema)),
+                ScalarValue::Varchar(Some(foreign_table_name)),
+                ScalarValue::Varchar(Some(foreign_column_names)),
+                ScalarValue::Varchar(Some(update_rule)),
+                ScalarValue::Varchar(Some(delete_rule)),
+            ) = (
+                constraint_tuple.value(6)?,
+                constraint_tuple.value(7)?,
+                constraint_tuple.value(8)?,
+                constraint_tuple.value(9)?,
+                constraint_tuple.value(10)?,
+            )
+            else {
+                return error;
+            };
+            ConstraintKind::ForeignKey(ForeignKey {
+                table: TableReference::partial(foreign_table_schema, foreign_table_name),
+                columns: parse_constraint_columns_from_varchar(foreign_column_names)?,
+                on_delete: delete_rule.as_str().try_into()?,
+                on_update: update_rule.as_str().try_into()?,
+            })
+        } else {
+            constraint_type.as_str().try_into()?
+        };
+
+        let table_ref = TableReference::full(catalog_name, table_schema_name, table_name);
+        let constraint = Constraint {
+            name: constraint_name.clone(),
+            kind,
+            columns: parse_constraint_columns_from_varchar(column_names)?,
+        };
+        db.catalog.load_constraint(table_ref, constraint)?;
+    }
+    Ok(())
+}
+
+fn load_views(db: &mut Database) -> BustubxResult<()> {
+    let view_tuples = db.run(&format!(
+        "select * from {}.{}",
+        INFORMATION_SCHEMA_NAME, INFORMATION_SCHEMA_VIEWS
+    ))?;
+    for view_tuple in view_tuples.into_iter() {
+        let error = Err(BustubxError::Internal(format!(
+            "Failed to decode view tuple: {:?}",
+            view_tuple
+        )));
+        let (
+            ScalarValue::Varchar(Some(catalog_name)),
+            ScalarValue::Varchar(Some(view_schema_name)),
+            ScalarValue::Varchar(Some(view_name)),
+            ScalarValue::Varchar(Some(definition)),
+            ScalarValue::Varchar(column_names),
+            ScalarValue::Boolean(Some(materialized)),
+        ) = (
+            view_tuple.value(0)?,
+            view_tuple.value(1)?,
+            view_tuple.value(2)?,
+            view_tuple.value(3)?,
+            view_tuple.value(4)?,
+            view_tuple.value(5)?,
+        )
+        else {
+            return error;
+        };
+        let columns = match column_names {
+            Some(column_names) => parse_constraint_columns_from_varchar(column_names)?,
+            None => vec![],
+        };
+        db.catalog.load_view(
+            TableReference::full(catalog_name, view_schema_name, view_name),
+            CatalogView {
+                name: view_name.clone(),
+                definition: definition.clone(),
+                columns,
+                materialized: *materialized,
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Binds a persisted default or check expression again.
+fn bind_stored_expr(catalog: &Catalog, sql: &str) -> BustubxResult<StoredExpr> {
+    let planner = LogicalPlanner {
+        context: PlannerContext::new(catalog),
+    };
+    let expr = planner.bind_expr(&crate::parser::parse_expr(sql)?)?;
+    Ok(StoredExpr {
+        sql: sql.to_string(),
+        expr,
+    })
+}
+
 fn load_table_last_page_id(
     catalog: &mut Catalog,
     first_page_id: PageId,
@@ -312,20 +646,29 @@ pub fn key_schema_to_varchar(key_schema: &Schema) -> String {
     key_schema
         .columns
         .iter()
-        .map(|col| col.name.as_str())
+        .map(|col| {
+            if col.descending {
+                format!("{} DESC", col.name)
+            } else {
+                col.name.clone()
+            }
+        })
         .collect::<Vec<_>>()
         .join(", ")
 }
 
 fn parse_key_schema_from_varchar(varchar: &str, table_schema: SchemaRef) -> BustubxResult<Schema> {
-    let column_names = varchar
+    let columns = varchar
         .split(",")
-        .into_iter()
-        .map(|name| name.trim())
-        .collect::<Vec<&str>>();
-    let indices = column_names
-        .into_iter()
-        .map(|name| table_schema.index_of(None, name))
-        .collect::<BustubxResult<Vec<usize>>>()?;
-    table_schema.project(&indices)
+        .map(|name| {
+            let name = name.trim();
+            let (name, descending) = match name.strip_suffix(" DESC") {
+                Some(name) => (name, true),
+                None => (name, false),
+            };
+            let column = table_schema.column_with_name(None, name)?;
+            Ok(column.as_ref().clone().with_descending(descending))
+        })
+        .collect::<BustubxResult<Vec<Column>>>()?;
+    Ok(Schema::new(columns))
 }
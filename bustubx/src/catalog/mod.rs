@@ -1,11 +1,15 @@
 mod catalog;
 mod column;
+mod constraint;
 mod data_type;
 mod information;
 mod schema;
+mod sequence;
 
 pub use catalog::*;
-pub use column::{Column, ColumnRef};
+pub use column::{Column, ColumnRef, IdentityGeneration, StoredExpr};
+pub use constraint::*;
 pub use data_type::DataType;
 pub use information::*;
 pub use schema::*;
+pub use sequence::*;
@@ -0,0 +1,328 @@
+use std::sync::{Arc, Mutex};
+
+use crate::catalog::{DataType, DEFAULT_CATALOG_NAME, SEQUENCES_SCHMEA};
+use crate::storage::{RecordId, TableHeap, EMPTY_TUPLE_META};
+use crate::{BustubxError, BustubxResult, Tuple};
+
+/// Sequence options given by `CREATE SEQUENCE` or an identity column, unset ones take defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SequenceOptions {
+    pub start: Option<i64>,
+    pub increment: Option<i64>,
+    pub min_value: Option<i64>,
+    pub max_value: Option<i64>,
+    pub cycle: bool,
+}
+
+/// Sequence options with defaults filled in and validated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedSequenceOptions {
+    pub start: i64,
+    pub increment: i64,
+    pub min_value: i64,
+    pub max_value: i64,
+    pub cycle: bool,
+}
+
+impl SequenceOptions {
+    /// Fills in defaults like postgres, the bounds default to the range of the data type.
+    pub fn resolve(
+        &self,
+        name: &str,
+        data_type: DataType,
+    ) -> BustubxResult<ResolvedSequenceOptions> {
+        let (type_min, type_max) = match data_type {
+            DataType::Int16 => (i16::MIN as i64, i16::MAX as i64),
+            DataType::Int32 => (i32::MIN as i64, i32::MAX as i64),
+            DataType::Int64 => (i64::MIN, i64::MAX),
+            _ => {
+                return Err(BustubxError::Plan(format!(
+                    "sequence type must be smallint, integer, or bigint, got {data_type}"
+                )))
+            }
+        };
+        let increment = self.increment.unwrap_or(1);
+        if increment == 0 {
+            return Err(BustubxError::Plan("INCREMENT must not be zero".to_string()));
+        }
+        let min_value = self
+            .min_value
+            .unwrap_or(if increment > 0 { 1 } else { type_min });
+        let max_value = self
+            .max_value
+            .unwrap_or(if increment > 0 { type_max } else { -1 });
+        if min_value < type_min || max_value > type_max {
+            return Err(BustubxError::Plan(format!(
+                "MINVALUE and MAXVALUE of sequence \"{name}\" are out of range for {data_type}"
+            )));
+        }
+        if min_value >= max_value {
+            return Err(BustubxError::Plan(format!(
+                "MINVALUE ({min_value}) must be less than MAXVALUE ({max_value})"
+            )));
+        }
+        let start = self
+            .start
+            .unwrap_or(if increment > 0 { min_value } else { max_value });
+        if start < min_value || start > max_value {
+            return Err(BustubxError::Plan(format!(
+                "START value ({start}) must be between MINVALUE ({min_value}) and MAXVALUE ({max_value})"
+            )));
+        }
+        Ok(ResolvedSequenceOptions {
+            start,
+            increment,
+            min_value,
+            max_value,
+            cycle: self.cycle,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct SequenceState {
+    last_value: i64,
+    is_called: bool,
+    /// The value last returned by nextval in this session.
+    current: Option<i64>,
+}
+
+/// A sequence generator, its state is persisted in `information_schema.sequences`.
+#[derive(Debug)]
+pub struct Sequence {
+    pub schema_name: String,
+    pub name: String,
+    pub options: ResolvedSequenceOptions,
+    row: SequenceRow,
+    state: Mutex<SequenceState>,
+}
+
+impl PartialEq for Sequence {
+    fn eq(&self, other: &Self) -> bool {
+        self.schema_name == other.schema_name && self.name == other.name
+    }
+}
+
+impl Eq for Sequence {}
+
+impl Sequence {
+    pub fn new(
+        schema_name: impl Into<String>,
+        name: impl Into<String>,
+        options: ResolvedSequenceOptions,
+        row: SequenceRow,
+        last_value: i64,
+        is_called: bool,
+    ) -> Self {
+        Self {
+            schema_name: schema_name.into(),
+            name: name.into(),
+            options,
+            row,
+            state: Mutex::new(SequenceState {
+                last_value,
+                is_called,
+                current: None,
+            }),
+        }
+    }
+
+    /// Advances the sequence and persists it before handing the value out,
+    /// the state lock makes concurrent callers receive distinct values.
+    pub fn next_value(&self) -> BustubxResult<i64> {
+        let mut state = self.state.lock().unwrap();
+        let value = if state.is_called {
+            let options = &self.options;
+            match state.last_value.checked_add(options.increment) {
+                Some(value) if value >= options.min_value && value <= options.max_value => value,
+                _ if options.cycle => {
+                    if options.increment > 0 {
+                        options.min_value
+                    } else {
+                        options.max_value
+                    }
+                }
+                _ => {
+                    let (bound, limit) = if options.increment > 0 {
+                        ("maximum", options.max_value)
+                    } else {
+                        ("minimum", options.min_value)
+                    };
+                    return Err(BustubxError::Execution(format!(
+                        "nextval: reached {bound} value of sequence \"{}\" ({limit})",
+                        self.name
+                    )));
+                }
+            }
+        } else {
+            state.last_value
+        };
+
+        let tuple = sequence_tuple(&self.schema_name, &self.name, &self.options, value, true);
+        self.row.update(tuple)?;
+        state.last_value = value;
+        state.is_called = true;
+        state.current = Some(value);
+        Ok(value)
+    }
+
+    /// Removes the row of the sequence from `information_schema.sequences`.
+    pub fn delete_tuple(&self) -> BustubxResult<()> {
+        self.row.delete()
+    }
+
+    /// The value most recently returned by [`Sequence::next_value`].
+    pub fn current_value(&self) -> BustubxResult<i64> {
+        self.state.lock().unwrap().current.ok_or_else(|| {
+            BustubxError::Execution(format!(
+                "currval of sequence \"{}\" is not yet defined in this session",
+                self.name
+            ))
+        })
+    }
+}
+
+/// The row of a sequence in `information_schema.sequences`.
+#[derive(Debug)]
+pub struct SequenceRow {
+    heap: Arc<TableHeap>,
+    /// See [`Catalog::sequences_latch`]
+    ///
+    /// [`Catalog::sequences_latch`]: crate::catalog::Catalog::sequences_latch
+    heap_latch: Arc<Mutex<()>>,
+    rid: RecordId,
+}
+
+impl SequenceRow {
+    pub fn new(heap: Arc<TableHeap>, heap_latch: Arc<Mutex<()>>, rid: RecordId) -> Self {
+        Self {
+            heap,
+            heap_latch,
+            rid,
+        }
+    }
+
+    /// Inserts the row of a new sequence.
+    pub fn insert(
+        heap: Arc<TableHeap>,
+        heap_latch: Arc<Mutex<()>>,
+        tuple: &Tuple,
+    ) -> BustubxResult<Self> {
+        let rid = {
+            let _guard = heap_latch.lock().unwrap();
+            heap.insert_tuple(&EMPTY_TUPLE_META, tuple)?
+        };
+        Ok(Self::new(heap, heap_latch, rid))
+    }
+
+    fn update(&self, tuple: Tuple) -> BustubxResult<()> {
+        let _guard = self.heap_latch.lock().unwrap();
+        // the row keeps its size so it is updated in place
+        if self.heap.update_tuple(self.rid, tuple)? != self.rid {
+            return Err(BustubxError::Internal(format!(
+                "row of sequence moved from {:?}",
+                self.rid
+            )));
+        }
+        Ok(())
+    }
+
+    fn delete(&self) -> BustubxResult<()> {
+        let _guard = self.heap_latch.lock().unwrap();
+        let mut meta = self.heap.tuple_meta(self.rid)?;
+        meta.is_deleted = true;
+        self.heap.update_tuple_meta(meta, self.rid)
+    }
+}
+
+pub fn sequence_tuple(
+    schema_name: &str,
+    name: &str,
+    options: &ResolvedSequenceOptions,
+    last_value: i64,
+    is_called: bool,
+) -> Tuple {
+    Tuple::new(
+        SEQUENCES_SCHMEA.clone(),
+        vec![
+            DEFAULT_CATALOG_NAME.to_string().into(),
+            schema_name.to_string().into(),
+            name.to_string().into(),
+            options.start.into(),
+            options.min_value.into(),
+            options.max_value.into(),
+            options.increment.into(),
+            options.cycle.into(),
+            last_value.into(),
+            is_called.into(),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    use crate::catalog::{DataType, SequenceOptions};
+    use crate::common::TableReference;
+    use crate::Database;
+
+    #[test]
+    pub fn test_sequence_options_resolve() {
+        let options = SequenceOptions::default()
+            .resolve("s", DataType::Int32)
+            .unwrap();
+        assert_eq!(options.start, 1);
+        assert_eq!(options.max_value, i32::MAX as i64);
+
+        let options = SequenceOptions {
+            increment: Some(-2),
+            ..Default::default()
+        }
+        .resolve("s", DataType::Int64)
+        .unwrap();
+        assert_eq!(options.start, -1);
+        assert_eq!(options.min_value, i64::MIN);
+
+        assert!(SequenceOptions {
+            start: Some(10),
+            max_value: Some(5),
+            ..Default::default()
+        }
+        .resolve("s", DataType::Int64)
+        .is_err());
+    }
+
+    #[test]
+    pub fn test_sequence_concurrent_next_value() {
+        let mut db = Database::new_temp().unwrap();
+        let sequence = db
+            .catalog
+            .create_sequence(
+                TableReference::bare("seq"),
+                SequenceOptions::default()
+                    .resolve("seq", DataType::Int64)
+                    .unwrap(),
+            )
+            .unwrap();
+        assert!(sequence.current_value().is_err());
+
+        let handles = (0..4)
+            .map(|_| {
+                let sequence = Arc::clone(&sequence);
+                std::thread::spawn(move || {
+                    (0..100)
+                        .map(|_| sequence.next_value().unwrap())
+                        .collect::<Vec<i64>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        let values = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<HashSet<i64>>();
+        assert_eq!(values.len(), 400);
+        assert_eq!(values.iter().max(), Some(&400));
+    }
+}
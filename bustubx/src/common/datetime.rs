@@ -0,0 +1,531 @@
+use crate::{BustubxError, BustubxResult};
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+pub const MICROS_PER_SECOND: i64 = 1_000_000;
+pub const MICROS_PER_MINUTE: i64 = 60 * MICROS_PER_SECOND;
+pub const MICROS_PER_HOUR: i64 = 60 * MICROS_PER_MINUTE;
+pub const MICROS_PER_DAY: i64 = 24 * MICROS_PER_HOUR;
+const DAYS_PER_MONTH: i64 = 30;
+
+/// A time span with separate month, day and microsecond parts since months and days
+/// don't have a fixed length, e.g. `1 month` added to Jan 31 gives Feb 28/29.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub micros: i64,
+}
+
+impl Interval {
+    pub fn new(months: i32, days: i32, micros: i64) -> Self {
+        Self {
+            months,
+            days,
+            micros,
+        }
+    }
+
+    /// Length in microseconds assuming a month has 30 days and a day has 24 hours,
+    /// used to compare intervals.
+    fn normalized(&self) -> i128 {
+        (self.months as i128 * DAYS_PER_MONTH as i128 + self.days as i128) * MICROS_PER_DAY as i128
+            + self.micros as i128
+    }
+
+    pub fn checked_add(&self, other: &Interval) -> BustubxResult<Interval> {
+        match (
+            self.months.checked_add(other.months),
+            self.days.checked_add(other.days),
+            self.micros.checked_add(other.micros),
+        ) {
+            (Some(months), Some(days), Some(micros)) => Ok(Interval::new(months, days, micros)),
+            _ => Err(interval_out_of_range()),
+        }
+    }
+
+    pub fn checked_neg(&self) -> BustubxResult<Interval> {
+        match (
+            self.months.checked_neg(),
+            self.days.checked_neg(),
+            self.micros.checked_neg(),
+        ) {
+            (Some(months), Some(days), Some(micros)) => Ok(Interval::new(months, days, micros)),
+            _ => Err(interval_out_of_range()),
+        }
+    }
+
+    /// Multiplies each part, fractional months and days cascade down to the smaller parts.
+    pub fn checked_mul(&self, factor: f64) -> BustubxResult<Interval> {
+        let months = self.months as f64 * factor;
+        let days = self.days as f64 * factor + months.fract() * DAYS_PER_MONTH as f64;
+        let micros = self.micros as f64 * factor + days.fract() * MICROS_PER_DAY as f64;
+        if months.abs() > i32::MAX as f64
+            || days.abs() > i32::MAX as f64
+            || micros.abs() > i64::MAX as f64
+        {
+            return Err(interval_out_of_range());
+        }
+        Ok(Interval::new(
+            months.trunc() as i32,
+            days.trunc() as i32,
+            micros.round() as i64,
+        ))
+    }
+
+    /// Parses PostgreSQL style interval text, e.g. `1 year 2 months 3 days 04:05:06`
+    /// or `-2 hours 30 minutes`.
+    pub fn parse(text: &str) -> BustubxResult<Interval> {
+        let error =
+            || BustubxError::Execution(format!("invalid input syntax for interval: \"{}\"", text));
+        let tokens = text.split_whitespace().collect::<Vec<&str>>();
+        if tokens.is_empty() {
+            return Err(error());
+        }
+        let mut interval = Interval::default();
+        let mut idx = 0;
+        while idx < tokens.len() {
+            let token = tokens[idx];
+            if token.contains(':') {
+                let (negative, time) = match token.strip_prefix('-') {
+                    Some(time) => (true, time),
+                    None => (false, token.strip_prefix('+').unwrap_or(token)),
+                };
+                let micros = parse_time_of_day(time, true).map_err(|_| error())?;
+                interval = interval.checked_add(&Interval::new(
+                    0,
+                    0,
+                    if negative { -micros } else { micros },
+                ))?;
+                idx += 1;
+                continue;
+            }
+            let value = token.parse::<f64>().map_err(|_| error())?;
+            let unit = tokens.get(idx + 1).ok_or_else(error)?;
+            interval =
+                interval.checked_add(&Interval::from_unit(value, unit).ok_or_else(error)?)?;
+            idx += 2;
+        }
+        Ok(interval)
+    }
+
+    /// `value` of the given unit, e.g. `(3, "days")`, returns None if the unit is unknown.
+    pub fn from_unit(value: f64, unit: &str) -> Option<Interval> {
+        let interval = match unit.to_lowercase().as_str() {
+            "millennium" | "millennia" | "millenniums" => {
+                Interval::new(12000, 0, 0).checked_mul(value)
+            }
+            "century" | "centuries" => Interval::new(1200, 0, 0).checked_mul(value),
+            "decade" | "decades" => Interval::new(120, 0, 0).checked_mul(value),
+            "year" | "years" | "y" | "yr" | "yrs" => Interval::new(12, 0, 0).checked_mul(value),
+            "month" | "months" | "mon" | "mons" => Interval::new(1, 0, 0).checked_mul(value),
+            "week" | "weeks" | "w" => Interval::new(0, 7, 0).checked_mul(value),
+            "day" | "days" | "d" => Interval::new(0, 1, 0).checked_mul(value),
+            "hour" | "hours" | "h" | "hr" | "hrs" => {
+                Interval::new(0, 0, MICROS_PER_HOUR).checked_mul(value)
+            }
+            "minute" | "minutes" | "m" | "min" | "mins" => {
+                Interval::new(0, 0, MICROS_PER_MINUTE).checked_mul(value)
+            }
+            "second" | "seconds" | "s" | "sec" | "secs" => {
+                Interval::new(0, 0, MICROS_PER_SECOND).checked_mul(value)
+            }
+            "millisecond" | "milliseconds" | "ms" | "msec" | "msecs" => {
+                Interval::new(0, 0, 1000).checked_mul(value)
+            }
+            "microsecond" | "microseconds" | "us" | "usec" | "usecs" => {
+                Interval::new(0, 0, 1).checked_mul(value)
+            }
+            _ => return None,
+        };
+        interval.ok()
+    }
+}
+
+impl PartialEq for Interval {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized() == other.normalized()
+    }
+}
+
+impl Eq for Interval {}
+
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Interval {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.normalized().cmp(&other.normalized())
+    }
+}
+
+impl std::hash::Hash for Interval {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.normalized().hash(state)
+    }
+}
+
+impl std::fmt::Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = vec![];
+        let (years, months) = (self.months / 12, self.months % 12);
+        if years != 0 {
+            parts.push(format!(
+                "{} year{}",
+                years,
+                if years.abs() == 1 { "" } else { "s" }
+            ));
+        }
+        if months != 0 {
+            parts.push(format!(
+                "{} mon{}",
+                months,
+                if months.abs() == 1 { "" } else { "s" }
+            ));
+        }
+        if self.days != 0 {
+            parts.push(format!(
+                "{} day{}",
+                self.days,
+                if self.days.abs() == 1 { "" } else { "s" }
+            ));
+        }
+        if self.micros != 0 || parts.is_empty() {
+            let sign = if self.micros < 0 { "-" } else { "" };
+            parts.push(format!(
+                "{}{}",
+                sign,
+                format_time(self.micros.unsigned_abs() as i64)
+            ));
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+fn interval_out_of_range() -> BustubxError {
+    BustubxError::Execution("interval out of range".to_string())
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian calendar date.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The (year, month, day) of days since 1970-01-01.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = if days >= 0 { days } else { days - 146096 } / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+pub fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        _ => {
+            if is_leap_year(year) {
+                29
+            } else {
+                28
+            }
+        }
+    }
+}
+
+/// Adds months to a date, the day is clamped to the last day of the resulting month.
+pub fn add_months(days: i64, months: i32) -> i64 {
+    let (year, month, day) = civil_from_days(days);
+    let total_months = year * 12 + (month as i64 - 1) + months as i64;
+    let year = total_months.div_euclid(12);
+    let month = (total_months.rem_euclid(12) + 1) as u32;
+    days_from_civil(year, month, day.min(days_in_month(year, month)))
+}
+
+/// Adds an interval to microseconds since epoch.
+pub fn timestamp_add_interval(timestamp: i64, interval: &Interval) -> BustubxResult<i64> {
+    let days = timestamp.div_euclid(MICROS_PER_DAY);
+    let time = timestamp.rem_euclid(MICROS_PER_DAY);
+    let days = add_months(days, interval.months) + interval.days as i64;
+    days.checked_mul(MICROS_PER_DAY)
+        .and_then(|micros| micros.checked_add(time))
+        .and_then(|micros| micros.checked_add(interval.micros))
+        .ok_or(BustubxError::Execution(
+            "timestamp out of range".to_string(),
+        ))
+}
+
+thread_local! {
+    /// Start time of the statement executing on this thread, see [`StatementTimestampGuard`].
+    static STATEMENT_TIMESTAMP: Cell<Option<i64>> = const { Cell::new(None) };
+}
+
+/// Microseconds since epoch of now. Within a statement this is the time the statement
+/// started, so `now()` and `current_timestamp` give one value for all rows like postgres.
+pub fn now_micros() -> i64 {
+    STATEMENT_TIMESTAMP
+        .with(|timestamp| timestamp.get())
+        .unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_micros() as i64)
+                .unwrap_or(0)
+        })
+}
+
+/// Fixes [`now_micros`] on this thread to the start time of a statement until dropped.
+pub struct StatementTimestampGuard {
+    previous: Option<i64>,
+}
+
+impl StatementTimestampGuard {
+    pub fn new(timestamp: i64) -> Self {
+        let previous = STATEMENT_TIMESTAMP.with(|cell| cell.replace(Some(timestamp)));
+        Self { previous }
+    }
+}
+
+impl Drop for StatementTimestampGuard {
+    fn drop(&mut self) {
+        STATEMENT_TIMESTAMP.with(|cell| cell.set(self.previous));
+    }
+}
+
+/// Parses `YYYY-MM-DD` to days since epoch.
+pub fn parse_date(text: &str) -> BustubxResult<i32> {
+    let error =
+        || BustubxError::Execution(format!("invalid input syntax for type date: \"{}\"", text));
+    let parts = text.trim().split('-').collect::<Vec<&str>>();
+    let [year, month, day] = parts.as_slice() else {
+        return Err(error());
+    };
+    let year = year.parse::<i64>().map_err(|_| error())?;
+    let month = month.parse::<u32>().map_err(|_| error())?;
+    let day = day.parse::<u32>().map_err(|_| error())?;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return Err(BustubxError::Execution(format!(
+            "date/time field value out of range: \"{}\"",
+            text
+        )));
+    }
+    i32::try_from(days_from_civil(year, month, day)).map_err(|_| error())
+}
+
+/// Parses `HH:MM[:SS[.ffffff]]` to microseconds since midnight, hours can exceed 23
+/// if `allow_overflow` is set (used for intervals).
+fn parse_time_of_day(text: &str, allow_overflow: bool) -> Result<i64, ()> {
+    let parts = text.split(':').collect::<Vec<&str>>();
+    if parts.len() < 2 || parts.len() > 3 {
+        return Err(());
+    }
+    let hour = parts[0].parse::<i64>().map_err(|_| ())?;
+    let minute = parts[1].parse::<i64>().map_err(|_| ())?;
+    let (second, fraction) = match parts.get(2) {
+        Some(second) => match second.split_once('.') {
+            Some((second, fraction)) => (second, fraction),
+            None => (*second, ""),
+        },
+        None => ("0", ""),
+    };
+    let second = second.parse::<i64>().map_err(|_| ())?;
+    if fraction.len() > 6 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(());
+    }
+    let fraction = format!("{:0<6}", fraction).parse::<i64>().map_err(|_| ())?;
+    if (!allow_overflow && hour > 23)
+        || hour < 0
+        || !(0..60).contains(&minute)
+        || !(0..60).contains(&second)
+    {
+        return Err(());
+    }
+    Ok(hour * MICROS_PER_HOUR + minute * MICROS_PER_MINUTE + second * MICROS_PER_SECOND + fraction)
+}
+
+/// Parses `HH:MM[:SS[.ffffff]]` to microseconds since midnight.
+pub fn parse_time(text: &str) -> BustubxResult<i64> {
+    parse_time_of_day(text.trim(), false).map_err(|_| {
+        BustubxError::Execution(format!("invalid input syntax for type time: \"{}\"", text))
+    })
+}
+
+/// Parses `YYYY-MM-DD[( |T)HH:MM[:SS[.ffffff]]][Z|UTC|(+|-)HH[:MM]]` to microseconds since
+/// epoch of the local time and the UTC offset in microseconds if present.
+pub fn parse_timestamp(text: &str) -> BustubxResult<(i64, Option<i64>)> {
+    let error = || {
+        BustubxError::Execution(format!(
+            "invalid input syntax for type timestamp: \"{}\"",
+            text
+        ))
+    };
+    let trimmed = text.trim();
+    let (date, rest) = match trimmed.find([' ', 'T']) {
+        Some(pos) => (&trimmed[..pos], trimmed[pos + 1..].trim()),
+        None => (trimmed, ""),
+    };
+    let days = parse_date(date)? as i64;
+
+    // split the time zone suffix from the time
+    let (time, zone) = if let Some(time) = rest.strip_suffix('Z') {
+        (time.trim(), Some(0))
+    } else if let Some(time) = rest
+        .strip_suffix("UTC")
+        .or_else(|| rest.strip_suffix("utc"))
+    {
+        (time.trim(), Some(0))
+    } else {
+        match rest.rfind(['+', '-']) {
+            Some(pos) => {
+                let sign = if &rest[pos..pos + 1] == "-" { -1 } else { 1 };
+                let offset = rest[pos + 1..].trim();
+                let (hour, minute) = match offset.split_once(':') {
+                    Some((hour, minute)) => (hour, minute),
+                    None if offset.len() == 4 => offset.split_at(2),
+                    None => (offset, "0"),
+                };
+                let hour = hour.parse::<i64>().map_err(|_| error())?;
+                let minute = minute.parse::<i64>().map_err(|_| error())?;
+                if hour > 15 || minute > 59 {
+                    return Err(error());
+                }
+                (
+                    rest[..pos].trim(),
+                    Some(sign * (hour * MICROS_PER_HOUR + minute * MICROS_PER_MINUTE)),
+                )
+            }
+            None => (rest, None),
+        }
+    };
+    let time = if time.is_empty() {
+        0
+    } else {
+        parse_time_of_day(time, false).map_err(|_| error())?
+    };
+    let timestamp = days
+        .checked_mul(MICROS_PER_DAY)
+        .and_then(|micros| micros.checked_add(time))
+        .ok_or_else(|| {
+            BustubxError::Execution(format!("date/timestamp out of range: \"{}\"", text))
+        })?;
+    Ok((timestamp, zone))
+}
+
+pub fn format_date(days: i32) -> String {
+    let (year, month, day) = civil_from_days(days as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+pub fn format_time(micros: i64) -> String {
+    let hour = micros / MICROS_PER_HOUR;
+    let minute = micros % MICROS_PER_HOUR / MICROS_PER_MINUTE;
+    let second = micros % MICROS_PER_MINUTE / MICROS_PER_SECOND;
+    let fraction = micros % MICROS_PER_SECOND;
+    if fraction == 0 {
+        format!("{:02}:{:02}:{:02}", hour, minute, second)
+    } else {
+        let fraction = format!("{:06}", fraction);
+        format!(
+            "{:02}:{:02}:{:02}.{}",
+            hour,
+            minute,
+            second,
+            fraction.trim_end_matches('0')
+        )
+    }
+}
+
+pub fn format_timestamp(micros: i64) -> String {
+    let days = micros.div_euclid(MICROS_PER_DAY);
+    let time = micros.rem_euclid(MICROS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02} {}", year, month, day, format_time(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_days_roundtrip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        for days in -800000..800000 {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn add_months_clamps_day() {
+        let jan31 = days_from_civil(2024, 1, 31);
+        assert_eq!(civil_from_days(add_months(jan31, 1)), (2024, 2, 29));
+        assert_eq!(civil_from_days(add_months(jan31, -2)), (2023, 11, 30));
+        assert_eq!(civil_from_days(add_months(jan31, 12)), (2025, 1, 31));
+    }
+
+    #[test]
+    fn parse_and_format() {
+        assert_eq!(format_date(parse_date("2024-02-29").unwrap()), "2024-02-29");
+        assert!(parse_date("2023-02-29").is_err());
+        assert_eq!(format_time(parse_time("01:02:03.5").unwrap()), "01:02:03.5");
+        assert!(parse_time("24:00:00").is_err());
+
+        let (ts, zone) = parse_timestamp("2024-01-02 03:04:05+08:00").unwrap();
+        assert_eq!(format_timestamp(ts), "2024-01-02 03:04:05");
+        assert_eq!(zone, Some(8 * MICROS_PER_HOUR));
+        let (ts, zone) = parse_timestamp("2024-01-02T03:04:05Z").unwrap();
+        assert_eq!(format_timestamp(ts), "2024-01-02 03:04:05");
+        assert_eq!(zone, Some(0));
+        let (ts, zone) = parse_timestamp("1969-12-31").unwrap();
+        assert_eq!(ts, -MICROS_PER_DAY);
+        assert_eq!(zone, None);
+        assert!(parse_timestamp("300000-01-01").is_err());
+    }
+
+    #[test]
+    fn parse_interval() {
+        let interval = Interval::parse("1 year 2 mons 3 days 04:05:06").unwrap();
+        assert_eq!(interval.months, 14);
+        assert_eq!(interval.days, 3);
+        assert_eq!(
+            interval.micros,
+            4 * MICROS_PER_HOUR + 5 * MICROS_PER_MINUTE + 6 * MICROS_PER_SECOND
+        );
+        assert_eq!(interval.to_string(), "1 year 2 mons 3 days 04:05:06");
+        assert_eq!(
+            Interval::parse("-2 hours").unwrap().to_string(),
+            "-02:00:00"
+        );
+        assert_eq!(
+            Interval::parse("1.5 days").unwrap().to_string(),
+            "1 day 12:00:00"
+        );
+        assert_eq!(
+            Interval::parse("1 month").unwrap(),
+            Interval::parse("30 days").unwrap()
+        );
+        assert!(Interval::parse("1 fortnight").is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::{BustubxError, BustubxResult};
+use std::cmp::Ordering;
+
+/// The max number of digits a decimal backed by i128 can always hold.
+pub const MAX_DECIMAL_PRECISION: u8 = 38;
+/// The precision and scale of `DECIMAL` without type modifiers.
+pub const DEFAULT_DECIMAL_PRECISION: u8 = 38;
+pub const DEFAULT_DECIMAL_SCALE: u8 = 10;
+
+fn overflow() -> BustubxError {
+    BustubxError::Execution("numeric field overflow".to_string())
+}
+
+/// 10 to the power of `exp`, None if it doesn't fit in i128.
+pub fn pow10(exp: u32) -> Option<i128> {
+    10i128.checked_pow(exp)
+}
+
+/// Divides and rounds half away from zero.
+pub fn div_round(numerator: i128, denominator: i128) -> i128 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    if remainder.unsigned_abs() * 2 >= denominator.unsigned_abs() {
+        if (numerator < 0) == (denominator < 0) {
+            quotient + 1
+        } else {
+            quotient - 1
+        }
+    } else {
+        quotient
+    }
+}
+
+/// Checks the unscaled value has at most `precision` digits.
+pub fn validate_precision(value: i128, precision: u8) -> BustubxResult<()> {
+    match pow10(precision as u32) {
+        Some(max) if value.unsigned_abs() >= max.unsigned_abs() => Err(overflow()),
+        _ => Ok(()),
+    }
+}
+
+/// Changes the scale of an unscaled value, rounding half away from zero when digits
+/// are dropped.
+pub fn rescale(value: i128, from_scale: u8, to_scale: u8) -> BustubxResult<i128> {
+    match from_scale.cmp(&to_scale) {
+        Ordering::Equal => Ok(value),
+        Ordering::Less => pow10((to_scale - from_scale) as u32)
+            .and_then(|factor| value.checked_mul(factor))
+            .ok_or_else(overflow),
+        Ordering::Greater => match pow10((from_scale - to_scale) as u32) {
+            Some(factor) => Ok(div_round(value, factor)),
+            // every digit is dropped
+            None => Ok(0),
+        },
+    }
+}
+
+/// Compares two unscaled values with different scales.
+pub fn compare(v1: i128, s1: u8, v2: i128, s2: u8) -> Ordering {
+    match s1.cmp(&s2) {
+        Ordering::Equal => v1.cmp(&v2),
+        Ordering::Less => match rescale(v1, s1, s2) {
+            Ok(v1) => v1.cmp(&v2),
+            // too large to rescale, so larger in magnitude than v2
+            Err(_) => v1.cmp(&0),
+        },
+        Ordering::Greater => compare(v2, s2, v1, s1).reverse(),
+    }
+}
+
+/// Removes trailing fractional zeros, values equal in number have the same
+/// normalized form.
+pub fn normalize(mut value: i128, mut scale: u8) -> (i128, u8) {
+    if value == 0 {
+        return (0, 0);
+    }
+    while scale > 0 && value % 10 == 0 {
+        value /= 10;
+        scale -= 1;
+    }
+    (value, scale)
+}
+
+/// Parses text like `-123.45` or `1.5e3` to an unscaled value of `scale`.
+pub fn parse_decimal(text: &str, scale: u8) -> BustubxResult<i128> {
+    let error = || {
+        BustubxError::Execution(format!(
+            "invalid input syntax for type numeric: \"{}\"",
+            text
+        ))
+    };
+    let trimmed = text.trim();
+    let (negative, unsigned) = match trimmed.as_bytes().first() {
+        Some(b'-') => (true, &trimmed[1..]),
+        Some(b'+') => (false, &trimmed[1..]),
+        _ => (false, trimmed),
+    };
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().map_err(|_| error())?),
+        None => (unsigned, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if integer.is_empty() && fraction.is_empty()
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(error());
+    }
+
+    let digits = format!("{}{}", integer, fraction);
+    let digits = digits.trim_start_matches('0').as_bytes();
+    // the value is digits * 10^shift
+    let shift = exponent as i64 - fraction.len() as i64 + scale as i64;
+    let value = if shift >= 0 {
+        let mut value: i128 = 0;
+        for digit in digits {
+            value = value
+                .checked_mul(10)
+                .and_then(|v| v.checked_add((digit - b'0') as i128))
+                .ok_or_else(overflow)?;
+        }
+        if value == 0 {
+            0
+        } else {
+            u32::try_from(shift)
+                .ok()
+                .and_then(pow10)
+                .and_then(|factor| value.checked_mul(factor))
+                .ok_or_else(overflow)?
+        }
+    } else {
+        let drop = (-shift) as usize;
+        let keep = digits.len().saturating_sub(drop);
+        let mut value: i128 = 0;
+        for digit in &digits[..keep] {
+            value = value
+                .checked_mul(10)
+                .and_then(|v| v.checked_add((digit - b'0') as i128))
+                .ok_or_else(overflow)?;
+        }
+        // round half away from zero on the first dropped digit
+        match digits.len().checked_sub(drop) {
+            Some(first_dropped) if digits[first_dropped] >= b'5' => value + 1,
+            _ => value,
+        }
+    };
+    Ok(if negative { -value } else { value })
+}
+
+/// Formats an unscaled value with exactly `scale` fractional digits.
+pub fn format_decimal(value: i128, scale: u8) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let sign = if value < 0 { "-" } else { "" };
+    if scale == 0 {
+        return format!("{}{}", sign, digits);
+    }
+    let digits = format!("{:0>width$}", digits, width = scale as usize + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - scale as usize);
+    format!("{}{}.{}", sign, integer, fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_format() {
+        assert_eq!(parse_decimal("123.45", 2).unwrap(), 12345);
+        assert_eq!(parse_decimal("-0.005", 2).unwrap(), -1);
+        assert_eq!(parse_decimal("0.004", 2).unwrap(), 0);
+        assert_eq!(parse_decimal("1.5e2", 0).unwrap(), 150);
+        assert_eq!(parse_decimal(".5", 1).unwrap(), 5);
+        assert_eq!(parse_decimal("0", 38).unwrap(), 0);
+        assert!(parse_decimal("1.2.3", 2).is_err());
+        assert!(parse_decimal("abc", 2).is_err());
+        assert!(parse_decimal("1e40", 0).is_err());
+
+        assert_eq!(format_decimal(12345, 2), "123.45");
+        assert_eq!(format_decimal(-5, 3), "-0.005");
+        assert_eq!(format_decimal(7, 0), "7");
+    }
+
+    #[test]
+    fn rescale_and_compare() {
+        assert_eq!(rescale(12345, 2, 1).unwrap(), 1235);
+        assert_eq!(rescale(-12345, 2, 1).unwrap(), -1235);
+        assert_eq!(rescale(15, 1, 3).unwrap(), 1500);
+        assert!(rescale(i128::MAX, 0, 1).is_err());
+
+        assert_eq!(compare(150, 2, 15, 1), Ordering::Equal);
+        assert_eq!(compare(-1, 0, 5, 1), Ordering::Less);
+        assert_eq!(compare(i128::MAX, 0, 1, 2), Ordering::Greater);
+        assert_eq!(normalize(1500, 3), (15, 1));
+
+        assert!(validate_precision(99999, 5).is_ok());
+        assert!(validate_precision(-100000, 5).is_err());
+    }
+}
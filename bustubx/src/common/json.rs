@@ -0,0 +1,528 @@
+use crate::{BustubxError, BustubxResult};
+use std::cmp::Ordering;
+
+/// A parsed JSON document. Like jsonb, object keys are sorted shorter first then bytewise
+/// and a duplicate key keeps the last value, so equal documents have the same form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    /// The number literal as written, validated against the JSON grammar
+    Number(String),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    pub fn parse(text: &str) -> BustubxResult<JsonValue> {
+        let mut parser = JsonParser {
+            text,
+            bytes: text.as_bytes(),
+            pos: 0,
+        };
+        parser.skip_whitespace();
+        let value = parser.parse_value(0)?;
+        parser.skip_whitespace();
+        if parser.pos != parser.bytes.len() {
+            return Err(parser.error("unexpected trailing characters"));
+        }
+        Ok(value)
+    }
+
+    /// `object`, `array`, `string`, `number`, `boolean` or `null`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Bool(_) => "boolean",
+            JsonValue::Number(_) => "number",
+            JsonValue::String(_) => "string",
+            JsonValue::Array(_) => "array",
+            JsonValue::Object(_) => "object",
+        }
+    }
+
+    /// The value of an object field.
+    pub fn get_field(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(fields) => fields
+                .binary_search_by(|(k, _)| k.len().cmp(&key.len()).then(k.as_str().cmp(key)))
+                .ok()
+                .map(|i| &fields[i].1),
+            _ => None,
+        }
+    }
+
+    /// The array element at `index`, negative index counts from the end.
+    pub fn get_index(&self, index: i64) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Array(elements) => {
+                let index = if index < 0 {
+                    elements.len() as i64 + index
+                } else {
+                    index
+                };
+                usize::try_from(index).ok().and_then(|i| elements.get(i))
+            }
+            _ => None,
+        }
+    }
+
+    /// Follows a path of field names or array indexes.
+    pub fn get_path<S: AsRef<str>>(&self, path: &[S]) -> Option<&JsonValue> {
+        path.iter().try_fold(self, |value, step| {
+            let step = step.as_ref();
+            match value {
+                JsonValue::Array(_) => step.parse::<i64>().ok().and_then(|i| value.get_index(i)),
+                _ => value.get_field(step),
+            }
+        })
+    }
+
+    /// The text of a scalar value without quotes, other values are serialized, JSON null
+    /// is SQL NULL.
+    pub fn to_text(&self) -> Option<String> {
+        match self {
+            JsonValue::Null => None,
+            JsonValue::String(s) => Some(s.clone()),
+            _ => Some(self.to_string()),
+        }
+    }
+}
+
+/// Parses a text array path of `#>` like `{a,b,0}`.
+pub fn parse_text_path(text: &str) -> BustubxResult<Vec<String>> {
+    let error = || BustubxError::Execution(format!("malformed array literal: \"{}\"", text));
+    let inner = text
+        .trim()
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .ok_or_else(error)?;
+    if inner.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    inner
+        .split(',')
+        .map(|step| {
+            let step = step.trim();
+            match step.strip_prefix('"') {
+                Some(quoted) => quoted
+                    .strip_suffix('"')
+                    .map(|s| s.to_string())
+                    .ok_or_else(error),
+                None if step.is_empty() => Err(error()),
+                None => Ok(step.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// Parses a path of `json_extract` like `$.a."b c"[0]`.
+pub fn parse_json_path(text: &str) -> BustubxResult<Vec<String>> {
+    let error = || BustubxError::Execution(format!("invalid json path: \"{}\"", text));
+    let mut rest = text.trim().strip_prefix('$').ok_or_else(error)?;
+    let mut path = vec![];
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(error)?;
+            let index = after[..end].trim();
+            index.parse::<i64>().map_err(|_| error())?;
+            path.push(index.to_string());
+            rest = &after[end + 1..];
+        } else if let Some(after) = rest.strip_prefix('.') {
+            if let Some(quoted) = after.strip_prefix('"') {
+                let end = quoted.find('"').ok_or_else(error)?;
+                path.push(quoted[..end].to_string());
+                rest = &quoted[end + 1..];
+            } else {
+                let end = after.find(['.', '[']).unwrap_or(after.len());
+                if end == 0 {
+                    return Err(error());
+                }
+                path.push(after[..end].to_string());
+                rest = &after[end..];
+            }
+        } else {
+            return Err(error());
+        }
+    }
+    Ok(path)
+}
+
+impl PartialOrd for JsonValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for JsonValue {
+    /// Values of different types are ordered by type, numbers by their numeric value.
+    fn cmp(&self, other: &Self) -> Ordering {
+        fn rank(value: &JsonValue) -> u8 {
+            match value {
+                JsonValue::Null => 0,
+                JsonValue::String(_) => 1,
+                JsonValue::Number(_) => 2,
+                JsonValue::Bool(_) => 3,
+                JsonValue::Array(_) => 4,
+                JsonValue::Object(_) => 5,
+            }
+        }
+        match (self, other) {
+            (JsonValue::Bool(v1), JsonValue::Bool(v2)) => v1.cmp(v2),
+            (JsonValue::Number(v1), JsonValue::Number(v2)) => {
+                let f1 = v1.parse::<f64>().unwrap_or_default();
+                let f2 = v2.parse::<f64>().unwrap_or_default();
+                f1.total_cmp(&f2).then_with(|| v1.cmp(v2))
+            }
+            (JsonValue::String(v1), JsonValue::String(v2)) => v1.cmp(v2),
+            (JsonValue::Array(v1), JsonValue::Array(v2)) => v1.cmp(v2),
+            (JsonValue::Object(v1), JsonValue::Object(v2)) => v1.cmp(v2),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl std::fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(v) => write!(f, "{v}"),
+            JsonValue::Number(v) => write!(f, "{v}"),
+            JsonValue::String(v) => write_json_string(f, v),
+            JsonValue::Array(elements) => {
+                write!(f, "[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{element}")?;
+                }
+                write!(f, "]")
+            }
+            JsonValue::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write_json_string(f, key)?;
+                    write!(f, ": {value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_json_string(f: &mut std::fmt::Formatter<'_>, s: &str) -> std::fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Nesting deeper than this is rejected to bound the recursion.
+const MAX_DEPTH: usize = 256;
+
+struct JsonParser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl JsonParser<'_> {
+    fn error(&self, reason: &str) -> BustubxError {
+        BustubxError::Execution(format!(
+            "invalid input syntax for type json: {} at position {} of \"{}\"",
+            reason, self.pos, self.text
+        ))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> BustubxResult<()> {
+        if self.bytes.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", byte as char)))
+        }
+    }
+
+    fn parse_value(&mut self, depth: usize) -> BustubxResult<JsonValue> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+        match self.bytes.get(self.pos) {
+            Some(b'{') => self.parse_object(depth),
+            Some(b'[') => self.parse_array(depth),
+            Some(b'"') => Ok(JsonValue::String(self.parse_string()?)),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(_) => {
+                for (literal, value) in [
+                    ("null", JsonValue::Null),
+                    ("true", JsonValue::Bool(true)),
+                    ("false", JsonValue::Bool(false)),
+                ] {
+                    if self.bytes[self.pos..].starts_with(literal.as_bytes()) {
+                        self.pos += literal.len();
+                        return Ok(value);
+                    }
+                }
+                Err(self.error("unexpected token"))
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_object(&mut self, depth: usize) -> BustubxResult<JsonValue> {
+        self.expect(b'{')?;
+        let mut fields: Vec<(String, JsonValue)> = vec![];
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return Ok(JsonValue::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            self.skip_whitespace();
+            let value = self.parse_value(depth + 1)?;
+            match fields.binary_search_by(|(k, _)| k.len().cmp(&key.len()).then(k.cmp(&key))) {
+                Ok(i) => fields[i].1 = value,
+                Err(i) => fields.insert(i, (key, value)),
+            }
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(fields));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn parse_array(&mut self, depth: usize) -> BustubxResult<JsonValue> {
+        self.expect(b'[')?;
+        let mut elements = vec![];
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return Ok(JsonValue::Array(elements));
+        }
+        loop {
+            self.skip_whitespace();
+            elements.push(self.parse_value(depth + 1)?);
+            self.skip_whitespace();
+            match self.bytes.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(elements));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> BustubxResult<String> {
+        self.expect(b'"')?;
+        let mut result = String::new();
+        loop {
+            let start = self.pos;
+            while !matches!(self.bytes.get(self.pos), None | Some(b'"' | b'\\')) {
+                if self.bytes[self.pos] < 0x20 {
+                    return Err(self.error("control character in string"));
+                }
+                self.pos += 1;
+            }
+            result.push_str(&self.text[start..self.pos]);
+            match self.bytes.get(self.pos) {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(result);
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let escaped = match self.bytes.get(self.pos) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let c = self.parse_unicode_escape()?;
+                            result.push(c);
+                            continue;
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.pos += 1;
+                    result.push(escaped);
+                }
+                _ => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    /// Parses `uXXXX` after a backslash, including surrogate pairs.
+    fn parse_unicode_escape(&mut self) -> BustubxResult<char> {
+        let read_hex = |parser: &mut Self| -> BustubxResult<u32> {
+            let hex = parser
+                .text
+                .get(parser.pos + 1..parser.pos + 5)
+                .ok_or_else(|| parser.error("invalid unicode escape"))?;
+            let code =
+                u32::from_str_radix(hex, 16).map_err(|_| parser.error("invalid unicode escape"))?;
+            parser.pos += 5;
+            Ok(code)
+        };
+        let high = read_hex(self)?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.bytes[self.pos..].starts_with(b"\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.pos += 1;
+            let low = read_hex(self)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn parse_number(&mut self) -> BustubxResult<JsonValue> {
+        let start = self.pos;
+        let digits = |parser: &mut Self| {
+            let from = parser.pos;
+            while matches!(parser.bytes.get(parser.pos), Some(b'0'..=b'9')) {
+                parser.pos += 1;
+            }
+            parser.pos - from
+        };
+        if self.bytes.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        let integer_start = self.pos;
+        let integer_digits = digits(self);
+        if integer_digits == 0 || (integer_digits > 1 && self.bytes[integer_start] == b'0') {
+            return Err(self.error("invalid number"));
+        }
+        if self.bytes.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            if digits(self) == 0 {
+                return Err(self.error("invalid number"));
+            }
+        }
+        if matches!(self.bytes.get(self.pos), Some(b'e' | b'E')) {
+            self.pos += 1;
+            if matches!(self.bytes.get(self.pos), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if digits(self) == 0 {
+                return Err(self.error("invalid number"));
+            }
+        }
+        Ok(JsonValue::Number(self.text[start..self.pos].to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_json_path, parse_text_path, JsonValue};
+
+    #[test]
+    fn parse_and_format() {
+        let value = JsonValue::parse(
+            r#" {"a": [1, -2.5e3, true, null], "b": {"c": "x\"\u00e9\n"}, "a": 0} "#,
+        )
+        .unwrap();
+        assert_eq!(value.to_string(), r#"{"a": 0, "b": {"c": "x\"é\n"}}"#);
+        assert_eq!(
+            value.get_path(&["b", "c"]).unwrap().to_text().unwrap(),
+            "x\"é\n"
+        );
+        assert_eq!(
+            JsonValue::parse("[1, 2, 3]").unwrap().get_index(-1),
+            Some(&JsonValue::Number("3".to_string()))
+        );
+        assert_eq!(
+            JsonValue::parse(r#""\ud83d\ude00""#).unwrap(),
+            JsonValue::String("😀".to_string())
+        );
+
+        for invalid in [
+            "",
+            "{",
+            "[1,]",
+            "01",
+            "1.",
+            "tru",
+            "{\"a\" 1}",
+            "\"\\x\"",
+            "1 2",
+        ] {
+            assert!(JsonValue::parse(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn compare() {
+        let parse = |text: &str| JsonValue::parse(text).unwrap();
+        assert!(parse("2") > parse("10.5e-1"));
+        assert!(parse("\"b\"") > parse("\"a\""));
+        assert!(parse("true") > parse("1"));
+        assert_eq!(parse("{\"a\": 1}"), parse("{ \"a\" : 1 }"));
+        assert_eq!(
+            parse("{\"bb\": 1, \"a\": 2}"),
+            parse("{\"a\": 2, \"bb\": 1}")
+        );
+        assert_eq!(
+            parse("{\"bb\": 1, \"a\": 2}").to_string(),
+            "{\"a\": 2, \"bb\": 1}"
+        );
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(
+            parse_text_path("{a, \"b c\", 0}").unwrap(),
+            vec!["a", "b c", "0"]
+        );
+        assert!(parse_text_path("{}").unwrap().is_empty());
+        assert!(parse_text_path("a,b").is_err());
+
+        assert_eq!(
+            parse_json_path("$.a.\"b.c\"[1]").unwrap(),
+            vec!["a", "b.c", "1"]
+        );
+        assert!(parse_json_path("$").unwrap().is_empty());
+        for invalid in ["a", "$.", "$[x]", "$a"] {
+            assert!(parse_json_path(invalid).is_err(), "{invalid}");
+        }
+    }
+}
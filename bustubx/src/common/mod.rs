@@ -1,4 +1,7 @@
 mod bitmap;
+pub mod datetime;
+pub mod decimal;
+pub mod json;
 mod scalar;
 mod table_ref;
 pub mod util;
@@ -1,4 +1,7 @@
 use crate::catalog::DataType;
+use crate::common::datetime::{self, Interval};
+use crate::common::json::JsonValue;
+use crate::common::{decimal, util};
 use crate::{BustubxError, BustubxResult};
 use std::cmp::Ordering;
 
@@ -15,7 +18,20 @@ pub enum ScalarValue {
     UInt64(Option<u64>),
     Float32(Option<f32>),
     Float64(Option<f64>),
+    /// Unscaled value, precision and scale
+    Decimal(Option<i128>, u8, u8),
     Varchar(Option<String>),
+    Binary(Option<Vec<u8>>),
+    Json(Option<JsonValue>),
+    /// Days since 1970-01-01
+    Date(Option<i32>),
+    /// Microseconds since midnight
+    Time(Option<i64>),
+    /// Microseconds since 1970-01-01 00:00:00
+    Timestamp(Option<i64>),
+    /// Microseconds since 1970-01-01 00:00:00 UTC
+    TimestampTz(Option<i64>),
+    Interval(Option<Interval>),
 }
 
 impl ScalarValue {
@@ -32,7 +48,15 @@ impl ScalarValue {
             DataType::UInt64 => Self::UInt64(None),
             DataType::Float32 => Self::Float32(None),
             DataType::Float64 => Self::Float64(None),
+            DataType::Decimal(precision, scale) => Self::Decimal(None, precision, scale),
             DataType::Varchar(_) => Self::Varchar(None),
+            DataType::Binary => Self::Binary(None),
+            DataType::Json => Self::Json(None),
+            DataType::Date => Self::Date(None),
+            DataType::Time => Self::Time(None),
+            DataType::Timestamp => Self::Timestamp(None),
+            DataType::TimestampTz => Self::TimestampTz(None),
+            DataType::Interval => Self::Interval(None),
         }
     }
 
@@ -49,7 +73,15 @@ impl ScalarValue {
             ScalarValue::UInt64(_) => DataType::UInt64,
             ScalarValue::Float32(_) => DataType::Float32,
             ScalarValue::Float64(_) => DataType::Float64,
+            ScalarValue::Decimal(_, precision, scale) => DataType::Decimal(*precision, *scale),
             ScalarValue::Varchar(_) => DataType::Varchar(None),
+            ScalarValue::Binary(_) => DataType::Binary,
+            ScalarValue::Json(_) => DataType::Json,
+            ScalarValue::Date(_) => DataType::Date,
+            ScalarValue::Time(_) => DataType::Time,
+            ScalarValue::Timestamp(_) => DataType::Timestamp,
+            ScalarValue::TimestampTz(_) => DataType::TimestampTz,
+            ScalarValue::Interval(_) => DataType::Interval,
         }
     }
 
@@ -66,7 +98,15 @@ impl ScalarValue {
             ScalarValue::UInt64(v) => v.is_none(),
             ScalarValue::Float32(v) => v.is_none(),
             ScalarValue::Float64(v) => v.is_none(),
+            ScalarValue::Decimal(v, _, _) => v.is_none(),
             ScalarValue::Varchar(v) => v.is_none(),
+            ScalarValue::Binary(v) => v.is_none(),
+            ScalarValue::Json(v) => v.is_none(),
+            ScalarValue::Date(v) => v.is_none(),
+            ScalarValue::Time(v) => v.is_none(),
+            ScalarValue::Timestamp(v) => v.is_none(),
+            ScalarValue::TimestampTz(v) => v.is_none(),
+            ScalarValue::Interval(v) => v.is_none(),
         }
     }
 
@@ -78,97 +118,184 @@ impl ScalarValue {
         if &self.data_type() == data_type {
             return Ok(self.clone());
         }
+        if self.is_null() {
+            return Ok(Self::new_empty(*data_type));
+        }
 
-        match data_type {
-            DataType::Int8 => {
-                let data = match self {
-                    ScalarValue::Int64(v) => Ok(v.map(|v| v as i8)),
-                    _ => Err(error),
-                };
-                data.map(ScalarValue::Int8)
-            }
-            DataType::Int16 => {
-                let data = match self {
-                    ScalarValue::Int8(v) => Ok(v.map(|v| v as i16)),
-                    ScalarValue::Int64(v) => Ok(v.map(|v| v as i16)),
-                    _ => Err(error),
-                };
-                data.map(ScalarValue::Int16)
-            }
-            DataType::Int32 => {
-                let data = match self {
-                    ScalarValue::Int8(v) => Ok(v.map(|v| v as i32)),
-                    ScalarValue::Int64(v) => Ok(v.map(|v| v as i32)),
-                    _ => Err(error),
-                };
-                data.map(ScalarValue::Int32)
-            }
-            DataType::Int64 => {
-                let data = match self {
-                    ScalarValue::Int8(v) => Ok(v.map(|v| v as i64)),
-                    ScalarValue::Int32(v) => Ok(v.map(|v| v as i64)),
-                    _ => Err(error),
-                };
-                data.map(ScalarValue::Int64)
-            }
-            DataType::UInt8 => {
-                let data = match self {
-                    ScalarValue::Int8(v) => Ok(v.map(|v| v as u8)),
-                    ScalarValue::Int64(v) => Ok(v.map(|v| v as u8)),
-                    _ => Err(error),
-                };
-                data.map(ScalarValue::UInt8)
-            }
-            DataType::UInt16 => {
-                let data = match self {
-                    ScalarValue::Int8(v) => Ok(v.map(|v| v as u16)),
-                    ScalarValue::Int64(v) => Ok(v.map(|v| v as u16)),
-                    _ => Err(error),
-                };
-                data.map(ScalarValue::UInt16)
-            }
-            DataType::UInt32 => {
-                let data = match self {
-                    ScalarValue::Int8(v) => Ok(v.map(|v| v as u32)),
-                    ScalarValue::Int64(v) => Ok(v.map(|v| v as u32)),
-                    _ => Err(error),
+        macro_rules! cast_integer {
+            ($ty:ty, $scalar:tt) => {{
+                let v = match self {
+                    ScalarValue::Float32(Some(v)) => v.round() as i128,
+                    ScalarValue::Float64(Some(v)) => v.round() as i128,
+                    ScalarValue::Decimal(Some(v), _, scale) => decimal::rescale(*v, *scale, 0)?,
+                    ScalarValue::Varchar(Some(v)) => v.trim().parse::<i128>().map_err(|_| {
+                        BustubxError::Execution(format!(
+                            "invalid input syntax for type {}: \"{}\"",
+                            data_type, v
+                        ))
+                    })?,
+                    ScalarValue::Boolean(_) => return Err(error),
+                    _ => self.as_i128()?.ok_or(error)?,
                 };
-                data.map(ScalarValue::UInt32)
-            }
-            DataType::UInt64 => {
-                let data = match self {
-                    ScalarValue::Int8(v) => Ok(v.map(|v| v as u64)),
-                    ScalarValue::Int64(v) => Ok(v.map(|v| v as u64)),
+                let v = <$ty>::try_from(v).map_err(|_| {
+                    BustubxError::Execution(format!("{} is out of range for type {}", v, data_type))
+                })?;
+                Ok(ScalarValue::$scalar(Some(v)))
+            }};
+        }
+
+        match data_type {
+            DataType::Boolean => match self {
+                ScalarValue::Varchar(Some(v)) => match v.trim().to_lowercase().as_str() {
+                    "true" | "t" | "yes" | "y" | "on" | "1" => Ok(ScalarValue::Boolean(Some(true))),
+                    "false" | "f" | "no" | "n" | "off" | "0" => {
+                        Ok(ScalarValue::Boolean(Some(false)))
+                    }
                     _ => Err(error),
+                },
+                _ => Err(error),
+            },
+            DataType::Int8 => cast_integer!(i8, Int8),
+            DataType::Int16 => cast_integer!(i16, Int16),
+            DataType::Int32 => cast_integer!(i32, Int32),
+            DataType::Int64 => cast_integer!(i64, Int64),
+            DataType::UInt8 => cast_integer!(u8, UInt8),
+            DataType::UInt16 => cast_integer!(u16, UInt16),
+            DataType::UInt32 => cast_integer!(u32, UInt32),
+            DataType::UInt64 => cast_integer!(u64, UInt64),
+            DataType::Float32 | DataType::Float64 => {
+                let v = match self {
+                    ScalarValue::Varchar(Some(v)) => v.trim().parse::<f64>().map_err(|_| {
+                        BustubxError::Execution(format!(
+                            "invalid input syntax for type {}: \"{}\"",
+                            data_type, v
+                        ))
+                    })?,
+                    ScalarValue::Boolean(_) => return Err(error),
+                    _ => self.as_f64()?.ok_or(error)?,
                 };
-                data.map(ScalarValue::UInt64)
+                if data_type == &DataType::Float32 {
+                    Ok(ScalarValue::Float32(Some(v as f32)))
+                } else {
+                    Ok(ScalarValue::Float64(Some(v)))
+                }
             }
-            DataType::Float32 => {
-                let data = match self {
-                    ScalarValue::Int8(v) => Ok(v.map(|v| v as f32)),
-                    ScalarValue::Int64(v) => Ok(v.map(|v| v as f32)),
-                    ScalarValue::Float64(v) => Ok(v.map(|v| v as f32)),
-                    _ => Err(error),
+            DataType::Decimal(precision, scale) => {
+                let v = match self {
+                    ScalarValue::Decimal(Some(v), _, from_scale) => {
+                        decimal::rescale(*v, *from_scale, *scale)?
+                    }
+                    ScalarValue::Varchar(Some(v)) => decimal::parse_decimal(v, *scale)?,
+                    // the shortest text that round-trips the float
+                    ScalarValue::Float32(Some(v)) => {
+                        decimal::parse_decimal(&v.to_string(), *scale)?
+                    }
+                    ScalarValue::Float64(Some(v)) => {
+                        decimal::parse_decimal(&v.to_string(), *scale)?
+                    }
+                    _ if self.data_type().is_integer() => {
+                        decimal::rescale(self.as_i128()?.ok_or(error)?, 0, *scale)?
+                    }
+                    _ => return Err(error),
                 };
-                data.map(ScalarValue::Float32)
+                decimal::validate_precision(v, *precision)?;
+                Ok(ScalarValue::Decimal(Some(v), *precision, *scale))
             }
-            DataType::Float64 => {
-                let data = match self {
-                    ScalarValue::Int8(v) => Ok(v.map(|v| v as f64)),
-                    ScalarValue::Int32(v) => Ok(v.map(|v| v as f64)),
-                    ScalarValue::Int64(v) => Ok(v.map(|v| v as f64)),
-                    _ => Err(error),
-                };
-                data.map(ScalarValue::Float64)
+            DataType::Varchar(len) => {
+                let v = self.to_string();
+                if let Some(len) = len {
+                    if v.chars().count() > *len {
+                        return Err(BustubxError::Execution(format!(
+                            "value too long for type {}",
+                            data_type
+                        )));
+                    }
+                }
+                Ok(ScalarValue::Varchar(Some(v)))
             }
-            DataType::Varchar(_) => {
-                let data = match self {
-                    ScalarValue::Int8(v) => Ok(v.map(|v| v.to_string())),
-                    _ => Err(error),
+            DataType::Binary => match self {
+                // hex format `\x0aff` or the raw bytes of the text
+                ScalarValue::Varchar(Some(v)) => match v.strip_prefix("\\x") {
+                    Some(hex) => Ok(ScalarValue::Binary(Some(util::decode_hex(hex)?))),
+                    None => Ok(ScalarValue::Binary(Some(v.as_bytes().to_vec()))),
+                },
+                _ => Err(error),
+            },
+            DataType::Json => match self {
+                ScalarValue::Varchar(Some(v)) => Ok(ScalarValue::Json(Some(JsonValue::parse(v)?))),
+                _ => Err(error),
+            },
+            DataType::Date => match self {
+                ScalarValue::Varchar(Some(v)) => {
+                    let (timestamp, _) = datetime::parse_timestamp(v)?;
+                    Ok(ScalarValue::Date(Some(
+                        timestamp.div_euclid(datetime::MICROS_PER_DAY) as i32,
+                    )))
+                }
+                ScalarValue::Timestamp(Some(v)) | ScalarValue::TimestampTz(Some(v)) => Ok(
+                    ScalarValue::Date(Some(v.div_euclid(datetime::MICROS_PER_DAY) as i32)),
+                ),
+                _ => Err(error),
+            },
+            DataType::Time => match self {
+                ScalarValue::Varchar(Some(v)) => {
+                    Ok(ScalarValue::Time(Some(datetime::parse_time(v)?)))
+                }
+                ScalarValue::Timestamp(Some(v)) | ScalarValue::TimestampTz(Some(v)) => Ok(
+                    ScalarValue::Time(Some(v.rem_euclid(datetime::MICROS_PER_DAY))),
+                ),
+                ScalarValue::Interval(Some(v)) => Ok(ScalarValue::Time(Some(
+                    v.micros.rem_euclid(datetime::MICROS_PER_DAY),
+                ))),
+                _ => Err(error),
+            },
+            DataType::Timestamp | DataType::TimestampTz => {
+                let v = match self {
+                    // the session time zone is UTC
+                    ScalarValue::Varchar(Some(v)) => {
+                        let (timestamp, zone) = datetime::parse_timestamp(v)?;
+                        match zone {
+                            Some(zone) if data_type == &DataType::TimestampTz => {
+                                timestamp.checked_sub(zone).ok_or_else(|| {
+                                    BustubxError::Execution(format!(
+                                        "date/timestamp out of range: \"{}\"",
+                                        v
+                                    ))
+                                })?
+                            }
+                            _ => timestamp,
+                        }
+                    }
+                    ScalarValue::Date(Some(v)) => (*v as i64)
+                        .checked_mul(datetime::MICROS_PER_DAY)
+                        .ok_or_else(|| {
+                            BustubxError::Execution("date out of range for timestamp".to_string())
+                        })?,
+                    ScalarValue::Timestamp(Some(v)) | ScalarValue::TimestampTz(Some(v)) => *v,
+                    _ => return Err(error),
                 };
-                data.map(ScalarValue::Varchar)
+                if data_type == &DataType::Timestamp {
+                    Ok(ScalarValue::Timestamp(Some(v)))
+                } else {
+                    Ok(ScalarValue::TimestampTz(Some(v)))
+                }
             }
-            _ => Err(error),
+            DataType::Interval => match self {
+                ScalarValue::Varchar(Some(v)) => {
+                    Ok(ScalarValue::Interval(Some(Interval::parse(v)?)))
+                }
+                ScalarValue::Time(Some(v)) => {
+                    Ok(ScalarValue::Interval(Some(Interval::new(0, 0, *v))))
+                }
+                _ => Err(error),
+            },
+        }
+    }
+
+    fn as_i128(&self) -> BustubxResult<Option<i128>> {
+        match self {
+            ScalarValue::UInt64(v) => Ok(v.map(|v| v as i128)),
+            _ => Ok(self.as_i64()?.map(|v| v as i128)),
         }
     }
 
@@ -182,6 +309,40 @@ impl ScalarValue {
         }
     }
 
+    pub fn as_i64(&self) -> BustubxResult<Option<i64>> {
+        match self {
+            ScalarValue::Int8(v) => Ok(v.map(|v| v as i64)),
+            ScalarValue::Int16(v) => Ok(v.map(|v| v as i64)),
+            ScalarValue::Int32(v) => Ok(v.map(|v| v as i64)),
+            ScalarValue::Int64(v) => Ok(*v),
+            ScalarValue::UInt8(v) => Ok(v.map(|v| v as i64)),
+            ScalarValue::UInt16(v) => Ok(v.map(|v| v as i64)),
+            ScalarValue::UInt32(v) => Ok(v.map(|v| v as i64)),
+            ScalarValue::UInt64(v) => v
+                .map(|v| {
+                    i64::try_from(v).map_err(|_| {
+                        BustubxError::Execution(format!("{} is out of range for i64", v))
+                    })
+                })
+                .transpose(),
+            _ => Err(BustubxError::Internal(format!(
+                "Cannot treat {:?} as i64",
+                self
+            ))),
+        }
+    }
+
+    pub fn as_f64(&self) -> BustubxResult<Option<f64>> {
+        match self {
+            ScalarValue::Float32(v) => Ok(v.map(|v| v as f64)),
+            ScalarValue::Float64(v) => Ok(*v),
+            ScalarValue::Decimal(v, _, scale) => {
+                Ok(v.map(|v| v as f64 / 10f64.powi(*scale as i32)))
+            }
+            _ => Ok(self.as_i64()?.map(|v| v as f64)),
+        }
+    }
+
     pub fn wrapping_add(&self, _other: Self) -> BustubxResult<Self> {
         todo!()
     }
@@ -318,6 +479,20 @@ impl ScalarValue {
                 let v = if is_null { None } else { Some(string.clone()) };
                 Ok(ScalarValue::Varchar(v))
             }
+            DataType::Decimal(_, _)
+            | DataType::Binary
+            | DataType::Json
+            | DataType::Date
+            | DataType::Time
+            | DataType::Timestamp
+            | DataType::TimestampTz
+            | DataType::Interval => {
+                if is_null {
+                    Ok(ScalarValue::new_empty(data_type))
+                } else {
+                    ScalarValue::Varchar(Some(string.clone())).cast_to(&data_type)
+                }
+            }
         }
     }
 }
@@ -354,8 +529,27 @@ impl PartialEq for ScalarValue {
                 _ => v1.eq(v2),
             },
             (Float64(_), _) => false,
+            (Decimal(v1, _, s1), Decimal(v2, _, s2)) => match (v1, v2) {
+                (Some(v1), Some(v2)) => decimal::compare(*v1, *s1, *v2, *s2) == Ordering::Equal,
+                _ => v1.eq(v2),
+            },
+            (Decimal(_, _, _), _) => false,
             (Varchar(v1), Varchar(v2)) => v1.eq(v2),
             (Varchar(_), _) => false,
+            (Binary(v1), Binary(v2)) => v1.eq(v2),
+            (Binary(_), _) => false,
+            (Json(v1), Json(v2)) => v1.eq(v2),
+            (Json(_), _) => false,
+            (Date(v1), Date(v2)) => v1.eq(v2),
+            (Date(_), _) => false,
+            (Time(v1), Time(v2)) => v1.eq(v2),
+            (Time(_), _) => false,
+            (Timestamp(v1), Timestamp(v2)) => v1.eq(v2),
+            (Timestamp(_), _) => false,
+            (TimestampTz(v1), TimestampTz(v2)) => v1.eq(v2),
+            (TimestampTz(_), _) => false,
+            (Interval(v1), Interval(v2)) => v1.eq(v2),
+            (Interval(_), _) => false,
         }
     }
 }
@@ -394,8 +588,27 @@ impl PartialOrd for ScalarValue {
                 _ => v1.partial_cmp(v2),
             },
             (Float64(_), _) => None,
+            (Decimal(v1, _, s1), Decimal(v2, _, s2)) => match (v1, v2) {
+                (Some(v1), Some(v2)) => Some(decimal::compare(*v1, *s1, *v2, *s2)),
+                _ => v1.partial_cmp(v2),
+            },
+            (Decimal(_, _, _), _) => None,
             (Varchar(v1), Varchar(v2)) => v1.partial_cmp(v2),
             (Varchar(_), _) => None,
+            (Binary(v1), Binary(v2)) => v1.partial_cmp(v2),
+            (Binary(_), _) => None,
+            (Json(v1), Json(v2)) => v1.partial_cmp(v2),
+            (Json(_), _) => None,
+            (Date(v1), Date(v2)) => v1.partial_cmp(v2),
+            (Date(_), _) => None,
+            (Time(v1), Time(v2)) => v1.partial_cmp(v2),
+            (Time(_), _) => None,
+            (Timestamp(v1), Timestamp(v2)) => v1.partial_cmp(v2),
+            (Timestamp(_), _) => None,
+            (TimestampTz(v1), TimestampTz(v2)) => v1.partial_cmp(v2),
+            (TimestampTz(_), _) => None,
+            (Interval(v1), Interval(v2)) => v1.partial_cmp(v2),
+            (Interval(_), _) => None,
         }
     }
 }
@@ -407,6 +620,8 @@ impl std::hash::Hash for ScalarValue {
             Boolean(v) => v.hash(state),
             Float32(v) => v.map(Fl).hash(state),
             Float64(v) => v.map(Fl).hash(state),
+            // equal decimals with different scales must have the same hash
+            Decimal(v, _, scale) => v.map(|v| decimal::normalize(v, *scale)).hash(state),
             Int8(v) => v.hash(state),
             Int16(v) => v.hash(state),
             Int32(v) => v.hash(state),
@@ -416,6 +631,13 @@ impl std::hash::Hash for ScalarValue {
             UInt32(v) => v.hash(state),
             UInt64(v) => v.hash(state),
             Varchar(v) => v.hash(state),
+            Binary(v) => v.hash(state),
+            Json(v) => v.hash(state),
+            Date(v) => v.hash(state),
+            Time(v) => v.hash(state),
+            Timestamp(v) => v.hash(state),
+            TimestampTz(v) => v.hash(state),
+            Interval(v) => v.hash(state),
         }
     }
 }
@@ -461,8 +683,28 @@ impl std::fmt::Display for ScalarValue {
             ScalarValue::Float32(Some(v)) => write!(f, "{v}"),
             ScalarValue::Float64(None) => write!(f, "NULL"),
             ScalarValue::Float64(Some(v)) => write!(f, "{v}"),
+            ScalarValue::Decimal(None, _, _) => write!(f, "NULL"),
+            ScalarValue::Decimal(Some(v), _, scale) => {
+                write!(f, "{}", decimal::format_decimal(*v, *scale))
+            }
             ScalarValue::Varchar(None) => write!(f, "NULL"),
             ScalarValue::Varchar(Some(v)) => write!(f, "{v}"),
+            ScalarValue::Binary(None) => write!(f, "NULL"),
+            ScalarValue::Binary(Some(v)) => write!(f, "\\x{}", util::encode_hex(v)),
+            ScalarValue::Json(None) => write!(f, "NULL"),
+            ScalarValue::Json(Some(v)) => write!(f, "{v}"),
+            ScalarValue::Date(None) => write!(f, "NULL"),
+            ScalarValue::Date(Some(v)) => write!(f, "{}", datetime::format_date(*v)),
+            ScalarValue::Time(None) => write!(f, "NULL"),
+            ScalarValue::Time(Some(v)) => write!(f, "{}", datetime::format_time(*v)),
+            ScalarValue::Timestamp(None) => write!(f, "NULL"),
+            ScalarValue::Timestamp(Some(v)) => write!(f, "{}", datetime::format_timestamp(*v)),
+            ScalarValue::TimestampTz(None) => write!(f, "NULL"),
+            ScalarValue::TimestampTz(Some(v)) => {
+                write!(f, "{}+00", datetime::format_timestamp(*v))
+            }
+            ScalarValue::Interval(None) => write!(f, "NULL"),
+            ScalarValue::Interval(Some(v)) => write!(f, "{v}"),
         }
     }
 }
@@ -495,3 +737,4 @@ impl_from_for_scalar!(u64, UInt64);
 impl_from_for_scalar!(f32, Float32);
 impl_from_for_scalar!(f64, Float64);
 impl_from_for_scalar!(String, Varchar);
+impl_from_for_scalar!(Vec<u8>, Binary);
@@ -3,7 +3,7 @@ use crate::execution::physical_plan::PhysicalPlan;
 use crate::planner::logical_plan::LogicalPlan;
 use crate::storage::codec::BPlusTreePageCodec;
 use crate::storage::index::BPlusTreeIndex;
-use crate::BustubxResult;
+use crate::{BustubxError, BustubxResult};
 use comfy_table::Cell;
 use std::collections::VecDeque;
 use std::sync::atomic::Ordering;
@@ -71,6 +71,23 @@ pub fn page_bytes_to_array(bytes: &[u8]) -> [u8; BUSTUBX_PAGE_SIZE] {
     data
 }
 
+/// Formats bytes as lowercase hex digits.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Parses hex digits, e.g. `0aff`, to bytes.
+pub fn decode_hex(hex: &str) -> BustubxResult<Vec<u8>> {
+    let error = || BustubxError::Execution(format!("invalid hexadecimal data: \"{}\"", hex));
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return Err(error());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| error()))
+        .collect()
+}
+
 pub(crate) fn pretty_format_index_tree(index: &BPlusTreeIndex) -> BustubxResult<String> {
     let mut display = String::new();
 
@@ -78,6 +95,8 @@ pub(crate) fn pretty_format_index_tree(index: &BPlusTreeIndex) -> BustubxResult<
         display.push_str("Empty tree.");
         return Ok(display);
     }
+    // 只打印key列，entry末尾的record id列省略
+    let key_count = index.key_schema.column_count();
     // 层序遍历
     let mut curr_queue = VecDeque::new();
     curr_queue.push_back(index.root_page_id.load(Ordering::SeqCst));
@@ -100,7 +119,7 @@ pub(crate) fn pretty_format_index_tree(index: &BPlusTreeIndex) -> BustubxResult<
         while let Some(page_id) = curr_queue.pop_front() {
             let (_, curr_page) = index
                 .buffer_pool
-                .fetch_tree_page(page_id, index.key_schema.clone())?;
+                .fetch_tree_page(page_id, index.entry_schema.clone())?;
 
             match curr_page {
                 BPlusTreePage::Internal(internal_page) => {
@@ -114,6 +133,7 @@ pub(crate) fn pretty_format_index_tree(index: &BPlusTreeIndex) -> BustubxResult<
                             tuple
                                 .data
                                 .iter()
+                                .take(key_count)
                                 .map(|v| format!("{v}"))
                                 .collect::<Vec<_>>()
                                 .join(", "),
@@ -141,6 +161,7 @@ pub(crate) fn pretty_format_index_tree(index: &BPlusTreeIndex) -> BustubxResult<
                             tuple
                                 .data
                                 .iter()
+                                .take(key_count)
                                 .map(|v| format!("{v}"))
                                 .collect::<Vec<_>>()
                                 .join(", "),
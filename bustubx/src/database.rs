@@ -81,9 +81,7 @@ impl Database {
         );
 
         // logical plan -> physical plan
-        let physical_planner = PhysicalPlanner {
-            catalog: &self.catalog,
-        };
+        let physical_planner = PhysicalPlanner::new(&self.catalog);
         let physical_plan = physical_planner.create_physical_plan(optimized_logical_plan);
         debug!(
             "Physical Plan: \n{}",
@@ -99,6 +97,12 @@ impl Database {
     }
 
     pub fn create_logical_plan(&mut self, sql: &str) -> BustubxResult<LogicalPlan> {
+        if let Some(stmt) = crate::parser::parse_extension_statement(sql)? {
+            let planner = LogicalPlanner {
+                context: PlannerContext::new(&self.catalog),
+            };
+            return planner.plan_extension_statement(&stmt);
+        }
         // sql -> ast
         let stmts = crate::parser::parse_sql(sql)?;
         if stmts.len() != 1 {
@@ -108,15 +112,16 @@ impl Database {
         }
         let stmt = &stmts[0];
         let mut planner = LogicalPlanner {
-            context: PlannerContext {
-                catalog: &self.catalog,
-            },
+            context: PlannerContext::new(&self.catalog),
         };
         // ast -> logical plan
         planner.plan(stmt)
     }
 
     pub fn flush(&self) -> BustubxResult<()> {
+        for catalog in self.catalog.attached.values() {
+            catalog.buffer_pool.flush_all_pages()?;
+        }
         self.buffer_pool.flush_all_pages()
     }
 }
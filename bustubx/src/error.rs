@@ -24,4 +24,7 @@ pub enum BustubxError {
 
     #[error("Storage error: {0}")]
     Storage(String),
+
+    #[error("Constraint violation: {0}")]
+    ConstraintViolation(String),
 }
@@ -0,0 +1,698 @@
+use crate::catalog::{
+    Catalog, Constraint, ConstraintKind, ForeignKey, ReferentialAction, DEFAULT_CATALOG_NAME,
+    DEFAULT_SCHEMA_NAME,
+};
+use crate::common::{ScalarValue, TableReference};
+use crate::expression::ExprTrait;
+use crate::storage::hash_index::HashIndex;
+use crate::storage::index::BPlusTreeIndex;
+use crate::storage::{RecordId, TableIterator, EMPTY_TUPLE_META};
+use crate::{BustubxError, BustubxResult, Tuple};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Rows moved to the end of their table heap by the updates of a statement, including
+/// the updates of foreign key actions, as the table, old rid and new rid in order.
+pub(crate) type MovedRows = Vec<(TableReference, RecordId, RecordId)>;
+
+/// A row written by a statement, including the writes of foreign key actions.
+pub(crate) enum RowWrite {
+    Insert {
+        table: TableReference,
+        rid: RecordId,
+        tuple: Tuple,
+    },
+    /// The row was at `rid` with `old_tuple` and is at `new_rid` with `tuple` afterwards
+    Update {
+        table: TableReference,
+        rid: RecordId,
+        new_rid: RecordId,
+        old_tuple: Tuple,
+        tuple: Tuple,
+    },
+    Delete {
+        table: TableReference,
+        rid: RecordId,
+        tuple: Tuple,
+    },
+}
+
+/// Rows written by a statement in order, undone by [`undo_writes`] when the statement
+/// fails so that a failed statement changes nothing.
+pub(crate) type WriteLog = Vec<RowWrite>;
+
+/// Checks the NOT NULL columns of a tuple about to be written to `table`.
+pub(crate) fn check_not_null(table: &TableReference, tuple: &Tuple) -> BustubxResult<()> {
+    for (col, value) in tuple.schema.columns.iter().zip(tuple.data.iter()) {
+        if !col.nullable && value.is_null() {
+            return Err(BustubxError::ConstraintViolation(format!(
+                "null value in column \"{}\" of relation \"{}\" violates not-null constraint",
+                col.name,
+                table.table()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the check constraints of a tuple about to be written to `table`, a check
+/// evaluating to NULL is satisfied.
+pub(crate) fn check_checks(
+    catalog: &Catalog,
+    table: &TableReference,
+    tuple: &Tuple,
+) -> BustubxResult<()> {
+    for constraint in catalog.table_constraints(table)? {
+        let ConstraintKind::Check(check) = &constraint.kind else {
+            continue;
+        };
+        if check.expr.evaluate(tuple)?.as_boolean()? == Some(false) {
+            return Err(BustubxError::ConstraintViolation(format!(
+                "new row for relation \"{}\" violates check constraint \"{}\"",
+                table.table(),
+                constraint.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the keys of a tuple about to be written to `table` are not too large for its
+/// indexes, so that the row is refused before anything is written.
+pub(crate) fn check_index_keys(
+    catalog: &Catalog,
+    table: &TableReference,
+    tuple: &Tuple,
+) -> BustubxResult<()> {
+    for index in catalog.table_indexes(table)? {
+        if let Ok(key) = tuple.project_with_schema(index.key_schema.clone()) {
+            BPlusTreeIndex::check_key_size(&key)?;
+        }
+    }
+    for index in catalog.table_hash_indexes(table)? {
+        HashIndex::check_key_size(&tuple.project_with_schema(index.key_schema.clone())?)?;
+    }
+    Ok(())
+}
+
+/// Checks the unique constraints of a tuple about to be written to `table`. `rid` is the
+/// tuple being updated which doesn't conflict with itself. Keys containing NULL never
+/// conflict.
+pub(crate) fn check_unique(
+    catalog: &Catalog,
+    table: &TableReference,
+    tuple: &Tuple,
+    rid: Option<RecordId>,
+) -> BustubxResult<()> {
+    for constraint in catalog.table_constraints(table)? {
+        if !constraint.kind.is_unique() {
+            continue;
+        }
+        let index = constraint_index(catalog, table, &constraint)?;
+        let key = tuple.project_with_schema(index.key_schema.clone())?;
+        if key.data.iter().any(|value| value.is_null()) {
+            continue;
+        }
+        if let Some(found_rid) = lookup_live(catalog, table, &index, &key)? {
+            if Some(found_rid) != rid {
+                return Err(BustubxError::ConstraintViolation(format!(
+                    "duplicate key value violates unique constraint \"{}\", key ({})=({}) already exists",
+                    constraint.name,
+                    constraint.columns.join(", "),
+                    format_values(&key.data)
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Finds the live row a tuple about to be inserted into `table` conflicts with on one of
+/// the given unique constraints.
+pub(crate) fn find_conflict(
+    catalog: &Catalog,
+    table: &TableReference,
+    tuple: &Tuple,
+    constraints: &[Constraint],
+) -> BustubxResult<Option<RecordId>> {
+    for constraint in constraints {
+        let index = constraint_index(catalog, table, constraint)?;
+        let key = tuple.project_with_schema(index.key_schema.clone())?;
+        if key.data.iter().any(|value| value.is_null()) {
+            continue;
+        }
+        if let Some(rid) = lookup_live(catalog, table, &index, &key)? {
+            return Ok(Some(rid));
+        }
+    }
+    Ok(None)
+}
+
+/// Checks the foreign keys of a tuple about to be written to `table` through the indexes
+/// of the referenced tables. Keys not changed since `old_tuple` are not checked again.
+pub(crate) fn check_foreign_keys(
+    catalog: &Catalog,
+    table: &TableReference,
+    tuple: &Tuple,
+    old_tuple: Option<&Tuple>,
+) -> BustubxResult<()> {
+    for constraint in catalog.table_constraints(table)? {
+        let ConstraintKind::ForeignKey(foreign_key) = &constraint.kind else {
+            continue;
+        };
+        let values = column_values(tuple, &constraint.columns)?;
+        if values.iter().any(|value| value.is_null()) {
+            continue;
+        }
+        if let Some(old_tuple) = old_tuple {
+            if column_values(old_tuple, &constraint.columns)? == values {
+                continue;
+            }
+        }
+        // a row may reference itself
+        if is_same_table(table, &foreign_key.table)
+            && column_values(tuple, &foreign_key.columns)? == values
+        {
+            continue;
+        }
+
+        let referenced_constraint = referenced_constraint(catalog, foreign_key)?;
+        let index = constraint_index(catalog, &foreign_key.table, &referenced_constraint)?;
+        let key = index
+            .key_schema
+            .columns
+            .iter()
+            .map(|col| {
+                let pos = foreign_key
+                    .columns
+                    .iter()
+                    .position(|name| name == &col.name)
+                    .ok_or_else(|| {
+                        BustubxError::Internal(format!(
+                            "column {} not found in foreign key {}",
+                            col.name, constraint.name
+                        ))
+                    })?;
+                values[pos].cast_to(&col.data_type)
+            })
+            .collect::<BustubxResult<Vec<ScalarValue>>>()?;
+        let key = Tuple::new(index.key_schema.clone(), key);
+        if lookup_live(catalog, &foreign_key.table, &index, &key)?.is_none() {
+            return Err(BustubxError::ConstraintViolation(format!(
+                "insert or update on table \"{}\" violates foreign key constraint \"{}\", key ({})=({}) is not present in table \"{}\"",
+                table.table(),
+                constraint.name,
+                constraint.columns.join(", "),
+                format_values(&values),
+                foreign_key.table.table()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Updates a row after checking its constraints, then applies the update actions of
+/// foreign keys referencing it. Returns the rid of the row, which changes when the row
+/// had to move, every moved row is added to `moved`.
+pub(crate) fn update_row(
+    catalog: &Catalog,
+    table: &TableReference,
+    rid: RecordId,
+    old_tuple: &Tuple,
+    tuple: Tuple,
+    moved: &mut MovedRows,
+    write_log: &mut WriteLog,
+) -> BustubxResult<RecordId> {
+    check_not_null(table, &tuple)?;
+    check_checks(catalog, table, &tuple)?;
+    check_index_keys(catalog, table, &tuple)?;
+    check_unique(catalog, table, &tuple, Some(rid))?;
+    check_foreign_keys(catalog, table, &tuple, Some(old_tuple))?;
+    let referencing = changed_references(catalog, table, old_tuple, Some(&tuple))?;
+    check_restricted_references(table, rid, &referencing, false)?;
+
+    let new_rid = catalog
+        .table_heap(table)?
+        .update_tuple(rid, tuple.clone())?;
+    if new_rid != rid {
+        moved.push((table.clone(), rid, new_rid));
+    }
+    write_log.push(RowWrite::Update {
+        table: table.clone(),
+        rid,
+        new_rid,
+        old_tuple: old_tuple.clone(),
+        tuple: tuple.clone(),
+    });
+    for index in catalog.table_indexes(table)? {
+        let old_key = old_tuple.project_with_schema(index.key_schema.clone())?;
+        let new_key = tuple.project_with_schema(index.key_schema.clone())?;
+        if old_key != new_key || new_rid != rid {
+            index.delete(&old_key, rid)?;
+            index.insert(&new_key, new_rid)?;
+        }
+    }
+    for index in catalog.table_hash_indexes(table)? {
+        let old_key = old_tuple.project_with_schema(index.key_schema.clone())?;
+        let new_key = tuple.project_with_schema(index.key_schema.clone())?;
+        if old_key != new_key || new_rid != rid {
+            index.delete(&old_key, rid)?;
+            index.insert(&new_key, new_rid)?;
+        }
+    }
+
+    for reference in referencing {
+        if !matches!(
+            reference.foreign_key.on_update,
+            ReferentialAction::Cascade | ReferentialAction::SetNull | ReferentialAction::SetDefault
+        ) {
+            continue;
+        }
+        for (child_rid, child_tuple) in reference.current_rows(catalog)? {
+            let mut new_child = child_tuple.clone();
+            if reference.foreign_key.on_update == ReferentialAction::Cascade {
+                for (col_name, referenced) in reference
+                    .constraint
+                    .columns
+                    .iter()
+                    .zip(reference.foreign_key.columns.iter())
+                {
+                    let idx = new_child.schema.index_of(None, col_name)?;
+                    let data_type = new_child.schema.columns[idx].data_type;
+                    new_child.data[idx] =
+                        tuple.value_by_name(None, referenced)?.cast_to(&data_type)?;
+                }
+            } else {
+                reset_referencing_columns(
+                    catalog,
+                    &reference.table,
+                    &mut new_child,
+                    &reference.constraint,
+                    reference.foreign_key.on_update,
+                )?;
+            }
+            update_row(
+                catalog,
+                &reference.table,
+                child_rid,
+                &child_tuple,
+                new_child,
+                moved,
+                write_log,
+            )?;
+        }
+    }
+    Ok(new_rid)
+}
+
+/// Deletes a row, then applies the delete actions of foreign keys referencing it. Rows
+/// moved by updating referencing columns are added to `moved`.
+pub(crate) fn delete_row(
+    catalog: &Catalog,
+    table: &TableReference,
+    rid: RecordId,
+    tuple: &Tuple,
+    moved: &mut MovedRows,
+    write_log: &mut WriteLog,
+) -> BustubxResult<()> {
+    let referencing = changed_references(catalog, table, tuple, None)?;
+    check_restricted_references(table, rid, &referencing, true)?;
+
+    let table_heap = catalog.table_heap(table)?;
+    let mut meta = table_heap.tuple_meta(rid)?;
+    meta.is_deleted = true;
+    table_heap.update_tuple_meta(meta, rid)?;
+    write_log.push(RowWrite::Delete {
+        table: table.clone(),
+        rid,
+        tuple: tuple.clone(),
+    });
+    // other indexes keep the entry, scans skip deleted tuples
+    for constraint in catalog.table_constraints(table)? {
+        if !constraint.kind.is_unique() {
+            continue;
+        }
+        let index = constraint_index(catalog, table, &constraint)?;
+        let key = tuple.project_with_schema(index.key_schema.clone())?;
+        index.delete(&key, rid)?;
+    }
+
+    for reference in referencing {
+        for (child_rid, child_tuple) in reference.current_rows(catalog)? {
+            match reference.foreign_key.on_delete {
+                ReferentialAction::Cascade => delete_row(
+                    catalog,
+                    &reference.table,
+                    child_rid,
+                    &child_tuple,
+                    moved,
+                    write_log,
+                )?,
+                ReferentialAction::SetNull | ReferentialAction::SetDefault => {
+                    let mut new_child = child_tuple.clone();
+                    reset_referencing_columns(
+                        catalog,
+                        &reference.table,
+                        &mut new_child,
+                        &reference.constraint,
+                        reference.foreign_key.on_delete,
+                    )?;
+                    update_row(
+                        catalog,
+                        &reference.table,
+                        child_rid,
+                        &child_tuple,
+                        new_child,
+                        moved,
+                        write_log,
+                    )?;
+                }
+                ReferentialAction::NoAction | ReferentialAction::Restrict => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Adds the index entries of a row written to `table`.
+pub(crate) fn insert_index_entries(
+    catalog: &Catalog,
+    table: &TableReference,
+    tuple: &Tuple,
+    rid: RecordId,
+) -> BustubxResult<()> {
+    for index in catalog.table_indexes(table)? {
+        if let Ok(key) = tuple.project_with_schema(index.key_schema.clone()) {
+            index.insert(&key, rid)?;
+        }
+    }
+    for index in catalog.table_hash_indexes(table)? {
+        index.insert(&tuple.project_with_schema(index.key_schema.clone())?, rid)?;
+    }
+    Ok(())
+}
+
+/// Removes the index entries of a row of `table`, entries not found are ignored.
+fn delete_index_entries(
+    catalog: &Catalog,
+    table: &TableReference,
+    tuple: &Tuple,
+    rid: RecordId,
+) -> BustubxResult<()> {
+    for index in catalog.table_indexes(table)? {
+        if let Ok(key) = tuple.project_with_schema(index.key_schema.clone()) {
+            index.delete(&key, rid)?;
+        }
+    }
+    for index in catalog.table_hash_indexes(table)? {
+        index.delete(&tuple.project_with_schema(index.key_schema.clone())?, rid)?;
+    }
+    Ok(())
+}
+
+/// Undoes the writes of a failed statement, latest first. Undoing may put a row back at
+/// another rid than the one earlier writes left it at, those rids are followed.
+pub(crate) fn undo_writes(catalog: &Catalog, write_log: WriteLog) -> BustubxResult<()> {
+    let mut moved_back: HashMap<(TableReference, RecordId), RecordId> = HashMap::new();
+    for write in write_log.into_iter().rev() {
+        match write {
+            RowWrite::Insert { table, rid, tuple } => {
+                let rid = moved_back
+                    .get(&(table.clone(), rid))
+                    .copied()
+                    .unwrap_or(rid);
+                let table_heap = catalog.table_heap(&table)?;
+                let mut meta = table_heap.tuple_meta(rid)?;
+                meta.is_deleted = true;
+                table_heap.update_tuple_meta(meta, rid)?;
+                delete_index_entries(catalog, &table, &tuple, rid)?;
+            }
+            RowWrite::Update {
+                table,
+                rid,
+                new_rid,
+                old_tuple,
+                tuple,
+            } => {
+                let new_rid = moved_back
+                    .get(&(table.clone(), new_rid))
+                    .copied()
+                    .unwrap_or(new_rid);
+                let restored_rid = catalog
+                    .table_heap(&table)?
+                    .update_tuple(new_rid, old_tuple.clone())?;
+                delete_index_entries(catalog, &table, &tuple, new_rid)?;
+                insert_index_entries(catalog, &table, &old_tuple, restored_rid)?;
+                if restored_rid != rid {
+                    moved_back.insert((table, rid), restored_rid);
+                }
+            }
+            RowWrite::Delete { table, rid, tuple } => {
+                // the deleted tuple lost its overflow pages, so it is inserted again
+                let restored_rid = catalog
+                    .table_heap(&table)?
+                    .insert_tuple(&EMPTY_TUPLE_META, &tuple)?;
+                insert_index_entries(catalog, &table, &tuple, restored_rid)?;
+                moved_back.insert((table, rid), restored_rid);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Adds to `skipped` the new rids of the rows of `table` which moved after the scan of
+/// `iterator` went past them, so that the scan doesn't visit them again.
+pub(crate) fn skip_moved_rows(
+    table: &TableReference,
+    moved: MovedRows,
+    iterator: &TableIterator,
+    skipped: &mut HashSet<RecordId>,
+) -> BustubxResult<()> {
+    for (moved_table, old_rid, new_rid) in moved {
+        if &moved_table == table && (skipped.contains(&old_rid) || iterator.is_passed(old_rid)?) {
+            skipped.insert(new_rid);
+        }
+    }
+    Ok(())
+}
+
+/// Rows of a referencing table holding a referenced key about to be removed or changed.
+struct Reference {
+    table: TableReference,
+    constraint: Constraint,
+    foreign_key: ForeignKey,
+    /// Positions of the referencing columns in the referencing table
+    indices: Vec<usize>,
+    /// The referenced key, cast to the types of the referencing columns
+    values: Vec<ScalarValue>,
+    rids: Vec<RecordId>,
+}
+
+impl Reference {
+    /// Re-reads the rows as cascades applied meanwhile may have changed or deleted them.
+    fn current_rows(&self, catalog: &Catalog) -> BustubxResult<Vec<(RecordId, Tuple)>> {
+        let table_heap = catalog.table_heap(&self.table)?;
+        let mut rows = vec![];
+        for rid in self.rids.iter() {
+            let (meta, tuple) = table_heap.full_tuple(*rid)?;
+            if !meta.is_deleted && self.matches(&tuple) {
+                rows.push((*rid, tuple));
+            }
+        }
+        Ok(rows)
+    }
+
+    fn matches(&self, tuple: &Tuple) -> bool {
+        self.indices
+            .iter()
+            .zip(self.values.iter())
+            .all(|(idx, value)| &tuple.data[*idx] == value)
+    }
+}
+
+/// Finds rows referencing the referenced key of `old_tuple`, for every foreign key whose
+/// referenced key is removed or changed by `new_tuple`.
+fn changed_references(
+    catalog: &Catalog,
+    table: &TableReference,
+    old_tuple: &Tuple,
+    new_tuple: Option<&Tuple>,
+) -> BustubxResult<Vec<Reference>> {
+    let mut references = vec![];
+    for (child_table, constraint) in catalog.referencing_constraints(table)? {
+        let ConstraintKind::ForeignKey(foreign_key) = &constraint.kind else {
+            continue;
+        };
+        let old_values = column_values(old_tuple, &foreign_key.columns)?;
+        if old_values.iter().any(|value| value.is_null()) {
+            continue;
+        }
+        if let Some(new_tuple) = new_tuple {
+            if column_values(new_tuple, &foreign_key.columns)? == old_values {
+                continue;
+            }
+        }
+
+        let child_heap = catalog.table_heap(&child_table)?;
+        let indices = constraint
+            .columns
+            .iter()
+            .map(|name| child_heap.schema.index_of(None, name))
+            .collect::<BustubxResult<Vec<usize>>>()?;
+        let values = indices
+            .iter()
+            .zip(old_values.iter())
+            .map(|(idx, value)| value.cast_to(&child_heap.schema.columns[*idx].data_type))
+            .collect::<BustubxResult<Vec<ScalarValue>>>()?;
+        let mut reference = Reference {
+            table: child_table,
+            constraint: constraint.clone(),
+            foreign_key: foreign_key.clone(),
+            indices,
+            values,
+            rids: vec![],
+        };
+
+        let mut iterator = TableIterator::new(child_heap.clone(), ..);
+        while let Some((child_rid, child_tuple)) = iterator.next()? {
+            if reference.matches(&child_tuple) {
+                reference.rids.push(child_rid);
+            }
+        }
+        if !reference.rids.is_empty() {
+            references.push(reference);
+        }
+    }
+    Ok(references)
+}
+
+/// Fails if a row is still referenced by a foreign key without a delete or update action.
+fn check_restricted_references(
+    table: &TableReference,
+    rid: RecordId,
+    references: &[Reference],
+    delete: bool,
+) -> BustubxResult<()> {
+    for reference in references {
+        let action = if delete {
+            reference.foreign_key.on_delete
+        } else {
+            reference.foreign_key.on_update
+        };
+        if !matches!(
+            action,
+            ReferentialAction::NoAction | ReferentialAction::Restrict
+        ) {
+            continue;
+        }
+        // a deleted row referencing itself goes away with it
+        if delete
+            && is_same_table(table, &reference.table)
+            && reference.rids.iter().all(|child_rid| *child_rid == rid)
+        {
+            continue;
+        }
+        return Err(BustubxError::ConstraintViolation(format!(
+            "update or delete on table \"{}\" violates foreign key constraint \"{}\" on table \"{}\"",
+            table.table(),
+            reference.constraint.name,
+            reference.table.table()
+        )));
+    }
+    Ok(())
+}
+
+/// Sets the referencing columns of a row to null or to their defaults. A default must
+/// be present in the referenced table, which is checked even when it equals the old key.
+fn reset_referencing_columns(
+    catalog: &Catalog,
+    table: &TableReference,
+    tuple: &mut Tuple,
+    constraint: &Constraint,
+    action: ReferentialAction,
+) -> BustubxResult<()> {
+    for col_name in constraint.columns.iter() {
+        let idx = tuple.schema.index_of(None, col_name)?;
+        let col = &tuple.schema.columns[idx];
+        tuple.data[idx] = if action == ReferentialAction::SetDefault {
+            col.default_value()?
+        } else {
+            ScalarValue::new_empty(col.data_type)
+        };
+    }
+    if action == ReferentialAction::SetDefault {
+        check_foreign_keys(catalog, table, tuple, None)?;
+    }
+    Ok(())
+}
+
+/// The unique constraint of the referenced table a foreign key is checked with.
+fn referenced_constraint(catalog: &Catalog, foreign_key: &ForeignKey) -> BustubxResult<Constraint> {
+    catalog
+        .table_constraints(&foreign_key.table)?
+        .into_iter()
+        .find(|c| {
+            c.kind.is_unique()
+                && c.columns.len() == foreign_key.columns.len()
+                && c.columns
+                    .iter()
+                    .all(|col| foreign_key.columns.contains(col))
+        })
+        .ok_or_else(|| {
+            BustubxError::Internal(format!(
+                "no unique constraint on {} matching foreign key",
+                foreign_key.table
+            ))
+        })
+}
+
+fn constraint_index(
+    catalog: &Catalog,
+    table: &TableReference,
+    constraint: &Constraint,
+) -> BustubxResult<Arc<BPlusTreeIndex>> {
+    catalog.index(table, &constraint.name)?.ok_or_else(|| {
+        BustubxError::Internal(format!("index of constraint {} not found", constraint.name))
+    })
+}
+
+/// Looks up a key in an index of `table`, ignoring entries of deleted tuples or tuples
+/// whose key has changed since.
+fn lookup_live(
+    catalog: &Catalog,
+    table: &TableReference,
+    index: &BPlusTreeIndex,
+    key: &Tuple,
+) -> BustubxResult<Option<RecordId>> {
+    let table_heap = catalog.table_heap(table)?;
+    for rid in index.get(key)? {
+        let (meta, tuple) = table_heap.full_tuple(rid)?;
+        if !meta.is_deleted && &tuple.project_with_schema(index.key_schema.clone())? == key {
+            return Ok(Some(rid));
+        }
+    }
+    Ok(None)
+}
+
+fn column_values(tuple: &Tuple, columns: &[String]) -> BustubxResult<Vec<ScalarValue>> {
+    columns
+        .iter()
+        .map(|name| tuple.value_by_name(None, name).cloned())
+        .collect()
+}
+
+fn is_same_table(left: &TableReference, right: &TableReference) -> bool {
+    left.table() == right.table()
+        && left.schema().unwrap_or(DEFAULT_SCHEMA_NAME)
+            == right.schema().unwrap_or(DEFAULT_SCHEMA_NAME)
+        && left.catalog().unwrap_or(DEFAULT_CATALOG_NAME)
+            == right.catalog().unwrap_or(DEFAULT_CATALOG_NAME)
+}
+
+fn format_values(values: &[ScalarValue]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
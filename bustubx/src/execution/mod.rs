@@ -1,8 +1,11 @@
+mod constraint;
 pub mod physical_plan;
 
 use std::sync::Arc;
 
 use crate::catalog::SchemaRef;
+use crate::common::datetime::{self, StatementTimestampGuard};
+use crate::execution::constraint::{undo_writes, WriteLog};
 use crate::execution::physical_plan::PhysicalPlan;
 use crate::{catalog::Catalog, storage::Tuple, BustubxResult};
 
@@ -19,6 +22,12 @@ pub trait VolcanoExecutor {
 #[derive(derive_new::new)]
 pub struct ExecutionContext<'a> {
     pub catalog: &'a mut Catalog,
+    /// Microseconds since epoch when the statement started, returned by `now()`
+    #[new(value = "datetime::now_micros()")]
+    pub statement_timestamp: i64,
+    /// Rows written by the statement, undone if it fails
+    #[new(default)]
+    pub(crate) write_log: WriteLog,
 }
 
 pub struct ExecutionEngine<'a> {
@@ -26,6 +35,17 @@ pub struct ExecutionEngine<'a> {
 }
 impl ExecutionEngine<'_> {
     pub fn execute(&mut self, plan: Arc<PhysicalPlan>) -> BustubxResult<Vec<Tuple>> {
+        let _timestamp = StatementTimestampGuard::new(self.context.statement_timestamp);
+        let result = self.execute_plan(plan);
+        let write_log = std::mem::take(&mut self.context.write_log);
+        if result.is_err() {
+            // a failed statement changes nothing
+            undo_writes(self.context.catalog, write_log)?;
+        }
+        result
+    }
+
+    fn execute_plan(&mut self, plan: Arc<PhysicalPlan>) -> BustubxResult<Vec<Tuple>> {
         plan.init(&mut self.context)?;
         let mut result = Vec::new();
         loop {
@@ -63,6 +63,7 @@ impl PhysicalAggregate {
 impl VolcanoExecutor for PhysicalAggregate {
     fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
         self.input.init(context)?;
+        self.output_rows.lock().unwrap().clear();
         self.cursor.store(0, Ordering::SeqCst);
         Ok(())
     }
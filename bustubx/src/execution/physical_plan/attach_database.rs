@@ -0,0 +1,28 @@
+use crate::catalog::{SchemaRef, EMPTY_SCHEMA_REF};
+use crate::{
+    execution::{ExecutionContext, VolcanoExecutor},
+    storage::Tuple,
+    BustubxResult,
+};
+
+#[derive(Debug, derive_new::new)]
+pub struct PhysicalAttachDatabase {
+    pub path: String,
+    pub name: String,
+}
+
+impl VolcanoExecutor for PhysicalAttachDatabase {
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        context.catalog.attach(&self.name, &self.path)?;
+        Ok(None)
+    }
+    fn output_schema(&self) -> SchemaRef {
+        EMPTY_SCHEMA_REF.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalAttachDatabase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AttachDatabase: {} AS {}", self.path, self.name)
+    }
+}
@@ -1,10 +1,12 @@
-use crate::catalog::{SchemaRef, EMPTY_SCHEMA_REF};
+use crate::catalog::{IndexType, Schema, SchemaRef, EMPTY_SCHEMA_REF};
 use crate::common::TableReference;
 use crate::expression::{ColumnExpr, Expr};
 use crate::planner::logical_plan::OrderByExpr;
+use crate::storage::hash_index::HashIndex;
+use crate::storage::index::BPlusTreeIndex;
 use crate::{
     execution::{ExecutionContext, VolcanoExecutor},
-    storage::Tuple,
+    storage::{TableIterator, Tuple},
     BustubxError, BustubxResult,
 };
 use std::sync::Arc;
@@ -15,15 +17,17 @@ pub struct PhysicalCreateIndex {
     pub table: TableReference,
     pub table_schema: SchemaRef,
     pub columns: Vec<OrderByExpr>,
+    pub index_type: IndexType,
 }
 
 impl VolcanoExecutor for PhysicalCreateIndex {
     fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
-        let mut key_indices = vec![];
+        let mut key_columns = vec![];
         for col in self.columns.iter() {
             match col.expr.as_ref() {
                 Expr::Column(ColumnExpr { name, .. }) => {
-                    key_indices.push(self.table_schema.index_of(None, name)?);
+                    let column = self.table_schema.column_with_name(None, name)?;
+                    key_columns.push(column.as_ref().clone().with_descending(!col.asc));
                 }
                 _ => {
                     return Err(BustubxError::Execution(format!(
@@ -33,10 +37,38 @@ impl VolcanoExecutor for PhysicalCreateIndex {
                 }
             }
         }
-        let key_schema = Arc::new(self.table_schema.project(&key_indices)?);
-        context
+        let key_schema = Arc::new(Schema::new(key_columns));
+        let table_heap = context.catalog.table_heap(&self.table)?;
+
+        // keys of the rows already in the table are checked before the index is created
+        let mut iterator = TableIterator::new(table_heap, ..);
+        let mut kvs = vec![];
+        while let Some((rid, tuple)) = iterator.next()? {
+            let key = tuple.project_with_schema(key_schema.clone())?;
+            if self.index_type == IndexType::Hash {
+                HashIndex::check_key_size(&key)?;
+            } else {
+                BPlusTreeIndex::check_key_size(&key)?;
+            }
+            kvs.push((key, rid));
+        }
+        // a hash index has no order to build bottom-up, rows are inserted one by one
+        if self.index_type == IndexType::Hash {
+            let index =
+                context
+                    .catalog
+                    .create_hash_index(self.name.clone(), &self.table, key_schema)?;
+            for (key, rid) in kvs {
+                index.insert(&key, rid)?;
+            }
+            return Ok(None);
+        }
+        let index = context
             .catalog
             .create_index(self.name.clone(), &self.table, key_schema)?;
+
+        // rows already in the table are loaded bottom-up
+        index.bulk_load(kvs, context.catalog.index_fill_factor)?;
         Ok(None)
     }
     fn output_schema(&self) -> SchemaRef {
@@ -0,0 +1,31 @@
+use crate::catalog::{SchemaRef, EMPTY_SCHEMA_REF};
+use crate::{
+    execution::{ExecutionContext, VolcanoExecutor},
+    storage::Tuple,
+    BustubxResult,
+};
+
+#[derive(Debug, derive_new::new)]
+pub struct PhysicalCreateSchema {
+    pub name: String,
+    pub if_not_exists: bool,
+}
+
+impl VolcanoExecutor for PhysicalCreateSchema {
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        if self.if_not_exists && context.catalog.schemas.contains_key(&self.name) {
+            return Ok(None);
+        }
+        context.catalog.create_schema(self.name.clone())?;
+        Ok(None)
+    }
+    fn output_schema(&self) -> SchemaRef {
+        EMPTY_SCHEMA_REF.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalCreateSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CreateSchema: {}", self.name)
+    }
+}
@@ -0,0 +1,31 @@
+use crate::catalog::{ResolvedSequenceOptions, SchemaRef, EMPTY_SCHEMA_REF};
+use crate::common::TableReference;
+use crate::{
+    execution::{ExecutionContext, VolcanoExecutor},
+    storage::Tuple,
+    BustubxResult,
+};
+
+#[derive(Debug, derive_new::new)]
+pub struct PhysicalCreateSequence {
+    pub name: TableReference,
+    pub options: ResolvedSequenceOptions,
+}
+
+impl VolcanoExecutor for PhysicalCreateSequence {
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        context
+            .catalog
+            .create_sequence(self.name.clone(), self.options)?;
+        Ok(None)
+    }
+    fn output_schema(&self) -> SchemaRef {
+        EMPTY_SCHEMA_REF.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalCreateSequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CreateSequence: {}", self.name)
+    }
+}
@@ -1,10 +1,12 @@
-use crate::catalog::SchemaRef;
+use crate::catalog::{Constraint, SchemaRef, StoredExpr, DEFAULT_SCHEMA_NAME};
 use crate::common::TableReference;
+use crate::expression::{Expr, SequenceFunction, SequenceFunctionKind};
+use crate::planner::logical_plan::ColumnSequence;
 use crate::{
     catalog::Schema,
     execution::{ExecutionContext, VolcanoExecutor},
     storage::Tuple,
-    BustubxResult,
+    BustubxError, BustubxResult,
 };
 use std::sync::Arc;
 
@@ -12,13 +14,51 @@ use std::sync::Arc;
 pub struct PhysicalCreateTable {
     pub table: TableReference,
     pub schema: Schema,
+    pub constraints: Vec<Constraint>,
+    pub sequences: Vec<ColumnSequence>,
 }
 
 impl VolcanoExecutor for PhysicalCreateTable {
     fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        let mut schema = self.schema.clone();
+        if !self.sequences.is_empty() {
+            // don't leave sequences behind when the table cannot be created
+            if context.catalog.table_heap(&self.table).is_ok() {
+                return Err(BustubxError::Storage(
+                    "Cannot create duplicated table".to_string(),
+                ));
+            }
+            for column_sequence in self.sequences.iter() {
+                let sequence = context
+                    .catalog
+                    .create_sequence(column_sequence.sequence.clone(), column_sequence.options)?;
+                let idx = schema.index_of(None, &column_sequence.column)?;
+                let mut column = schema.columns[idx].as_ref().clone();
+                column.default = Some(StoredExpr {
+                    sql: format!(
+                        "nextval('{}.{}')",
+                        column_sequence
+                            .sequence
+                            .schema()
+                            .unwrap_or(DEFAULT_SCHEMA_NAME),
+                        column_sequence.sequence.table()
+                    ),
+                    expr: Expr::SequenceFunction(SequenceFunction {
+                        kind: SequenceFunctionKind::NextVal,
+                        sequence,
+                    }),
+                });
+                schema.columns[idx] = Arc::new(column);
+            }
+        }
         context
             .catalog
-            .create_table(self.table.clone(), Arc::new(self.schema.clone()))?;
+            .create_table(self.table.clone(), Arc::new(schema))?;
+        for constraint in self.constraints.iter() {
+            context
+                .catalog
+                .create_constraint(&self.table, constraint.clone())?;
+        }
         Ok(None)
     }
     fn output_schema(&self) -> SchemaRef {
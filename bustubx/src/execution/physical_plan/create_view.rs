@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use crate::catalog::{CatalogView, Column, Schema, SchemaRef, EMPTY_SCHEMA_REF};
+use crate::common::TableReference;
+use crate::execution::{ExecutionContext, VolcanoExecutor};
+use crate::storage::EMPTY_TUPLE_META;
+use crate::{BustubxResult, Tuple};
+
+use super::PhysicalPlan;
+
+#[derive(Debug, derive_new::new)]
+pub struct PhysicalCreateView {
+    pub name: TableReference,
+    pub definition: String,
+    pub columns: Vec<String>,
+    pub materialized: bool,
+    pub input: Arc<PhysicalPlan>,
+}
+
+impl VolcanoExecutor for PhysicalCreateView {
+    fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
+        if self.materialized {
+            self.input.init(context)?;
+        }
+        Ok(())
+    }
+
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        if self.materialized {
+            let schema = Schema::new(
+                self.input
+                    .output_schema()
+                    .columns
+                    .iter()
+                    .map(|col| {
+                        Column::new(col.name.clone(), col.data_type, col.nullable)
+                            .with_relation(Some(self.name.clone()))
+                    })
+                    .collect(),
+            );
+            context
+                .catalog
+                .create_table(self.name.clone(), Arc::new(schema))?;
+        }
+        context.catalog.create_view(
+            self.name.clone(),
+            CatalogView {
+                name: self.name.table().to_string(),
+                definition: self.definition.clone(),
+                columns: self.columns.clone(),
+                materialized: self.materialized,
+            },
+        )?;
+        if self.materialized {
+            insert_view_rows(context, &self.name, &self.input)?;
+        }
+        Ok(None)
+    }
+
+    fn output_schema(&self) -> SchemaRef {
+        EMPTY_SCHEMA_REF.clone()
+    }
+}
+
+/// Stores the query result into the table of a materialized view.
+pub(super) fn insert_view_rows(
+    context: &mut ExecutionContext,
+    table: &TableReference,
+    input: &PhysicalPlan,
+) -> BustubxResult<()> {
+    let table_heap = context.catalog.table_heap(table)?;
+    let indexes = context.catalog.table_indexes(table)?;
+    let hash_indexes = context.catalog.table_hash_indexes(table)?;
+    while let Some(tuple) = input.next(context)? {
+        let tuple = Tuple::new(table_heap.schema.clone(), tuple.data);
+        let rid = table_heap.insert_tuple(&EMPTY_TUPLE_META, &tuple)?;
+        for index in indexes.iter() {
+            let key_tuple = tuple.project_with_schema(index.key_schema.clone())?;
+            index.insert(&key_tuple, rid)?;
+        }
+        for index in hash_indexes.iter() {
+            let key_tuple = tuple.project_with_schema(index.key_schema.clone())?;
+            index.insert(&key_tuple, rid)?;
+        }
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for PhysicalCreateView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.materialized {
+            write!(f, "CreateMaterializedView: {}", self.name)
+        } else {
+            write!(f, "CreateView: {}", self.name)
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::catalog::SchemaRef;
+use crate::{
+    execution::{ExecutionContext, VolcanoExecutor},
+    storage::Tuple,
+    BustubxResult,
+};
+
+use super::PhysicalPlan;
+
+/// Materialized result of a common table expression, shared by all of its scans.
+pub type CteResult = Arc<Mutex<Option<Vec<Tuple>>>>;
+
+#[derive(Debug)]
+pub struct PhysicalCteScan {
+    pub name: String,
+    pub input: Arc<PhysicalPlan>,
+    pub result: CteResult,
+
+    cursor: AtomicUsize,
+}
+
+impl PhysicalCteScan {
+    pub fn new(name: String, input: Arc<PhysicalPlan>, result: CteResult) -> Self {
+        Self {
+            name,
+            input,
+            result,
+            cursor: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl VolcanoExecutor for PhysicalCteScan {
+    fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
+        let mut result = self.result.lock().unwrap();
+        if result.is_none() {
+            // the first scan evaluates the CTE, the others reuse its result
+            self.input.init(context)?;
+            let mut tuples = vec![];
+            while let Some(tuple) = self.input.next(context)? {
+                tuples.push(tuple);
+            }
+            *result = Some(tuples);
+        }
+        self.cursor.store(0, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn next(&self, _context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        let cursor = self.cursor.fetch_add(1, Ordering::SeqCst);
+        Ok(self
+            .result
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|tuples| tuples.get(cursor).cloned()))
+    }
+
+    fn output_schema(&self) -> SchemaRef {
+        self.input.output_schema()
+    }
+}
+
+impl std::fmt::Display for PhysicalCteScan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CteScan: {}", self.name)
+    }
+}
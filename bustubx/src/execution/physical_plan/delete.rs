@@ -0,0 +1,114 @@
+use crate::catalog::{SchemaRef, DELETE_OUTPUT_SCHEMA_REF};
+use crate::common::{ScalarValue, TableReference};
+use crate::execution::constraint::{delete_row, skip_moved_rows, MovedRows};
+use crate::execution::{ExecutionContext, VolcanoExecutor};
+use crate::expression::{Expr, ExprTrait};
+use crate::planner::logical_plan::Returning;
+use crate::storage::{RecordId, TableIterator};
+use crate::{BustubxError, BustubxResult, Tuple};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+#[derive(Debug)]
+pub struct PhysicalDelete {
+    pub table: TableReference,
+    pub selection: Option<Expr>,
+    pub returning: Option<Returning>,
+
+    delete_rows: AtomicU32,
+    table_iterator: Mutex<Option<TableIterator>>,
+    /// Rows moved to the end of the heap by foreign key actions after being visited,
+    /// not to be checked again
+    moved_rids: Mutex<HashSet<RecordId>>,
+}
+
+impl PhysicalDelete {
+    pub fn new(
+        table: TableReference,
+        selection: Option<Expr>,
+        returning: Option<Returning>,
+    ) -> Self {
+        Self {
+            table,
+            selection,
+            returning,
+            delete_rows: AtomicU32::new(0),
+            table_iterator: Mutex::new(None),
+            moved_rids: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+impl VolcanoExecutor for PhysicalDelete {
+    fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
+        self.delete_rows.store(0, Ordering::SeqCst);
+        self.moved_rids.lock().unwrap().clear();
+        let table_heap = context.catalog.table_heap(&self.table)?;
+        *self.table_iterator.lock().unwrap() = Some(TableIterator::new(table_heap.clone(), ..));
+        Ok(())
+    }
+
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        let Some(table_iterator) = &mut *self.table_iterator.lock().unwrap() else {
+            return Err(BustubxError::Execution(
+                "table iterator not created".to_string(),
+            ));
+        };
+
+        loop {
+            if let Some((rid, tuple)) = table_iterator.next()? {
+                if self.moved_rids.lock().unwrap().contains(&rid) {
+                    continue;
+                }
+                if let Some(selection) = &self.selection {
+                    if !selection.evaluate(&tuple)?.as_boolean()?.unwrap_or(false) {
+                        continue;
+                    }
+                }
+                let mut moved = MovedRows::new();
+                delete_row(
+                    context.catalog,
+                    &self.table,
+                    rid,
+                    &tuple,
+                    &mut moved,
+                    &mut context.write_log,
+                )?;
+                skip_moved_rows(
+                    &self.table,
+                    moved,
+                    table_iterator,
+                    &mut self.moved_rids.lock().unwrap(),
+                )?;
+                if let Some(returning) = &self.returning {
+                    return Ok(Some(returning.project(&tuple)?));
+                }
+                self.delete_rows.fetch_add(1, Ordering::SeqCst);
+            } else {
+                return if self.delete_rows.load(Ordering::SeqCst) == 0 {
+                    Ok(None)
+                } else {
+                    let delete_rows = self.delete_rows.swap(0, Ordering::SeqCst);
+                    Ok(Some(Tuple::new(
+                        self.output_schema(),
+                        vec![ScalarValue::Int32(Some(delete_rows as i32))],
+                    )))
+                };
+            }
+        }
+    }
+
+    fn output_schema(&self) -> SchemaRef {
+        match &self.returning {
+            Some(returning) => returning.schema.clone(),
+            None => DELETE_OUTPUT_SCHEMA_REF.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for PhysicalDelete {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Delete")
+    }
+}
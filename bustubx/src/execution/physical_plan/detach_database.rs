@@ -0,0 +1,27 @@
+use crate::catalog::{SchemaRef, EMPTY_SCHEMA_REF};
+use crate::{
+    execution::{ExecutionContext, VolcanoExecutor},
+    storage::Tuple,
+    BustubxResult,
+};
+
+#[derive(Debug, derive_new::new)]
+pub struct PhysicalDetachDatabase {
+    pub name: String,
+}
+
+impl VolcanoExecutor for PhysicalDetachDatabase {
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        context.catalog.detach(&self.name)?;
+        Ok(None)
+    }
+    fn output_schema(&self) -> SchemaRef {
+        EMPTY_SCHEMA_REF.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalDetachDatabase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DetachDatabase: {}", self.name)
+    }
+}
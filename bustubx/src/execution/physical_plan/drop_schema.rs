@@ -0,0 +1,34 @@
+use crate::catalog::{SchemaRef, EMPTY_SCHEMA_REF};
+use crate::{
+    execution::{ExecutionContext, VolcanoExecutor},
+    storage::Tuple,
+    BustubxResult,
+};
+
+#[derive(Debug, derive_new::new)]
+pub struct PhysicalDropSchema {
+    pub names: Vec<String>,
+    pub if_exists: bool,
+    pub cascade: bool,
+}
+
+impl VolcanoExecutor for PhysicalDropSchema {
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        for name in self.names.iter() {
+            if self.if_exists && !context.catalog.schemas.contains_key(name) {
+                continue;
+            }
+            context.catalog.drop_schema(name, self.cascade)?;
+        }
+        Ok(None)
+    }
+    fn output_schema(&self) -> SchemaRef {
+        EMPTY_SCHEMA_REF.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalDropSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DropSchema: {}", self.names.join(", "))
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::catalog::SchemaRef;
+use crate::common::ScalarValue;
+use crate::{
+    execution::{ExecutionContext, VolcanoExecutor},
+    storage::Tuple,
+    BustubxResult,
+};
+
+use super::PhysicalPlan;
+
+#[derive(Debug)]
+pub struct PhysicalExcept {
+    pub left: Arc<PhysicalPlan>,
+    pub right: Arc<PhysicalPlan>,
+    pub is_all: bool,
+    pub schema: SchemaRef,
+
+    /// How many times each row of the right input can still remove a left row
+    right_counts: Mutex<Option<HashMap<Vec<ScalarValue>, usize>>>,
+    /// Rows already emitted, only used without ALL
+    emitted: Mutex<HashSet<Vec<ScalarValue>>>,
+}
+
+impl PhysicalExcept {
+    pub fn new(
+        left: Arc<PhysicalPlan>,
+        right: Arc<PhysicalPlan>,
+        is_all: bool,
+        schema: SchemaRef,
+    ) -> Self {
+        Self {
+            left,
+            right,
+            is_all,
+            schema,
+            right_counts: Mutex::new(None),
+            emitted: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+impl VolcanoExecutor for PhysicalExcept {
+    fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
+        self.left.init(context)?;
+        self.right.init(context)?;
+        *self.right_counts.lock().unwrap() = None;
+        self.emitted.lock().unwrap().clear();
+        Ok(())
+    }
+
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        if self.right_counts.lock().unwrap().is_none() {
+            let mut right_counts = HashMap::new();
+            while let Some(tuple) = self.right.next(context)? {
+                *right_counts.entry(tuple.data).or_insert(0) += 1;
+            }
+            *self.right_counts.lock().unwrap() = Some(right_counts);
+        }
+
+        while let Some(tuple) = self.left.next(context)? {
+            let mut guard = self.right_counts.lock().unwrap();
+            let right_counts = guard.as_mut().expect("right counts have been built");
+            if self.is_all {
+                match right_counts.get_mut(&tuple.data) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        continue;
+                    }
+                    _ => {}
+                }
+            } else if right_counts.contains_key(&tuple.data)
+                || !self.emitted.lock().unwrap().insert(tuple.data.clone())
+            {
+                continue;
+            }
+            return Ok(Some(Tuple::new(self.schema.clone(), tuple.data)));
+        }
+        Ok(None)
+    }
+
+    fn output_schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalExcept {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Except: is_all={}", self.is_all)
+    }
+}
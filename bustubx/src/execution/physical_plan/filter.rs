@@ -28,8 +28,9 @@ impl VolcanoExecutor for PhysicalFilter {
         loop {
             if let Some(tuple) = self.input.next(context)? {
                 let compare_res = self.predicate.evaluate(&tuple)?;
-                if let ScalarValue::Boolean(Some(v)) = compare_res {
-                    if v {
+                // rows whose predicate is NULL are filtered out
+                if let ScalarValue::Boolean(v) = compare_res {
+                    if v == Some(true) {
                         return Ok(Some(tuple));
                     }
                 } else {
@@ -0,0 +1,73 @@
+use crate::catalog::SchemaRef;
+use crate::common::TableReference;
+use crate::execution::{ExecutionContext, VolcanoExecutor};
+use crate::storage::RecordId;
+use crate::{BustubxError, BustubxResult, Tuple};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Reads the rows of a key from a hash index.
+#[derive(Debug)]
+pub struct PhysicalHashIndexScan {
+    table_ref: TableReference,
+    index_name: String,
+    table_schema: SchemaRef,
+    key: Tuple,
+    rids: Mutex<VecDeque<RecordId>>,
+}
+
+impl PhysicalHashIndexScan {
+    pub fn new(
+        table_ref: TableReference,
+        index_name: String,
+        table_schema: SchemaRef,
+        key: Tuple,
+    ) -> Self {
+        Self {
+            table_ref,
+            index_name,
+            table_schema,
+            key,
+            rids: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl VolcanoExecutor for PhysicalHashIndexScan {
+    fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
+        let Some(index) = context
+            .catalog
+            .hash_index(&self.table_ref, &self.index_name)?
+        else {
+            return Err(BustubxError::Execution(format!(
+                "hash index {} not found",
+                self.index_name
+            )));
+        };
+        *self.rids.lock().unwrap() = index.get(&self.key)?.into();
+        Ok(())
+    }
+
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        let table_heap = context.catalog.table_heap(&self.table_ref)?;
+        let mut rids = self.rids.lock().unwrap();
+        // deleted tuples may keep their index entries
+        while let Some(rid) = rids.pop_front() {
+            let (meta, tuple) = table_heap.full_tuple(rid)?;
+            if !meta.is_deleted {
+                return Ok(Some(tuple));
+            }
+        }
+        Ok(None)
+    }
+
+    fn output_schema(&self) -> SchemaRef {
+        self.table_schema.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalHashIndexScan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HashIndexScan: {}", self.index_name)
+    }
+}
@@ -0,0 +1,91 @@
+use crate::catalog::SchemaRef;
+use crate::common::TableReference;
+use crate::execution::{ExecutionContext, VolcanoExecutor};
+use crate::storage::index::TreeIndexIterator;
+use crate::{BustubxError, BustubxResult, Tuple};
+use std::ops::{Bound, RangeBounds};
+use std::sync::Mutex;
+
+/// Scans a range of an index whose key holds every output column, so rows are built
+/// from the index keys and the table is only read to check rows of pages with deleted
+/// tuples.
+#[derive(Debug)]
+pub struct PhysicalIndexOnlyScan {
+    table_ref: TableReference,
+    index_name: String,
+    schema: SchemaRef,
+    /// Positions of the output columns in the index key
+    key_positions: Vec<usize>,
+    start_bound: Bound<Tuple>,
+    end_bound: Bound<Tuple>,
+    iterator: Mutex<Option<TreeIndexIterator>>,
+}
+
+impl PhysicalIndexOnlyScan {
+    pub fn new<R: RangeBounds<Tuple>>(
+        table_ref: TableReference,
+        index_name: String,
+        schema: SchemaRef,
+        key_positions: Vec<usize>,
+        range: R,
+    ) -> Self {
+        Self {
+            table_ref,
+            index_name,
+            schema,
+            key_positions,
+            start_bound: range.start_bound().cloned(),
+            end_bound: range.end_bound().cloned(),
+            iterator: Mutex::new(None),
+        }
+    }
+}
+
+impl VolcanoExecutor for PhysicalIndexOnlyScan {
+    fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
+        let Some(index) = context.catalog.index(&self.table_ref, &self.index_name)? else {
+            return Err(BustubxError::Execution(format!(
+                "index {} not found",
+                self.index_name
+            )));
+        };
+        *self.iterator.lock().unwrap() = Some(TreeIndexIterator::new(
+            index,
+            (self.start_bound.clone(), self.end_bound.clone()),
+        ));
+        Ok(())
+    }
+
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        let mut guard = self.iterator.lock().unwrap();
+        let Some(iterator) = &mut *guard else {
+            return Err(BustubxError::Execution(
+                "index iterator not created".to_string(),
+            ));
+        };
+        let table_heap = context.catalog.table_heap(&self.table_ref)?;
+        while let Some((key, rid)) = iterator.next_entry()? {
+            // deleted tuples may keep their index entries
+            if !table_heap.is_all_visible(rid.page_id)? && table_heap.tuple_meta(rid)?.is_deleted {
+                continue;
+            }
+            let data = self
+                .key_positions
+                .iter()
+                .map(|pos| key.data[*pos].clone())
+                .collect();
+            return Ok(Some(Tuple::new(self.schema.clone(), data)));
+        }
+        Ok(None)
+    }
+
+    fn output_schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalIndexOnlyScan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IndexOnlyScan: {}", self.index_name)
+    }
+}
@@ -55,11 +55,14 @@ impl VolcanoExecutor for PhysicalIndexScan {
             ));
         };
         let table_heap = context.catalog.table_heap(&self.table_ref)?;
-        if let Some(rid) = iterator.next()? {
-            Ok(Some(table_heap.tuple(rid)?))
-        } else {
-            Ok(None)
+        // deleted tuples may keep their index entries
+        while let Some(rid) = iterator.next()? {
+            let (meta, tuple) = table_heap.full_tuple(rid)?;
+            if !meta.is_deleted {
+                return Ok(Some(tuple));
+            }
         }
+        Ok(None)
     }
 
     fn output_schema(&self) -> SchemaRef {
@@ -1,15 +1,22 @@
 use log::debug;
+use std::collections::HashSet;
 use std::sync::atomic::Ordering;
-use std::sync::{atomic::AtomicU32, Arc};
+use std::sync::{atomic::AtomicU32, Arc, Mutex};
 
-use crate::catalog::{SchemaRef, INSERT_OUTPUT_SCHEMA_REF};
+use crate::catalog::{Schema, SchemaRef, INSERT_OUTPUT_SCHEMA_REF};
 use crate::common::TableReference;
-use crate::storage::EMPTY_TUPLE_META;
+use crate::execution::constraint::{
+    check_checks, check_foreign_keys, check_index_keys, check_not_null, check_unique,
+    find_conflict, insert_index_entries, update_row, MovedRows, RowWrite,
+};
+use crate::expression::ExprTrait;
+use crate::planner::logical_plan::{OnConflict, OnConflictAction, Returning};
+use crate::storage::{RecordId, EMPTY_TUPLE_META};
 use crate::{
     common::ScalarValue,
     execution::{ExecutionContext, VolcanoExecutor},
     storage::Tuple,
-    BustubxResult,
+    BustubxError, BustubxResult,
 };
 
 use super::PhysicalPlan;
@@ -20,8 +27,12 @@ pub struct PhysicalInsert {
     pub table_schema: SchemaRef,
     pub projected_schema: SchemaRef,
     pub input: Arc<PhysicalPlan>,
+    pub on_conflict: Option<OnConflict>,
+    pub returning: Option<Returning>,
 
     insert_rows: AtomicU32,
+    /// Rows inserted or updated by this statement
+    affected_rids: Mutex<HashSet<RecordId>>,
 }
 impl PhysicalInsert {
     pub fn new(
@@ -29,14 +40,116 @@ impl PhysicalInsert {
         table_schema: SchemaRef,
         projected_schema: SchemaRef,
         input: Arc<PhysicalPlan>,
+        on_conflict: Option<OnConflict>,
+        returning: Option<Returning>,
     ) -> Self {
         Self {
             table,
             table_schema,
             projected_schema,
             input,
+            on_conflict,
+            returning,
             insert_rows: AtomicU32::new(0),
+            affected_rids: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Inserts a row or resolves its conflict, returning the row written if any.
+    fn insert_row(
+        &self,
+        context: &mut ExecutionContext,
+        tuple: Tuple,
+    ) -> BustubxResult<Option<Tuple>> {
+        check_not_null(&self.table, &tuple)?;
+        check_checks(context.catalog, &self.table, &tuple)?;
+        check_index_keys(context.catalog, &self.table, &tuple)?;
+        if let Some(on_conflict) = &self.on_conflict {
+            if let Some(rid) = find_conflict(
+                context.catalog,
+                &self.table,
+                &tuple,
+                &on_conflict.constraints,
+            )? {
+                return self.resolve_conflict(context, on_conflict, rid, tuple);
+            }
         }
+        check_unique(context.catalog, &self.table, &tuple, None)?;
+        check_foreign_keys(context.catalog, &self.table, &tuple, None)?;
+
+        let table_heap = context.catalog.table_heap(&self.table)?;
+        let rid = table_heap.insert_tuple(&EMPTY_TUPLE_META, &tuple)?;
+        context.write_log.push(RowWrite::Insert {
+            table: self.table.clone(),
+            rid,
+            tuple: tuple.clone(),
+        });
+        insert_index_entries(context.catalog, &self.table, &tuple, rid)?;
+        self.affected_rids.lock().unwrap().insert(rid);
+        Ok(Some(tuple))
+    }
+
+    fn resolve_conflict(
+        &self,
+        context: &mut ExecutionContext,
+        on_conflict: &OnConflict,
+        rid: RecordId,
+        tuple: Tuple,
+    ) -> BustubxResult<Option<Tuple>> {
+        let OnConflictAction::DoUpdate {
+            assignments,
+            selection,
+        } = &on_conflict.action
+        else {
+            return Ok(None);
+        };
+        if !self.affected_rids.lock().unwrap().insert(rid) {
+            return Err(BustubxError::Execution(
+                "ON CONFLICT DO UPDATE command cannot affect row a second time".to_string(),
+            ));
+        }
+
+        let existing = context.catalog.table_heap(&self.table)?.tuple(rid)?;
+        // the proposed row is visible as `excluded`, unqualified columns are the existing row
+        let excluded_schema = Schema::new(
+            self.table_schema
+                .columns
+                .iter()
+                .map(|col| {
+                    col.as_ref()
+                        .clone()
+                        .with_relation(Some(TableReference::bare("excluded")))
+                })
+                .collect(),
+        );
+        let merged = Tuple::try_merge([
+            existing.clone(),
+            Tuple::new(Arc::new(excluded_schema), tuple.data),
+        ])?;
+        if let Some(selection) = selection {
+            if !selection.evaluate(&merged)?.as_boolean()?.unwrap_or(false) {
+                return Ok(None);
+            }
+        }
+
+        let mut new_tuple = existing.clone();
+        for (col_name, value_expr) in assignments.iter() {
+            let index = new_tuple.schema.index_of(None, col_name)?;
+            let col_datatype = new_tuple.schema.columns[index].data_type;
+            new_tuple.data[index] = value_expr.evaluate(&merged)?.cast_to(&col_datatype)?;
+        }
+        let new_rid = update_row(
+            context.catalog,
+            &self.table,
+            rid,
+            &existing,
+            new_tuple.clone(),
+            // inserts don't scan the table, so moved rows are never visited again
+            &mut MovedRows::new(),
+            &mut context.write_log,
+        )?;
+        self.affected_rids.lock().unwrap().insert(new_rid);
+        Ok(Some(new_tuple))
     }
 }
 impl VolcanoExecutor for PhysicalInsert {
@@ -44,6 +157,7 @@ impl VolcanoExecutor for PhysicalInsert {
         debug!("init insert executor");
         self.input.init(context)?;
         self.insert_rows.store(0, Ordering::SeqCst);
+        self.affected_rids.lock().unwrap().clear();
         Ok(())
     }
     fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
@@ -79,33 +193,26 @@ impl VolcanoExecutor for PhysicalInsert {
                 {
                     full_data.push(casted_data[idx].clone());
                 } else {
-                    full_data.push(col.default.clone())
+                    full_data.push(col.default_value()?)
                 }
             }
 
             let tuple = Tuple::new(self.table_schema.clone(), full_data);
-
-            let table_heap = context.catalog.table_heap(&self.table)?;
-            let rid = table_heap.insert_tuple(&EMPTY_TUPLE_META, &tuple)?;
-
-            let indexes = context.catalog.table_indexes(&self.table)?;
-            for index in indexes {
-                if let Ok(key_tuple) = tuple.project_with_schema(index.key_schema.clone()) {
-                    let root_page_id = index.root_page_id.load(Ordering::SeqCst);
-                    index.insert(&key_tuple, rid)?;
-                    let new_root_page_id = index.root_page_id.load(Ordering::SeqCst);
-                    if new_root_page_id != root_page_id {
-                        // TODO update system table
-                    }
-                }
+            let Some(written) = self.insert_row(context, tuple)? else {
+                continue;
+            };
+            if let Some(returning) = &self.returning {
+                return Ok(Some(returning.project(&written)?));
             }
-
             self.insert_rows.fetch_add(1, Ordering::SeqCst);
         }
     }
 
     fn output_schema(&self) -> SchemaRef {
-        INSERT_OUTPUT_SCHEMA_REF.clone()
+        match &self.returning {
+            Some(returning) => returning.schema.clone(),
+            None => INSERT_OUTPUT_SCHEMA_REF.clone(),
+        }
     }
 }
 
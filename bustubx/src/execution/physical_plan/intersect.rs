@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::catalog::SchemaRef;
+use crate::common::ScalarValue;
+use crate::{
+    execution::{ExecutionContext, VolcanoExecutor},
+    storage::Tuple,
+    BustubxResult,
+};
+
+use super::PhysicalPlan;
+
+#[derive(Debug)]
+pub struct PhysicalIntersect {
+    pub left: Arc<PhysicalPlan>,
+    pub right: Arc<PhysicalPlan>,
+    pub is_all: bool,
+    pub schema: SchemaRef,
+
+    /// How many times each row of the right input can still be matched
+    right_counts: Mutex<Option<HashMap<Vec<ScalarValue>, usize>>>,
+}
+
+impl PhysicalIntersect {
+    pub fn new(
+        left: Arc<PhysicalPlan>,
+        right: Arc<PhysicalPlan>,
+        is_all: bool,
+        schema: SchemaRef,
+    ) -> Self {
+        Self {
+            left,
+            right,
+            is_all,
+            schema,
+            right_counts: Mutex::new(None),
+        }
+    }
+}
+
+impl VolcanoExecutor for PhysicalIntersect {
+    fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
+        self.left.init(context)?;
+        self.right.init(context)?;
+        *self.right_counts.lock().unwrap() = None;
+        Ok(())
+    }
+
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        if self.right_counts.lock().unwrap().is_none() {
+            let mut right_counts = HashMap::new();
+            while let Some(tuple) = self.right.next(context)? {
+                *right_counts.entry(tuple.data).or_insert(0) += 1;
+            }
+            *self.right_counts.lock().unwrap() = Some(right_counts);
+        }
+
+        while let Some(tuple) = self.left.next(context)? {
+            let mut guard = self.right_counts.lock().unwrap();
+            let right_counts = guard.as_mut().expect("right counts have been built");
+            let Some(count) = right_counts.get_mut(&tuple.data) else {
+                continue;
+            };
+            if *count == 0 {
+                continue;
+            }
+            // without ALL each distinct row is emitted once
+            *count = if self.is_all { *count - 1 } else { 0 };
+            return Ok(Some(Tuple::new(self.schema.clone(), tuple.data)));
+        }
+        Ok(None)
+    }
+
+    fn output_schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalIntersect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Intersect: is_all={}", self.is_all)
+    }
+}
@@ -1,32 +1,68 @@
 mod aggregate;
+mod attach_database;
 mod create_index;
+mod create_schema;
+mod create_sequence;
 mod create_table;
+mod create_view;
+mod cte_scan;
+mod delete;
+mod detach_database;
+mod drop_schema;
 mod empty;
+mod except;
 mod filter;
+mod hash_index_scan;
+mod index_only_scan;
 mod index_scan;
 mod insert;
+mod intersect;
 mod limit;
 mod nested_loop_join;
 mod project;
+mod recursive_query;
+mod refresh_materialized_view;
 mod seq_scan;
+mod set_variable;
 mod sort;
+mod union;
 mod update;
 mod values;
+mod window;
+mod work_table_scan;
 
 pub use aggregate::PhysicalAggregate;
+pub use attach_database::PhysicalAttachDatabase;
 pub use create_index::PhysicalCreateIndex;
+pub use create_schema::PhysicalCreateSchema;
+pub use create_sequence::PhysicalCreateSequence;
 pub use create_table::PhysicalCreateTable;
+pub use create_view::PhysicalCreateView;
+pub use cte_scan::PhysicalCteScan;
+pub use delete::PhysicalDelete;
+pub use detach_database::PhysicalDetachDatabase;
+pub use drop_schema::PhysicalDropSchema;
 pub use empty::PhysicalEmpty;
+pub use except::PhysicalExcept;
 pub use filter::PhysicalFilter;
+pub use hash_index_scan::PhysicalHashIndexScan;
+pub use index_only_scan::PhysicalIndexOnlyScan;
 pub use index_scan::PhysicalIndexScan;
 pub use insert::PhysicalInsert;
+pub use intersect::PhysicalIntersect;
 pub use limit::PhysicalLimit;
 pub use nested_loop_join::PhysicalNestedLoopJoin;
 pub use project::PhysicalProject;
+pub use recursive_query::PhysicalRecursiveQuery;
+pub use refresh_materialized_view::PhysicalRefreshMaterializedView;
 pub use seq_scan::PhysicalSeqScan;
+pub use set_variable::PhysicalSetVariable;
 pub use sort::PhysicalSort;
+pub use union::PhysicalUnion;
 pub use update::PhysicalUpdate;
 pub use values::PhysicalValues;
+pub use window::PhysicalWindow;
+pub use work_table_scan::{PhysicalWorkTableScan, WorkTable};
 
 use crate::catalog::SchemaRef;
 use crate::{
@@ -38,12 +74,22 @@ use crate::{
 #[derive(Debug)]
 pub enum PhysicalPlan {
     Empty(PhysicalEmpty),
+    CreateSchema(PhysicalCreateSchema),
+    DropSchema(PhysicalDropSchema),
+    SetVariable(PhysicalSetVariable),
+    AttachDatabase(PhysicalAttachDatabase),
+    DetachDatabase(PhysicalDetachDatabase),
     CreateTable(PhysicalCreateTable),
     CreateIndex(PhysicalCreateIndex),
+    CreateSequence(PhysicalCreateSequence),
+    CreateView(PhysicalCreateView),
+    RefreshMaterializedView(PhysicalRefreshMaterializedView),
     Project(PhysicalProject),
     Filter(PhysicalFilter),
     SeqScan(PhysicalSeqScan),
     IndexScan(PhysicalIndexScan),
+    IndexOnlyScan(PhysicalIndexOnlyScan),
+    HashIndexScan(PhysicalHashIndexScan),
     Limit(PhysicalLimit),
     Insert(PhysicalInsert),
     Values(PhysicalValues),
@@ -51,6 +97,14 @@ pub enum PhysicalPlan {
     Sort(PhysicalSort),
     Aggregate(PhysicalAggregate),
     Update(PhysicalUpdate),
+    Delete(PhysicalDelete),
+    CteScan(PhysicalCteScan),
+    RecursiveQuery(PhysicalRecursiveQuery),
+    WorkTableScan(PhysicalWorkTableScan),
+    Union(PhysicalUnion),
+    Intersect(PhysicalIntersect),
+    Except(PhysicalExcept),
+    Window(PhysicalWindow),
 }
 
 impl PhysicalPlan {
@@ -67,12 +121,37 @@ impl PhysicalPlan {
             }) => vec![left_input, right_input],
             PhysicalPlan::Sort(PhysicalSort { input, .. }) => vec![input],
             PhysicalPlan::Aggregate(PhysicalAggregate { input, .. }) => vec![input],
+            PhysicalPlan::CteScan(PhysicalCteScan { input, .. }) => vec![input],
+            PhysicalPlan::RecursiveQuery(PhysicalRecursiveQuery {
+                static_term,
+                recursive_term,
+                ..
+            }) => vec![static_term, recursive_term],
+            PhysicalPlan::Union(PhysicalUnion { left, right, .. }) => vec![left, right],
+            PhysicalPlan::Intersect(PhysicalIntersect { left, right, .. }) => vec![left, right],
+            PhysicalPlan::Except(PhysicalExcept { left, right, .. }) => vec![left, right],
+            PhysicalPlan::Window(PhysicalWindow { input, .. }) => vec![input],
+            PhysicalPlan::CreateView(PhysicalCreateView { input, .. }) => vec![input],
+            PhysicalPlan::RefreshMaterializedView(PhysicalRefreshMaterializedView {
+                input,
+                ..
+            }) => vec![input],
             PhysicalPlan::Empty(_)
+            | PhysicalPlan::CreateSchema(_)
+            | PhysicalPlan::DropSchema(_)
+            | PhysicalPlan::SetVariable(_)
+            | PhysicalPlan::AttachDatabase(_)
+            | PhysicalPlan::DetachDatabase(_)
             | PhysicalPlan::CreateTable(_)
             | PhysicalPlan::CreateIndex(_)
+            | PhysicalPlan::CreateSequence(_)
             | PhysicalPlan::SeqScan(_)
             | PhysicalPlan::IndexScan(_)
+            | PhysicalPlan::IndexOnlyScan(_)
+            | PhysicalPlan::HashIndexScan(_)
             | PhysicalPlan::Update(_)
+            | PhysicalPlan::Delete(_)
+            | PhysicalPlan::WorkTableScan(_)
             | PhysicalPlan::Values(_) => vec![],
         }
     }
@@ -82,57 +161,111 @@ impl VolcanoExecutor for PhysicalPlan {
     fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
         match self {
             PhysicalPlan::Empty(op) => op.init(context),
+            PhysicalPlan::CreateSchema(op) => op.init(context),
+            PhysicalPlan::DropSchema(op) => op.init(context),
+            PhysicalPlan::SetVariable(op) => op.init(context),
+            PhysicalPlan::AttachDatabase(op) => op.init(context),
+            PhysicalPlan::DetachDatabase(op) => op.init(context),
             PhysicalPlan::CreateTable(op) => op.init(context),
             PhysicalPlan::CreateIndex(op) => op.init(context),
+            PhysicalPlan::CreateSequence(op) => op.init(context),
+            PhysicalPlan::CreateView(op) => op.init(context),
+            PhysicalPlan::RefreshMaterializedView(op) => op.init(context),
             PhysicalPlan::Insert(op) => op.init(context),
             PhysicalPlan::Values(op) => op.init(context),
             PhysicalPlan::Project(op) => op.init(context),
             PhysicalPlan::Filter(op) => op.init(context),
             PhysicalPlan::SeqScan(op) => op.init(context),
             PhysicalPlan::IndexScan(op) => op.init(context),
+            PhysicalPlan::IndexOnlyScan(op) => op.init(context),
+            PhysicalPlan::HashIndexScan(op) => op.init(context),
             PhysicalPlan::Limit(op) => op.init(context),
             PhysicalPlan::NestedLoopJoin(op) => op.init(context),
             PhysicalPlan::Sort(op) => op.init(context),
             PhysicalPlan::Aggregate(op) => op.init(context),
             PhysicalPlan::Update(op) => op.init(context),
+            PhysicalPlan::Delete(op) => op.init(context),
+            PhysicalPlan::CteScan(op) => op.init(context),
+            PhysicalPlan::RecursiveQuery(op) => op.init(context),
+            PhysicalPlan::WorkTableScan(op) => op.init(context),
+            PhysicalPlan::Union(op) => op.init(context),
+            PhysicalPlan::Intersect(op) => op.init(context),
+            PhysicalPlan::Except(op) => op.init(context),
+            PhysicalPlan::Window(op) => op.init(context),
         }
     }
 
     fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
         match self {
             PhysicalPlan::Empty(op) => op.next(context),
+            PhysicalPlan::CreateSchema(op) => op.next(context),
+            PhysicalPlan::DropSchema(op) => op.next(context),
+            PhysicalPlan::SetVariable(op) => op.next(context),
+            PhysicalPlan::AttachDatabase(op) => op.next(context),
+            PhysicalPlan::DetachDatabase(op) => op.next(context),
             PhysicalPlan::CreateTable(op) => op.next(context),
             PhysicalPlan::CreateIndex(op) => op.next(context),
+            PhysicalPlan::CreateSequence(op) => op.next(context),
+            PhysicalPlan::CreateView(op) => op.next(context),
+            PhysicalPlan::RefreshMaterializedView(op) => op.next(context),
             PhysicalPlan::Insert(op) => op.next(context),
             PhysicalPlan::Values(op) => op.next(context),
             PhysicalPlan::Project(op) => op.next(context),
             PhysicalPlan::Filter(op) => op.next(context),
             PhysicalPlan::SeqScan(op) => op.next(context),
             PhysicalPlan::IndexScan(op) => op.next(context),
+            PhysicalPlan::IndexOnlyScan(op) => op.next(context),
+            PhysicalPlan::HashIndexScan(op) => op.next(context),
             PhysicalPlan::Limit(op) => op.next(context),
             PhysicalPlan::NestedLoopJoin(op) => op.next(context),
             PhysicalPlan::Sort(op) => op.next(context),
             PhysicalPlan::Aggregate(op) => op.next(context),
             PhysicalPlan::Update(op) => op.next(context),
+            PhysicalPlan::Delete(op) => op.next(context),
+            PhysicalPlan::CteScan(op) => op.next(context),
+            PhysicalPlan::RecursiveQuery(op) => op.next(context),
+            PhysicalPlan::WorkTableScan(op) => op.next(context),
+            PhysicalPlan::Union(op) => op.next(context),
+            PhysicalPlan::Intersect(op) => op.next(context),
+            PhysicalPlan::Except(op) => op.next(context),
+            PhysicalPlan::Window(op) => op.next(context),
         }
     }
 
     fn output_schema(&self) -> SchemaRef {
         match self {
             Self::Empty(op) => op.output_schema(),
+            Self::CreateSchema(op) => op.output_schema(),
+            Self::DropSchema(op) => op.output_schema(),
+            Self::SetVariable(op) => op.output_schema(),
+            Self::AttachDatabase(op) => op.output_schema(),
+            Self::DetachDatabase(op) => op.output_schema(),
             Self::CreateTable(op) => op.output_schema(),
             Self::CreateIndex(op) => op.output_schema(),
+            Self::CreateSequence(op) => op.output_schema(),
+            Self::CreateView(op) => op.output_schema(),
+            Self::RefreshMaterializedView(op) => op.output_schema(),
             Self::Insert(op) => op.output_schema(),
             Self::Values(op) => op.output_schema(),
             Self::Project(op) => op.output_schema(),
             Self::Filter(op) => op.output_schema(),
             Self::SeqScan(op) => op.output_schema(),
             Self::IndexScan(op) => op.output_schema(),
+            Self::IndexOnlyScan(op) => op.output_schema(),
+            Self::HashIndexScan(op) => op.output_schema(),
             Self::Limit(op) => op.output_schema(),
             Self::NestedLoopJoin(op) => op.output_schema(),
             Self::Sort(op) => op.output_schema(),
             Self::Aggregate(op) => op.output_schema(),
             Self::Update(op) => op.output_schema(),
+            Self::Delete(op) => op.output_schema(),
+            Self::CteScan(op) => op.output_schema(),
+            Self::RecursiveQuery(op) => op.output_schema(),
+            Self::WorkTableScan(op) => op.output_schema(),
+            Self::Union(op) => op.output_schema(),
+            Self::Intersect(op) => op.output_schema(),
+            Self::Except(op) => op.output_schema(),
+            Self::Window(op) => op.output_schema(),
         }
     }
 }
@@ -141,19 +274,37 @@ impl std::fmt::Display for PhysicalPlan {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Empty(op) => write!(f, "{op}"),
+            Self::CreateSchema(op) => write!(f, "{op}"),
+            Self::DropSchema(op) => write!(f, "{op}"),
+            Self::SetVariable(op) => write!(f, "{op}"),
+            Self::AttachDatabase(op) => write!(f, "{op}"),
+            Self::DetachDatabase(op) => write!(f, "{op}"),
             Self::CreateTable(op) => write!(f, "{op}"),
             Self::CreateIndex(op) => write!(f, "{op}"),
+            Self::CreateSequence(op) => write!(f, "{op}"),
+            Self::CreateView(op) => write!(f, "{op}"),
+            Self::RefreshMaterializedView(op) => write!(f, "{op}"),
             Self::Insert(op) => write!(f, "{op}"),
             Self::Values(op) => write!(f, "{op}"),
             Self::Project(op) => write!(f, "{op}"),
             Self::Filter(op) => write!(f, "{op}"),
             Self::SeqScan(op) => write!(f, "{op}"),
             Self::IndexScan(op) => write!(f, "{op}"),
+            Self::IndexOnlyScan(op) => write!(f, "{op}"),
+            Self::HashIndexScan(op) => write!(f, "{op}"),
             Self::Limit(op) => write!(f, "{op}"),
             Self::NestedLoopJoin(op) => write!(f, "{op}"),
             Self::Sort(op) => write!(f, "{op}"),
             Self::Aggregate(op) => write!(f, "{op}"),
             Self::Update(op) => write!(f, "{op}"),
+            Self::Delete(op) => write!(f, "{op}"),
+            Self::CteScan(op) => write!(f, "{op}"),
+            Self::RecursiveQuery(op) => write!(f, "{op}"),
+            Self::WorkTableScan(op) => write!(f, "{op}"),
+            Self::Union(op) => write!(f, "{op}"),
+            Self::Intersect(op) => write!(f, "{op}"),
+            Self::Except(op) => write!(f, "{op}"),
+            Self::Window(op) => write!(f, "{op}"),
         }
     }
 }
@@ -78,8 +78,8 @@ impl VolcanoExecutor for PhysicalNestedLoopJoin {
                         Tuple::try_merge(vec![left_tuple.clone(), right_tuple.clone()])?;
                     let evaluate_res = condition.evaluate(&merged_tuple)?;
                     // TODO support left/right join after null support added
-                    if let ScalarValue::Boolean(Some(v)) = evaluate_res {
-                        if v {
+                    if let ScalarValue::Boolean(v) = evaluate_res {
+                        if v == Some(true) {
                             // save latest left_next_result before return
                             *self.left_tuple.lock().unwrap() = Some(left_tuple.clone());
 
@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use crate::catalog::SchemaRef;
+use crate::common::ScalarValue;
+use crate::{
+    execution::{ExecutionContext, VolcanoExecutor},
+    storage::Tuple,
+    BustubxResult,
+};
+
+use super::{PhysicalPlan, WorkTable};
+
+#[derive(Debug)]
+pub struct PhysicalRecursiveQuery {
+    pub name: String,
+    pub static_term: Arc<PhysicalPlan>,
+    pub recursive_term: Arc<PhysicalPlan>,
+    pub is_distinct: bool,
+    /// Shared with the work table scans inside the recursive term
+    pub work_table: WorkTable,
+
+    state: Mutex<RecursiveState>,
+}
+
+#[derive(Debug, Default)]
+struct RecursiveState {
+    static_term_done: bool,
+    /// Rows produced by the current iteration, they become the next work table
+    intermediate: Vec<Tuple>,
+    /// Rows already produced, only used by UNION
+    seen: HashSet<Vec<ScalarValue>>,
+}
+
+impl PhysicalRecursiveQuery {
+    pub fn new(
+        name: String,
+        static_term: Arc<PhysicalPlan>,
+        recursive_term: Arc<PhysicalPlan>,
+        is_distinct: bool,
+        work_table: WorkTable,
+    ) -> Self {
+        Self {
+            name,
+            static_term,
+            recursive_term,
+            is_distinct,
+            work_table,
+            state: Mutex::new(RecursiveState::default()),
+        }
+    }
+}
+
+impl VolcanoExecutor for PhysicalRecursiveQuery {
+    fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
+        self.static_term.init(context)?;
+        *self.state.lock().unwrap() = RecursiveState::default();
+        self.work_table.lock().unwrap().clear();
+        Ok(())
+    }
+
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        loop {
+            let static_term_done = self.state.lock().unwrap().static_term_done;
+            let next_tuple = if static_term_done {
+                self.recursive_term.next(context)?
+            } else {
+                self.static_term.next(context)?
+            };
+
+            let mut state = self.state.lock().unwrap();
+            match next_tuple {
+                Some(tuple) => {
+                    if self.is_distinct && !state.seen.insert(tuple.data.clone()) {
+                        continue;
+                    }
+                    let tuple = Tuple::new(self.output_schema(), tuple.data);
+                    state.intermediate.push(tuple.clone());
+                    return Ok(Some(tuple));
+                }
+                None => {
+                    // start next iteration with the rows of this iteration
+                    state.static_term_done = true;
+                    let intermediate = std::mem::take(&mut state.intermediate);
+                    drop(state);
+                    if intermediate.is_empty() {
+                        return Ok(None);
+                    }
+                    *self.work_table.lock().unwrap() = intermediate;
+                    self.recursive_term.init(context)?;
+                }
+            }
+        }
+    }
+
+    fn output_schema(&self) -> SchemaRef {
+        self.static_term.output_schema()
+    }
+}
+
+impl std::fmt::Display for PhysicalRecursiveQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "RecursiveQuery: {}, is_distinct={}",
+            self.name, self.is_distinct
+        )
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use crate::catalog::{SchemaRef, EMPTY_SCHEMA_REF};
+use crate::common::TableReference;
+use crate::execution::{ExecutionContext, VolcanoExecutor};
+use crate::storage::TableIterator;
+use crate::{BustubxResult, Tuple};
+
+use super::create_view::insert_view_rows;
+use super::PhysicalPlan;
+
+#[derive(Debug, derive_new::new)]
+pub struct PhysicalRefreshMaterializedView {
+    pub name: TableReference,
+    pub input: Arc<PhysicalPlan>,
+}
+
+impl VolcanoExecutor for PhysicalRefreshMaterializedView {
+    fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
+        self.input.init(context)
+    }
+
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        let table_heap = context.catalog.table_heap(&self.name)?;
+        let indexes = context.catalog.table_indexes(&self.name)?;
+        let hash_indexes = context.catalog.table_hash_indexes(&self.name)?;
+        let mut iterator = TableIterator::new(table_heap.clone(), ..);
+        while let Some((rid, tuple)) = iterator.next()? {
+            let mut meta = table_heap.tuple_meta(rid)?;
+            meta.is_deleted = true;
+            table_heap.update_tuple_meta(meta, rid)?;
+            for index in indexes.iter() {
+                index.delete(&tuple.project_with_schema(index.key_schema.clone())?, rid)?;
+            }
+            for index in hash_indexes.iter() {
+                index.delete(&tuple.project_with_schema(index.key_schema.clone())?, rid)?;
+            }
+        }
+        insert_view_rows(context, &self.name, &self.input)?;
+        Ok(None)
+    }
+
+    fn output_schema(&self) -> SchemaRef {
+        EMPTY_SCHEMA_REF.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalRefreshMaterializedView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RefreshMaterializedView: {}", self.name)
+    }
+}
@@ -0,0 +1,54 @@
+use crate::catalog::{SchemaRef, EMPTY_SCHEMA_REF};
+use crate::{
+    execution::{ExecutionContext, VolcanoExecutor},
+    storage::Tuple,
+    BustubxError, BustubxResult,
+};
+
+#[derive(Debug, derive_new::new)]
+pub struct PhysicalSetVariable {
+    pub variable: String,
+    pub values: Vec<String>,
+}
+
+impl VolcanoExecutor for PhysicalSetVariable {
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        match self.variable.as_str() {
+            "search_path" => context.catalog.search_path = self.values.clone(),
+            "index_fill_factor" => {
+                let value = self.values.join(", ");
+                context.catalog.index_fill_factor = value
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|fill_factor| (10..=100).contains(fill_factor))
+                    .ok_or_else(|| {
+                        BustubxError::Execution(format!(
+                            "invalid value for parameter \"index_fill_factor\": \"{}\", expected an integer between 10 and 100",
+                            value
+                        ))
+                    })?;
+            }
+            _ => {
+                return Err(BustubxError::Execution(format!(
+                    "unrecognized configuration parameter \"{}\"",
+                    self.variable
+                )))
+            }
+        }
+        Ok(None)
+    }
+    fn output_schema(&self) -> SchemaRef {
+        EMPTY_SCHEMA_REF.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalSetVariable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SetVariable: {} = {}",
+            self.variable,
+            self.values.join(", ")
+        )
+    }
+}
@@ -35,17 +35,25 @@ impl PhysicalSort {
         let mut ordering = CmpOrdering::Equal;
         let mut index = 0;
         while ordering == CmpOrdering::Equal && index < self.order_bys.len() {
-            let a_value = self.order_bys[index].expr.evaluate(a)?;
-            let b_value = self.order_bys[index].expr.evaluate(b)?;
-            ordering = if self.order_bys[index].asc {
-                a_value.partial_cmp(&b_value)
-            } else {
-                b_value.partial_cmp(&a_value)
-            }
-            .ok_or(BustubxError::Execution(format!(
-                "Can not compare {:?} and {:?}",
-                a_value, b_value
-            )))?;
+            let order_by = &self.order_bys[index];
+            let a_value = order_by.expr.evaluate(a)?;
+            let b_value = order_by.expr.evaluate(b)?;
+            ordering = match (a_value.is_null(), b_value.is_null()) {
+                (true, true) => CmpOrdering::Equal,
+                (true, false) if order_by.nulls_first => CmpOrdering::Less,
+                (true, false) => CmpOrdering::Greater,
+                (false, true) if order_by.nulls_first => CmpOrdering::Greater,
+                (false, true) => CmpOrdering::Less,
+                (false, false) => if order_by.asc {
+                    a_value.partial_cmp(&b_value)
+                } else {
+                    b_value.partial_cmp(&a_value)
+                }
+                .ok_or(BustubxError::Execution(format!(
+                    "Can not compare {:?} and {:?}",
+                    a_value, b_value
+                )))?,
+            };
             index += 1;
         }
         Ok(ordering)
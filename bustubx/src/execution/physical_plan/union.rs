@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::catalog::SchemaRef;
+use crate::common::ScalarValue;
+use crate::{
+    execution::{ExecutionContext, VolcanoExecutor},
+    storage::Tuple,
+    BustubxResult,
+};
+
+use super::PhysicalPlan;
+
+#[derive(Debug)]
+pub struct PhysicalUnion {
+    pub left: Arc<PhysicalPlan>,
+    pub right: Arc<PhysicalPlan>,
+    pub is_all: bool,
+    pub schema: SchemaRef,
+
+    left_done: AtomicBool,
+    seen: Mutex<HashSet<Vec<ScalarValue>>>,
+}
+
+impl PhysicalUnion {
+    pub fn new(
+        left: Arc<PhysicalPlan>,
+        right: Arc<PhysicalPlan>,
+        is_all: bool,
+        schema: SchemaRef,
+    ) -> Self {
+        Self {
+            left,
+            right,
+            is_all,
+            schema,
+            left_done: AtomicBool::new(false),
+            seen: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+impl VolcanoExecutor for PhysicalUnion {
+    fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
+        self.left.init(context)?;
+        self.right.init(context)?;
+        self.left_done.store(false, Ordering::SeqCst);
+        self.seen.lock().unwrap().clear();
+        Ok(())
+    }
+
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        loop {
+            let next_tuple = if self.left_done.load(Ordering::SeqCst) {
+                self.right.next(context)?
+            } else {
+                match self.left.next(context)? {
+                    Some(tuple) => Some(tuple),
+                    None => {
+                        self.left_done.store(true, Ordering::SeqCst);
+                        continue;
+                    }
+                }
+            };
+            let Some(tuple) = next_tuple else {
+                return Ok(None);
+            };
+            if !self.is_all && !self.seen.lock().unwrap().insert(tuple.data.clone()) {
+                continue;
+            }
+            return Ok(Some(Tuple::new(self.schema.clone(), tuple.data)));
+        }
+    }
+
+    fn output_schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalUnion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Union: is_all={}", self.is_all)
+    }
+}
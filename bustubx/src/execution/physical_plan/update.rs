@@ -1,10 +1,12 @@
 use crate::catalog::{SchemaRef, UPDATE_OUTPUT_SCHEMA_REF};
 use crate::common::{ScalarValue, TableReference};
+use crate::execution::constraint::{skip_moved_rows, update_row, MovedRows};
 use crate::execution::{ExecutionContext, VolcanoExecutor};
 use crate::expression::{Expr, ExprTrait};
-use crate::storage::{TableIterator, EMPTY_TUPLE};
+use crate::planner::logical_plan::Returning;
+use crate::storage::{RecordId, TableIterator, EMPTY_TUPLE};
 use crate::{BustubxError, BustubxResult, Tuple};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Mutex;
 
@@ -14,9 +16,12 @@ pub struct PhysicalUpdate {
     pub table_schema: SchemaRef,
     pub assignments: HashMap<String, Expr>,
     pub selection: Option<Expr>,
+    pub returning: Option<Returning>,
 
     update_rows: AtomicU32,
     table_iterator: Mutex<Option<TableIterator>>,
+    /// Rows moved to the end of the heap after being visited, not to be updated again
+    moved_rids: Mutex<HashSet<RecordId>>,
 }
 
 impl PhysicalUpdate {
@@ -25,14 +30,17 @@ impl PhysicalUpdate {
         table_schema: SchemaRef,
         assignments: HashMap<String, Expr>,
         selection: Option<Expr>,
+        returning: Option<Returning>,
     ) -> Self {
         Self {
             table,
             table_schema,
             assignments,
             selection,
+            returning,
             update_rows: AtomicU32::new(0),
             table_iterator: Mutex::new(None),
+            moved_rids: Mutex::new(HashSet::new()),
         }
     }
 }
@@ -40,6 +48,7 @@ impl PhysicalUpdate {
 impl VolcanoExecutor for PhysicalUpdate {
     fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
         self.update_rows.store(0, Ordering::SeqCst);
+        self.moved_rids.lock().unwrap().clear();
         let table_heap = context.catalog.table_heap(&self.table)?;
         *self.table_iterator.lock().unwrap() = Some(TableIterator::new(table_heap.clone(), ..));
         Ok(())
@@ -52,15 +61,18 @@ impl VolcanoExecutor for PhysicalUpdate {
                 "table iterator not created".to_string(),
             ));
         };
-        let table_heap = context.catalog.table_heap(&self.table)?;
 
         loop {
             if let Some((rid, mut tuple)) = table_iterator.next()? {
+                if self.moved_rids.lock().unwrap().contains(&rid) {
+                    continue;
+                }
                 if let Some(selection) = &self.selection {
                     if !selection.evaluate(&tuple)?.as_boolean()?.unwrap_or(false) {
                         continue;
                     }
                 }
+                let old_tuple = tuple.clone();
                 // update tuple data
                 for (col_name, value_expr) in self.assignments.iter() {
                     let index = tuple.schema.index_of(None, &col_name)?;
@@ -68,7 +80,29 @@ impl VolcanoExecutor for PhysicalUpdate {
                     let new_value = value_expr.evaluate(&EMPTY_TUPLE)?.cast_to(&col_datatype)?;
                     tuple.data[index] = new_value;
                 }
-                table_heap.update_tuple(rid, tuple)?;
+                let returned = match &self.returning {
+                    Some(returning) => Some(returning.project(&tuple)?),
+                    None => None,
+                };
+                let mut moved = MovedRows::new();
+                update_row(
+                    context.catalog,
+                    &self.table,
+                    rid,
+                    &old_tuple,
+                    tuple,
+                    &mut moved,
+                    &mut context.write_log,
+                )?;
+                skip_moved_rows(
+                    &self.table,
+                    moved,
+                    table_iterator,
+                    &mut self.moved_rids.lock().unwrap(),
+                )?;
+                if returned.is_some() {
+                    return Ok(returned);
+                }
                 self.update_rows.fetch_add(1, Ordering::SeqCst);
             } else {
                 return if self.update_rows.load(Ordering::SeqCst) == 0 {
@@ -85,7 +119,10 @@ impl VolcanoExecutor for PhysicalUpdate {
     }
 
     fn output_schema(&self) -> SchemaRef {
-        UPDATE_OUTPUT_SCHEMA_REF.clone()
+        match &self.returning {
+            Some(returning) => returning.schema.clone(),
+            None => UPDATE_OUTPUT_SCHEMA_REF.clone(),
+        }
     }
 }
 
@@ -27,6 +27,11 @@ impl PhysicalValues {
     }
 }
 impl VolcanoExecutor for PhysicalValues {
+    fn init(&self, _context: &mut ExecutionContext) -> BustubxResult<()> {
+        self.cursor.store(0, Ordering::SeqCst);
+        Ok(())
+    }
+
     fn next(&self, _context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
         let cursor = self.cursor.fetch_add(1, Ordering::SeqCst) as usize;
         if cursor < self.rows.len() {
@@ -0,0 +1,392 @@
+use crate::catalog::{DataType, SchemaRef};
+use crate::common::ScalarValue;
+use crate::execution::physical_plan::PhysicalPlan;
+use crate::execution::{ExecutionContext, VolcanoExecutor};
+use crate::expression::{
+    Expr, ExprTrait, WindowFrame, WindowFrameBound, WindowFrameUnits, WindowFunction,
+};
+use crate::function::{BuiltInWindowFunctionKind, WindowFunctionKind};
+use crate::{BustubxError, BustubxResult, Tuple};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug)]
+pub struct PhysicalWindow {
+    /// The incoming physical plan, sorted by PARTITION BY and ORDER BY of the window functions
+    pub input: Arc<PhysicalPlan>,
+    /// Window function expressions
+    pub window_exprs: Vec<Expr>,
+    /// The schema of input followed by the window function columns
+    pub schema: SchemaRef,
+
+    output_rows: Mutex<Vec<Tuple>>,
+    cursor: AtomicUsize,
+}
+
+impl PhysicalWindow {
+    pub fn new(input: Arc<PhysicalPlan>, window_exprs: Vec<Expr>, schema: SchemaRef) -> Self {
+        Self {
+            input,
+            window_exprs,
+            schema,
+            output_rows: Mutex::new(vec![]),
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    /// Evaluates a window function for all rows, the results are in the same order as `tuples`.
+    fn evaluate_window(
+        &self,
+        window: &WindowFunction,
+        tuples: &[Tuple],
+    ) -> BustubxResult<Vec<ScalarValue>> {
+        // rows keep their input order inside a partition
+        let mut partition_indexes: HashMap<Vec<ScalarValue>, usize> = HashMap::new();
+        let mut partitions: Vec<Vec<usize>> = vec![];
+        for (row, tuple) in tuples.iter().enumerate() {
+            let key = window
+                .partition_by
+                .iter()
+                .map(|e| e.evaluate(tuple))
+                .collect::<BustubxResult<Vec<ScalarValue>>>()?;
+            match partition_indexes.get(&key) {
+                Some(idx) => partitions[*idx].push(row),
+                None => {
+                    partition_indexes.insert(key, partitions.len());
+                    partitions.push(vec![row]);
+                }
+            }
+        }
+
+        let mut results = vec![ScalarValue::Boolean(None); tuples.len()];
+        for partition in partitions {
+            let partition_tuples = partition
+                .iter()
+                .map(|row| &tuples[*row])
+                .collect::<Vec<&Tuple>>();
+            let values = evaluate_partition(window, &partition_tuples)?;
+            for (row, value) in partition.into_iter().zip(values) {
+                results[row] = value;
+            }
+        }
+        Ok(results)
+    }
+}
+
+impl VolcanoExecutor for PhysicalWindow {
+    fn init(&self, context: &mut ExecutionContext) -> BustubxResult<()> {
+        self.input.init(context)?;
+        self.output_rows.lock().unwrap().clear();
+        self.cursor.store(0, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn next(&self, context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        let output_rows_len = self.output_rows.lock().unwrap().len();
+        // build output rows
+        if output_rows_len == 0 {
+            let mut tuples = vec![];
+            while let Some(tuple) = self.input.next(context)? {
+                tuples.push(tuple);
+            }
+
+            let mut window_results = vec![];
+            for expr in self.window_exprs.iter() {
+                let Expr::WindowFunction(window) = expr else {
+                    return Err(BustubxError::Execution(format!(
+                        "window expr is not WindowFunction instead of {}",
+                        expr
+                    )));
+                };
+                window_results.push(self.evaluate_window(window, &tuples)?);
+            }
+
+            let mut output_rows = self.output_rows.lock().unwrap();
+            for (row, tuple) in tuples.into_iter().enumerate() {
+                let mut values = tuple.data;
+                values.extend(window_results.iter().map(|results| results[row].clone()));
+                output_rows.push(Tuple::new(self.schema.clone(), values));
+            }
+        }
+
+        let cursor = self.cursor.fetch_add(1, Ordering::SeqCst);
+        Ok(self.output_rows.lock().unwrap().get(cursor).cloned())
+    }
+
+    fn output_schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Window")
+    }
+}
+
+fn evaluate_partition(
+    window: &WindowFunction,
+    tuples: &[&Tuple],
+) -> BustubxResult<Vec<ScalarValue>> {
+    let len = tuples.len();
+
+    // peers are the rows with equal ORDER BY values, rows are already sorted
+    let order_keys = tuples
+        .iter()
+        .map(|tuple| {
+            window
+                .order_by
+                .iter()
+                .map(|order_by| order_by.expr.evaluate(tuple))
+                .collect::<BustubxResult<Vec<ScalarValue>>>()
+        })
+        .collect::<BustubxResult<Vec<Vec<ScalarValue>>>>()?;
+    let mut peer_start = vec![0; len];
+    let mut peer_end = vec![0; len];
+    let mut start = 0;
+    while start < len {
+        let mut end = start;
+        while end + 1 < len && order_keys[end + 1] == order_keys[start] {
+            end += 1;
+        }
+        for row in start..=end {
+            peer_start[row] = start;
+            peer_end[row] = end;
+        }
+        start = end + 1;
+    }
+
+    let frames = (0..len)
+        .map(|row| {
+            frame_range(
+                window,
+                &window.window_frame,
+                tuples,
+                row,
+                &peer_start,
+                &peer_end,
+            )
+        })
+        .collect::<BustubxResult<Vec<(usize, usize)>>>()?;
+
+    let arg_values = |idx: usize| -> BustubxResult<Vec<ScalarValue>> {
+        tuples
+            .iter()
+            .map(|tuple| window.args[idx].evaluate(tuple))
+            .collect::<BustubxResult<Vec<ScalarValue>>>()
+    };
+
+    match &window.func_kind {
+        WindowFunctionKind::BuiltIn(BuiltInWindowFunctionKind::RowNumber) => Ok((0..len)
+            .map(|row| ScalarValue::Int64(Some(row as i64 + 1)))
+            .collect()),
+        WindowFunctionKind::BuiltIn(BuiltInWindowFunctionKind::Rank) => Ok(peer_start
+            .iter()
+            .map(|start| ScalarValue::Int64(Some(*start as i64 + 1)))
+            .collect()),
+        WindowFunctionKind::BuiltIn(BuiltInWindowFunctionKind::DenseRank) => {
+            let mut rank = 0;
+            Ok((0..len)
+                .map(|row| {
+                    if peer_start[row] == row {
+                        rank += 1;
+                    }
+                    ScalarValue::Int64(Some(rank))
+                })
+                .collect())
+        }
+        WindowFunctionKind::BuiltIn(
+            kind @ (BuiltInWindowFunctionKind::Lag | BuiltInWindowFunctionKind::Lead),
+        ) => {
+            let values = arg_values(0)?;
+            let data_type = first_arg_type(window, tuples)?;
+            let mut results = vec![];
+            for (row, tuple) in tuples.iter().enumerate() {
+                let offset = match window.args.get(1) {
+                    Some(expr) => expr.evaluate(tuple)?.as_i64()?,
+                    None => Some(1),
+                };
+                let Some(offset) = offset else {
+                    results.push(ScalarValue::new_empty(data_type));
+                    continue;
+                };
+                let target = if matches!(kind, BuiltInWindowFunctionKind::Lag) {
+                    row as i64 - offset
+                } else {
+                    row as i64 + offset
+                };
+                if target >= 0 && (target as usize) < len {
+                    results.push(values[target as usize].clone());
+                } else {
+                    results.push(match window.args.get(2) {
+                        Some(expr) => expr.evaluate(tuple)?.cast_to(&data_type)?,
+                        None => ScalarValue::new_empty(data_type),
+                    });
+                }
+            }
+            Ok(results)
+        }
+        WindowFunctionKind::BuiltIn(
+            kind @ (BuiltInWindowFunctionKind::FirstValue | BuiltInWindowFunctionKind::LastValue),
+        ) => {
+            let values = arg_values(0)?;
+            let data_type = first_arg_type(window, tuples)?;
+            Ok(frames
+                .iter()
+                .map(|(start, end)| {
+                    if start >= end {
+                        ScalarValue::new_empty(data_type)
+                    } else if matches!(kind, BuiltInWindowFunctionKind::FirstValue) {
+                        values[*start].clone()
+                    } else {
+                        values[*end - 1].clone()
+                    }
+                })
+                .collect())
+        }
+        WindowFunctionKind::Aggregate(func_kind) => {
+            let values = arg_values(0)?;
+            let mut results = vec![];
+            if window.window_frame.start_bound == WindowFrameBound::Preceding(None) {
+                // frame end never moves backwards, so feed the accumulator incrementally
+                let mut accumulator = func_kind.create_accumulator();
+                let mut fed = 0;
+                for (_, end) in frames.iter() {
+                    while fed < *end {
+                        accumulator.update_value(&values[fed])?;
+                        fed += 1;
+                    }
+                    results.push(accumulator.evaluate()?);
+                }
+            } else {
+                for (start, end) in frames.iter() {
+                    let mut accumulator = func_kind.create_accumulator();
+                    for value in values.iter().take(*end).skip(*start) {
+                        accumulator.update_value(value)?;
+                    }
+                    results.push(accumulator.evaluate()?);
+                }
+            }
+            Ok(results)
+        }
+    }
+}
+
+fn first_arg_type(window: &WindowFunction, tuples: &[&Tuple]) -> BustubxResult<DataType> {
+    let tuple = tuples.first().ok_or(BustubxError::Internal(format!(
+        "window function {} is evaluated on empty partition",
+        window
+    )))?;
+    window.args[0].data_type(&tuple.schema)
+}
+
+/// Returns the frame of the row as a half-open range of rows in the partition.
+fn frame_range(
+    window: &WindowFunction,
+    frame: &WindowFrame,
+    tuples: &[&Tuple],
+    row: usize,
+    peer_start: &[usize],
+    peer_end: &[usize],
+) -> BustubxResult<(usize, usize)> {
+    let len = tuples.len();
+    let (start, end) = match frame.units {
+        WindowFrameUnits::Rows => {
+            let start = match frame.start_bound {
+                WindowFrameBound::Preceding(None) => 0,
+                WindowFrameBound::Preceding(Some(n)) => row.saturating_sub(n as usize),
+                WindowFrameBound::CurrentRow => row,
+                WindowFrameBound::Following(n) => n.map_or(len, |n| row + n as usize),
+            };
+            let end = match frame.end_bound {
+                WindowFrameBound::Preceding(n) => {
+                    n.map_or(0, |n| (row + 1).saturating_sub(n as usize))
+                }
+                WindowFrameBound::CurrentRow => row + 1,
+                WindowFrameBound::Following(None) => len,
+                WindowFrameBound::Following(Some(n)) => row + n as usize + 1,
+            };
+            (start, end)
+        }
+        WindowFrameUnits::Range => {
+            let has_offset = matches!(
+                frame.start_bound,
+                WindowFrameBound::Preceding(Some(_)) | WindowFrameBound::Following(Some(_))
+            ) || matches!(
+                frame.end_bound,
+                WindowFrameBound::Preceding(Some(_)) | WindowFrameBound::Following(Some(_))
+            );
+            // offsets are applied to the single ORDER BY value, rows with NULL value only see peers
+            let current = if has_offset {
+                range_key(window, tuples[row])?
+            } else {
+                None
+            };
+            match current {
+                None => {
+                    let start = match frame.start_bound {
+                        WindowFrameBound::Preceding(None) => 0,
+                        WindowFrameBound::Following(None) => len,
+                        _ => peer_start[row],
+                    };
+                    let end = match frame.end_bound {
+                        WindowFrameBound::Preceding(None) => 0,
+                        WindowFrameBound::Following(None) => len,
+                        _ => peer_end[row] + 1,
+                    };
+                    (start, end)
+                }
+                Some(current) => {
+                    let keys = tuples
+                        .iter()
+                        .map(|tuple| range_key(window, tuple))
+                        .collect::<BustubxResult<Vec<Option<f64>>>>()?;
+                    let first_at_least = |bound: f64| {
+                        keys.iter()
+                            .position(|key| key.is_some_and(|key| key >= bound))
+                            .unwrap_or(len)
+                    };
+                    let last_at_most = |bound: f64| {
+                        keys.iter()
+                            .rposition(|key| key.is_some_and(|key| key <= bound))
+                            .map_or(0, |pos| pos + 1)
+                    };
+                    let start = match frame.start_bound {
+                        WindowFrameBound::Preceding(None) => 0,
+                        WindowFrameBound::Preceding(Some(n)) => first_at_least(current - n as f64),
+                        WindowFrameBound::CurrentRow => peer_start[row],
+                        WindowFrameBound::Following(Some(n)) => first_at_least(current + n as f64),
+                        WindowFrameBound::Following(None) => len,
+                    };
+                    let end = match frame.end_bound {
+                        WindowFrameBound::Preceding(None) => 0,
+                        WindowFrameBound::Preceding(Some(n)) => last_at_most(current - n as f64),
+                        WindowFrameBound::CurrentRow => peer_end[row] + 1,
+                        WindowFrameBound::Following(Some(n)) => last_at_most(current + n as f64),
+                        WindowFrameBound::Following(None) => len,
+                    };
+                    (start, end)
+                }
+            }
+        }
+    };
+    Ok((start.min(len), end.min(len)))
+}
+
+/// The ORDER BY value used by RANGE offsets, negated for descending order so that
+/// the keys are always ascending within a partition.
+fn range_key(window: &WindowFunction, tuple: &Tuple) -> BustubxResult<Option<f64>> {
+    let order_by = window.order_by.first().ok_or(BustubxError::Execution(
+        "RANGE with offset requires exactly one ORDER BY column".to_string(),
+    ))?;
+    let value = order_by.expr.evaluate(tuple)?;
+    if !value.data_type().is_numeric() {
+        return Err(BustubxError::Execution(format!(
+            "RANGE with offset does not support ORDER BY {} value",
+            value.data_type()
+        )));
+    }
+    let key = value.as_f64()?;
+    Ok(if order_by.asc { key } else { key.map(|k| -k) })
+}
@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::catalog::SchemaRef;
+use crate::{
+    execution::{ExecutionContext, VolcanoExecutor},
+    storage::Tuple,
+    BustubxResult,
+};
+
+/// Rows produced by the previous iteration of a recursive query.
+pub type WorkTable = Arc<Mutex<Vec<Tuple>>>;
+
+#[derive(Debug)]
+pub struct PhysicalWorkTableScan {
+    pub name: String,
+    pub schema: SchemaRef,
+    pub work_table: WorkTable,
+
+    cursor: AtomicUsize,
+}
+
+impl PhysicalWorkTableScan {
+    pub fn new(name: String, schema: SchemaRef, work_table: WorkTable) -> Self {
+        Self {
+            name,
+            schema,
+            work_table,
+            cursor: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl VolcanoExecutor for PhysicalWorkTableScan {
+    fn init(&self, _context: &mut ExecutionContext) -> BustubxResult<()> {
+        self.cursor.store(0, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn next(&self, _context: &mut ExecutionContext) -> BustubxResult<Option<Tuple>> {
+        let cursor = self.cursor.fetch_add(1, Ordering::SeqCst);
+        Ok(self
+            .work_table
+            .lock()
+            .unwrap()
+            .get(cursor)
+            .map(|tuple| Tuple::new(self.schema.clone(), tuple.data.clone())))
+    }
+
+    fn output_schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl std::fmt::Display for PhysicalWorkTableScan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WorkTableScan: {}", self.name)
+    }
+}
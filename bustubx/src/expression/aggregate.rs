@@ -1,4 +1,5 @@
 use crate::catalog::{Column, DataType, Schema};
+use crate::common::decimal::MAX_DECIMAL_PRECISION;
 use crate::common::ScalarValue;
 use crate::expression::{Expr, ExprTrait};
 use crate::function::AggregateFunctionKind;
@@ -16,10 +17,23 @@ pub struct AggregateFunction {
 }
 
 impl ExprTrait for AggregateFunction {
-    fn data_type(&self, _input_schema: &Schema) -> BustubxResult<DataType> {
+    fn data_type(&self, input_schema: &Schema) -> BustubxResult<DataType> {
         match self.func_kind {
             AggregateFunctionKind::Count => Ok(DataType::Int64),
             AggregateFunctionKind::Avg => Ok(DataType::Float64),
+            AggregateFunctionKind::Sum => {
+                let expr = self.args.first().ok_or(BustubxError::Plan(format!(
+                    "aggregate function {} should have one arg instead of {:?}",
+                    self.func_kind, self.args
+                )))?;
+                match expr.data_type(input_schema)? {
+                    DataType::Float32 | DataType::Float64 => Ok(DataType::Float64),
+                    DataType::Decimal(_, scale) => {
+                        Ok(DataType::Decimal(MAX_DECIMAL_PRECISION, scale))
+                    }
+                    _ => Ok(DataType::Int64),
+                }
+            }
         }
     }
 
@@ -29,7 +43,9 @@ impl ExprTrait for AggregateFunction {
 
     fn evaluate(&self, tuple: &Tuple) -> BustubxResult<ScalarValue> {
         match self.func_kind {
-            AggregateFunctionKind::Count | AggregateFunctionKind::Avg => {
+            AggregateFunctionKind::Count
+            | AggregateFunctionKind::Avg
+            | AggregateFunctionKind::Sum => {
                 let expr = self.args.first().ok_or(BustubxError::Internal(format!(
                     "aggregate function {} should have one arg instead of {:?}",
                     self.func_kind, self.args
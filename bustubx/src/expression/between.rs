@@ -0,0 +1,63 @@
+use crate::catalog::{Column, DataType, Schema};
+use crate::common::ScalarValue;
+use crate::expression::binary::{as_boolean, evaluate_and, evaluate_comparison};
+use crate::expression::{Expr, ExprTrait};
+use crate::{BustubxResult, Tuple};
+use std::cmp::Ordering;
+
+/// `expr [NOT] BETWEEN low AND high`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Between {
+    pub expr: Box<Expr>,
+    pub negated: bool,
+    pub low: Box<Expr>,
+    pub high: Box<Expr>,
+}
+
+impl ExprTrait for Between {
+    fn data_type(&self, _input_schema: &Schema) -> BustubxResult<DataType> {
+        Ok(DataType::Boolean)
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> BustubxResult<bool> {
+        Ok(self.expr.nullable(input_schema)?
+            || self.low.nullable(input_schema)?
+            || self.high.nullable(input_schema)?)
+    }
+
+    fn evaluate(&self, tuple: &Tuple) -> BustubxResult<ScalarValue> {
+        let value = self.expr.evaluate(tuple)?;
+        let low = self.low.evaluate(tuple)?;
+        let high = self.high.evaluate(tuple)?;
+        let result = evaluate_and(
+            &evaluate_comparison(value.clone(), low, &[Ordering::Greater, Ordering::Equal])?,
+            &evaluate_comparison(value, high, &[Ordering::Less, Ordering::Equal])?,
+        )?;
+        if self.negated {
+            Ok(ScalarValue::Boolean(as_boolean(&result)?.map(|v| !v)))
+        } else {
+            Ok(result)
+        }
+    }
+
+    fn to_column(&self, input_schema: &Schema) -> BustubxResult<Column> {
+        Ok(Column::new(
+            format!("{self}"),
+            self.data_type(input_schema)?,
+            self.nullable(input_schema)?,
+        ))
+    }
+}
+
+impl std::fmt::Display for Between {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}BETWEEN {} AND {}",
+            self.expr,
+            if self.negated { "NOT " } else { "" },
+            self.low,
+            self.high
+        )
+    }
+}
@@ -1,5 +1,8 @@
 use crate::catalog::Schema;
 use crate::catalog::{Column, DataType};
+use crate::common::datetime::{timestamp_add_interval, Interval, MICROS_PER_DAY};
+use crate::common::decimal::{self, MAX_DECIMAL_PRECISION};
+use crate::common::json::parse_text_path;
 use crate::common::ScalarValue;
 use crate::error::BustubxResult;
 use crate::expression::{Expr, ExprTrait};
@@ -20,8 +23,8 @@ pub struct BinaryExpr {
 
 impl ExprTrait for BinaryExpr {
     fn data_type(&self, input_schema: &Schema) -> BustubxResult<DataType> {
-        let _left_type = self.left.data_type(input_schema)?;
-        let _right_type = self.right.data_type(input_schema)?;
+        let left_type = self.left.data_type(input_schema)?;
+        let right_type = self.right.data_type(input_schema)?;
         match self.op {
             BinaryOp::Gt
             | BinaryOp::Lt
@@ -31,10 +34,15 @@ impl ExprTrait for BinaryExpr {
             | BinaryOp::NotEq
             | BinaryOp::And
             | BinaryOp::Or => Ok(DataType::Boolean),
-            BinaryOp::Plus => todo!(),
-            BinaryOp::Minus => todo!(),
-            BinaryOp::Multiply => todo!(),
-            BinaryOp::Divide => todo!(),
+            BinaryOp::Plus
+            | BinaryOp::Minus
+            | BinaryOp::Multiply
+            | BinaryOp::Divide
+            | BinaryOp::Modulo => arithmetic_type(&left_type, self.op, &right_type),
+            BinaryOp::JsonGet
+            | BinaryOp::JsonGetText
+            | BinaryOp::JsonGetPath
+            | BinaryOp::JsonGetPathText => json_access_type(&left_type, self.op, &right_type),
         }
     }
 
@@ -52,20 +60,17 @@ impl ExprTrait for BinaryExpr {
             BinaryOp::LtEq => evaluate_comparison(l, r, &[Ordering::Less, Ordering::Equal]),
             BinaryOp::Eq => evaluate_comparison(l, r, &[Ordering::Equal]),
             BinaryOp::NotEq => evaluate_comparison(l, r, &[Ordering::Greater, Ordering::Less]),
-            BinaryOp::And => {
-                let l_bool = l.as_boolean()?;
-                let r_bool = r.as_boolean()?;
-                match (l_bool, r_bool) {
-                    (Some(v1), Some(v2)) => Ok((v1 && v2).into()),
-                    (Some(_), None) | (None, Some(_)) | (None, None) => {
-                        Ok(ScalarValue::Boolean(Some(false)))
-                    }
-                }
-            }
-            _ => Err(BustubxError::NotSupport(format!(
-                "binary operator {:?} not support evaluating yet",
-                self.op
-            ))),
+            BinaryOp::And => evaluate_and(&l, &r),
+            BinaryOp::Or => evaluate_or(&l, &r),
+            BinaryOp::Plus
+            | BinaryOp::Minus
+            | BinaryOp::Multiply
+            | BinaryOp::Divide
+            | BinaryOp::Modulo => evaluate_arithmetic(l, self.op, r),
+            BinaryOp::JsonGet
+            | BinaryOp::JsonGetText
+            | BinaryOp::JsonGetPath
+            | BinaryOp::JsonGetPathText => evaluate_json_access(l, self.op, r),
         }
     }
 
@@ -78,13 +83,75 @@ impl ExprTrait for BinaryExpr {
     }
 }
 
-fn evaluate_comparison(
+fn json_access_type(left: &DataType, op: BinaryOp, right: &DataType) -> BustubxResult<DataType> {
+    let valid_key = match op {
+        BinaryOp::JsonGet | BinaryOp::JsonGetText => {
+            matches!(right, DataType::Varchar(_)) || right.is_integer()
+        }
+        _ => matches!(right, DataType::Varchar(_)),
+    };
+    if !matches!(left, DataType::Json | DataType::Varchar(_)) || !valid_key {
+        return Err(BustubxError::Plan(format!(
+            "operator does not exist: {} {} {}",
+            left, op, right
+        )));
+    }
+    match op {
+        BinaryOp::JsonGet | BinaryOp::JsonGetPath => Ok(DataType::Json),
+        _ => Ok(DataType::Varchar(None)),
+    }
+}
+
+/// Extracts a value from a json document, the result is NULL if either side is NULL or
+/// nothing is found.
+fn evaluate_json_access(
+    left: ScalarValue,
+    op: BinaryOp,
+    right: ScalarValue,
+) -> BustubxResult<ScalarValue> {
+    let as_text = matches!(op, BinaryOp::JsonGetText | BinaryOp::JsonGetPathText);
+    let null = if as_text {
+        ScalarValue::Varchar(None)
+    } else {
+        ScalarValue::Json(None)
+    };
+    let ScalarValue::Json(Some(document)) = left.cast_to(&DataType::Json)? else {
+        return Ok(null);
+    };
+    let found = match (op, &right) {
+        (_, ScalarValue::Varchar(None)) => None,
+        (BinaryOp::JsonGet | BinaryOp::JsonGetText, ScalarValue::Varchar(Some(key))) => {
+            document.get_field(key)
+        }
+        (BinaryOp::JsonGet | BinaryOp::JsonGetText, _) => match right.as_i64()? {
+            Some(index) => document.get_index(index),
+            None => None,
+        },
+        (_, ScalarValue::Varchar(Some(path))) => document.get_path(&parse_text_path(path)?),
+        _ => {
+            return Err(BustubxError::Execution(format!(
+                "json path {} is not text",
+                right
+            )))
+        }
+    };
+    Ok(match found {
+        Some(value) if as_text => ScalarValue::Varchar(value.to_text()),
+        Some(value) => ScalarValue::Json(Some(value.clone())),
+        None => null,
+    })
+}
+
+/// Compares two values, the result is NULL if either side is NULL.
+pub(crate) fn evaluate_comparison(
     left: ScalarValue,
     right: ScalarValue,
     accepted_orderings: &[Ordering],
 ) -> BustubxResult<ScalarValue> {
-    let coercion_type =
-        DataType::comparison_numeric_coercion(&left.data_type(), &right.data_type())?;
+    if left.is_null() || right.is_null() {
+        return Ok(ScalarValue::Boolean(None));
+    }
+    let coercion_type = DataType::comparison_coercion(&left.data_type(), &right.data_type())?;
     let order = left
         .cast_to(&coercion_type)?
         .partial_cmp(&right.cast_to(&coercion_type)?)
@@ -97,12 +164,334 @@ fn evaluate_comparison(
     )))
 }
 
+/// `AND` with SQL three-valued logic, FALSE wins over NULL.
+pub(crate) fn evaluate_and(left: &ScalarValue, right: &ScalarValue) -> BustubxResult<ScalarValue> {
+    match (as_boolean(left)?, as_boolean(right)?) {
+        (Some(false), _) | (_, Some(false)) => Ok(ScalarValue::Boolean(Some(false))),
+        (Some(true), Some(true)) => Ok(ScalarValue::Boolean(Some(true))),
+        _ => Ok(ScalarValue::Boolean(None)),
+    }
+}
+
+/// `OR` with SQL three-valued logic, TRUE wins over NULL.
+pub(crate) fn evaluate_or(left: &ScalarValue, right: &ScalarValue) -> BustubxResult<ScalarValue> {
+    match (as_boolean(left)?, as_boolean(right)?) {
+        (Some(true), _) | (_, Some(true)) => Ok(ScalarValue::Boolean(Some(true))),
+        (Some(false), Some(false)) => Ok(ScalarValue::Boolean(Some(false))),
+        _ => Ok(ScalarValue::Boolean(None)),
+    }
+}
+
+/// Treats an untyped NULL as an unknown boolean.
+pub(crate) fn as_boolean(value: &ScalarValue) -> BustubxResult<Option<bool>> {
+    if value.is_null() {
+        Ok(None)
+    } else {
+        value.as_boolean()
+    }
+}
+
+/// The result type of an arithmetic operation.
+pub(crate) fn arithmetic_type(
+    left: &DataType,
+    op: BinaryOp,
+    right: &DataType,
+) -> BustubxResult<DataType> {
+    use DataType::*;
+    let data_type = match (left, op, right) {
+        (Decimal(_, _), _, r) | (r, _, Decimal(_, _)) if r.is_numeric() => {
+            match (
+                left.decimal_precision_scale(),
+                right.decimal_precision_scale(),
+            ) {
+                (Some(l), Some(r)) => Some(decimal_arithmetic_type(op, l, r)),
+                // with a float
+                _ => Some(Float64),
+            }
+        }
+        (l, _, r) if l.is_numeric() && r.is_numeric() => {
+            Some(DataType::comparison_numeric_coercion(l, r)?)
+        }
+        (Date, BinaryOp::Plus, r) | (r, BinaryOp::Plus, Date) if r.is_integer() => Some(Date),
+        (Date, BinaryOp::Minus, r) if r.is_integer() => Some(Date),
+        (Date, BinaryOp::Minus, Date) => Some(Int32),
+        (Date, BinaryOp::Plus, Time) | (Time, BinaryOp::Plus, Date) => Some(Timestamp),
+        (Date | Timestamp, BinaryOp::Plus | BinaryOp::Minus, Interval)
+        | (Interval, BinaryOp::Plus, Date | Timestamp) => Some(Timestamp),
+        (TimestampTz, BinaryOp::Plus | BinaryOp::Minus, Interval)
+        | (Interval, BinaryOp::Plus, TimestampTz) => Some(TimestampTz),
+        (Time, BinaryOp::Plus | BinaryOp::Minus, Interval) | (Interval, BinaryOp::Plus, Time) => {
+            Some(Time)
+        }
+        (Timestamp, BinaryOp::Minus, Timestamp)
+        | (TimestampTz, BinaryOp::Minus, TimestampTz)
+        | (Time, BinaryOp::Minus, Time)
+        | (Interval, BinaryOp::Plus | BinaryOp::Minus, Interval) => Some(Interval),
+        (Interval, BinaryOp::Multiply | BinaryOp::Divide, r) if r.is_numeric() => Some(Interval),
+        (l, BinaryOp::Multiply, Interval) if l.is_numeric() => Some(Interval),
+        _ => None,
+    };
+    data_type.ok_or(BustubxError::Plan(format!(
+        "operator {} does not exist for {} and {}",
+        op, left, right
+    )))
+}
+
+/// The precision and scale of a decimal arithmetic result, when the precision exceeds the
+/// max, fractional digits are given up first but at least 6 are kept.
+fn decimal_arithmetic_type(op: BinaryOp, l: (u8, u8), r: (u8, u8)) -> DataType {
+    let ((p1, s1), (p2, s2)) = ((l.0 as u32, l.1 as u32), (r.0 as u32, r.1 as u32));
+    let (precision, scale) = match op {
+        BinaryOp::Plus | BinaryOp::Minus => {
+            let scale = s1.max(s2);
+            ((p1 - s1).max(p2 - s2) + scale + 1, scale)
+        }
+        BinaryOp::Multiply => (p1 + p2 + 1, s1 + s2),
+        BinaryOp::Divide => {
+            let scale = (s1 + p2 + 1).max(6);
+            (p1 - s1 + s2 + scale, scale)
+        }
+        _ => {
+            let scale = s1.max(s2);
+            ((p1 - s1).min(p2 - s2) + scale, scale)
+        }
+    };
+    let max = MAX_DECIMAL_PRECISION as u32;
+    if precision <= max {
+        return DataType::Decimal(precision as u8, scale as u8);
+    }
+    let scale = scale.min(6).max(scale.saturating_sub(precision - max));
+    DataType::Decimal(max as u8, scale.min(max) as u8)
+}
+
+fn evaluate_decimal_arithmetic(
+    left: &ScalarValue,
+    op: BinaryOp,
+    right: &ScalarValue,
+    precision: u8,
+    scale: u8,
+) -> BustubxResult<ScalarValue> {
+    let overflow = || BustubxError::Execution("numeric field overflow".to_string());
+    let unscaled = |value: &ScalarValue| -> BustubxResult<(i128, u8)> {
+        let decimal_type = value.data_type().decimal_precision_scale();
+        match decimal_type.map(|(p, s)| value.cast_to(&DataType::Decimal(p, s))) {
+            Some(Ok(ScalarValue::Decimal(Some(v), _, scale))) => Ok((v, scale)),
+            _ => Err(BustubxError::Internal(format!(
+                "Cannot treat {:?} as decimal",
+                value
+            ))),
+        }
+    };
+    let (l, s1) = unscaled(left)?;
+    let (r, s2) = unscaled(right)?;
+    let v = match op {
+        BinaryOp::Plus | BinaryOp::Minus | BinaryOp::Modulo => {
+            let common_scale = s1.max(s2);
+            let l = decimal::rescale(l, s1, common_scale)?;
+            let r = decimal::rescale(r, s2, common_scale)?;
+            let v = match op {
+                BinaryOp::Plus => l.checked_add(r).ok_or_else(overflow)?,
+                BinaryOp::Minus => l.checked_sub(r).ok_or_else(overflow)?,
+                _ if r == 0 => return Err(BustubxError::Execution("division by zero".to_string())),
+                _ => l % r,
+            };
+            decimal::rescale(v, common_scale, scale)?
+        }
+        BinaryOp::Multiply => {
+            decimal::rescale(l.checked_mul(r).ok_or_else(overflow)?, s1 + s2, scale)?
+        }
+        BinaryOp::Divide => {
+            if r == 0 {
+                return Err(BustubxError::Execution("division by zero".to_string()));
+            }
+            // l / 10^s1 / (r / 10^s2) * 10^scale
+            let l = decimal::rescale(l, s1, scale + s2)?;
+            decimal::div_round(l, r)
+        }
+        _ => unreachable!(),
+    };
+    decimal::validate_precision(v, precision)?;
+    Ok(ScalarValue::Decimal(Some(v), precision, scale))
+}
+
+fn evaluate_arithmetic(
+    left: ScalarValue,
+    op: BinaryOp,
+    right: ScalarValue,
+) -> BustubxResult<ScalarValue> {
+    let data_type = arithmetic_type(&left.data_type(), op, &right.data_type())?;
+    if left.is_null() || right.is_null() {
+        return Ok(ScalarValue::new_empty(data_type));
+    }
+    if !(left.data_type().is_numeric() && right.data_type().is_numeric()) {
+        return evaluate_temporal_arithmetic(left, op, right);
+    }
+
+    let overflow = || BustubxError::Execution(format!("{} out of range", data_type));
+    let division_by_zero = || BustubxError::Execution("division by zero".to_string());
+    macro_rules! integer_arithmetic {
+        ($scalar:tt) => {{
+            let (ScalarValue::$scalar(Some(l)), ScalarValue::$scalar(Some(r))) =
+                (left.cast_to(&data_type)?, right.cast_to(&data_type)?)
+            else {
+                unreachable!()
+            };
+            let v = match op {
+                BinaryOp::Plus => l.checked_add(r).ok_or_else(overflow)?,
+                BinaryOp::Minus => l.checked_sub(r).ok_or_else(overflow)?,
+                BinaryOp::Multiply => l.checked_mul(r).ok_or_else(overflow)?,
+                BinaryOp::Divide | BinaryOp::Modulo if r == 0 => return Err(division_by_zero()),
+                BinaryOp::Divide => l.checked_div(r).ok_or_else(overflow)?,
+                BinaryOp::Modulo => l.checked_rem(r).ok_or_else(overflow)?,
+                _ => unreachable!(),
+            };
+            Ok(ScalarValue::$scalar(Some(v)))
+        }};
+    }
+    macro_rules! float_arithmetic {
+        ($scalar:tt) => {{
+            let (ScalarValue::$scalar(Some(l)), ScalarValue::$scalar(Some(r))) =
+                (left.cast_to(&data_type)?, right.cast_to(&data_type)?)
+            else {
+                unreachable!()
+            };
+            let v = match op {
+                BinaryOp::Plus => l + r,
+                BinaryOp::Minus => l - r,
+                BinaryOp::Multiply => l * r,
+                BinaryOp::Divide | BinaryOp::Modulo if r == 0.0 => return Err(division_by_zero()),
+                BinaryOp::Divide => l / r,
+                BinaryOp::Modulo => l % r,
+                _ => unreachable!(),
+            };
+            Ok(ScalarValue::$scalar(Some(v)))
+        }};
+    }
+
+    match data_type {
+        DataType::Int8 => integer_arithmetic!(Int8),
+        DataType::Int16 => integer_arithmetic!(Int16),
+        DataType::Int32 => integer_arithmetic!(Int32),
+        DataType::Int64 => integer_arithmetic!(Int64),
+        DataType::UInt8 => integer_arithmetic!(UInt8),
+        DataType::UInt16 => integer_arithmetic!(UInt16),
+        DataType::UInt32 => integer_arithmetic!(UInt32),
+        DataType::UInt64 => integer_arithmetic!(UInt64),
+        DataType::Float32 => float_arithmetic!(Float32),
+        DataType::Float64 => float_arithmetic!(Float64),
+        DataType::Decimal(precision, scale) => {
+            evaluate_decimal_arithmetic(&left, op, &right, precision, scale)
+        }
+        _ => Err(BustubxError::Internal(format!(
+            "{} is not a numeric type",
+            data_type
+        ))),
+    }
+}
+
+fn evaluate_temporal_arithmetic(
+    left: ScalarValue,
+    op: BinaryOp,
+    right: ScalarValue,
+) -> BustubxResult<ScalarValue> {
+    let out_of_range = || BustubxError::Execution("date/time value out of range".to_string());
+    let negate = |interval: &Interval| {
+        if op == BinaryOp::Minus {
+            interval.checked_neg()
+        } else {
+            Ok(*interval)
+        }
+    };
+
+    match (&left, &right) {
+        (ScalarValue::Date(Some(date)), r) | (r, ScalarValue::Date(Some(date)))
+            if r.data_type().is_integer() =>
+        {
+            let days = r.as_i64()?.unwrap_or_default();
+            let days = if op == BinaryOp::Minus { -days } else { days };
+            i32::try_from(*date as i64 + days)
+                .map(|v| ScalarValue::Date(Some(v)))
+                .map_err(|_| out_of_range())
+        }
+        (ScalarValue::Date(Some(l)), ScalarValue::Date(Some(r))) => {
+            Ok(ScalarValue::Int32(Some(l - r)))
+        }
+        (ScalarValue::Date(Some(date)), ScalarValue::Time(Some(time)))
+        | (ScalarValue::Time(Some(time)), ScalarValue::Date(Some(date))) => Ok(
+            ScalarValue::Timestamp(Some(*date as i64 * MICROS_PER_DAY + time)),
+        ),
+        (ScalarValue::Date(Some(date)), ScalarValue::Interval(Some(interval)))
+        | (ScalarValue::Interval(Some(interval)), ScalarValue::Date(Some(date))) => {
+            let timestamp = *date as i64 * MICROS_PER_DAY;
+            Ok(ScalarValue::Timestamp(Some(timestamp_add_interval(
+                timestamp,
+                &negate(interval)?,
+            )?)))
+        }
+        (ScalarValue::Timestamp(Some(timestamp)), ScalarValue::Interval(Some(interval)))
+        | (ScalarValue::Interval(Some(interval)), ScalarValue::Timestamp(Some(timestamp))) => {
+            Ok(ScalarValue::Timestamp(Some(timestamp_add_interval(
+                *timestamp,
+                &negate(interval)?,
+            )?)))
+        }
+        (ScalarValue::TimestampTz(Some(timestamp)), ScalarValue::Interval(Some(interval)))
+        | (ScalarValue::Interval(Some(interval)), ScalarValue::TimestampTz(Some(timestamp))) => {
+            Ok(ScalarValue::TimestampTz(Some(timestamp_add_interval(
+                *timestamp,
+                &negate(interval)?,
+            )?)))
+        }
+        (ScalarValue::Time(Some(time)), ScalarValue::Interval(Some(interval)))
+        | (ScalarValue::Interval(Some(interval)), ScalarValue::Time(Some(time))) => {
+            // time wraps around midnight, months and days are ignored
+            let micros = negate(interval)?.micros % MICROS_PER_DAY;
+            Ok(ScalarValue::Time(Some(
+                (time + micros).rem_euclid(MICROS_PER_DAY),
+            )))
+        }
+        (ScalarValue::Timestamp(Some(l)), ScalarValue::Timestamp(Some(r)))
+        | (ScalarValue::TimestampTz(Some(l)), ScalarValue::TimestampTz(Some(r))) => {
+            let diff = l.checked_sub(*r).ok_or_else(out_of_range)?;
+            Ok(ScalarValue::Interval(Some(Interval::new(
+                0,
+                (diff / MICROS_PER_DAY) as i32,
+                diff % MICROS_PER_DAY,
+            ))))
+        }
+        (ScalarValue::Time(Some(l)), ScalarValue::Time(Some(r))) => {
+            Ok(ScalarValue::Interval(Some(Interval::new(0, 0, l - r))))
+        }
+        (ScalarValue::Interval(Some(l)), ScalarValue::Interval(Some(r))) => {
+            Ok(ScalarValue::Interval(Some(l.checked_add(&negate(r)?)?)))
+        }
+        (ScalarValue::Interval(Some(interval)), factor)
+        | (factor, ScalarValue::Interval(Some(interval))) => {
+            let factor = factor.as_f64()?.unwrap_or_default();
+            let factor = if op == BinaryOp::Divide {
+                if factor == 0.0 {
+                    return Err(BustubxError::Execution("division by zero".to_string()));
+                }
+                1.0 / factor
+            } else {
+                factor
+            };
+            Ok(ScalarValue::Interval(Some(interval.checked_mul(factor)?)))
+        }
+        _ => Err(BustubxError::Execution(format!(
+            "Can not evaluate {:?} {} {:?}",
+            left, op, right
+        ))),
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Hash)]
 pub enum BinaryOp {
     Plus,
     Minus,
     Multiply,
     Divide,
+    Modulo,
     Gt,
     Lt,
     GtEq,
@@ -111,6 +500,14 @@ pub enum BinaryOp {
     NotEq,
     And,
     Or,
+    /// `json -> key`, a field or an array element as json
+    JsonGet,
+    /// `json ->> key`, a field or an array element as text
+    JsonGetText,
+    /// `json #> '{a,b}'`, the value at a path as json
+    JsonGetPath,
+    /// `json #>> '{a,b}'`, the value at a path as text
+    JsonGetPathText,
 }
 
 impl TryFrom<&sqlparser::ast::BinaryOperator> for BinaryOp {
@@ -122,6 +519,7 @@ impl TryFrom<&sqlparser::ast::BinaryOperator> for BinaryOp {
             sqlparser::ast::BinaryOperator::Minus => Ok(BinaryOp::Minus),
             sqlparser::ast::BinaryOperator::Multiply => Ok(BinaryOp::Multiply),
             sqlparser::ast::BinaryOperator::Divide => Ok(BinaryOp::Divide),
+            sqlparser::ast::BinaryOperator::Modulo => Ok(BinaryOp::Modulo),
             sqlparser::ast::BinaryOperator::Gt => Ok(BinaryOp::Gt),
             sqlparser::ast::BinaryOperator::Lt => Ok(BinaryOp::Lt),
             sqlparser::ast::BinaryOperator::GtEq => Ok(BinaryOp::GtEq),
@@ -138,6 +536,23 @@ impl TryFrom<&sqlparser::ast::BinaryOperator> for BinaryOp {
     }
 }
 
+impl TryFrom<&sqlparser::ast::JsonOperator> for BinaryOp {
+    type Error = BustubxError;
+
+    fn try_from(value: &sqlparser::ast::JsonOperator) -> Result<Self, Self::Error> {
+        match value {
+            sqlparser::ast::JsonOperator::Arrow => Ok(BinaryOp::JsonGet),
+            sqlparser::ast::JsonOperator::LongArrow => Ok(BinaryOp::JsonGetText),
+            sqlparser::ast::JsonOperator::HashArrow => Ok(BinaryOp::JsonGetPath),
+            sqlparser::ast::JsonOperator::HashLongArrow => Ok(BinaryOp::JsonGetPathText),
+            _ => Err(BustubxError::NotSupport(format!(
+                "sqlparser json operator {} not supported",
+                value
+            ))),
+        }
+    }
+}
+
 impl std::fmt::Display for BinaryExpr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "({} {} {})", self.left, self.op, self.right)
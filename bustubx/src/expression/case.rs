@@ -0,0 +1,108 @@
+use crate::catalog::{Column, DataType, Schema};
+use crate::common::ScalarValue;
+use crate::expression::binary::{as_boolean, evaluate_comparison};
+use crate::expression::{Expr, ExprTrait, Literal};
+use crate::{BustubxError, BustubxResult, Tuple};
+use std::cmp::Ordering;
+
+/// `CASE [expr] WHEN condition THEN result [WHEN ...] [ELSE result] END`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Case {
+    /// Compared to each WHEN value if present, otherwise WHEN expressions are conditions
+    pub expr: Option<Box<Expr>>,
+    pub when_then_expr: Vec<(Box<Expr>, Box<Expr>)>,
+    pub else_expr: Option<Box<Expr>>,
+}
+
+impl Case {
+    fn result_exprs(&self) -> impl Iterator<Item = &Expr> {
+        self.when_then_expr
+            .iter()
+            .map(|(_, then)| then.as_ref())
+            .chain(self.else_expr.iter().map(|e| e.as_ref()))
+    }
+}
+
+impl ExprTrait for Case {
+    fn data_type(&self, input_schema: &Schema) -> BustubxResult<DataType> {
+        // untyped NULL results take the type of the other results
+        let mut data_type: Option<DataType> = None;
+        for expr in self.result_exprs() {
+            if let Expr::Literal(Literal { value }) = expr {
+                if value.is_null() {
+                    continue;
+                }
+            }
+            let expr_type = expr.data_type(input_schema)?;
+            data_type = Some(match data_type {
+                Some(data_type) => DataType::set_operation_coercion(&data_type, &expr_type)
+                    .map_err(|_| {
+                        BustubxError::Plan(format!(
+                            "CASE types {} and {} cannot be matched",
+                            data_type, expr_type
+                        ))
+                    })?,
+                None => expr_type,
+            });
+        }
+        Ok(data_type.unwrap_or(DataType::Varchar(None)))
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> BustubxResult<bool> {
+        if self.else_expr.is_none() {
+            return Ok(true);
+        }
+        for expr in self.result_exprs() {
+            if expr.nullable(input_schema)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn evaluate(&self, tuple: &Tuple) -> BustubxResult<ScalarValue> {
+        let data_type = self.data_type(&tuple.schema)?;
+        let base = match &self.expr {
+            Some(expr) => Some(expr.evaluate(tuple)?),
+            None => None,
+        };
+        for (when, then) in self.when_then_expr.iter() {
+            let when = when.evaluate(tuple)?;
+            let matched = match &base {
+                Some(base) => evaluate_comparison(base.clone(), when, &[Ordering::Equal])?,
+                None => when,
+            };
+            if as_boolean(&matched)? == Some(true) {
+                return then.evaluate(tuple)?.cast_to(&data_type);
+            }
+        }
+        match &self.else_expr {
+            Some(expr) => expr.evaluate(tuple)?.cast_to(&data_type),
+            None => Ok(ScalarValue::new_empty(data_type)),
+        }
+    }
+
+    fn to_column(&self, input_schema: &Schema) -> BustubxResult<Column> {
+        Ok(Column::new(
+            format!("{self}"),
+            self.data_type(input_schema)?,
+            self.nullable(input_schema)?,
+        ))
+    }
+}
+
+impl std::fmt::Display for Case {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CASE ")?;
+        if let Some(expr) = &self.expr {
+            write!(f, "{} ", expr)?;
+        }
+        for (when, then) in self.when_then_expr.iter() {
+            write!(f, "WHEN {} THEN {} ", when, then)?;
+        }
+        if let Some(else_expr) = &self.else_expr {
+            write!(f, "ELSE {} ", else_expr)?;
+        }
+        write!(f, "END")
+    }
+}
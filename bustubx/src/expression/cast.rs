@@ -1,7 +1,7 @@
 use crate::catalog::{Column, DataType, Schema};
 use crate::common::ScalarValue;
 use crate::expression::{Expr, ExprTrait};
-use crate::{BustubxError, BustubxResult, Tuple};
+use crate::{BustubxResult, Tuple};
 
 /// Cast expression
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -26,11 +26,12 @@ impl ExprTrait for Cast {
         value.cast_to(&self.data_type)
     }
 
-    fn to_column(&self, _input_schema: &Schema) -> BustubxResult<Column> {
-        Err(BustubxError::Plan(format!(
-            "expr {:?} as column not supported",
-            self
-        )))
+    fn to_column(&self, input_schema: &Schema) -> BustubxResult<Column> {
+        Ok(Column::new(
+            format!("{self}"),
+            self.data_type,
+            self.nullable(input_schema)?,
+        ))
     }
 }
 
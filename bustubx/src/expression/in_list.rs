@@ -0,0 +1,82 @@
+use crate::catalog::{Column, DataType, Schema};
+use crate::common::ScalarValue;
+use crate::expression::binary::{as_boolean, evaluate_comparison};
+use crate::expression::{Expr, ExprTrait};
+use crate::{BustubxResult, Tuple};
+use std::cmp::Ordering;
+
+/// `expr [NOT] IN (list)`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InList {
+    pub expr: Box<Expr>,
+    pub list: Vec<Expr>,
+    pub negated: bool,
+}
+
+impl ExprTrait for InList {
+    fn data_type(&self, _input_schema: &Schema) -> BustubxResult<DataType> {
+        Ok(DataType::Boolean)
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> BustubxResult<bool> {
+        if self.expr.nullable(input_schema)? {
+            return Ok(true);
+        }
+        for expr in self.list.iter() {
+            if expr.nullable(input_schema)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn evaluate(&self, tuple: &Tuple) -> BustubxResult<ScalarValue> {
+        let value = self.expr.evaluate(tuple)?;
+        // NULL if there is no match but some comparison is unknown
+        let mut result = Some(false);
+        for expr in self.list.iter() {
+            let item = expr.evaluate(tuple)?;
+            match as_boolean(&evaluate_comparison(
+                value.clone(),
+                item,
+                &[Ordering::Equal],
+            )?)? {
+                Some(true) => {
+                    result = Some(true);
+                    break;
+                }
+                Some(false) => {}
+                None => result = None,
+            }
+        }
+        if self.negated {
+            Ok(ScalarValue::Boolean(result.map(|v| !v)))
+        } else {
+            Ok(ScalarValue::Boolean(result))
+        }
+    }
+
+    fn to_column(&self, input_schema: &Schema) -> BustubxResult<Column> {
+        Ok(Column::new(
+            format!("{self}"),
+            self.data_type(input_schema)?,
+            self.nullable(input_schema)?,
+        ))
+    }
+}
+
+impl std::fmt::Display for InList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}IN ({})",
+            self.expr,
+            if self.negated { "NOT " } else { "" },
+            self.list
+                .iter()
+                .map(|e| format!("{e}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
@@ -0,0 +1,177 @@
+use crate::catalog::{Column, DataType, Schema};
+use crate::common::ScalarValue;
+use crate::expression::{Expr, ExprTrait};
+use crate::{BustubxError, BustubxResult, Tuple};
+
+/// `expr [NOT] LIKE pattern [ESCAPE escape_char]`, `%` matches any sequence of characters
+/// and `_` matches any single character.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Like {
+    pub negated: bool,
+    pub expr: Box<Expr>,
+    pub pattern: Box<Expr>,
+    pub escape_char: Option<char>,
+    /// ILIKE
+    pub case_insensitive: bool,
+}
+
+impl ExprTrait for Like {
+    fn data_type(&self, _input_schema: &Schema) -> BustubxResult<DataType> {
+        Ok(DataType::Boolean)
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> BustubxResult<bool> {
+        Ok(self.expr.nullable(input_schema)? || self.pattern.nullable(input_schema)?)
+    }
+
+    fn evaluate(&self, tuple: &Tuple) -> BustubxResult<ScalarValue> {
+        let value = self.expr.evaluate(tuple)?;
+        let pattern = self.pattern.evaluate(tuple)?;
+        let (value, pattern) = match (value, pattern) {
+            (ScalarValue::Varchar(Some(value)), ScalarValue::Varchar(Some(pattern))) => {
+                (value, pattern)
+            }
+            (value, pattern) if value.is_null() || pattern.is_null() => {
+                return Ok(ScalarValue::Boolean(None))
+            }
+            (value, pattern) => {
+                return Err(BustubxError::Execution(format!(
+                    "Can not match {:?} with pattern {:?}",
+                    value, pattern
+                )))
+            }
+        };
+        let (value, pattern) = if self.case_insensitive {
+            (value.to_lowercase(), pattern.to_lowercase())
+        } else {
+            (value, pattern)
+        };
+
+        let tokens = parse_pattern(&pattern, self.escape_char.unwrap_or('\\'))?;
+        let matched = like_match(&value.chars().collect::<Vec<char>>(), &tokens);
+        Ok(ScalarValue::Boolean(Some(matched != self.negated)))
+    }
+
+    fn to_column(&self, input_schema: &Schema) -> BustubxResult<Column> {
+        Ok(Column::new(
+            format!("{self}"),
+            self.data_type(input_schema)?,
+            self.nullable(input_schema)?,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PatternToken {
+    /// `%`
+    AnySequence,
+    /// `_`
+    AnyChar,
+    Char(char),
+}
+
+fn parse_pattern(pattern: &str, escape_char: char) -> BustubxResult<Vec<PatternToken>> {
+    let mut tokens = vec![];
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == escape_char {
+            let escaped = chars.next().ok_or(BustubxError::Execution(format!(
+                "LIKE pattern \"{}\" must not end with escape character",
+                pattern
+            )))?;
+            tokens.push(PatternToken::Char(escaped));
+        } else if c == '%' {
+            tokens.push(PatternToken::AnySequence);
+        } else if c == '_' {
+            tokens.push(PatternToken::AnyChar);
+        } else {
+            tokens.push(PatternToken::Char(c));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Greedy matching with backtracking to the last `%`.
+fn like_match(value: &[char], tokens: &[PatternToken]) -> bool {
+    let (mut v, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while v < value.len() {
+        match tokens.get(t) {
+            Some(PatternToken::AnySequence) => {
+                backtrack = Some((t, v));
+                t += 1;
+            }
+            Some(PatternToken::AnyChar) => {
+                v += 1;
+                t += 1;
+            }
+            Some(PatternToken::Char(c)) if *c == value[v] => {
+                v += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // let the last `%` consume one more character
+                Some((bt, bv)) => {
+                    backtrack = Some((bt, bv + 1));
+                    t = bt + 1;
+                    v = bv + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    tokens[t..]
+        .iter()
+        .all(|token| *token == PatternToken::AnySequence)
+}
+
+impl std::fmt::Display for Like {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}{} {}",
+            self.expr,
+            if self.negated { "NOT " } else { "" },
+            if self.case_insensitive {
+                "ILIKE"
+            } else {
+                "LIKE"
+            },
+            self.pattern
+        )?;
+        if let Some(escape_char) = self.escape_char {
+            write!(f, " ESCAPE '{}'", escape_char)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{like_match, parse_pattern};
+
+    fn matches(value: &str, pattern: &str) -> bool {
+        like_match(
+            &value.chars().collect::<Vec<char>>(),
+            &parse_pattern(pattern, '\\').unwrap(),
+        )
+    }
+
+    #[test]
+    fn like_pattern_match() {
+        assert!(matches("abc", "abc"));
+        assert!(matches("abc", "a%"));
+        assert!(matches("abc", "%c"));
+        assert!(matches("abc", "%b%"));
+        assert!(matches("abc", "a_c"));
+        assert!(matches("abc", "%%"));
+        assert!(matches("", "%"));
+        assert!(matches("aab", "%ab"));
+        assert!(matches("a%c", "a\\%c"));
+        assert!(!matches("abc", "a\\%c"));
+        assert!(!matches("abc", "ab"));
+        assert!(!matches("abc", "_"));
+        assert!(!matches("", "_"));
+        assert!(!matches("abcd", "%b_"));
+    }
+}
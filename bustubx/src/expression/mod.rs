@@ -1,24 +1,40 @@
 mod aggregate;
 mod alias;
+mod between;
 mod binary;
+mod case;
 mod cast;
 mod column;
+mod in_list;
+mod like;
 mod literal;
+mod scalar_function;
+mod sequence_function;
+mod unary;
 mod util;
+mod window;
 
 pub use aggregate::AggregateFunction;
 pub use alias::Alias;
-pub use binary::BinaryExpr;
+pub use between::Between;
+pub use binary::{BinaryExpr, BinaryOp};
+pub use case::Case;
 pub use cast::Cast;
 pub use column::ColumnExpr;
+pub use in_list::InList;
+pub use like::Like;
 pub use literal::Literal;
+pub use scalar_function::ScalarFunction;
+pub use sequence_function::{SequenceFunction, SequenceFunctionKind};
+pub use unary::{UnaryExpr, UnaryOp};
 pub use util::*;
+pub use window::{WindowFrame, WindowFrameBound, WindowFrameUnits, WindowFunction};
 
 use crate::catalog::Schema;
 use crate::catalog::{Column, DataType};
 use crate::common::ScalarValue;
 use crate::storage::Tuple;
-use crate::BustubxResult;
+use crate::{BustubxError, BustubxResult};
 
 pub trait ExprTrait {
     /// Get the data type of this expression, given the schema of the input
@@ -44,11 +60,27 @@ pub enum Expr {
     Literal(Literal),
     /// A binary expression such as "age > 21"
     Binary(BinaryExpr),
+    /// A unary expression such as "NOT a" or "a IS NULL"
+    Unary(UnaryExpr),
+    /// Whether an expression is between a given range.
+    Between(Between),
+    /// Returns whether the list contains the expr value.
+    InList(InList),
+    /// LIKE or ILIKE pattern matching
+    Like(Like),
+    /// The CASE expression, returns the result of the first matched WHEN.
+    Case(Case),
     /// Casts the expression to a given type and will return a runtime error if the expression cannot be cast.
     /// This expression is guaranteed to have a fixed type.
     Cast(Cast),
+    /// Represents the call of a scalar built-in function with arguments.
+    ScalarFunction(ScalarFunction),
+    /// Represents the call of a sequence function such as `nextval('seq')`.
+    SequenceFunction(SequenceFunction),
     /// Represents the call of an aggregate built-in function with arguments.
     AggregateFunction(AggregateFunction),
+    /// Represents the call of a window function with an OVER clause.
+    WindowFunction(WindowFunction),
 }
 
 impl ExprTrait for Expr {
@@ -58,8 +90,16 @@ impl ExprTrait for Expr {
             Expr::Column(column) => column.data_type(input_schema),
             Expr::Literal(literal) => literal.data_type(input_schema),
             Expr::Binary(binary) => binary.data_type(input_schema),
+            Expr::Unary(unary) => unary.data_type(input_schema),
+            Expr::Between(between) => between.data_type(input_schema),
+            Expr::InList(in_list) => in_list.data_type(input_schema),
+            Expr::Like(like) => like.data_type(input_schema),
+            Expr::Case(case) => case.data_type(input_schema),
             Expr::Cast(cast) => cast.data_type(input_schema),
+            Expr::ScalarFunction(func) => func.data_type(input_schema),
+            Expr::SequenceFunction(func) => func.data_type(input_schema),
             Expr::AggregateFunction(aggr) => aggr.data_type(input_schema),
+            Expr::WindowFunction(window) => window.data_type(input_schema),
         }
     }
 
@@ -69,8 +109,16 @@ impl ExprTrait for Expr {
             Expr::Column(column) => column.nullable(input_schema),
             Expr::Literal(literal) => literal.nullable(input_schema),
             Expr::Binary(binary) => binary.nullable(input_schema),
+            Expr::Unary(unary) => unary.nullable(input_schema),
+            Expr::Between(between) => between.nullable(input_schema),
+            Expr::InList(in_list) => in_list.nullable(input_schema),
+            Expr::Like(like) => like.nullable(input_schema),
+            Expr::Case(case) => case.nullable(input_schema),
             Expr::Cast(cast) => cast.nullable(input_schema),
+            Expr::ScalarFunction(func) => func.nullable(input_schema),
+            Expr::SequenceFunction(func) => func.nullable(input_schema),
             Expr::AggregateFunction(aggr) => aggr.nullable(input_schema),
+            Expr::WindowFunction(window) => window.nullable(input_schema),
         }
     }
 
@@ -80,8 +128,16 @@ impl ExprTrait for Expr {
             Expr::Column(column) => column.evaluate(tuple),
             Expr::Literal(literal) => literal.evaluate(tuple),
             Expr::Binary(binary) => binary.evaluate(tuple),
+            Expr::Unary(unary) => unary.evaluate(tuple),
+            Expr::Between(between) => between.evaluate(tuple),
+            Expr::InList(in_list) => in_list.evaluate(tuple),
+            Expr::Like(like) => like.evaluate(tuple),
+            Expr::Case(case) => case.evaluate(tuple),
             Expr::Cast(cast) => cast.evaluate(tuple),
+            Expr::ScalarFunction(func) => func.evaluate(tuple),
+            Expr::SequenceFunction(func) => func.evaluate(tuple),
             Expr::AggregateFunction(aggr) => aggr.evaluate(tuple),
+            Expr::WindowFunction(window) => window.evaluate(tuple),
         }
     }
 
@@ -91,12 +147,132 @@ impl ExprTrait for Expr {
             Expr::Column(column) => column.to_column(input_schema),
             Expr::Literal(literal) => literal.to_column(input_schema),
             Expr::Binary(binary) => binary.to_column(input_schema),
+            Expr::Unary(unary) => unary.to_column(input_schema),
+            Expr::Between(between) => between.to_column(input_schema),
+            Expr::InList(in_list) => in_list.to_column(input_schema),
+            Expr::Like(like) => like.to_column(input_schema),
+            Expr::Case(case) => case.to_column(input_schema),
             Expr::Cast(cast) => cast.to_column(input_schema),
+            Expr::ScalarFunction(func) => func.to_column(input_schema),
+            Expr::SequenceFunction(func) => func.to_column(input_schema),
             Expr::AggregateFunction(aggr) => aggr.to_column(input_schema),
+            Expr::WindowFunction(window) => window.to_column(input_schema),
         }
     }
 }
 
+impl Expr {
+    /// The direct sub expressions evaluated as part of this expression
+    pub fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Alias(Alias { expr, .. })
+            | Expr::Cast(Cast { expr, .. })
+            | Expr::Unary(UnaryExpr { expr, .. }) => vec![expr],
+            Expr::Binary(BinaryExpr { left, right, .. }) => vec![left, right],
+            Expr::Between(Between {
+                expr, low, high, ..
+            }) => vec![expr, low, high],
+            Expr::InList(InList { expr, list, .. }) => {
+                let mut children = vec![expr.as_ref()];
+                children.extend(list.iter());
+                children
+            }
+            Expr::Like(Like { expr, pattern, .. }) => vec![expr, pattern],
+            Expr::Case(Case {
+                expr,
+                when_then_expr,
+                else_expr,
+            }) => {
+                let mut children = vec![];
+                children.extend(expr.iter().map(|e| e.as_ref()));
+                for (when, then) in when_then_expr.iter() {
+                    children.push(when.as_ref());
+                    children.push(then.as_ref());
+                }
+                children.extend(else_expr.iter().map(|e| e.as_ref()));
+                children
+            }
+            Expr::ScalarFunction(ScalarFunction { args, .. })
+            | Expr::AggregateFunction(AggregateFunction { args, .. })
+            | Expr::WindowFunction(WindowFunction { args, .. }) => args.iter().collect(),
+            Expr::Column(_) | Expr::Literal(_) | Expr::SequenceFunction(_) => vec![],
+        }
+    }
+
+    /// Rebuilds this expression with new children in the order of [`Expr::children`]
+    pub fn with_new_children(&self, children: Vec<Expr>) -> BustubxResult<Expr> {
+        if children.len() != self.children().len() {
+            return Err(BustubxError::Internal(format!(
+                "expr {} expects {} children but got {}",
+                self,
+                self.children().len(),
+                children.len()
+            )));
+        }
+        let mut children = children.into_iter().map(Box::new);
+        let mut next = || children.next().unwrap();
+        Ok(match self {
+            Expr::Alias(alias) => Expr::Alias(Alias {
+                expr: next(),
+                name: alias.name.clone(),
+            }),
+            Expr::Cast(cast) => Expr::Cast(Cast {
+                expr: next(),
+                data_type: cast.data_type,
+            }),
+            Expr::Unary(unary) => Expr::Unary(UnaryExpr {
+                op: unary.op,
+                expr: next(),
+            }),
+            Expr::Binary(binary) => Expr::Binary(BinaryExpr {
+                left: next(),
+                op: binary.op,
+                right: next(),
+            }),
+            Expr::Between(between) => Expr::Between(Between {
+                expr: next(),
+                negated: between.negated,
+                low: next(),
+                high: next(),
+            }),
+            Expr::InList(in_list) => Expr::InList(InList {
+                expr: next(),
+                list: in_list.list.iter().map(|_| *next()).collect(),
+                negated: in_list.negated,
+            }),
+            Expr::Like(like) => Expr::Like(Like {
+                negated: like.negated,
+                expr: next(),
+                pattern: next(),
+                escape_char: like.escape_char,
+                case_insensitive: like.case_insensitive,
+            }),
+            Expr::Case(case) => Expr::Case(Case {
+                expr: case.expr.as_ref().map(|_| next()),
+                when_then_expr: case
+                    .when_then_expr
+                    .iter()
+                    .map(|_| (next(), next()))
+                    .collect(),
+                else_expr: case.else_expr.as_ref().map(|_| next()),
+            }),
+            Expr::ScalarFunction(func) => Expr::ScalarFunction(ScalarFunction {
+                func_kind: func.func_kind.clone(),
+                args: func.args.iter().map(|_| *next()).collect(),
+            }),
+            Expr::AggregateFunction(aggr) => Expr::AggregateFunction(AggregateFunction {
+                args: aggr.args.iter().map(|_| *next()).collect(),
+                ..aggr.clone()
+            }),
+            Expr::WindowFunction(window) => Expr::WindowFunction(WindowFunction {
+                args: window.args.iter().map(|_| *next()).collect(),
+                ..window.clone()
+            }),
+            Expr::Column(_) | Expr::Literal(_) | Expr::SequenceFunction(_) => self.clone(),
+        })
+    }
+}
+
 impl std::fmt::Display for Expr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -104,8 +280,16 @@ impl std::fmt::Display for Expr {
             Expr::Column(e) => write!(f, "{e}"),
             Expr::Literal(e) => write!(f, "{e}"),
             Expr::Binary(e) => write!(f, "{e}"),
+            Expr::Unary(e) => write!(f, "{e}"),
+            Expr::Between(e) => write!(f, "{e}"),
+            Expr::InList(e) => write!(f, "{e}"),
+            Expr::Like(e) => write!(f, "{e}"),
+            Expr::Case(e) => write!(f, "{e}"),
             Expr::Cast(e) => write!(f, "{e}"),
+            Expr::ScalarFunction(e) => write!(f, "{e}"),
+            Expr::SequenceFunction(e) => write!(f, "{e}"),
             Expr::AggregateFunction(e) => write!(f, "{e}"),
+            Expr::WindowFunction(e) => write!(f, "{e}"),
         }
     }
 }
@@ -0,0 +1,66 @@
+use crate::catalog::{Column, DataType, Schema};
+use crate::common::ScalarValue;
+use crate::expression::{Expr, ExprTrait};
+use crate::function::ScalarFunctionKind;
+use crate::{BustubxResult, Tuple};
+
+/// Scalar function call, e.g. `date_trunc('day', ts)`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ScalarFunction {
+    /// the function kind
+    pub func_kind: ScalarFunctionKind,
+    /// List of expressions to feed to the functions as arguments
+    pub args: Vec<Expr>,
+}
+
+impl ExprTrait for ScalarFunction {
+    fn data_type(&self, input_schema: &Schema) -> BustubxResult<DataType> {
+        let arg_types = self
+            .args
+            .iter()
+            .map(|arg| arg.data_type(input_schema))
+            .collect::<BustubxResult<Vec<DataType>>>()?;
+        self.func_kind.return_type(&arg_types)
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> BustubxResult<bool> {
+        for arg in self.args.iter() {
+            if arg.nullable(input_schema)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn evaluate(&self, tuple: &Tuple) -> BustubxResult<ScalarValue> {
+        let args = self
+            .args
+            .iter()
+            .map(|arg| arg.evaluate(tuple))
+            .collect::<BustubxResult<Vec<ScalarValue>>>()?;
+        self.func_kind.invoke(&args)
+    }
+
+    fn to_column(&self, input_schema: &Schema) -> BustubxResult<Column> {
+        Ok(Column::new(
+            format!("{self}"),
+            self.data_type(input_schema)?,
+            self.nullable(input_schema)?,
+        ))
+    }
+}
+
+impl std::fmt::Display for ScalarFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}({})",
+            self.func_kind,
+            self.args
+                .iter()
+                .map(|e| format!("{e}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use crate::catalog::{Column, DataType, Schema, Sequence};
+use crate::common::ScalarValue;
+use crate::expression::ExprTrait;
+use crate::{BustubxResult, Tuple};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceFunctionKind {
+    NextVal,
+    CurrVal,
+}
+
+/// A sequence function call bound to its sequence, e.g. `nextval('seq')`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceFunction {
+    pub kind: SequenceFunctionKind,
+    pub sequence: Arc<Sequence>,
+}
+
+impl ExprTrait for SequenceFunction {
+    fn data_type(&self, _input_schema: &Schema) -> BustubxResult<DataType> {
+        Ok(DataType::Int64)
+    }
+
+    fn nullable(&self, _input_schema: &Schema) -> BustubxResult<bool> {
+        Ok(false)
+    }
+
+    fn evaluate(&self, _tuple: &Tuple) -> BustubxResult<ScalarValue> {
+        let value = match self.kind {
+            SequenceFunctionKind::NextVal => self.sequence.next_value()?,
+            SequenceFunctionKind::CurrVal => self.sequence.current_value()?,
+        };
+        Ok(value.into())
+    }
+
+    fn to_column(&self, input_schema: &Schema) -> BustubxResult<Column> {
+        Ok(Column::new(
+            format!("{self}"),
+            self.data_type(input_schema)?,
+            self.nullable(input_schema)?,
+        ))
+    }
+}
+
+impl std::fmt::Display for SequenceFunctionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SequenceFunctionKind::NextVal => write!(f, "nextval"),
+            SequenceFunctionKind::CurrVal => write!(f, "currval"),
+        }
+    }
+}
+
+impl std::fmt::Display for SequenceFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}('{}.{}')",
+            self.kind, self.sequence.schema_name, self.sequence.name
+        )
+    }
+}
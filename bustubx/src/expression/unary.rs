@@ -0,0 +1,79 @@
+use crate::catalog::{Column, DataType, Schema};
+use crate::common::ScalarValue;
+use crate::expression::binary::as_boolean;
+use crate::expression::{Expr, ExprTrait};
+use crate::{BustubxError, BustubxResult, Tuple};
+
+/// Unary expression, e.g. `NOT a` or `a IS NULL`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UnaryExpr {
+    pub op: UnaryOp,
+    pub expr: Box<Expr>,
+}
+
+impl ExprTrait for UnaryExpr {
+    fn data_type(&self, input_schema: &Schema) -> BustubxResult<DataType> {
+        match self.op {
+            UnaryOp::Not | UnaryOp::IsNull | UnaryOp::IsNotNull => Ok(DataType::Boolean),
+            UnaryOp::Minus => self.expr.data_type(input_schema),
+        }
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> BustubxResult<bool> {
+        match self.op {
+            UnaryOp::IsNull | UnaryOp::IsNotNull => Ok(false),
+            UnaryOp::Not | UnaryOp::Minus => self.expr.nullable(input_schema),
+        }
+    }
+
+    fn evaluate(&self, tuple: &Tuple) -> BustubxResult<ScalarValue> {
+        let value = self.expr.evaluate(tuple)?;
+        match self.op {
+            UnaryOp::Not => Ok(ScalarValue::Boolean(as_boolean(&value)?.map(|v| !v))),
+            UnaryOp::IsNull => Ok(value.is_null().into()),
+            UnaryOp::IsNotNull => Ok((!value.is_null()).into()),
+            UnaryOp::Minus => match value {
+                ScalarValue::Int8(v) => Ok(ScalarValue::Int8(v.map(|v| -v))),
+                ScalarValue::Int16(v) => Ok(ScalarValue::Int16(v.map(|v| -v))),
+                ScalarValue::Int32(v) => Ok(ScalarValue::Int32(v.map(|v| -v))),
+                ScalarValue::Int64(v) => Ok(ScalarValue::Int64(v.map(|v| -v))),
+                ScalarValue::Float32(v) => Ok(ScalarValue::Float32(v.map(|v| -v))),
+                ScalarValue::Float64(v) => Ok(ScalarValue::Float64(v.map(|v| -v))),
+                ScalarValue::Decimal(v, precision, scale) => {
+                    Ok(ScalarValue::Decimal(v.map(|v| -v), precision, scale))
+                }
+                _ => Err(BustubxError::Execution(format!(
+                    "Can not negate {:?}",
+                    value
+                ))),
+            },
+        }
+    }
+
+    fn to_column(&self, input_schema: &Schema) -> BustubxResult<Column> {
+        Ok(Column::new(
+            format!("{self}"),
+            self.data_type(input_schema)?,
+            self.nullable(input_schema)?,
+        ))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum UnaryOp {
+    Not,
+    Minus,
+    IsNull,
+    IsNotNull,
+}
+
+impl std::fmt::Display for UnaryExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.op {
+            UnaryOp::Not => write!(f, "NOT {}", self.expr),
+            UnaryOp::Minus => write!(f, "(- {})", self.expr),
+            UnaryOp::IsNull => write!(f, "{} IS NULL", self.expr),
+            UnaryOp::IsNotNull => write!(f, "{} IS NOT NULL", self.expr),
+        }
+    }
+}
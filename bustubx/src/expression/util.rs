@@ -1,27 +1,102 @@
 use crate::catalog::SchemaRef;
-use crate::expression::{Alias, Cast, ColumnExpr, Expr};
+use crate::expression::{BinaryExpr, BinaryOp, ColumnExpr, Expr};
 use crate::BustubxResult;
 
 /// Convert an expression into Column expression
 pub fn columnize_expr(e: &Expr, input_schema: &SchemaRef) -> BustubxResult<Expr> {
     match e {
-        Expr::Column(_) => Ok(e.clone()),
-        Expr::Alias(Alias { expr, name }) => Ok(Expr::Alias(Alias {
-            expr: Box::new(columnize_expr(expr, input_schema)?),
-            name: name.clone(),
-        })),
-        Expr::Cast(Cast { expr, data_type }) => Ok(Expr::Cast(Cast {
-            expr: Box::new(columnize_expr(expr, input_schema)?),
-            data_type: *data_type,
-        })),
-        _ => {
-            let name = e.to_string();
-            let idx = input_schema.index_of(None, name.as_str())?;
-            let col = input_schema.column_with_index(idx)?;
-            Ok(Expr::Column(ColumnExpr {
-                relation: col.relation.clone(),
-                name,
-            }))
+        Expr::Column(_) | Expr::Literal(_) | Expr::SequenceFunction(_) => Ok(e.clone()),
+        Expr::Alias(_) | Expr::Cast(_) => columnize_children(e, input_schema),
+        Expr::Binary(_)
+        | Expr::Unary(_)
+        | Expr::Between(_)
+        | Expr::InList(_)
+        | Expr::Like(_)
+        | Expr::Case(_)
+        | Expr::ScalarFunction(_) => match columnize_expr_by_name(e, input_schema) {
+            Ok(expr) => Ok(expr),
+            Err(_) => columnize_children(e, input_schema),
+        },
+        Expr::AggregateFunction(_) | Expr::WindowFunction(_) => {
+            columnize_expr_by_name(e, input_schema)
         }
     }
 }
+
+fn columnize_children(e: &Expr, input_schema: &SchemaRef) -> BustubxResult<Expr> {
+    let children = e
+        .children()
+        .into_iter()
+        .map(|child| columnize_expr(child, input_schema))
+        .collect::<BustubxResult<Vec<Expr>>>()?;
+    e.with_new_children(children)
+}
+
+fn columnize_expr_by_name(e: &Expr, input_schema: &SchemaRef) -> BustubxResult<Expr> {
+    let name = e.to_string();
+    let idx = input_schema.index_of(None, name.as_str())?;
+    let col = input_schema.column_with_index(idx)?;
+    Ok(Expr::Column(ColumnExpr {
+        relation: col.relation.clone(),
+        name,
+    }))
+}
+
+/// Collect the distinct window function calls inside expressions
+pub fn find_window_exprs(exprs: &[Expr]) -> Vec<Expr> {
+    fn visit(expr: &Expr, result: &mut Vec<Expr>) {
+        match expr {
+            Expr::WindowFunction(_) => {
+                if !result.contains(expr) {
+                    result.push(expr.clone());
+                }
+            }
+            Expr::AggregateFunction(_) => {}
+            _ => {
+                for child in expr.children() {
+                    visit(child, result);
+                }
+            }
+        }
+    }
+
+    let mut result = vec![];
+    for expr in exprs {
+        visit(expr, &mut result);
+    }
+    result
+}
+
+/// Collect the distinct columns referenced inside an expression
+pub fn find_column_exprs(expr: &Expr) -> Vec<ColumnExpr> {
+    fn visit(expr: &Expr, result: &mut Vec<ColumnExpr>) {
+        if let Expr::Column(column) = expr {
+            if !result.contains(column) {
+                result.push(column.clone());
+            }
+        }
+        for child in expr.children() {
+            visit(child, result);
+        }
+    }
+
+    let mut result = vec![];
+    visit(expr, &mut result);
+    result
+}
+
+/// Split the operands of `AND` into a list of predicates
+pub fn split_conjunction(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Binary(BinaryExpr {
+            left,
+            op: BinaryOp::And,
+            right,
+        }) => {
+            let mut result = split_conjunction(left);
+            result.extend(split_conjunction(right));
+            result
+        }
+        _ => vec![expr],
+    }
+}
@@ -0,0 +1,185 @@
+use crate::catalog::{Column, DataType, Schema};
+use crate::common::ScalarValue;
+use crate::expression::{AggregateFunction, Expr, ExprTrait};
+use crate::function::{BuiltInWindowFunctionKind, WindowFunctionKind};
+use crate::planner::logical_plan::OrderByExpr;
+use crate::{BustubxError, BustubxResult, Tuple};
+
+/// Window function call, e.g. `rank() OVER (PARTITION BY a ORDER BY b)`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WindowFunction {
+    /// the function kind
+    pub func_kind: WindowFunctionKind,
+    /// List of expressions to feed to the functions as arguments
+    pub args: Vec<Expr>,
+    /// Rows with the same values of these expressions belong to the same partition
+    pub partition_by: Vec<Expr>,
+    /// Order of rows inside a partition
+    pub order_by: Vec<OrderByExpr>,
+    pub window_frame: WindowFrame,
+}
+
+impl WindowFunction {
+    fn first_arg(&self) -> BustubxResult<&Expr> {
+        self.args.first().ok_or(BustubxError::Plan(format!(
+            "window function {} should have at least one arg",
+            self.func_kind
+        )))
+    }
+}
+
+impl ExprTrait for WindowFunction {
+    fn data_type(&self, input_schema: &Schema) -> BustubxResult<DataType> {
+        match &self.func_kind {
+            WindowFunctionKind::BuiltIn(
+                BuiltInWindowFunctionKind::RowNumber
+                | BuiltInWindowFunctionKind::Rank
+                | BuiltInWindowFunctionKind::DenseRank,
+            ) => Ok(DataType::Int64),
+            WindowFunctionKind::BuiltIn(
+                BuiltInWindowFunctionKind::Lag
+                | BuiltInWindowFunctionKind::Lead
+                | BuiltInWindowFunctionKind::FirstValue
+                | BuiltInWindowFunctionKind::LastValue,
+            ) => self.first_arg()?.data_type(input_schema),
+            WindowFunctionKind::Aggregate(func_kind) => AggregateFunction {
+                func_kind: func_kind.clone(),
+                args: self.args.clone(),
+                distinct: false,
+            }
+            .data_type(input_schema),
+        }
+    }
+
+    fn nullable(&self, _input_schema: &Schema) -> BustubxResult<bool> {
+        match &self.func_kind {
+            WindowFunctionKind::BuiltIn(
+                BuiltInWindowFunctionKind::RowNumber
+                | BuiltInWindowFunctionKind::Rank
+                | BuiltInWindowFunctionKind::DenseRank,
+            ) => Ok(false),
+            _ => Ok(true),
+        }
+    }
+
+    fn evaluate(&self, _tuple: &Tuple) -> BustubxResult<ScalarValue> {
+        Err(BustubxError::Execution(format!(
+            "window function {} can only be evaluated by window operator",
+            self
+        )))
+    }
+
+    fn to_column(&self, input_schema: &Schema) -> BustubxResult<Column> {
+        Ok(Column::new(
+            format!("{}", self),
+            self.data_type(input_schema)?,
+            self.nullable(input_schema)?,
+        ))
+    }
+}
+
+impl std::fmt::Display for WindowFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}({}) OVER (",
+            self.func_kind,
+            self.args
+                .iter()
+                .map(|e| format!("{e}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        if !self.partition_by.is_empty() {
+            write!(
+                f,
+                "PARTITION BY {} ",
+                self.partition_by
+                    .iter()
+                    .map(|e| format!("{e}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+        if !self.order_by.is_empty() {
+            write!(
+                f,
+                "ORDER BY {} ",
+                self.order_by
+                    .iter()
+                    .map(|e| format!("{e}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+        write!(f, "{})", self.window_frame)
+    }
+}
+
+/// The set of rows of a partition used by a window function for the current row
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WindowFrame {
+    pub units: WindowFrameUnits,
+    pub start_bound: WindowFrameBound,
+    pub end_bound: WindowFrameBound,
+}
+
+impl Default for WindowFrame {
+    /// `RANGE BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW`
+    fn default() -> Self {
+        Self {
+            units: WindowFrameUnits::Range,
+            start_bound: WindowFrameBound::Preceding(None),
+            end_bound: WindowFrameBound::CurrentRow,
+        }
+    }
+}
+
+impl std::fmt::Display for WindowFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} BETWEEN {} AND {}",
+            self.units, self.start_bound, self.end_bound
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WindowFrameUnits {
+    /// Offsets count physical rows
+    Rows,
+    /// Offsets are applied to the value of the ORDER BY column, rows with
+    /// equal ORDER BY values (peers) are always in the frame together
+    Range,
+}
+
+impl std::fmt::Display for WindowFrameUnits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowFrameUnits::Rows => write!(f, "ROWS"),
+            WindowFrameUnits::Range => write!(f, "RANGE"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WindowFrameBound {
+    /// `<N> PRECEDING` or `UNBOUNDED PRECEDING`
+    Preceding(Option<u64>),
+    CurrentRow,
+    /// `<N> FOLLOWING` or `UNBOUNDED FOLLOWING`
+    Following(Option<u64>),
+}
+
+impl std::fmt::Display for WindowFrameBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowFrameBound::Preceding(None) => write!(f, "UNBOUNDED PRECEDING"),
+            WindowFrameBound::Preceding(Some(n)) => write!(f, "{n} PRECEDING"),
+            WindowFrameBound::CurrentRow => write!(f, "CURRENT ROW"),
+            WindowFrameBound::Following(None) => write!(f, "UNBOUNDED FOLLOWING"),
+            WindowFrameBound::Following(Some(n)) => write!(f, "{n} FOLLOWING"),
+        }
+    }
+}
@@ -1,9 +1,11 @@
 mod avg;
 mod count;
+mod sum;
 
 pub use avg::AvgAccumulator;
 pub use count::CountAccumulator;
 use std::fmt::Debug;
+pub use sum::SumAccumulator;
 
 use crate::common::ScalarValue;
 use crate::BustubxResult;
@@ -13,6 +15,7 @@ use strum::{EnumIter, IntoEnumIterator};
 pub enum AggregateFunctionKind {
     Count,
     Avg,
+    Sum,
 }
 
 impl AggregateFunctionKind {
@@ -20,6 +23,7 @@ impl AggregateFunctionKind {
         match self {
             AggregateFunctionKind::Count => Box::new(CountAccumulator::new()),
             AggregateFunctionKind::Avg => Box::new(AvgAccumulator::new()),
+            AggregateFunctionKind::Sum => Box::new(SumAccumulator::new()),
         }
     }
 
@@ -0,0 +1,66 @@
+use crate::catalog::DataType;
+use crate::common::decimal::{self, MAX_DECIMAL_PRECISION};
+use crate::common::ScalarValue;
+use crate::function::Accumulator;
+use crate::{BustubxError, BustubxResult};
+
+#[derive(Debug, Clone)]
+pub struct SumAccumulator {
+    sum: Option<ScalarValue>,
+}
+
+impl SumAccumulator {
+    pub fn new() -> Self {
+        Self { sum: None }
+    }
+}
+
+impl Accumulator for SumAccumulator {
+    fn update_value(&mut self, value: &ScalarValue) -> BustubxResult<()> {
+        if value.is_null() {
+            return Ok(());
+        }
+        let sum = match (&self.sum, value) {
+            (None, ScalarValue::Float32(_) | ScalarValue::Float64(_)) => {
+                ScalarValue::Float64(value.as_f64()?)
+            }
+            (None, ScalarValue::Decimal(v, _, scale)) => {
+                ScalarValue::Decimal(*v, MAX_DECIMAL_PRECISION, *scale)
+            }
+            (None, _) => ScalarValue::Int64(value.as_i64()?),
+            (Some(ScalarValue::Float64(Some(sum))), _) => {
+                ScalarValue::Float64(value.as_f64()?.map(|v| sum + v))
+            }
+            (Some(ScalarValue::Decimal(Some(sum), precision, scale)), _) => {
+                let v = value.cast_to(&DataType::Decimal(*precision, *scale))?;
+                let sum = match v {
+                    ScalarValue::Decimal(Some(v), _, _) => sum.checked_add(v),
+                    _ => None,
+                }
+                .filter(|sum| decimal::validate_precision(*sum, *precision).is_ok())
+                .ok_or_else(|| {
+                    BustubxError::Execution(format!("sum overflow when adding {}", value))
+                })?;
+                ScalarValue::Decimal(Some(sum), *precision, *scale)
+            }
+            (Some(ScalarValue::Int64(Some(sum))), _) => {
+                let v = value.as_i64()?.unwrap_or_default();
+                ScalarValue::Int64(Some(sum.checked_add(v).ok_or_else(|| {
+                    BustubxError::Execution(format!("sum overflow when adding {}", value))
+                })?))
+            }
+            (Some(sum), _) => {
+                return Err(BustubxError::Internal(format!(
+                    "Unexpected sum state {:?}",
+                    sum
+                )))
+            }
+        };
+        self.sum = Some(sum);
+        Ok(())
+    }
+
+    fn evaluate(&self) -> BustubxResult<ScalarValue> {
+        Ok(self.sum.clone().unwrap_or(ScalarValue::Int64(None)))
+    }
+}
@@ -1,4 +1,7 @@
 mod aggregate;
 mod scalar;
+mod window;
 
 pub use aggregate::*;
+pub use scalar::*;
+pub use window::*;
@@ -0,0 +1,178 @@
+use crate::catalog::DataType;
+use crate::common::datetime::{
+    civil_from_days, days_from_civil, MICROS_PER_DAY, MICROS_PER_HOUR, MICROS_PER_MINUTE,
+    MICROS_PER_SECOND,
+};
+use crate::common::ScalarValue;
+use crate::{BustubxError, BustubxResult};
+
+fn field_name(field: &ScalarValue) -> BustubxResult<Option<String>> {
+    match field {
+        ScalarValue::Varchar(v) => Ok(v.as_ref().map(|v| v.to_lowercase())),
+        _ => Err(BustubxError::Execution(format!(
+            "date/time field should be a string instead of {:?}",
+            field
+        ))),
+    }
+}
+
+fn unsupported_unit(field: &str, source: &ScalarValue) -> BustubxError {
+    BustubxError::Execution(format!(
+        "unit \"{}\" not supported for type {}",
+        field,
+        source.data_type()
+    ))
+}
+
+/// `date_part('field', source)`, the result is NULL if either arg is NULL.
+pub fn date_part(field: &ScalarValue, source: &ScalarValue) -> BustubxResult<ScalarValue> {
+    let Some(field) = field_name(field)? else {
+        return Ok(ScalarValue::Float64(None));
+    };
+    let value = match source {
+        ScalarValue::Date(Some(days)) => {
+            timestamp_part(&field, *days as i64 * MICROS_PER_DAY, source)?
+        }
+        ScalarValue::Timestamp(Some(micros)) | ScalarValue::TimestampTz(Some(micros)) => {
+            timestamp_part(&field, *micros, source)?
+        }
+        ScalarValue::Time(Some(micros)) => match field.as_str() {
+            "epoch" => *micros as f64 / MICROS_PER_SECOND as f64,
+            _ => time_part(&field, *micros).ok_or_else(|| unsupported_unit(&field, source))?,
+        },
+        ScalarValue::Interval(Some(interval)) => match field.as_str() {
+            "millennium" => (interval.months / 12000) as f64,
+            "century" => (interval.months / 1200) as f64,
+            "decade" => (interval.months / 120) as f64,
+            "year" => (interval.months / 12) as f64,
+            "quarter" => (interval.months % 12 / 3 + 1) as f64,
+            "month" => (interval.months % 12) as f64,
+            "day" => interval.days as f64,
+            "epoch" => {
+                let years = (interval.months / 12) as f64;
+                let months = (interval.months % 12) as f64;
+                // a year is 365.25 days and a month is 30 days
+                years * 365.25 * 86400.0
+                    + months * 30.0 * 86400.0
+                    + interval.days as f64 * 86400.0
+                    + interval.micros as f64 / MICROS_PER_SECOND as f64
+            }
+            _ => time_part(&field, interval.micros)
+                .ok_or_else(|| unsupported_unit(&field, source))?,
+        },
+        _ if source.is_null() => return Ok(ScalarValue::Float64(None)),
+        _ => {
+            return Err(BustubxError::Execution(format!(
+                "date_part does not support {:?}",
+                source
+            )))
+        }
+    };
+    Ok(ScalarValue::Float64(Some(value)))
+}
+
+fn time_part(field: &str, micros: i64) -> Option<f64> {
+    match field {
+        "hour" => Some((micros / MICROS_PER_HOUR) as f64),
+        "minute" => Some((micros % MICROS_PER_HOUR / MICROS_PER_MINUTE) as f64),
+        "second" => Some((micros % MICROS_PER_MINUTE) as f64 / MICROS_PER_SECOND as f64),
+        "milliseconds" => Some((micros % MICROS_PER_MINUTE) as f64 / 1000.0),
+        "microseconds" => Some((micros % MICROS_PER_MINUTE) as f64),
+        _ => None,
+    }
+}
+
+fn timestamp_part(field: &str, micros: i64, source: &ScalarValue) -> BustubxResult<f64> {
+    let days = micros.div_euclid(MICROS_PER_DAY);
+    let time = micros.rem_euclid(MICROS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    // 1970-01-01 is a Thursday
+    let dow = (days + 4).rem_euclid(7);
+    let value = match field {
+        "millennium" => ((year - 1).div_euclid(1000) + 1) as f64,
+        "century" => ((year - 1).div_euclid(100) + 1) as f64,
+        "decade" => year.div_euclid(10) as f64,
+        "year" => year as f64,
+        "quarter" => ((month - 1) / 3 + 1) as f64,
+        "month" => month as f64,
+        "week" => {
+            // ISO 8601 week, the week containing the year's first Thursday is week 1
+            let thursday = days - (dow + 6) % 7 + 3;
+            let (iso_year, _, _) = civil_from_days(thursday);
+            ((thursday - days_from_civil(iso_year, 1, 1)) / 7 + 1) as f64
+        }
+        "day" => day as f64,
+        "dow" => dow as f64,
+        "isodow" => (if dow == 0 { 7 } else { dow }) as f64,
+        "doy" => (days - days_from_civil(year, 1, 1) + 1) as f64,
+        "epoch" => micros as f64 / MICROS_PER_SECOND as f64,
+        _ => time_part(field, time).ok_or_else(|| unsupported_unit(field, source))?,
+    };
+    Ok(value)
+}
+
+/// `date_trunc('field', source)`, truncates a timestamp to the precision of field.
+pub fn date_trunc(field: &ScalarValue, source: &ScalarValue) -> BustubxResult<ScalarValue> {
+    let (micros, with_time_zone) = match source {
+        ScalarValue::TimestampTz(v) => (*v, true),
+        ScalarValue::Date(_) | ScalarValue::Timestamp(_) | ScalarValue::Varchar(_) => {
+            match source.cast_to(&DataType::Timestamp)? {
+                ScalarValue::Timestamp(v) => (v, false),
+                _ => unreachable!(),
+            }
+        }
+        _ => {
+            return Err(BustubxError::Execution(format!(
+                "date_trunc does not support {:?}",
+                source
+            )))
+        }
+    };
+    let wrap = |v: Option<i64>| {
+        if with_time_zone {
+            ScalarValue::TimestampTz(v)
+        } else {
+            ScalarValue::Timestamp(v)
+        }
+    };
+    let (Some(field), Some(micros)) = (field_name(field)?, micros) else {
+        return Ok(wrap(None));
+    };
+
+    let days = micros.div_euclid(MICROS_PER_DAY);
+    let time = micros.rem_euclid(MICROS_PER_DAY);
+    let (year, month, _) = civil_from_days(days);
+    let truncate_time = |unit: i64| days * MICROS_PER_DAY + time / unit * unit;
+    let truncate_months = |months: i64| {
+        let total = year * 12 + month as i64 - 1;
+        let total = total - total.rem_euclid(months);
+        days_from_civil(total.div_euclid(12), (total.rem_euclid(12) + 1) as u32, 1) * MICROS_PER_DAY
+    };
+    let value = match field.as_str() {
+        "microseconds" => micros,
+        "milliseconds" => truncate_time(1000),
+        "second" => truncate_time(MICROS_PER_SECOND),
+        "minute" => truncate_time(MICROS_PER_MINUTE),
+        "hour" => truncate_time(MICROS_PER_HOUR),
+        "day" => truncate_time(MICROS_PER_DAY),
+        "week" => {
+            // weeks start on Monday, 1970-01-01 is a Thursday
+            let monday = days - (days + 3).rem_euclid(7);
+            monday * MICROS_PER_DAY
+        }
+        "month" => truncate_months(1),
+        "quarter" => truncate_months(3),
+        "year" => truncate_months(12),
+        "decade" => days_from_civil(year - year.rem_euclid(10), 1, 1) * MICROS_PER_DAY,
+        "century" => {
+            let first = (year - 1).div_euclid(100) * 100 + 1;
+            days_from_civil(first, 1, 1) * MICROS_PER_DAY
+        }
+        "millennium" => {
+            let first = (year - 1).div_euclid(1000) * 1000 + 1;
+            days_from_civil(first, 1, 1) * MICROS_PER_DAY
+        }
+        _ => return Err(unsupported_unit(&field, source)),
+    };
+    Ok(wrap(Some(value)))
+}
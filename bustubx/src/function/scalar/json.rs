@@ -0,0 +1,44 @@
+use crate::catalog::DataType;
+use crate::common::json::{parse_json_path, JsonValue};
+use crate::common::ScalarValue;
+use crate::{BustubxError, BustubxResult};
+
+fn as_json(value: &ScalarValue) -> BustubxResult<Option<JsonValue>> {
+    match value.cast_to(&DataType::Json)? {
+        ScalarValue::Json(v) => Ok(v),
+        _ => unreachable!(),
+    }
+}
+
+/// `json_extract(json, '$.a[0]')`, the value at a json path or NULL if nothing is found.
+pub fn json_extract(json: &ScalarValue, path: &ScalarValue) -> BustubxResult<ScalarValue> {
+    let (Some(json), ScalarValue::Varchar(Some(path))) = (as_json(json)?, path) else {
+        return Ok(ScalarValue::Json(None));
+    };
+    Ok(ScalarValue::Json(
+        json.get_path(&parse_json_path(path)?).cloned(),
+    ))
+}
+
+/// `json_array_length(json)`, the number of elements of a json array.
+pub fn json_array_length(json: &ScalarValue) -> BustubxResult<ScalarValue> {
+    match as_json(json)? {
+        None => Ok(ScalarValue::Int32(None)),
+        Some(JsonValue::Array(elements)) => i32::try_from(elements.len())
+            .map(|len| ScalarValue::Int32(Some(len)))
+            .map_err(|_| {
+                BustubxError::Execution(format!("length {} is out of range", elements.len()))
+            }),
+        Some(value) => Err(BustubxError::Execution(format!(
+            "cannot get array length of a {}",
+            value.type_name()
+        ))),
+    }
+}
+
+/// `json_typeof(json)`, the type name of the outermost json value.
+pub fn json_typeof(json: &ScalarValue) -> BustubxResult<ScalarValue> {
+    Ok(ScalarValue::Varchar(
+        as_json(json)?.map(|v| v.type_name().to_string()),
+    ))
+}
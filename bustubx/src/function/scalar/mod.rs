@@ -1 +1,141 @@
+mod datetime;
+mod json;
+mod string;
 
+use crate::catalog::DataType;
+use crate::common::ScalarValue;
+use crate::{BustubxError, BustubxResult};
+use strum::{EnumIter, IntoEnumIterator};
+
+#[derive(Clone, PartialEq, Eq, Debug, EnumIter)]
+pub enum ScalarFunctionKind {
+    /// `now()` or `current_timestamp`, the current time as timestamp with time zone
+    Now,
+    /// `current_date`
+    CurrentDate,
+    /// `date_part('field', source)` or `extract(field from source)`
+    DatePart,
+    /// `date_trunc('field', source)`
+    DateTrunc,
+    /// `length(string)` in characters or `length(bytes)` in bytes
+    Length,
+    /// `substr(source, start [, count])` or `substring(source from start [for count])`
+    Substr,
+    /// `repeat(string, count)`
+    Repeat,
+    /// `json_extract(json, '$.a[0]')`
+    JsonExtract,
+    /// `json_array_length(json)`
+    JsonArrayLength,
+    /// `json_typeof(json)`
+    JsonTypeof,
+}
+
+impl ScalarFunctionKind {
+    pub fn sql_names(&self) -> &'static [&'static str] {
+        match self {
+            ScalarFunctionKind::Now => &["now", "current_timestamp"],
+            ScalarFunctionKind::CurrentDate => &["current_date"],
+            ScalarFunctionKind::DatePart => &["date_part"],
+            ScalarFunctionKind::DateTrunc => &["date_trunc"],
+            ScalarFunctionKind::Length => &["length", "char_length", "octet_length"],
+            ScalarFunctionKind::Substr => &["substr", "substring"],
+            ScalarFunctionKind::Repeat => &["repeat"],
+            ScalarFunctionKind::JsonExtract => &["json_extract"],
+            ScalarFunctionKind::JsonArrayLength => &["json_array_length"],
+            ScalarFunctionKind::JsonTypeof => &["json_typeof"],
+        }
+    }
+
+    pub fn find(name: &str) -> Option<Self> {
+        ScalarFunctionKind::iter().find(|kind| {
+            kind.sql_names()
+                .iter()
+                .any(|sql_name| sql_name.eq_ignore_ascii_case(name))
+        })
+    }
+
+    pub fn return_type(&self, arg_types: &[DataType]) -> BustubxResult<DataType> {
+        let invalid_args = || {
+            BustubxError::Plan(format!(
+                "function {}({}) does not exist",
+                self,
+                arg_types
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        };
+        match (self, arg_types) {
+            (ScalarFunctionKind::Now, []) => Ok(DataType::TimestampTz),
+            (ScalarFunctionKind::CurrentDate, []) => Ok(DataType::Date),
+            (ScalarFunctionKind::DatePart, [DataType::Varchar(_), source])
+                if source.is_temporal() =>
+            {
+                Ok(DataType::Float64)
+            }
+            (ScalarFunctionKind::DateTrunc, [DataType::Varchar(_), source]) => match source {
+                DataType::Date | DataType::Timestamp | DataType::Varchar(_) => {
+                    Ok(DataType::Timestamp)
+                }
+                DataType::TimestampTz => Ok(DataType::TimestampTz),
+                _ => Err(invalid_args()),
+            },
+            (ScalarFunctionKind::Length, [DataType::Varchar(_) | DataType::Binary]) => {
+                Ok(DataType::Int32)
+            }
+            (ScalarFunctionKind::Substr, [source, start, count @ ..])
+                if matches!(source, DataType::Varchar(_) | DataType::Binary)
+                    && start.is_integer()
+                    && count.len() <= 1
+                    && count.iter().all(|t| t.is_integer()) =>
+            {
+                match source {
+                    DataType::Binary => Ok(DataType::Binary),
+                    _ => Ok(DataType::Varchar(None)),
+                }
+            }
+            (ScalarFunctionKind::Repeat, [DataType::Varchar(_), count]) if count.is_integer() => {
+                Ok(DataType::Varchar(None))
+            }
+            (
+                ScalarFunctionKind::JsonExtract,
+                [DataType::Json | DataType::Varchar(_), DataType::Varchar(_)],
+            ) => Ok(DataType::Json),
+            (ScalarFunctionKind::JsonArrayLength, [DataType::Json | DataType::Varchar(_)]) => {
+                Ok(DataType::Int32)
+            }
+            (ScalarFunctionKind::JsonTypeof, [DataType::Json | DataType::Varchar(_)]) => {
+                Ok(DataType::Varchar(None))
+            }
+            _ => Err(invalid_args()),
+        }
+    }
+
+    pub fn invoke(&self, args: &[ScalarValue]) -> BustubxResult<ScalarValue> {
+        match self {
+            ScalarFunctionKind::Now => Ok(ScalarValue::TimestampTz(Some(
+                crate::common::datetime::now_micros(),
+            ))),
+            ScalarFunctionKind::CurrentDate => {
+                ScalarValue::TimestampTz(Some(crate::common::datetime::now_micros()))
+                    .cast_to(&DataType::Date)
+            }
+            ScalarFunctionKind::DatePart => datetime::date_part(&args[0], &args[1]),
+            ScalarFunctionKind::DateTrunc => datetime::date_trunc(&args[0], &args[1]),
+            ScalarFunctionKind::Length => string::length(&args[0]),
+            ScalarFunctionKind::Substr => string::substr(&args[0], &args[1], args.get(2)),
+            ScalarFunctionKind::Repeat => string::repeat(&args[0], &args[1]),
+            ScalarFunctionKind::JsonExtract => json::json_extract(&args[0], &args[1]),
+            ScalarFunctionKind::JsonArrayLength => json::json_array_length(&args[0]),
+            ScalarFunctionKind::JsonTypeof => json::json_typeof(&args[0]),
+        }
+    }
+}
+
+impl std::fmt::Display for ScalarFunctionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.sql_names()[0])
+    }
+}
@@ -0,0 +1,83 @@
+use crate::common::ScalarValue;
+use crate::{BustubxError, BustubxResult};
+
+/// `length(source)`, characters of a string or bytes of a binary.
+pub fn length(source: &ScalarValue) -> BustubxResult<ScalarValue> {
+    let length = match source {
+        ScalarValue::Varchar(v) => v.as_ref().map(|v| v.chars().count()),
+        ScalarValue::Binary(v) => v.as_ref().map(|v| v.len()),
+        _ => {
+            return Err(BustubxError::Execution(format!(
+                "length does not support {:?}",
+                source
+            )))
+        }
+    };
+    length
+        .map(|length| {
+            i32::try_from(length)
+                .map_err(|_| BustubxError::Execution(format!("length {} is out of range", length)))
+        })
+        .transpose()
+        .map(ScalarValue::Int32)
+}
+
+/// `substr(source, start [, count])`, positions start from 1 and `start` may be less
+/// than 1, in which case fewer than `count` elements are returned.
+pub fn substr(
+    source: &ScalarValue,
+    start: &ScalarValue,
+    count: Option<&ScalarValue>,
+) -> BustubxResult<ScalarValue> {
+    let null = ScalarValue::new_empty(source.data_type());
+    let Some(start) = start.as_i64()? else {
+        return Ok(null);
+    };
+    let end = match count.map(|count| count.as_i64()).transpose()? {
+        Some(None) => return Ok(null),
+        Some(Some(count)) if count < 0 => {
+            return Err(BustubxError::Execution(
+                "negative substring length not allowed".to_string(),
+            ))
+        }
+        Some(Some(count)) => Some(start.saturating_add(count)),
+        None => None,
+    };
+    // zero based range of the elements to keep
+    let range = |len: usize| {
+        let from = (start.max(1) - 1).min(len as i64) as usize;
+        let to = end.map_or(len, |end| {
+            (end.max(1) - 1).clamp(from as i64, len as i64) as usize
+        });
+        from..to
+    };
+    match source {
+        ScalarValue::Varchar(Some(v)) => {
+            let chars = v.chars().collect::<Vec<char>>();
+            Ok(ScalarValue::Varchar(Some(
+                chars[range(chars.len())].iter().collect(),
+            )))
+        }
+        ScalarValue::Binary(Some(v)) => Ok(ScalarValue::Binary(Some(v[range(v.len())].to_vec()))),
+        ScalarValue::Varchar(None) | ScalarValue::Binary(None) => Ok(null),
+        _ => Err(BustubxError::Execution(format!(
+            "substr does not support {:?}",
+            source
+        ))),
+    }
+}
+
+/// `repeat(string, count)`, the string repeated `count` times, empty when `count` is not
+/// positive.
+pub fn repeat(source: &ScalarValue, count: &ScalarValue) -> BustubxResult<ScalarValue> {
+    let (ScalarValue::Varchar(Some(v)), Some(count)) = (source, count.as_i64()?) else {
+        return Ok(ScalarValue::Varchar(None));
+    };
+    let count = count.max(0) as usize;
+    if v.len().saturating_mul(count) > u32::MAX as usize {
+        return Err(BustubxError::Execution(
+            "requested length too large".to_string(),
+        ));
+    }
+    Ok(ScalarValue::Varchar(Some(v.repeat(count))))
+}
@@ -0,0 +1,55 @@
+use crate::function::AggregateFunctionKind;
+use strum::{EnumIter, IntoEnumIterator};
+
+#[derive(Clone, PartialEq, Eq, Debug, EnumIter)]
+pub enum BuiltInWindowFunctionKind {
+    RowNumber,
+    Rank,
+    DenseRank,
+    Lag,
+    Lead,
+    FirstValue,
+    LastValue,
+}
+
+impl BuiltInWindowFunctionKind {
+    pub fn sql_name(&self) -> &'static str {
+        match self {
+            BuiltInWindowFunctionKind::RowNumber => "row_number",
+            BuiltInWindowFunctionKind::Rank => "rank",
+            BuiltInWindowFunctionKind::DenseRank => "dense_rank",
+            BuiltInWindowFunctionKind::Lag => "lag",
+            BuiltInWindowFunctionKind::Lead => "lead",
+            BuiltInWindowFunctionKind::FirstValue => "first_value",
+            BuiltInWindowFunctionKind::LastValue => "last_value",
+        }
+    }
+
+    pub fn find(name: &str) -> Option<Self> {
+        BuiltInWindowFunctionKind::iter().find(|kind| kind.sql_name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// A function which can be used with an OVER clause
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum WindowFunctionKind {
+    BuiltIn(BuiltInWindowFunctionKind),
+    Aggregate(AggregateFunctionKind),
+}
+
+impl WindowFunctionKind {
+    pub fn find(name: &str) -> Option<Self> {
+        BuiltInWindowFunctionKind::find(name)
+            .map(WindowFunctionKind::BuiltIn)
+            .or_else(|| AggregateFunctionKind::find(name).map(WindowFunctionKind::Aggregate))
+    }
+}
+
+impl std::fmt::Display for WindowFunctionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowFunctionKind::BuiltIn(kind) => write!(f, "{kind:?}"),
+            WindowFunctionKind::Aggregate(kind) => write!(f, "{kind}"),
+        }
+    }
+}
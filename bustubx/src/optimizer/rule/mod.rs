@@ -1,7 +1,11 @@
 mod eliminate_limit;
 mod merge_limit;
+mod push_down_filter;
 mod push_down_limit;
+mod push_down_projection;
 
 pub use eliminate_limit::EliminateLimit;
 pub use merge_limit::MergeLimit;
+pub use push_down_filter::PushDownFilter;
 pub use push_down_limit::PushDownLimit;
+pub use push_down_projection::PushDownProjection;
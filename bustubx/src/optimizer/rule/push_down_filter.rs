@@ -0,0 +1,79 @@
+use crate::error::BustubxResult;
+use crate::expression::split_conjunction;
+use crate::optimizer::logical_optimizer::ApplyOrder;
+use crate::optimizer::LogicalOptimizerRule;
+use crate::planner::logical_plan::{LogicalPlan, TableScan};
+
+/// Copies the predicates of a filter into the table scan below it. The scan may use
+/// them to read less, e.g. a range of an index, the filter still applies them.
+pub struct PushDownFilter;
+
+impl LogicalOptimizerRule for PushDownFilter {
+    fn try_optimize(&self, plan: &LogicalPlan) -> BustubxResult<Option<LogicalPlan>> {
+        let LogicalPlan::Filter(filter) = plan else {
+            return Ok(None);
+        };
+        let LogicalPlan::TableScan(scan) = filter.input.as_ref() else {
+            return Ok(None);
+        };
+
+        let new_filters = split_conjunction(&filter.predicate)
+            .into_iter()
+            .filter(|expr| !scan.filters.contains(expr))
+            .cloned()
+            .collect::<Vec<_>>();
+        if new_filters.is_empty() {
+            return Ok(None);
+        }
+        let mut filters = scan.filters.clone();
+        filters.extend(new_filters);
+        let new_scan = LogicalPlan::TableScan(TableScan {
+            filters,
+            ..scan.clone()
+        });
+        plan.with_new_inputs(&[new_scan]).map(Some)
+    }
+
+    fn name(&self) -> &str {
+        "PushDownFilter"
+    }
+
+    fn apply_order(&self) -> Option<ApplyOrder> {
+        Some(ApplyOrder::TopDown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::optimizer::rule::PushDownFilter;
+    use crate::optimizer::LogicalOptimizer;
+    use crate::planner::logical_plan::LogicalPlan;
+    use crate::Database;
+    use std::sync::Arc;
+
+    fn build_optimizer() -> LogicalOptimizer {
+        LogicalOptimizer::with_rules(vec![Arc::new(PushDownFilter)])
+    }
+
+    #[test]
+    fn push_down_filter() {
+        let mut db = Database::new_temp().unwrap();
+        db.run("create table t1 (a int, b int)").unwrap();
+
+        let plan = db
+            .create_logical_plan("select a from t1 where a = 1 and b > 2")
+            .unwrap();
+        let optimized_plan = build_optimizer().optimize(&plan).unwrap();
+
+        let LogicalPlan::Project(project) = optimized_plan else {
+            panic!("the first node should be project");
+        };
+        let LogicalPlan::Filter(filter) = project.input.as_ref() else {
+            panic!("the second node should be filter");
+        };
+        let LogicalPlan::TableScan(scan) = filter.input.as_ref() else {
+            panic!("the third node should be table scan");
+        };
+        assert_eq!(scan.filters.len(), 2);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::error::BustubxResult;
+use crate::expression::{find_column_exprs, ColumnExpr};
+use crate::optimizer::logical_optimizer::ApplyOrder;
+use crate::optimizer::LogicalOptimizerRule;
+use crate::planner::logical_plan::{LogicalPlan, TableScan};
+use std::sync::Arc;
+
+/// Narrows a table scan below a projection or aggregate, possibly through filters, to the
+/// columns they reference. A scan may then read its columns from an index alone.
+pub struct PushDownProjection;
+
+impl LogicalOptimizerRule for PushDownProjection {
+    fn try_optimize(&self, plan: &LogicalPlan) -> BustubxResult<Option<LogicalPlan>> {
+        let exprs = match plan {
+            LogicalPlan::Project(project) => project.exprs.clone(),
+            LogicalPlan::Aggregate(aggregate) => {
+                let mut exprs = aggregate.group_exprs.clone();
+                exprs.extend(aggregate.aggr_exprs.clone());
+                exprs
+            }
+            _ => return Ok(None),
+        };
+        let Some(input) = plan.inputs().first().copied() else {
+            return Ok(None);
+        };
+        let mut columns = exprs
+            .iter()
+            .flat_map(find_column_exprs)
+            .collect::<Vec<ColumnExpr>>();
+        let Some(new_input) = push_down(input, &mut columns)? else {
+            return Ok(None);
+        };
+        plan.with_new_inputs(&[new_input]).map(Some)
+    }
+
+    fn name(&self) -> &str {
+        "PushDownProjection"
+    }
+
+    fn apply_order(&self) -> Option<ApplyOrder> {
+        Some(ApplyOrder::TopDown)
+    }
+}
+
+/// Rebuilds the filters down to the table scan, which outputs only `columns` and the
+/// columns of the filters.
+fn push_down(
+    plan: &LogicalPlan,
+    columns: &mut Vec<ColumnExpr>,
+) -> BustubxResult<Option<LogicalPlan>> {
+    match plan {
+        LogicalPlan::Filter(filter) => {
+            columns.extend(find_column_exprs(&filter.predicate));
+            let Some(new_input) = push_down(&filter.input, columns)? else {
+                return Ok(None);
+            };
+            plan.with_new_inputs(&[new_input]).map(Some)
+        }
+        LogicalPlan::TableScan(scan) if scan.projection.is_none() => {
+            for filter in scan.filters.iter() {
+                columns.extend(find_column_exprs(filter));
+            }
+            let mut projection = vec![];
+            for column in columns.iter() {
+                let Ok(idx) = scan
+                    .table_schema
+                    .index_of(column.relation.as_ref(), &column.name)
+                else {
+                    return Ok(None);
+                };
+                projection.push(idx);
+            }
+            projection.sort();
+            projection.dedup();
+            if projection.len() == scan.table_schema.column_count() {
+                return Ok(None);
+            }
+            let projected_schema = Arc::new(scan.table_schema.project(&projection)?);
+            Ok(Some(LogicalPlan::TableScan(TableScan {
+                projection: Some(projection),
+                projected_schema,
+                ..scan.clone()
+            })))
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::optimizer::rule::PushDownProjection;
+    use crate::optimizer::LogicalOptimizer;
+    use crate::planner::logical_plan::LogicalPlan;
+    use crate::Database;
+    use std::sync::Arc;
+
+    fn build_optimizer() -> LogicalOptimizer {
+        LogicalOptimizer::with_rules(vec![Arc::new(PushDownProjection)])
+    }
+
+    #[test]
+    fn push_down_projection() {
+        let mut db = Database::new_temp().unwrap();
+        db.run("create table t1 (a int, b int, c int)").unwrap();
+
+        let plan = db
+            .create_logical_plan("select a from t1 where c > 2")
+            .unwrap();
+        let optimized_plan = build_optimizer().optimize(&plan).unwrap();
+
+        let LogicalPlan::Project(project) = optimized_plan else {
+            panic!("the first node should be project");
+        };
+        let LogicalPlan::Filter(filter) = project.input.as_ref() else {
+            panic!("the second node should be filter");
+        };
+        let LogicalPlan::TableScan(scan) = filter.input.as_ref() else {
+            panic!("the third node should be table scan");
+        };
+        assert_eq!(scan.projection, Some(vec![0, 2]));
+        assert_eq!(scan.projected_schema.column_count(), 2);
+    }
+}
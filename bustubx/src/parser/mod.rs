@@ -1,11 +1,76 @@
 use crate::error::BustubxResult;
-use sqlparser::{ast::Statement, dialect::PostgreSqlDialect, parser::Parser};
+use sqlparser::{
+    ast::{Expr, Ident, ObjectName, Statement},
+    dialect::PostgreSqlDialect,
+    keywords::Keyword,
+    parser::Parser,
+    tokenizer::Token,
+};
 
 pub fn parse_sql(sql: &str) -> BustubxResult<Vec<Statement>> {
     let stmts = Parser::parse_sql(&PostgreSqlDialect {}, sql)?;
     Ok(stmts)
 }
 
+/// Parses a single expression, like a persisted column default.
+pub fn parse_expr(sql: &str) -> BustubxResult<Expr> {
+    let expr = Parser::new(&PostgreSqlDialect {})
+        .try_with_sql(sql)?
+        .parse_expr()?;
+    Ok(expr)
+}
+
+/// Statements sqlparser doesn't support yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtensionStatement {
+    /// `REFRESH MATERIALIZED VIEW name`
+    RefreshMaterializedView { name: ObjectName },
+    /// `ATTACH [DATABASE] 'path' AS name`
+    Attach { path: String, name: Ident },
+    /// `DETACH [DATABASE] name`
+    Detach { name: Ident },
+}
+
+/// Parses an [`ExtensionStatement`], returns `None` for any other statement.
+pub fn parse_extension_statement(sql: &str) -> BustubxResult<Option<ExtensionStatement>> {
+    let mut parser = Parser::new(&PostgreSqlDialect {}).try_with_sql(sql)?;
+    let Token::Word(word) = parser.peek_token().token else {
+        return Ok(None);
+    };
+    let stmt = match word.value.to_lowercase().as_str() {
+        "refresh" => {
+            parser.next_token();
+            parser.expect_keywords(&[Keyword::MATERIALIZED, Keyword::VIEW])?;
+            ExtensionStatement::RefreshMaterializedView {
+                name: parser.parse_object_name()?,
+            }
+        }
+        "attach" => {
+            parser.next_token();
+            let _ = parser.parse_keyword(Keyword::DATABASE);
+            let path = parser.parse_literal_string()?;
+            parser.expect_keyword(Keyword::AS)?;
+            ExtensionStatement::Attach {
+                path,
+                name: parser.parse_identifier()?,
+            }
+        }
+        "detach" => {
+            parser.next_token();
+            let _ = parser.parse_keyword(Keyword::DATABASE);
+            ExtensionStatement::Detach {
+                name: parser.parse_identifier()?,
+            }
+        }
+        _ => return Ok(None),
+    };
+    while parser.consume_token(&Token::SemiColon) {}
+    if parser.peek_token().token != Token::EOF {
+        return Ok(parser.expected("end of statement", parser.peek_token())?);
+    }
+    Ok(Some(stmt))
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -15,4 +80,33 @@ mod tests {
         let stmts = super::parse_sql(sql).unwrap();
         println!("{:#?}", stmts[0]);
     }
+
+    #[test]
+    pub fn test_parse_extension_statement() {
+        use super::{parse_extension_statement, ExtensionStatement};
+        use sqlparser::ast::{Ident, ObjectName};
+
+        assert_eq!(
+            parse_extension_statement("refresh materialized view s1.mv1;").unwrap(),
+            Some(ExtensionStatement::RefreshMaterializedView {
+                name: ObjectName(vec![Ident::new("s1"), Ident::new("mv1")]),
+            })
+        );
+        assert_eq!(
+            parse_extension_statement("ATTACH DATABASE '/tmp/a.db' AS other").unwrap(),
+            Some(ExtensionStatement::Attach {
+                path: "/tmp/a.db".to_string(),
+                name: Ident::new("other"),
+            })
+        );
+        assert_eq!(
+            parse_extension_statement("detach other").unwrap(),
+            Some(ExtensionStatement::Detach {
+                name: Ident::new("other"),
+            })
+        );
+        assert!(parse_extension_statement("select 1").unwrap().is_none());
+        assert!(parse_extension_statement("refresh materialized view mv1 mv2").is_err());
+        assert!(parse_extension_statement("attach other").is_err());
+    }
 }
@@ -0,0 +1,11 @@
+#[derive(derive_new::new, Debug, Clone)]
+pub struct AttachDatabase {
+    pub path: String,
+    pub name: String,
+}
+
+impl std::fmt::Display for AttachDatabase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AttachDatabase: {} AS {}", self.path, self.name)
+    }
+}
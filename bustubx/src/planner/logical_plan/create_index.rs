@@ -1,4 +1,4 @@
-use crate::catalog::SchemaRef;
+use crate::catalog::{IndexType, SchemaRef};
 use crate::common::TableReference;
 use crate::planner::logical_plan::OrderByExpr;
 
@@ -8,6 +8,7 @@ pub struct CreateIndex {
     pub table: TableReference,
     pub table_schema: SchemaRef,
     pub columns: Vec<OrderByExpr>,
+    pub index_type: IndexType,
 }
 
 impl std::fmt::Display for CreateIndex {
@@ -0,0 +1,11 @@
+#[derive(derive_new::new, Debug, Clone)]
+pub struct CreateSchema {
+    pub name: String,
+    pub if_not_exists: bool,
+}
+
+impl std::fmt::Display for CreateSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CreateSchema: {}", self.name)
+    }
+}
@@ -0,0 +1,14 @@
+use crate::catalog::ResolvedSequenceOptions;
+use crate::common::TableReference;
+
+#[derive(derive_new::new, Debug, Clone)]
+pub struct CreateSequence {
+    pub name: TableReference,
+    pub options: ResolvedSequenceOptions,
+}
+
+impl std::fmt::Display for CreateSequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CreateSequence: {}", self.name)
+    }
+}
@@ -1,10 +1,20 @@
-use crate::catalog::Column;
+use crate::catalog::{Column, Constraint, ResolvedSequenceOptions};
 use crate::common::TableReference;
 
 #[derive(Debug, Clone)]
 pub struct CreateTable {
     pub name: TableReference,
     pub columns: Vec<Column>,
+    pub constraints: Vec<Constraint>,
+    pub sequences: Vec<ColumnSequence>,
+}
+
+/// A sequence created along with the table, the column defaults to its next value.
+#[derive(Debug, Clone)]
+pub struct ColumnSequence {
+    pub column: String,
+    pub sequence: TableReference,
+    pub options: ResolvedSequenceOptions,
 }
 
 impl std::fmt::Display for CreateTable {
@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use crate::common::TableReference;
+use crate::planner::logical_plan::LogicalPlan;
+
+#[derive(derive_new::new, Debug, Clone)]
+pub struct CreateView {
+    pub name: TableReference,
+    /// SQL text of the view query, expanded again whenever the view is referenced
+    pub definition: String,
+    pub columns: Vec<String>,
+    pub materialized: bool,
+    /// Plan of the view query, it populates a materialized view
+    pub input: Arc<LogicalPlan>,
+}
+
+impl std::fmt::Display for CreateView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.materialized {
+            write!(f, "CreateMaterializedView: {}", self.name)
+        } else {
+            write!(f, "CreateView: {}", self.name)
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use crate::planner::logical_plan::LogicalPlan;
+use std::sync::Arc;
+
+/// Scan of a common table expression which is referenced more than once,
+/// all scans sharing the same `cte_id` read one materialized result.
+#[derive(derive_new::new, Debug, Clone)]
+pub struct CteScan {
+    pub cte_id: usize,
+    pub name: String,
+    pub input: Arc<LogicalPlan>,
+}
+
+impl std::fmt::Display for CteScan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CteScan: {}", self.name)
+    }
+}
@@ -0,0 +1,18 @@
+use crate::catalog::SchemaRef;
+use crate::common::TableReference;
+use crate::expression::Expr;
+use crate::planner::logical_plan::Returning;
+
+#[derive(derive_new::new, Debug, Clone)]
+pub struct Delete {
+    pub table: TableReference,
+    pub table_schema: SchemaRef,
+    pub selection: Option<Expr>,
+    pub returning: Option<Returning>,
+}
+
+impl std::fmt::Display for Delete {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Delete: {}", self.table)
+    }
+}
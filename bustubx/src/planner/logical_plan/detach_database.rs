@@ -0,0 +1,10 @@
+#[derive(derive_new::new, Debug, Clone)]
+pub struct DetachDatabase {
+    pub name: String,
+}
+
+impl std::fmt::Display for DetachDatabase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DetachDatabase: {}", self.name)
+    }
+}
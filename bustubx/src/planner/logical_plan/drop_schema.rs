@@ -0,0 +1,12 @@
+#[derive(derive_new::new, Debug, Clone)]
+pub struct DropSchema {
+    pub names: Vec<String>,
+    pub if_exists: bool,
+    pub cascade: bool,
+}
+
+impl std::fmt::Display for DropSchema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DropSchema: {}", self.names.join(", "))
+    }
+}
@@ -0,0 +1,19 @@
+use crate::catalog::SchemaRef;
+use crate::planner::logical_plan::LogicalPlan;
+use std::sync::Arc;
+
+/// `EXCEPT` or `EXCEPT ALL` of two inputs with compatible schemas
+#[derive(derive_new::new, Debug, Clone)]
+pub struct Except {
+    pub left: Arc<LogicalPlan>,
+    pub right: Arc<LogicalPlan>,
+    /// Whether to keep duplicate rows
+    pub is_all: bool,
+    pub schema: SchemaRef,
+}
+
+impl std::fmt::Display for Except {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Except: is_all={}", self.is_all)
+    }
+}
@@ -1,6 +1,8 @@
-use crate::catalog::SchemaRef;
+use crate::catalog::{Constraint, SchemaRef};
 use crate::common::TableReference;
-use crate::planner::logical_plan::LogicalPlan;
+use crate::expression::Expr;
+use crate::planner::logical_plan::{LogicalPlan, Returning};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[derive(derive_new::new, Debug, Clone)]
@@ -9,6 +11,26 @@ pub struct Insert {
     pub table_schema: SchemaRef,
     pub projected_schema: SchemaRef,
     pub input: Arc<LogicalPlan>,
+    pub on_conflict: Option<OnConflict>,
+    pub returning: Option<Returning>,
+}
+
+/// `ON CONFLICT` of an insert, resolved through the indexes of unique constraints.
+#[derive(Debug, Clone)]
+pub struct OnConflict {
+    /// The unique constraints whose conflicts are handled
+    pub constraints: Vec<Constraint>,
+    pub action: OnConflictAction,
+}
+
+#[derive(Debug, Clone)]
+pub enum OnConflictAction {
+    DoNothing,
+    /// Updates the conflicting row, the proposed row is visible as `excluded`
+    DoUpdate {
+        assignments: HashMap<String, Expr>,
+        selection: Option<Expr>,
+    },
 }
 
 impl std::fmt::Display for Insert {
@@ -0,0 +1,19 @@
+use crate::catalog::SchemaRef;
+use crate::planner::logical_plan::LogicalPlan;
+use std::sync::Arc;
+
+/// `INTERSECT` or `INTERSECT ALL` of two inputs with compatible schemas
+#[derive(derive_new::new, Debug, Clone)]
+pub struct Intersect {
+    pub left: Arc<LogicalPlan>,
+    pub right: Arc<LogicalPlan>,
+    /// Whether to keep duplicate rows
+    pub is_all: bool,
+    pub schema: SchemaRef,
+}
+
+impl std::fmt::Display for Intersect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Intersect: is_all={}", self.is_all)
+    }
+}
@@ -1,43 +1,86 @@
 mod aggregate;
+mod attach_database;
 mod create_index;
+mod create_schema;
+mod create_sequence;
 mod create_table;
+mod create_view;
+mod cte_scan;
+mod delete;
+mod detach_database;
+mod drop_schema;
 mod empty_relation;
+mod except;
 mod filter;
 mod insert;
+mod intersect;
 mod join;
 mod limit;
 mod project;
+mod recursive_query;
+mod refresh_materialized_view;
+mod returning;
+mod set_variable;
 mod sort;
 mod table_scan;
+mod union;
 mod update;
 mod util;
 mod values;
+mod window;
+mod work_table_scan;
 
 pub use aggregate::Aggregate;
+pub use attach_database::AttachDatabase;
 pub use create_index::CreateIndex;
-pub use create_table::CreateTable;
+pub use create_schema::CreateSchema;
+pub use create_sequence::CreateSequence;
+pub use create_table::{ColumnSequence, CreateTable};
+pub use create_view::CreateView;
+pub use cte_scan::CteScan;
+pub use delete::Delete;
+pub use detach_database::DetachDatabase;
+pub use drop_schema::DropSchema;
 pub use empty_relation::EmptyRelation;
+pub use except::Except;
 pub use filter::Filter;
-pub use insert::Insert;
+pub use insert::{Insert, OnConflict, OnConflictAction};
+pub use intersect::Intersect;
 pub use join::{Join, JoinType};
 pub use limit::Limit;
 pub use project::Project;
+pub use recursive_query::RecursiveQuery;
+pub use refresh_materialized_view::RefreshMaterializedView;
+pub use returning::Returning;
+pub use set_variable::SetVariable;
 pub use sort::{OrderByExpr, Sort};
 pub use table_scan::TableScan;
+pub use union::Union;
 pub use update::Update;
 pub use util::*;
 pub use values::Values;
+pub use window::Window;
+pub use work_table_scan::WorkTableScan;
 
 use crate::catalog::{
-    SchemaRef, EMPTY_SCHEMA_REF, INSERT_OUTPUT_SCHEMA_REF, UPDATE_OUTPUT_SCHEMA_REF,
+    SchemaRef, DELETE_OUTPUT_SCHEMA_REF, EMPTY_SCHEMA_REF, INSERT_OUTPUT_SCHEMA_REF,
+    UPDATE_OUTPUT_SCHEMA_REF,
 };
 use crate::{BustubxError, BustubxResult};
 use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub enum LogicalPlan {
+    CreateSchema(CreateSchema),
+    DropSchema(DropSchema),
+    SetVariable(SetVariable),
+    AttachDatabase(AttachDatabase),
+    DetachDatabase(DetachDatabase),
     CreateTable(CreateTable),
     CreateIndex(CreateIndex),
+    CreateSequence(CreateSequence),
+    CreateView(CreateView),
+    RefreshMaterializedView(RefreshMaterializedView),
     Filter(Filter),
     Insert(Insert),
     Join(Join),
@@ -49,24 +92,62 @@ pub enum LogicalPlan {
     EmptyRelation(EmptyRelation),
     Aggregate(Aggregate),
     Update(Update),
+    Delete(Delete),
+    CteScan(CteScan),
+    RecursiveQuery(RecursiveQuery),
+    WorkTableScan(WorkTableScan),
+    Union(Union),
+    Intersect(Intersect),
+    Except(Except),
+    Window(Window),
 }
 
 impl LogicalPlan {
     pub fn schema(&self) -> &SchemaRef {
         match self {
+            LogicalPlan::CreateSchema(_) => &EMPTY_SCHEMA_REF,
+            LogicalPlan::DropSchema(_) => &EMPTY_SCHEMA_REF,
+            LogicalPlan::SetVariable(_) => &EMPTY_SCHEMA_REF,
+            LogicalPlan::AttachDatabase(_) => &EMPTY_SCHEMA_REF,
+            LogicalPlan::DetachDatabase(_) => &EMPTY_SCHEMA_REF,
             LogicalPlan::CreateTable(_) => &EMPTY_SCHEMA_REF,
             LogicalPlan::CreateIndex(_) => &EMPTY_SCHEMA_REF,
+            LogicalPlan::CreateSequence(_) => &EMPTY_SCHEMA_REF,
+            LogicalPlan::CreateView(_) => &EMPTY_SCHEMA_REF,
+            LogicalPlan::RefreshMaterializedView(_) => &EMPTY_SCHEMA_REF,
             LogicalPlan::Filter(Filter { input, .. }) => input.schema(),
+            LogicalPlan::Insert(Insert {
+                returning: Some(returning),
+                ..
+            })
+            | LogicalPlan::Update(Update {
+                returning: Some(returning),
+                ..
+            })
+            | LogicalPlan::Delete(Delete {
+                returning: Some(returning),
+                ..
+            }) => &returning.schema,
             LogicalPlan::Insert(_) => &INSERT_OUTPUT_SCHEMA_REF,
             LogicalPlan::Join(Join { schema, .. }) => schema,
             LogicalPlan::Limit(Limit { input, .. }) => input.schema(),
             LogicalPlan::Project(Project { schema, .. }) => schema,
-            LogicalPlan::TableScan(TableScan { table_schema, .. }) => table_schema,
+            LogicalPlan::TableScan(TableScan {
+                projected_schema, ..
+            }) => projected_schema,
             LogicalPlan::Sort(Sort { input, .. }) => input.schema(),
             LogicalPlan::Values(Values { schema, .. }) => schema,
             LogicalPlan::EmptyRelation(EmptyRelation { schema, .. }) => schema,
             LogicalPlan::Aggregate(Aggregate { schema, .. }) => schema,
             LogicalPlan::Update(_) => &UPDATE_OUTPUT_SCHEMA_REF,
+            LogicalPlan::Delete(_) => &DELETE_OUTPUT_SCHEMA_REF,
+            LogicalPlan::CteScan(CteScan { input, .. }) => input.schema(),
+            LogicalPlan::RecursiveQuery(RecursiveQuery { static_term, .. }) => static_term.schema(),
+            LogicalPlan::WorkTableScan(WorkTableScan { schema, .. }) => schema,
+            LogicalPlan::Union(Union { schema, .. }) => schema,
+            LogicalPlan::Intersect(Intersect { schema, .. }) => schema,
+            LogicalPlan::Except(Except { schema, .. }) => schema,
+            LogicalPlan::Window(Window { schema, .. }) => schema,
         }
     }
 
@@ -79,11 +160,33 @@ impl LogicalPlan {
             LogicalPlan::Project(Project { input, .. }) => vec![input],
             LogicalPlan::Sort(Sort { input, .. }) => vec![input],
             LogicalPlan::Aggregate(Aggregate { input, .. }) => vec![input],
-            LogicalPlan::CreateTable(_)
+            LogicalPlan::CteScan(CteScan { input, .. }) => vec![input],
+            LogicalPlan::RecursiveQuery(RecursiveQuery {
+                static_term,
+                recursive_term,
+                ..
+            }) => vec![static_term, recursive_term],
+            LogicalPlan::Union(Union { left, right, .. }) => vec![left, right],
+            LogicalPlan::Intersect(Intersect { left, right, .. }) => vec![left, right],
+            LogicalPlan::Except(Except { left, right, .. }) => vec![left, right],
+            LogicalPlan::Window(Window { input, .. }) => vec![input],
+            LogicalPlan::CreateView(CreateView { input, .. }) => vec![input],
+            LogicalPlan::RefreshMaterializedView(RefreshMaterializedView { input, .. }) => {
+                vec![input]
+            }
+            LogicalPlan::CreateSchema(_)
+            | LogicalPlan::DropSchema(_)
+            | LogicalPlan::SetVariable(_)
+            | LogicalPlan::AttachDatabase(_)
+            | LogicalPlan::DetachDatabase(_)
+            | LogicalPlan::CreateTable(_)
             | LogicalPlan::CreateIndex(_)
+            | LogicalPlan::CreateSequence(_)
             | LogicalPlan::TableScan(_)
             | LogicalPlan::Values(_)
             | LogicalPlan::Update(_)
+            | LogicalPlan::Delete(_)
+            | LogicalPlan::WorkTableScan(_)
             | LogicalPlan::EmptyRelation(_) => vec![],
         }
     }
@@ -108,11 +211,15 @@ impl LogicalPlan {
                 table,
                 table_schema,
                 projected_schema,
+                on_conflict,
+                returning,
                 ..
             }) => Ok(LogicalPlan::Insert(Insert {
                 table: table.clone(),
                 table_schema: table_schema.clone(),
                 projected_schema: projected_schema.clone(),
+                on_conflict: on_conflict.clone(),
+                returning: returning.clone(),
                 input: Arc::new(
                     inputs
                         .first()
@@ -144,7 +251,7 @@ impl LogicalPlan {
                 ),
                 right: Arc::new(
                     inputs
-                        .first()
+                        .get(1)
                         .ok_or_else(|| {
                             BustubxError::Internal(format!(
                                 "inputs {:?} should have at least two",
@@ -227,11 +334,202 @@ impl LogicalPlan {
                         .clone(),
                 ),
             })),
-            LogicalPlan::CreateTable(_)
+            LogicalPlan::CteScan(CteScan { cte_id, name, .. }) => {
+                Ok(LogicalPlan::CteScan(CteScan {
+                    cte_id: *cte_id,
+                    name: name.clone(),
+                    input: Arc::new(
+                        inputs
+                            .first()
+                            .ok_or_else(|| {
+                                BustubxError::Internal(format!(
+                                    "inputs {:?} should have at least one",
+                                    inputs
+                                ))
+                            })?
+                            .clone(),
+                    ),
+                }))
+            }
+            LogicalPlan::RecursiveQuery(RecursiveQuery {
+                name, is_distinct, ..
+            }) => Ok(LogicalPlan::RecursiveQuery(RecursiveQuery {
+                name: name.clone(),
+                static_term: Arc::new(
+                    inputs
+                        .first()
+                        .ok_or_else(|| {
+                            BustubxError::Internal(format!(
+                                "inputs {:?} should have at least two",
+                                inputs
+                            ))
+                        })?
+                        .clone(),
+                ),
+                recursive_term: Arc::new(
+                    inputs
+                        .get(1)
+                        .ok_or_else(|| {
+                            BustubxError::Internal(format!(
+                                "inputs {:?} should have at least two",
+                                inputs
+                            ))
+                        })?
+                        .clone(),
+                ),
+                is_distinct: *is_distinct,
+            })),
+            LogicalPlan::Union(Union { is_all, schema, .. }) => Ok(LogicalPlan::Union(Union {
+                left: Arc::new(
+                    inputs
+                        .first()
+                        .ok_or_else(|| {
+                            BustubxError::Internal(format!(
+                                "inputs {:?} should have at least two",
+                                inputs
+                            ))
+                        })?
+                        .clone(),
+                ),
+                right: Arc::new(
+                    inputs
+                        .get(1)
+                        .ok_or_else(|| {
+                            BustubxError::Internal(format!(
+                                "inputs {:?} should have at least two",
+                                inputs
+                            ))
+                        })?
+                        .clone(),
+                ),
+                is_all: *is_all,
+                schema: schema.clone(),
+            })),
+            LogicalPlan::Intersect(Intersect { is_all, schema, .. }) => {
+                Ok(LogicalPlan::Intersect(Intersect {
+                    left: Arc::new(
+                        inputs
+                            .first()
+                            .ok_or_else(|| {
+                                BustubxError::Internal(format!(
+                                    "inputs {:?} should have at least two",
+                                    inputs
+                                ))
+                            })?
+                            .clone(),
+                    ),
+                    right: Arc::new(
+                        inputs
+                            .get(1)
+                            .ok_or_else(|| {
+                                BustubxError::Internal(format!(
+                                    "inputs {:?} should have at least two",
+                                    inputs
+                                ))
+                            })?
+                            .clone(),
+                    ),
+                    is_all: *is_all,
+                    schema: schema.clone(),
+                }))
+            }
+            LogicalPlan::Except(Except { is_all, schema, .. }) => Ok(LogicalPlan::Except(Except {
+                left: Arc::new(
+                    inputs
+                        .first()
+                        .ok_or_else(|| {
+                            BustubxError::Internal(format!(
+                                "inputs {:?} should have at least two",
+                                inputs
+                            ))
+                        })?
+                        .clone(),
+                ),
+                right: Arc::new(
+                    inputs
+                        .get(1)
+                        .ok_or_else(|| {
+                            BustubxError::Internal(format!(
+                                "inputs {:?} should have at least two",
+                                inputs
+                            ))
+                        })?
+                        .clone(),
+                ),
+                is_all: *is_all,
+                schema: schema.clone(),
+            })),
+            LogicalPlan::Window(Window {
+                window_exprs,
+                schema,
+                ..
+            }) => Ok(LogicalPlan::Window(Window {
+                window_exprs: window_exprs.clone(),
+                schema: schema.clone(),
+                input: Arc::new(
+                    inputs
+                        .first()
+                        .ok_or_else(|| {
+                            BustubxError::Internal(format!(
+                                "inputs {:?} should have at least one",
+                                inputs
+                            ))
+                        })?
+                        .clone(),
+                ),
+            })),
+            LogicalPlan::CreateView(CreateView {
+                name,
+                definition,
+                columns,
+                materialized,
+                ..
+            }) => Ok(LogicalPlan::CreateView(CreateView {
+                name: name.clone(),
+                definition: definition.clone(),
+                columns: columns.clone(),
+                materialized: *materialized,
+                input: Arc::new(
+                    inputs
+                        .first()
+                        .ok_or_else(|| {
+                            BustubxError::Internal(format!(
+                                "inputs {:?} should have at least one",
+                                inputs
+                            ))
+                        })?
+                        .clone(),
+                ),
+            })),
+            LogicalPlan::RefreshMaterializedView(RefreshMaterializedView { name, .. }) => Ok(
+                LogicalPlan::RefreshMaterializedView(RefreshMaterializedView {
+                    name: name.clone(),
+                    input: Arc::new(
+                        inputs
+                            .first()
+                            .ok_or_else(|| {
+                                BustubxError::Internal(format!(
+                                    "inputs {:?} should have at least one",
+                                    inputs
+                                ))
+                            })?
+                            .clone(),
+                    ),
+                }),
+            ),
+            LogicalPlan::CreateSchema(_)
+            | LogicalPlan::DropSchema(_)
+            | LogicalPlan::SetVariable(_)
+            | LogicalPlan::AttachDatabase(_)
+            | LogicalPlan::DetachDatabase(_)
+            | LogicalPlan::CreateTable(_)
             | LogicalPlan::CreateIndex(_)
+            | LogicalPlan::CreateSequence(_)
             | LogicalPlan::TableScan(_)
             | LogicalPlan::Values(_)
             | LogicalPlan::Update(_)
+            | LogicalPlan::Delete(_)
+            | LogicalPlan::WorkTableScan(_)
             | LogicalPlan::EmptyRelation(_) => Ok(self.clone()),
         }
     }
@@ -240,8 +538,16 @@ impl LogicalPlan {
 impl std::fmt::Display for LogicalPlan {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            LogicalPlan::CreateSchema(v) => write!(f, "{v}"),
+            LogicalPlan::DropSchema(v) => write!(f, "{v}"),
+            LogicalPlan::SetVariable(v) => write!(f, "{v}"),
+            LogicalPlan::AttachDatabase(v) => write!(f, "{v}"),
+            LogicalPlan::DetachDatabase(v) => write!(f, "{v}"),
             LogicalPlan::CreateTable(v) => write!(f, "{v}"),
             LogicalPlan::CreateIndex(v) => write!(f, "{v}"),
+            LogicalPlan::CreateSequence(v) => write!(f, "{v}"),
+            LogicalPlan::CreateView(v) => write!(f, "{v}"),
+            LogicalPlan::RefreshMaterializedView(v) => write!(f, "{v}"),
             LogicalPlan::Filter(v) => write!(f, "{v}"),
             LogicalPlan::Insert(v) => write!(f, "{v}"),
             LogicalPlan::Join(v) => write!(f, "{v}"),
@@ -253,6 +559,14 @@ impl std::fmt::Display for LogicalPlan {
             LogicalPlan::EmptyRelation(v) => write!(f, "{v}"),
             LogicalPlan::Aggregate(v) => write!(f, "{v}"),
             LogicalPlan::Update(v) => write!(f, "{v}"),
+            LogicalPlan::Delete(v) => write!(f, "{v}"),
+            LogicalPlan::CteScan(v) => write!(f, "{v}"),
+            LogicalPlan::RecursiveQuery(v) => write!(f, "{v}"),
+            LogicalPlan::WorkTableScan(v) => write!(f, "{v}"),
+            LogicalPlan::Union(v) => write!(f, "{v}"),
+            LogicalPlan::Intersect(v) => write!(f, "{v}"),
+            LogicalPlan::Except(v) => write!(f, "{v}"),
+            LogicalPlan::Window(v) => write!(f, "{v}"),
         }
     }
 }
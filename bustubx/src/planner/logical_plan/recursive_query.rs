@@ -0,0 +1,25 @@
+use crate::planner::logical_plan::LogicalPlan;
+use std::sync::Arc;
+
+/// `WITH RECURSIVE` query, the recursive term is evaluated repeatedly against
+/// the rows produced by the previous iteration until no new rows are produced.
+#[derive(derive_new::new, Debug, Clone)]
+pub struct RecursiveQuery {
+    pub name: String,
+    /// The non-recursive term, evaluated once
+    pub static_term: Arc<LogicalPlan>,
+    /// The recursive term, which reads the working table
+    pub recursive_term: Arc<LogicalPlan>,
+    /// UNION (true) or UNION ALL (false)
+    pub is_distinct: bool,
+}
+
+impl std::fmt::Display for RecursiveQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "RecursiveQuery: {}, is_distinct={}",
+            self.name, self.is_distinct
+        )
+    }
+}
@@ -0,0 +1,17 @@
+use std::sync::Arc;
+
+use crate::common::TableReference;
+use crate::planner::logical_plan::LogicalPlan;
+
+#[derive(derive_new::new, Debug, Clone)]
+pub struct RefreshMaterializedView {
+    pub name: TableReference,
+    /// Plan of the view query, its result replaces the rows of the view
+    pub input: Arc<LogicalPlan>,
+}
+
+impl std::fmt::Display for RefreshMaterializedView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RefreshMaterializedView: {}", self.name)
+    }
+}
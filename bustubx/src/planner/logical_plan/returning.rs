@@ -0,0 +1,35 @@
+use crate::catalog::SchemaRef;
+use crate::expression::{Expr, ExprTrait};
+use crate::{BustubxResult, Tuple};
+
+/// The RETURNING list of INSERT, UPDATE or DELETE, evaluated against every affected row.
+#[derive(derive_new::new, Debug, Clone)]
+pub struct Returning {
+    pub exprs: Vec<Expr>,
+    pub schema: SchemaRef,
+}
+
+impl Returning {
+    pub fn project(&self, tuple: &Tuple) -> BustubxResult<Tuple> {
+        let data = self
+            .exprs
+            .iter()
+            .map(|expr| expr.evaluate(tuple))
+            .collect::<BustubxResult<Vec<_>>>()?;
+        Ok(Tuple::new(self.schema.clone(), data))
+    }
+}
+
+impl std::fmt::Display for Returning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Returning: {}",
+            self.exprs
+                .iter()
+                .map(|e| format!("{e}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
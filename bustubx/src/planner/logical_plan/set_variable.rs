@@ -0,0 +1,16 @@
+#[derive(derive_new::new, Debug, Clone)]
+pub struct SetVariable {
+    pub variable: String,
+    pub values: Vec<String>,
+}
+
+impl std::fmt::Display for SetVariable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "SetVariable: {} = {}",
+            self.variable,
+            self.values.join(", ")
+        )
+    }
+}
@@ -8,6 +8,10 @@ pub struct TableScan {
     pub table_schema: SchemaRef,
     pub filters: Vec<Expr>,
     pub limit: Option<usize>,
+    /// Indices of the table columns to output, all of them if None
+    pub projection: Option<Vec<usize>>,
+    /// The schema of the output columns
+    pub projected_schema: SchemaRef,
 }
 
 impl std::fmt::Display for TableScan {
@@ -1,6 +1,7 @@
 use crate::catalog::SchemaRef;
 use crate::common::TableReference;
 use crate::expression::Expr;
+use crate::planner::logical_plan::Returning;
 use std::collections::HashMap;
 
 #[derive(derive_new::new, Debug, Clone)]
@@ -9,6 +10,7 @@ pub struct Update {
     pub table_schema: SchemaRef,
     pub assignments: HashMap<String, Expr>,
     pub selection: Option<Expr>,
+    pub returning: Option<Returning>,
 }
 
 impl std::fmt::Display for Update {
@@ -0,0 +1,28 @@
+use crate::catalog::SchemaRef;
+use crate::expression::Expr;
+use crate::planner::logical_plan::LogicalPlan;
+use std::sync::Arc;
+
+/// Appends the results of window functions to each input row, the window functions
+/// share the same PARTITION BY and ORDER BY, and the input is sorted by them.
+#[derive(derive_new::new, Debug, Clone)]
+pub struct Window {
+    pub input: Arc<LogicalPlan>,
+    pub window_exprs: Vec<Expr>,
+    /// The schema of input followed by the window function columns
+    pub schema: SchemaRef,
+}
+
+impl std::fmt::Display for Window {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Window: {}",
+            self.window_exprs
+                .iter()
+                .map(|e| format!("{e}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
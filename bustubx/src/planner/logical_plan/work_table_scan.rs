@@ -0,0 +1,15 @@
+use crate::catalog::SchemaRef;
+
+/// Scan of the working table of a recursive query, which holds the rows
+/// produced by the previous iteration.
+#[derive(derive_new::new, Debug, Clone)]
+pub struct WorkTableScan {
+    pub name: String,
+    pub schema: SchemaRef,
+}
+
+impl std::fmt::Display for WorkTableScan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WorkTableScan: {}", self.name)
+    }
+}
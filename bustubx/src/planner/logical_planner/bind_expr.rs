@@ -1,6 +1,15 @@
-use crate::common::{ScalarValue, TableReference};
-use crate::expression::{AggregateFunction, BinaryExpr, ColumnExpr, Expr, Literal};
-use crate::function::AggregateFunctionKind;
+use crate::catalog::DataType;
+use crate::common::datetime::Interval;
+use crate::common::decimal::{self, MAX_DECIMAL_PRECISION};
+use crate::common::{util, ScalarValue, TableReference};
+use crate::expression::{
+    AggregateFunction, Between, BinaryExpr, Case, Cast, ColumnExpr, Expr, InList, Like, Literal,
+    ScalarFunction, SequenceFunction, SequenceFunctionKind, UnaryExpr, UnaryOp, WindowFrame,
+    WindowFrameBound, WindowFrameUnits, WindowFunction,
+};
+use crate::function::{
+    AggregateFunctionKind, BuiltInWindowFunctionKind, ScalarFunctionKind, WindowFunctionKind,
+};
 use crate::planner::LogicalPlanner;
 use crate::{BustubxError, BustubxResult};
 
@@ -51,6 +60,164 @@ impl LogicalPlanner<'_> {
                 ))),
             },
             sqlparser::ast::Expr::Function(function) => self.bind_function(function),
+            sqlparser::ast::Expr::Nested(expr) => self.bind_expr(expr),
+            sqlparser::ast::Expr::UnaryOp { op, expr } => {
+                let op = match op {
+                    sqlparser::ast::UnaryOperator::Not => UnaryOp::Not,
+                    sqlparser::ast::UnaryOperator::Minus => UnaryOp::Minus,
+                    sqlparser::ast::UnaryOperator::Plus => return self.bind_expr(expr),
+                    _ => {
+                        return Err(BustubxError::NotSupport(format!(
+                            "sqlparser unary operator {} not supported",
+                            op
+                        )))
+                    }
+                };
+                Ok(Expr::Unary(UnaryExpr {
+                    op,
+                    expr: Box::new(self.bind_expr(expr)?),
+                }))
+            }
+            sqlparser::ast::Expr::IsNull(expr) => Ok(Expr::Unary(UnaryExpr {
+                op: UnaryOp::IsNull,
+                expr: Box::new(self.bind_expr(expr)?),
+            })),
+            sqlparser::ast::Expr::IsNotNull(expr) => Ok(Expr::Unary(UnaryExpr {
+                op: UnaryOp::IsNotNull,
+                expr: Box::new(self.bind_expr(expr)?),
+            })),
+            sqlparser::ast::Expr::Between {
+                expr,
+                negated,
+                low,
+                high,
+            } => Ok(Expr::Between(Between {
+                expr: Box::new(self.bind_expr(expr)?),
+                negated: *negated,
+                low: Box::new(self.bind_expr(low)?),
+                high: Box::new(self.bind_expr(high)?),
+            })),
+            sqlparser::ast::Expr::InList {
+                expr,
+                list,
+                negated,
+            } => Ok(Expr::InList(InList {
+                expr: Box::new(self.bind_expr(expr)?),
+                list: list
+                    .iter()
+                    .map(|e| self.bind_expr(e))
+                    .collect::<BustubxResult<Vec<Expr>>>()?,
+                negated: *negated,
+            })),
+            sqlparser::ast::Expr::Like {
+                negated,
+                expr,
+                pattern,
+                escape_char,
+            } => Ok(Expr::Like(Like {
+                negated: *negated,
+                expr: Box::new(self.bind_expr(expr)?),
+                pattern: Box::new(self.bind_expr(pattern)?),
+                escape_char: *escape_char,
+                case_insensitive: false,
+            })),
+            sqlparser::ast::Expr::ILike {
+                negated,
+                expr,
+                pattern,
+                escape_char,
+            } => Ok(Expr::Like(Like {
+                negated: *negated,
+                expr: Box::new(self.bind_expr(expr)?),
+                pattern: Box::new(self.bind_expr(pattern)?),
+                escape_char: *escape_char,
+                case_insensitive: true,
+            })),
+            sqlparser::ast::Expr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => {
+                let expr = match operand {
+                    Some(operand) => Some(Box::new(self.bind_expr(operand)?)),
+                    None => None,
+                };
+                let when_then_expr = conditions
+                    .iter()
+                    .zip(results.iter())
+                    .map(|(when, then)| {
+                        Ok((
+                            Box::new(self.bind_expr(when)?),
+                            Box::new(self.bind_expr(then)?),
+                        ))
+                    })
+                    .collect::<BustubxResult<Vec<(Box<Expr>, Box<Expr>)>>>()?;
+                let else_expr = match else_result {
+                    Some(else_result) => Some(Box::new(self.bind_expr(else_result)?)),
+                    None => None,
+                };
+                Ok(Expr::Case(Case {
+                    expr,
+                    when_then_expr,
+                    else_expr,
+                }))
+            }
+            sqlparser::ast::Expr::Cast { expr, data_type } => Ok(Expr::Cast(Cast {
+                expr: Box::new(self.bind_expr(expr)?),
+                data_type: data_type.try_into()?,
+            })),
+            sqlparser::ast::Expr::TypedString { data_type, value } => {
+                let value = ScalarValue::Varchar(Some(value.clone()))
+                    .cast_to(&DataType::try_from(data_type)?)?;
+                Ok(Expr::Literal(Literal { value }))
+            }
+            sqlparser::ast::Expr::Extract { field, expr } => {
+                Ok(Expr::ScalarFunction(ScalarFunction {
+                    func_kind: ScalarFunctionKind::DatePart,
+                    args: vec![
+                        Expr::Literal(Literal {
+                            value: field.to_string().to_lowercase().into(),
+                        }),
+                        self.bind_expr(expr)?,
+                    ],
+                }))
+            }
+            sqlparser::ast::Expr::Interval(interval) => self.bind_interval(interval),
+            sqlparser::ast::Expr::JsonAccess {
+                left,
+                operator,
+                right,
+            } => match rotate_json_access(left, operator, right) {
+                sqlparser::ast::Expr::JsonAccess {
+                    left,
+                    operator,
+                    right,
+                } => Ok(Expr::Binary(BinaryExpr {
+                    left: Box::new(self.bind_expr(&left)?),
+                    op: (&operator).try_into()?,
+                    right: Box::new(self.bind_expr(&right)?),
+                })),
+                rotated => self.bind_expr(&rotated),
+            },
+            sqlparser::ast::Expr::Substring {
+                expr,
+                substring_from,
+                substring_for,
+            } => {
+                let mut args = vec![self.bind_expr(expr)?];
+                args.push(match substring_from {
+                    Some(from) => self.bind_expr(from)?,
+                    None => Expr::Literal(Literal { value: 1i64.into() }),
+                });
+                if let Some(count) = substring_for {
+                    args.push(self.bind_expr(count)?);
+                }
+                Ok(Expr::ScalarFunction(ScalarFunction {
+                    func_kind: ScalarFunctionKind::Substr,
+                    args,
+                }))
+            }
             _ => Err(BustubxError::NotSupport(format!(
                 "sqlparser expr {} not supported",
                 sql
@@ -58,12 +225,63 @@ impl LogicalPlanner<'_> {
         }
     }
 
+    /// `INTERVAL '1 day'`, `INTERVAL '3' HOUR` or `INTERVAL 3 HOUR`
+    fn bind_interval(&self, interval: &sqlparser::ast::Interval) -> BustubxResult<Expr> {
+        if interval.last_field.is_some() || interval.fractional_seconds_precision.is_some() {
+            return Err(BustubxError::NotSupport(format!(
+                "interval {} not supported",
+                interval
+            )));
+        }
+        let text = match interval.value.as_ref() {
+            sqlparser::ast::Expr::Value(
+                sqlparser::ast::Value::SingleQuotedString(s) | sqlparser::ast::Value::Number(s, _),
+            ) => s.clone(),
+            _ => {
+                return Err(BustubxError::NotSupport(format!(
+                    "interval value {} should be a literal",
+                    interval.value
+                )))
+            }
+        };
+        let value = match (&interval.leading_field, text.trim().parse::<f64>()) {
+            (Some(field), Ok(num)) => {
+                let unit = field.to_string().to_lowercase();
+                Interval::from_unit(num, &unit).ok_or_else(|| {
+                    BustubxError::Plan(format!("interval unit {} not supported", unit))
+                })?
+            }
+            _ => Interval::parse(&text)?,
+        };
+        Ok(Expr::Literal(Literal {
+            value: ScalarValue::Interval(Some(value)),
+        }))
+    }
+
     pub fn bind_value(&self, value: &sqlparser::ast::Value) -> BustubxResult<Expr> {
         match value {
             sqlparser::ast::Value::Number(s, _) => {
                 if let Ok(num) = s.parse::<i64>() {
                     return Ok(Expr::Literal(Literal { value: num.into() }));
                 }
+                // numbers with a decimal point or too large for bigint are exact
+                if let Some((integer, fraction)) = Some(s.split_once('.').unwrap_or((s, "")))
+                    .filter(|(integer, fraction)| {
+                        integer
+                            .chars()
+                            .chain(fraction.chars())
+                            .all(|c| c.is_ascii_digit())
+                    })
+                {
+                    let precision = (integer.trim_start_matches('0').len() + fraction.len()).max(1);
+                    if precision <= MAX_DECIMAL_PRECISION as usize {
+                        let scale = fraction.len() as u8;
+                        let v = decimal::parse_decimal(s, scale)?;
+                        return Ok(Expr::Literal(Literal {
+                            value: ScalarValue::Decimal(Some(v), precision as u8, scale),
+                        }));
+                    }
+                }
                 if let Ok(num) = s.parse::<f64>() {
                     return Ok(Expr::Literal(Literal { value: num.into() }));
                 }
@@ -78,6 +296,9 @@ impl LogicalPlanner<'_> {
             sqlparser::ast::Value::SingleQuotedString(s) => Ok(Expr::Literal(Literal {
                 value: s.clone().into(),
             })),
+            sqlparser::ast::Value::HexStringLiteral(s) => Ok(Expr::Literal(Literal {
+                value: util::decode_hex(s)?.into(),
+            })),
             _ => Err(BustubxError::NotSupport(format!(
                 "sqlparser value {} not supported",
                 value
@@ -88,6 +309,36 @@ impl LogicalPlanner<'_> {
     pub fn bind_function(&self, function: &sqlparser::ast::Function) -> BustubxResult<Expr> {
         let name = function.name.to_string();
 
+        if let Some(window) = &function.over {
+            return self.bind_window_function(function, window);
+        }
+        if BuiltInWindowFunctionKind::find(name.as_str()).is_some() {
+            return Err(BustubxError::Plan(format!(
+                "window function {} requires an OVER clause",
+                name
+            )));
+        }
+
+        let sequence_function_kind = if name.eq_ignore_ascii_case("nextval") {
+            Some(SequenceFunctionKind::NextVal)
+        } else if name.eq_ignore_ascii_case("currval") {
+            Some(SequenceFunctionKind::CurrVal)
+        } else {
+            None
+        };
+        if let Some(kind) = sequence_function_kind {
+            return self.bind_sequence_function(kind, function);
+        }
+
+        if let Some(func_kind) = ScalarFunctionKind::find(name.as_str()) {
+            let args = function
+                .args
+                .iter()
+                .map(|arg| self.bind_function_arg(arg))
+                .collect::<BustubxResult<Vec<Expr>>>()?;
+            return Ok(Expr::ScalarFunction(ScalarFunction { func_kind, args }));
+        }
+
         if let Some(func_kind) = AggregateFunctionKind::find(name.as_str()) {
             let args = function
                 .args
@@ -107,8 +358,175 @@ impl LogicalPlanner<'_> {
         )))
     }
 
+    pub fn bind_window_function(
+        &self,
+        function: &sqlparser::ast::Function,
+        window: &sqlparser::ast::WindowType,
+    ) -> BustubxResult<Expr> {
+        let Some(func_kind) = WindowFunctionKind::find(function.name.to_string().as_str()) else {
+            return Err(BustubxError::Plan(format!(
+                "The window function {} is not supported",
+                function
+            )));
+        };
+        if function.distinct {
+            return Err(BustubxError::NotSupport(format!(
+                "DISTINCT is not supported in window function {}",
+                function
+            )));
+        }
+        let sqlparser::ast::WindowType::WindowSpec(spec) = window else {
+            return Err(BustubxError::NotSupport(format!(
+                "named window {} not supported",
+                window
+            )));
+        };
+
+        let args = function
+            .args
+            .iter()
+            .map(|arg| self.bind_function_arg(arg))
+            .collect::<BustubxResult<Vec<Expr>>>()?;
+        let partition_by = spec
+            .partition_by
+            .iter()
+            .map(|e| self.bind_expr(e))
+            .collect::<BustubxResult<Vec<Expr>>>()?;
+        let order_by = spec
+            .order_by
+            .iter()
+            .map(|e| self.bind_order_by_expr(e))
+            .collect::<BustubxResult<Vec<_>>>()?;
+        let window_frame = match &spec.window_frame {
+            Some(frame) => self.bind_window_frame(frame)?,
+            None => WindowFrame::default(),
+        };
+        if window_frame.units == WindowFrameUnits::Range
+            && order_by.len() != 1
+            && [window_frame.start_bound, window_frame.end_bound]
+                .iter()
+                .any(|bound| {
+                    matches!(
+                        bound,
+                        WindowFrameBound::Preceding(Some(_)) | WindowFrameBound::Following(Some(_))
+                    )
+                })
+        {
+            return Err(BustubxError::Plan(
+                "RANGE with offset PRECEDING/FOLLOWING requires exactly one ORDER BY column"
+                    .to_string(),
+            ));
+        }
+
+        Ok(Expr::WindowFunction(WindowFunction {
+            func_kind,
+            args,
+            partition_by,
+            order_by,
+            window_frame,
+        }))
+    }
+
+    fn bind_window_frame(&self, frame: &sqlparser::ast::WindowFrame) -> BustubxResult<WindowFrame> {
+        let units = match frame.units {
+            sqlparser::ast::WindowFrameUnits::Rows => WindowFrameUnits::Rows,
+            sqlparser::ast::WindowFrameUnits::Range => WindowFrameUnits::Range,
+            sqlparser::ast::WindowFrameUnits::Groups => {
+                return Err(BustubxError::NotSupport(
+                    "GROUPS window frame not supported".to_string(),
+                ))
+            }
+        };
+        let start_bound = self.bind_window_frame_bound(&frame.start_bound)?;
+        let end_bound = match &frame.end_bound {
+            Some(bound) => self.bind_window_frame_bound(bound)?,
+            None => WindowFrameBound::CurrentRow,
+        };
+        if matches!(start_bound, WindowFrameBound::Following(None))
+            || matches!(end_bound, WindowFrameBound::Preceding(None))
+        {
+            return Err(BustubxError::Plan(format!(
+                "window frame {:?} is invalid",
+                frame
+            )));
+        }
+        Ok(WindowFrame {
+            units,
+            start_bound,
+            end_bound,
+        })
+    }
+
+    fn bind_window_frame_bound(
+        &self,
+        bound: &sqlparser::ast::WindowFrameBound,
+    ) -> BustubxResult<WindowFrameBound> {
+        let bind_offset = |expr: &Option<Box<sqlparser::ast::Expr>>| match expr {
+            None => Ok(None),
+            Some(expr) => match self.bind_expr(expr)? {
+                Expr::Literal(Literal {
+                    value: ScalarValue::Int64(Some(v)),
+                }) if v >= 0 => Ok(Some(v as u64)),
+                _ => Err(BustubxError::Plan(format!(
+                    "window frame offset must be a non-negative integer, {}",
+                    expr
+                ))),
+            },
+        };
+        match bound {
+            sqlparser::ast::WindowFrameBound::CurrentRow => Ok(WindowFrameBound::CurrentRow),
+            sqlparser::ast::WindowFrameBound::Preceding(expr) => {
+                Ok(WindowFrameBound::Preceding(bind_offset(expr)?))
+            }
+            sqlparser::ast::WindowFrameBound::Following(expr) => {
+                Ok(WindowFrameBound::Following(bind_offset(expr)?))
+            }
+        }
+    }
+
+    /// `nextval('seq')` or `currval('schema.seq')`, the sequence is resolved when binding
+    fn bind_sequence_function(
+        &self,
+        kind: SequenceFunctionKind,
+        function: &sqlparser::ast::Function,
+    ) -> BustubxResult<Expr> {
+        let args = function
+            .args
+            .iter()
+            .map(|arg| self.bind_function_arg(arg))
+            .collect::<BustubxResult<Vec<Expr>>>()?;
+        let [Expr::Literal(Literal {
+            value: ScalarValue::Varchar(Some(sequence_name)),
+        })] = args.as_slice()
+        else {
+            return Err(BustubxError::Plan(format!(
+                "{} expects a sequence name string literal",
+                kind
+            )));
+        };
+        let name_parts = sequence_name
+            .split('.')
+            .map(sqlparser::ast::Ident::new)
+            .collect::<Vec<_>>();
+        if name_parts.len() > 3 {
+            return Err(BustubxError::Plan(format!(
+                "invalid sequence name {}",
+                sequence_name
+            )));
+        }
+        let sequence_ref = self.bind_table_name(&sqlparser::ast::ObjectName(name_parts))?;
+        Ok(Expr::SequenceFunction(SequenceFunction {
+            kind,
+            sequence: self.context.catalog.sequence(&sequence_ref)?,
+        }))
+    }
+
     pub fn bind_function_arg(&self, arg: &sqlparser::ast::FunctionArg) -> BustubxResult<Expr> {
         match arg {
+            sqlparser::ast::FunctionArg::Unnamed(sqlparser::ast::FunctionArgExpr::Wildcard) => {
+                // count(*) counts every row, any non-null value does
+                Ok(Expr::Literal(Literal { value: 1i64.into() }))
+            }
             sqlparser::ast::FunctionArg::Named {
                 name: _,
                 arg: sqlparser::ast::FunctionArgExpr::Expr(arg),
@@ -123,3 +541,59 @@ impl LogicalPlanner<'_> {
         }
     }
 }
+
+/// sqlparser parses the whole expression after a json operator as its right operand, so
+/// `data -> 'a' = 'x'` comes as `data -> ('a' = 'x')`. Json operators bind tighter than
+/// any other operator, this moves the json access down to the leftmost operand.
+fn rotate_json_access(
+    left: &sqlparser::ast::Expr,
+    operator: &sqlparser::ast::JsonOperator,
+    right: &sqlparser::ast::Expr,
+) -> sqlparser::ast::Expr {
+    use sqlparser::ast::Expr as SqlExpr;
+    let rotate = |expr: &SqlExpr| Box::new(rotate_json_access(left, operator, expr));
+    match right {
+        SqlExpr::BinaryOp {
+            left: inner,
+            op,
+            right,
+        } => SqlExpr::BinaryOp {
+            left: rotate(inner),
+            op: op.clone(),
+            right: right.clone(),
+        },
+        // left associative, the outer operator may need rotating again
+        SqlExpr::JsonAccess {
+            left: inner,
+            operator,
+            right,
+        } => rotate_json_access(&rotate(inner), operator, right),
+        SqlExpr::IsNull(inner) => SqlExpr::IsNull(rotate(inner)),
+        SqlExpr::IsNotNull(inner) => SqlExpr::IsNotNull(rotate(inner)),
+        SqlExpr::Like {
+            negated,
+            expr,
+            pattern,
+            escape_char,
+        } => SqlExpr::Like {
+            negated: *negated,
+            expr: rotate(expr),
+            pattern: pattern.clone(),
+            escape_char: *escape_char,
+        },
+        SqlExpr::InList {
+            expr,
+            list,
+            negated,
+        } => SqlExpr::InList {
+            expr: rotate(expr),
+            list: list.clone(),
+            negated: *negated,
+        },
+        _ => SqlExpr::JsonAccess {
+            left: Box::new(left.clone()),
+            operator: *operator,
+            right: Box::new(right.clone()),
+        },
+    }
+}
@@ -1,11 +1,33 @@
 use crate::{BustubxError, BustubxResult};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 
-use crate::catalog::Catalog;
+use crate::catalog::{Catalog, DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME};
 use crate::common::TableReference;
+use crate::parser::ExtensionStatement;
 use crate::planner::logical_plan::{LogicalPlan, OrderByExpr};
 
 pub struct PlannerContext<'a> {
     pub catalog: &'a Catalog,
+    /// Common table expressions visible to the query being planned, keyed by name
+    pub ctes: RefCell<HashMap<String, LogicalPlan>>,
+    pub next_cte_id: Cell<usize>,
+    /// Schemas searched for unqualified names, a view is expanded with its own schema only
+    pub search_path: RefCell<Vec<String>>,
+    /// Attached database unqualified names resolve in, `None` is the main database
+    pub current_catalog: RefCell<Option<String>>,
+}
+
+impl<'a> PlannerContext<'a> {
+    pub fn new(catalog: &'a Catalog) -> Self {
+        Self {
+            catalog,
+            ctes: RefCell::new(HashMap::new()),
+            next_cte_id: Cell::new(0),
+            search_path: RefCell::new(catalog.search_path.clone()),
+            current_catalog: RefCell::new(None),
+        }
+    }
 }
 
 pub struct LogicalPlanner<'a> {
@@ -14,41 +36,147 @@ pub struct LogicalPlanner<'a> {
 impl<'a> LogicalPlanner<'a> {
     pub fn plan(&mut self, stmt: &sqlparser::ast::Statement) -> BustubxResult<LogicalPlan> {
         match stmt {
-            sqlparser::ast::Statement::CreateTable { name, columns, .. } => {
-                self.plan_create_table(name, columns)
+            sqlparser::ast::Statement::CreateSchema {
+                schema_name,
+                if_not_exists,
+            } => self.plan_create_schema(schema_name, *if_not_exists),
+            sqlparser::ast::Statement::Drop {
+                object_type,
+                if_exists,
+                names,
+                cascade,
+                ..
+            } => self.plan_drop(object_type, *if_exists, names, *cascade),
+            sqlparser::ast::Statement::SetVariable {
+                variable, value, ..
+            } => self.plan_set_variable(variable, value),
+            sqlparser::ast::Statement::ShowVariable { variable } => {
+                self.plan_show_variable(variable)
             }
+            sqlparser::ast::Statement::CreateTable {
+                name,
+                columns,
+                constraints,
+                ..
+            } => self.plan_create_table(name, columns, constraints),
             sqlparser::ast::Statement::CreateIndex {
                 name,
                 table_name,
+                using,
+                columns,
+                ..
+            } => self.plan_create_index(name, table_name, using, columns),
+            sqlparser::ast::Statement::CreateSequence {
+                name,
+                data_type,
+                sequence_options,
+                ..
+            } => self.plan_create_sequence(name, data_type, sequence_options),
+            sqlparser::ast::Statement::CreateView {
+                or_replace,
+                materialized,
+                name,
                 columns,
+                query,
                 ..
-            } => self.plan_create_index(name, table_name, columns),
+            } => self.plan_create_view(*or_replace, *materialized, name, columns, query),
             sqlparser::ast::Statement::Query(query) => self.plan_query(query),
             sqlparser::ast::Statement::Insert {
                 table_name,
                 columns,
                 source,
+                on,
+                returning,
                 ..
-            } => self.plan_insert(table_name, columns, source),
+            } => self.plan_insert(table_name, columns, source, on, returning),
             sqlparser::ast::Statement::Update {
                 table,
                 assignments,
                 selection,
+                returning,
                 ..
-            } => self.plan_update(table, assignments, selection),
+            } => self.plan_update(table, assignments, selection, returning),
+            sqlparser::ast::Statement::Delete {
+                from,
+                selection,
+                returning,
+                ..
+            } => self.plan_delete(from, selection, returning),
             _ => unimplemented!(),
         }
     }
 
+    pub fn plan_extension_statement(
+        &self,
+        stmt: &ExtensionStatement,
+    ) -> BustubxResult<LogicalPlan> {
+        match stmt {
+            ExtensionStatement::RefreshMaterializedView { name } => {
+                self.plan_refresh_materialized_view(name)
+            }
+            ExtensionStatement::Attach { path, name } => self.plan_attach_database(path, name),
+            ExtensionStatement::Detach { name } => self.plan_detach_database(name),
+        }
+    }
+
+    /// Runs `f` with unqualified names resolving in the database `table_ref` belongs to.
+    pub fn with_catalog_of<T>(
+        &self,
+        table_ref: &TableReference,
+        f: impl FnOnce() -> BustubxResult<T>,
+    ) -> BustubxResult<T> {
+        let Some(catalog_name) = table_ref
+            .catalog()
+            .filter(|catalog| *catalog != DEFAULT_CATALOG_NAME)
+        else {
+            return f();
+        };
+        let search_path = self
+            .context
+            .catalog
+            .attached_catalog(catalog_name)?
+            .search_path
+            .clone();
+        let outer_catalog = self
+            .context
+            .current_catalog
+            .replace(Some(catalog_name.to_string()));
+        let outer_search_path = self.context.search_path.replace(search_path);
+        let result = f();
+        self.context.current_catalog.replace(outer_catalog);
+        self.context.search_path.replace(outer_search_path);
+        result
+    }
+
+    fn resolve_attached_relation(
+        &self,
+        catalog_name: &str,
+        search_path: &[String],
+        name: &str,
+    ) -> BustubxResult<TableReference> {
+        let table_ref = self
+            .context
+            .catalog
+            .attached_catalog(catalog_name)?
+            .resolve_relation(search_path, name)?;
+        Ok(TableReference::full(
+            catalog_name,
+            table_ref.schema().unwrap_or(DEFAULT_SCHEMA_NAME),
+            table_ref.table(),
+        ))
+    }
+
     pub fn bind_order_by_expr(
         &self,
         order_by: &sqlparser::ast::OrderByExpr,
     ) -> BustubxResult<OrderByExpr> {
         let expr = self.bind_expr(&order_by.expr)?;
+        let asc = order_by.asc.unwrap_or(true);
+        // like postgres, NULLs sort as if larger than every value by default
         Ok(OrderByExpr {
             expr: Box::new(expr),
-            asc: order_by.asc.unwrap_or(true),
-            nulls_first: order_by.nulls_first.unwrap_or(false),
+            asc,
+            nulls_first: order_by.nulls_first.unwrap_or(!asc),
         })
     }
 
@@ -56,12 +184,42 @@ impl<'a> LogicalPlanner<'a> {
         &self,
         table_name: &sqlparser::ast::ObjectName,
     ) -> BustubxResult<TableReference> {
+        let current_catalog = self.context.current_catalog.borrow().clone();
         match table_name.0.as_slice() {
-            [table] => Ok(TableReference::bare(table.value.clone())),
-            [schema, table] => Ok(TableReference::partial(
-                schema.value.clone(),
-                table.value.clone(),
-            )),
+            [table] => match current_catalog {
+                Some(catalog_name) => self.resolve_attached_relation(
+                    &catalog_name,
+                    &self.context.search_path.borrow(),
+                    &table.value,
+                ),
+                None => self
+                    .context
+                    .catalog
+                    .resolve_relation(&self.context.search_path.borrow(), &table.value),
+            },
+            [schema, table] => match current_catalog {
+                Some(catalog_name) => Ok(TableReference::full(
+                    catalog_name,
+                    schema.value.clone(),
+                    table.value.clone(),
+                )),
+                // `database.table` of an attached database resolves by its own search path
+                None if !self.context.catalog.schemas.contains_key(&schema.value)
+                    && self.context.catalog.attached.contains_key(&schema.value) =>
+                {
+                    let search_path = self
+                        .context
+                        .catalog
+                        .attached_catalog(&schema.value)?
+                        .search_path
+                        .clone();
+                    self.resolve_attached_relation(&schema.value, &search_path, &table.value)
+                }
+                None => Ok(TableReference::partial(
+                    schema.value.clone(),
+                    table.value.clone(),
+                )),
+            },
             [catalog, schema, table] => Ok(TableReference::full(
                 catalog.value.clone(),
                 schema.value.clone(),
@@ -73,4 +231,15 @@ impl<'a> LogicalPlanner<'a> {
             ))),
         }
     }
+
+    /// Rows of a materialized view only change by refreshing it.
+    pub fn check_table_writable(&self, table: &TableReference) -> BustubxResult<()> {
+        match self.context.catalog.view(table) {
+            Some(view) if view.materialized => Err(BustubxError::Plan(format!(
+                "cannot change materialized view \"{}\"",
+                view.name
+            ))),
+            _ => Ok(()),
+        }
+    }
 }
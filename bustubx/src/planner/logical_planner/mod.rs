@@ -1,10 +1,19 @@
 mod bind_expr;
 mod logical_planner;
+mod plan_attach_database;
 mod plan_create_index;
+mod plan_create_schema;
+mod plan_create_sequence;
 mod plan_create_table;
+mod plan_create_view;
+mod plan_cte;
+mod plan_delete;
+mod plan_drop;
 mod plan_insert;
 mod plan_query;
+mod plan_returning;
 mod plan_set_expr;
 mod plan_update;
+mod plan_variable;
 
 pub use logical_planner::{LogicalPlanner, PlannerContext};
@@ -0,0 +1,33 @@
+use crate::catalog::DEFAULT_CATALOG_NAME;
+use crate::planner::logical_plan::{AttachDatabase, DetachDatabase, LogicalPlan};
+use crate::{BustubxError, BustubxResult};
+
+use super::LogicalPlanner;
+
+impl<'a> LogicalPlanner<'a> {
+    pub fn plan_attach_database(
+        &self,
+        path: &str,
+        name: &sqlparser::ast::Ident,
+    ) -> BustubxResult<LogicalPlan> {
+        if name.value == DEFAULT_CATALOG_NAME
+            || self.context.catalog.attached.contains_key(&name.value)
+        {
+            return Err(BustubxError::Plan(format!(
+                "database \"{}\" already exists",
+                name.value
+            )));
+        }
+        Ok(LogicalPlan::AttachDatabase(AttachDatabase {
+            path: path.to_string(),
+            name: name.value.clone(),
+        }))
+    }
+
+    pub fn plan_detach_database(&self, name: &sqlparser::ast::Ident) -> BustubxResult<LogicalPlan> {
+        self.context.catalog.attached_catalog(&name.value)?;
+        Ok(LogicalPlan::DetachDatabase(DetachDatabase {
+            name: name.value.clone(),
+        }))
+    }
+}
@@ -1,3 +1,4 @@
+use crate::catalog::IndexType;
 use crate::planner::logical_plan::{CreateIndex, LogicalPlan};
 use crate::{BustubxError, BustubxResult};
 
@@ -8,6 +9,7 @@ impl<'a> LogicalPlanner<'a> {
         &self,
         index_name: &sqlparser::ast::ObjectName,
         table_name: &sqlparser::ast::ObjectName,
+        using: &Option<sqlparser::ast::Ident>,
         columns: &[sqlparser::ast::OrderByExpr],
     ) -> BustubxResult<LogicalPlan> {
         let index_name = index_name.0.first().map_or(
@@ -22,12 +24,17 @@ impl<'a> LogicalPlanner<'a> {
             let col_expr = self.bind_order_by_expr(col)?;
             columns_expr.push(col_expr);
         }
+        let index_type = match using {
+            Some(ident) => IndexType::try_from(ident.value.as_str())?,
+            None => IndexType::BTree,
+        };
         let table_schema = self.context.catalog.table_heap(&table)?.schema.clone();
         Ok(LogicalPlan::CreateIndex(CreateIndex {
             index_name,
             table,
             table_schema,
             columns: columns_expr,
+            index_type,
         }))
     }
 }
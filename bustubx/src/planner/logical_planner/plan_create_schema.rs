@@ -0,0 +1,33 @@
+use crate::planner::logical_plan::{CreateSchema, LogicalPlan};
+use crate::{BustubxError, BustubxResult};
+
+use super::LogicalPlanner;
+
+impl<'a> LogicalPlanner<'a> {
+    pub fn plan_create_schema(
+        &self,
+        schema_name: &sqlparser::ast::SchemaName,
+        if_not_exists: bool,
+    ) -> BustubxResult<LogicalPlan> {
+        let sqlparser::ast::SchemaName::Simple(name) = schema_name else {
+            return Err(BustubxError::NotSupport(format!(
+                "CREATE SCHEMA {} not supported",
+                schema_name
+            )));
+        };
+        Ok(LogicalPlan::CreateSchema(CreateSchema {
+            name: self.bind_schema_name(name)?,
+            if_not_exists,
+        }))
+    }
+
+    pub fn bind_schema_name(&self, name: &sqlparser::ast::ObjectName) -> BustubxResult<String> {
+        match name.0.as_slice() {
+            [schema] => Ok(schema.value.clone()),
+            _ => Err(BustubxError::Plan(format!(
+                "Fail to plan schema name: {}",
+                name
+            ))),
+        }
+    }
+}
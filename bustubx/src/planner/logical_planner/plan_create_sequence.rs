@@ -0,0 +1,74 @@
+use crate::catalog::{DataType, ResolvedSequenceOptions, SequenceOptions};
+use crate::expression::ExprTrait;
+use crate::planner::logical_plan::{CreateSequence, LogicalPlan};
+use crate::storage::EMPTY_TUPLE;
+use crate::{BustubxError, BustubxResult};
+
+use super::LogicalPlanner;
+
+impl<'a> LogicalPlanner<'a> {
+    pub fn plan_create_sequence(
+        &self,
+        name: &sqlparser::ast::ObjectName,
+        data_type: &Option<sqlparser::ast::DataType>,
+        sequence_options: &[sqlparser::ast::SequenceOptions],
+    ) -> BustubxResult<LogicalPlan> {
+        let name = self.bind_table_name(name)?;
+        let data_type = match data_type {
+            Some(data_type) => data_type.try_into()?,
+            None => DataType::Int64,
+        };
+        let options = self.bind_sequence_options(name.table(), data_type, sequence_options)?;
+        Ok(LogicalPlan::CreateSequence(CreateSequence {
+            name,
+            options,
+        }))
+    }
+
+    pub fn bind_sequence_options(
+        &self,
+        name: &str,
+        data_type: DataType,
+        sequence_options: &[sqlparser::ast::SequenceOptions],
+    ) -> BustubxResult<ResolvedSequenceOptions> {
+        let mut options = SequenceOptions::default();
+        for option in sequence_options {
+            match option {
+                sqlparser::ast::SequenceOptions::IncrementBy(expr, _) => {
+                    options.increment = Some(self.bind_sequence_value(expr)?)
+                }
+                sqlparser::ast::SequenceOptions::StartWith(expr, _) => {
+                    options.start = Some(self.bind_sequence_value(expr)?)
+                }
+                sqlparser::ast::SequenceOptions::MinValue(value) => {
+                    options.min_value = match value {
+                        sqlparser::ast::MinMaxValue::Some(expr) => {
+                            Some(self.bind_sequence_value(expr)?)
+                        }
+                        _ => None,
+                    }
+                }
+                sqlparser::ast::SequenceOptions::MaxValue(value) => {
+                    options.max_value = match value {
+                        sqlparser::ast::MinMaxValue::Some(expr) => {
+                            Some(self.bind_sequence_value(expr)?)
+                        }
+                        _ => None,
+                    }
+                }
+                // sqlparser marks NO CYCLE with true
+                sqlparser::ast::SequenceOptions::Cycle(no_cycle) => options.cycle = !no_cycle,
+                // values are not cached, every nextval is persisted
+                sqlparser::ast::SequenceOptions::Cache(_) => {}
+            }
+        }
+        options.resolve(name, data_type)
+    }
+
+    fn bind_sequence_value(&self, expr: &sqlparser::ast::Expr) -> BustubxResult<i64> {
+        self.bind_expr(expr)?
+            .evaluate(&EMPTY_TUPLE)?
+            .as_i64()?
+            .ok_or_else(|| BustubxError::Plan(format!("sequence option {} must not be NULL", expr)))
+    }
+}
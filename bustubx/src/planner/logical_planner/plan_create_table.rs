@@ -1,10 +1,13 @@
 use crate::{BustubxError, BustubxResult};
 use std::collections::HashSet;
 
-use crate::catalog::{Column, DataType};
-use crate::common::ScalarValue;
-use crate::expression::Expr;
-use crate::planner::logical_plan::{CreateTable, LogicalPlan};
+use crate::catalog::{
+    Column, Constraint, ConstraintKind, DataType, ForeignKey, IdentityGeneration, StoredExpr,
+    DEFAULT_CATALOG_NAME, DEFAULT_SCHEMA_NAME,
+};
+use crate::common::TableReference;
+use crate::expression::{find_column_exprs, Expr, Literal};
+use crate::planner::logical_plan::{ColumnSequence, CreateTable, LogicalPlan};
 
 use super::LogicalPlanner;
 
@@ -13,15 +16,104 @@ impl<'a> LogicalPlanner<'a> {
         &self,
         name: &sqlparser::ast::ObjectName,
         column_defs: &Vec<sqlparser::ast::ColumnDef>,
+        table_constraints: &[sqlparser::ast::TableConstraint],
     ) -> BustubxResult<LogicalPlan> {
         let name = self.bind_table_name(name)?;
+        // defaults and referenced tables resolve in the database the table is created in
+        self.with_catalog_of(&name, || {
+            self.plan_table_definition(name.clone(), column_defs, table_constraints)
+        })
+    }
+
+    fn plan_table_definition(
+        &self,
+        name: TableReference,
+        column_defs: &Vec<sqlparser::ast::ColumnDef>,
+        table_constraints: &[sqlparser::ast::TableConstraint],
+    ) -> BustubxResult<LogicalPlan> {
         let mut columns = vec![];
+        let mut sequences = vec![];
+        // (name, kind, columns) before default names are given
+        let mut constraints: Vec<(Option<String>, ConstraintKind, Vec<String>)> = vec![];
         for col_def in column_defs {
-            let data_type: DataType = (&col_def.data_type).try_into()?;
-            let not_null: bool = col_def
-                .options
-                .iter()
-                .any(|opt| matches!(opt.option, sqlparser::ast::ColumnOption::NotNull));
+            for opt in col_def.options.iter() {
+                match &opt.option {
+                    sqlparser::ast::ColumnOption::Unique { is_primary } => constraints.push((
+                        opt.name.as_ref().map(|ident| ident.value.clone()),
+                        if *is_primary {
+                            ConstraintKind::PrimaryKey
+                        } else {
+                            ConstraintKind::Unique
+                        },
+                        vec![col_def.name.value.clone()],
+                    )),
+                    sqlparser::ast::ColumnOption::ForeignKey {
+                        foreign_table,
+                        referred_columns,
+                        on_delete,
+                        on_update,
+                    } => constraints.push((
+                        opt.name.as_ref().map(|ident| ident.value.clone()),
+                        ConstraintKind::ForeignKey(ForeignKey {
+                            table: self.bind_table_name(foreign_table)?,
+                            columns: referred_columns
+                                .iter()
+                                .map(|ident| ident.value.clone())
+                                .collect(),
+                            on_delete: on_delete.as_ref().map(Into::into).unwrap_or_default(),
+                            on_update: on_update.as_ref().map(Into::into).unwrap_or_default(),
+                        }),
+                        vec![col_def.name.value.clone()],
+                    )),
+                    sqlparser::ast::ColumnOption::Check(expr) => {
+                        let (kind, columns) = self.bind_check(expr)?;
+                        constraints.push((
+                            opt.name.as_ref().map(|ident| ident.value.clone()),
+                            kind,
+                            columns,
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+            let serial_type = serial_data_type(&col_def.data_type);
+            let data_type: DataType = match serial_type {
+                Some(data_type) => data_type,
+                None => (&col_def.data_type).try_into()?,
+            };
+            let mut identity = None;
+            let mut identity_options: &[sqlparser::ast::SequenceOptions] = &[];
+            for opt in col_def.options.iter() {
+                if let sqlparser::ast::ColumnOption::Generated {
+                    generated_as,
+                    sequence_options,
+                    generation_expr,
+                } = &opt.option
+                {
+                    identity = match (generated_as, generation_expr) {
+                        (sqlparser::ast::GeneratedAs::Always, None) => {
+                            Some(IdentityGeneration::Always)
+                        }
+                        (sqlparser::ast::GeneratedAs::ByDefault, None) => {
+                            Some(IdentityGeneration::ByDefault)
+                        }
+                        _ => {
+                            return Err(BustubxError::NotSupport(format!(
+                                "generated column {} not supported",
+                                col_def.name.value
+                            )))
+                        }
+                    };
+                    identity_options = sequence_options.as_deref().unwrap_or_default();
+                }
+            }
+            // identity and serial columns are not null and draw from a sequence
+            let not_null: bool = serial_type.is_some()
+                || identity.is_some()
+                || col_def
+                    .options
+                    .iter()
+                    .any(|opt| matches!(opt.option, sqlparser::ast::ColumnOption::NotNull));
             let default_expr: Option<&sqlparser::ast::Expr> = col_def
                 .options
                 .iter()
@@ -33,30 +125,357 @@ impl<'a> LogicalPlanner<'a> {
                         unreachable!()
                     }
                 });
-            let default = if let Some(expr) = default_expr {
-                let expr = self.bind_expr(expr)?;
-                match expr {
-                    Expr::Literal(lit) => lit.value.cast_to(&data_type)?,
-                    _ => {
-                        return Err(BustubxError::Internal(
-                            "The expr is not literal".to_string(),
-                        ))
-                    }
+            if serial_type.is_some() || identity.is_some() {
+                if default_expr.is_some() || (serial_type.is_some() && identity.is_some()) {
+                    return Err(BustubxError::Plan(format!(
+                        "multiple default values specified for column \"{}\"",
+                        col_def.name.value
+                    )));
+                }
+                let sequence_name = format!("{}_{}_seq", name.table(), col_def.name.value);
+                let options =
+                    self.bind_sequence_options(&sequence_name, data_type, identity_options)?;
+                sequences.push(ColumnSequence {
+                    column: col_def.name.value.clone(),
+                    sequence: match name.catalog() {
+                        Some(catalog) => TableReference::full(
+                            catalog,
+                            name.schema().unwrap_or(DEFAULT_SCHEMA_NAME),
+                            sequence_name,
+                        ),
+                        None => TableReference::partial(
+                            name.schema().unwrap_or(DEFAULT_SCHEMA_NAME),
+                            sequence_name,
+                        ),
+                    },
+                    options,
+                });
+            }
+            let default = if let Some(sql_expr) = default_expr {
+                let expr = match self.bind_expr(sql_expr)? {
+                    // a constant must fit the column type already
+                    Expr::Literal(lit) => Expr::Literal(Literal {
+                        value: lit.value.cast_to(&data_type)?,
+                    }),
+                    expr => expr,
+                };
+                if !find_column_exprs(&expr).is_empty() {
+                    return Err(BustubxError::Plan(format!(
+                        "cannot use column reference in DEFAULT expression of column {}",
+                        col_def.name.value
+                    )));
                 }
+                Some(StoredExpr {
+                    sql: sql_expr.to_string(),
+                    expr,
+                })
             } else {
-                ScalarValue::new_empty(data_type)
+                None
             };
 
             columns.push(
                 Column::new(col_def.name.value.clone(), data_type, !not_null)
                     .with_relation(Some(name.clone()))
-                    .with_default(default),
+                    .with_default(default)
+                    .with_identity(identity),
             )
         }
 
         check_column_name_conflict(&columns)?;
-        Ok(LogicalPlan::CreateTable(CreateTable { name, columns }))
+
+        for constraint in table_constraints {
+            match constraint {
+                sqlparser::ast::TableConstraint::Unique {
+                    name,
+                    columns,
+                    is_primary,
+                } => constraints.push((
+                    name.as_ref().map(|ident| ident.value.clone()),
+                    if *is_primary {
+                        ConstraintKind::PrimaryKey
+                    } else {
+                        ConstraintKind::Unique
+                    },
+                    columns.iter().map(|ident| ident.value.clone()).collect(),
+                )),
+                sqlparser::ast::TableConstraint::ForeignKey {
+                    name,
+                    columns,
+                    foreign_table,
+                    referred_columns,
+                    on_delete,
+                    on_update,
+                } => constraints.push((
+                    name.as_ref().map(|ident| ident.value.clone()),
+                    ConstraintKind::ForeignKey(ForeignKey {
+                        table: self.bind_table_name(foreign_table)?,
+                        columns: referred_columns
+                            .iter()
+                            .map(|ident| ident.value.clone())
+                            .collect(),
+                        on_delete: on_delete.as_ref().map(Into::into).unwrap_or_default(),
+                        on_update: on_update.as_ref().map(Into::into).unwrap_or_default(),
+                    }),
+                    columns.iter().map(|ident| ident.value.clone()).collect(),
+                )),
+                sqlparser::ast::TableConstraint::Check { name, expr } => {
+                    let (kind, columns) = self.bind_check(expr)?;
+                    constraints.push((
+                        name.as_ref().map(|ident| ident.value.clone()),
+                        kind,
+                        columns,
+                    ))
+                }
+                _ => {
+                    return Err(BustubxError::NotSupport(format!(
+                        "table constraint {} not supported",
+                        constraint
+                    )))
+                }
+            }
+        }
+
+        let mut constraints = self.name_constraints(&name, constraints)?;
+        for constraint in constraints.iter() {
+            let mut seen = HashSet::new();
+            for col_name in constraint.columns.iter() {
+                if !seen.insert(col_name.as_str()) {
+                    return Err(BustubxError::Plan(format!(
+                        "column \"{}\" appears twice in constraint {}",
+                        col_name, constraint.name
+                    )));
+                }
+                let Some(col) = columns.iter_mut().find(|col| &col.name == col_name) else {
+                    return Err(BustubxError::Plan(format!(
+                        "column \"{}\" named in constraint {} does not exist",
+                        col_name, constraint.name
+                    )));
+                };
+                // primary key columns are implicitly not null
+                if constraint.kind == ConstraintKind::PrimaryKey {
+                    col.nullable = false;
+                }
+            }
+        }
+        if constraints
+            .iter()
+            .filter(|c| c.kind == ConstraintKind::PrimaryKey)
+            .count()
+            > 1
+        {
+            return Err(BustubxError::Plan(format!(
+                "multiple primary keys for table \"{}\" are not allowed",
+                name.table()
+            )));
+        }
+
+        let unique_constraints = constraints
+            .iter()
+            .filter(|c| c.kind.is_unique())
+            .cloned()
+            .collect::<Vec<Constraint>>();
+        for constraint in constraints.iter_mut() {
+            if let ConstraintKind::ForeignKey(foreign_key) = &mut constraint.kind {
+                self.resolve_foreign_key(
+                    &name,
+                    &columns,
+                    &unique_constraints,
+                    &constraint.name,
+                    &constraint.columns,
+                    foreign_key,
+                )?;
+            }
+        }
+
+        Ok(LogicalPlan::CreateTable(CreateTable {
+            name,
+            columns,
+            constraints,
+            sequences,
+        }))
+    }
+
+    /// Binds a check expression, returning the columns it references.
+    fn bind_check(
+        &self,
+        sql_expr: &sqlparser::ast::Expr,
+    ) -> BustubxResult<(ConstraintKind, Vec<String>)> {
+        let expr = self.bind_expr(sql_expr)?;
+        if contains_aggregate_or_window(&expr) {
+            return Err(BustubxError::Plan(format!(
+                "cannot use aggregate or window function in check constraint {}",
+                sql_expr
+            )));
+        }
+        let columns = find_column_exprs(&expr)
+            .into_iter()
+            .map(|col| col.name)
+            .fold(vec![], |mut columns, name| {
+                if !columns.contains(&name) {
+                    columns.push(name);
+                }
+                columns
+            });
+        Ok((
+            ConstraintKind::Check(StoredExpr {
+                sql: sql_expr.to_string(),
+                expr,
+            }),
+            columns,
+        ))
+    }
+
+    /// Qualifies the referenced table of a foreign key and checks the referenced columns
+    /// match a unique constraint, no referenced columns means the primary key.
+    fn resolve_foreign_key(
+        &self,
+        table: &TableReference,
+        table_columns: &[Column],
+        table_unique_constraints: &[Constraint],
+        constraint_name: &str,
+        constraint_columns: &[String],
+        foreign_key: &mut ForeignKey,
+    ) -> BustubxResult<()> {
+        let catalog_name = table.catalog().unwrap_or(DEFAULT_CATALOG_NAME);
+        if foreign_key.table.catalog().unwrap_or(DEFAULT_CATALOG_NAME) != catalog_name {
+            return Err(BustubxError::NotSupport(
+                "cross-database references are not implemented".to_string(),
+            ));
+        }
+        let foreign_table = match table.catalog() {
+            Some(catalog) => TableReference::full(
+                catalog,
+                foreign_key.table.schema().unwrap_or(DEFAULT_SCHEMA_NAME),
+                foreign_key.table.table(),
+            ),
+            None => TableReference::partial(
+                foreign_key.table.schema().unwrap_or(DEFAULT_SCHEMA_NAME),
+                foreign_key.table.table(),
+            ),
+        };
+        let self_referencing = foreign_table.table() == table.table()
+            && foreign_table.schema() == Some(table.schema().unwrap_or(DEFAULT_SCHEMA_NAME));
+        let (foreign_columns, foreign_unique_constraints) = if self_referencing {
+            (table_columns.to_vec(), table_unique_constraints.to_vec())
+        } else {
+            (
+                self.context
+                    .catalog
+                    .table_heap(&foreign_table)?
+                    .schema
+                    .columns
+                    .iter()
+                    .map(|col| col.as_ref().clone())
+                    .collect(),
+                self.context.catalog.table_constraints(&foreign_table)?,
+            )
+        };
+
+        if foreign_key.columns.is_empty() {
+            let Some(primary_key) = foreign_unique_constraints
+                .iter()
+                .find(|c| c.kind == ConstraintKind::PrimaryKey)
+            else {
+                return Err(BustubxError::Plan(format!(
+                    "there is no primary key for referenced table \"{}\"",
+                    foreign_table.table()
+                )));
+            };
+            foreign_key.columns = primary_key.columns.clone();
+        }
+        for col_name in foreign_key.columns.iter() {
+            if !foreign_columns.iter().any(|col| &col.name == col_name) {
+                return Err(BustubxError::Plan(format!(
+                    "column \"{}\" referenced in foreign key constraint {} does not exist",
+                    col_name, constraint_name
+                )));
+            }
+        }
+        if foreign_key.columns.len() != constraint_columns.len() {
+            return Err(BustubxError::Plan(format!(
+                "number of referencing and referenced columns for foreign key {} disagree",
+                constraint_name
+            )));
+        }
+        let matches_unique = foreign_unique_constraints.iter().any(|c| {
+            c.columns.len() == foreign_key.columns.len()
+                && c.columns
+                    .iter()
+                    .all(|col| foreign_key.columns.contains(col))
+        });
+        if !matches_unique {
+            return Err(BustubxError::Plan(format!(
+                "there is no unique constraint matching given keys for referenced table \"{}\"",
+                foreign_table.table()
+            )));
+        }
+        foreign_key.table = foreign_table;
+        Ok(())
     }
+
+    /// Names unnamed constraints like `t_pkey` and `t_a_b_key`.
+    fn name_constraints(
+        &self,
+        table: &TableReference,
+        constraints: Vec<(Option<String>, ConstraintKind, Vec<String>)>,
+    ) -> BustubxResult<Vec<Constraint>> {
+        let mut names: HashSet<String> = constraints
+            .iter()
+            .filter_map(|(name, _, _)| name.clone())
+            .collect();
+        let mut named = vec![];
+        for (name, kind, columns) in constraints {
+            let name = match name {
+                Some(name) => name,
+                None => {
+                    let base = match kind {
+                        ConstraintKind::PrimaryKey => format!("{}_pkey", table.table()),
+                        ConstraintKind::Unique => {
+                            format!("{}_{}_key", table.table(), columns.join("_"))
+                        }
+                        ConstraintKind::ForeignKey(_) => {
+                            format!("{}_{}_fkey", table.table(), columns.join("_"))
+                        }
+                        ConstraintKind::Check(_) if columns.is_empty() => {
+                            format!("{}_check", table.table())
+                        }
+                        ConstraintKind::Check(_) => {
+                            format!("{}_{}_check", table.table(), columns.join("_"))
+                        }
+                    };
+                    let mut name = base.clone();
+                    let mut suffix = 0;
+                    while names.contains(&name) {
+                        suffix += 1;
+                        name = format!("{}{}", base, suffix);
+                    }
+                    names.insert(name.clone());
+                    name
+                }
+            };
+            if named.iter().any(|c: &Constraint| c.name == name) {
+                return Err(BustubxError::Plan(format!(
+                    "constraint \"{}\" for relation \"{}\" already exists",
+                    name,
+                    table.table()
+                )));
+            }
+            named.push(Constraint {
+                name,
+                kind,
+                columns,
+            });
+        }
+        Ok(named)
+    }
+}
+
+fn contains_aggregate_or_window(expr: &Expr) -> bool {
+    matches!(expr, Expr::AggregateFunction(_) | Expr::WindowFunction(_))
+        || expr
+            .children()
+            .into_iter()
+            .any(contains_aggregate_or_window)
 }
 
 fn check_column_name_conflict(columns: &[Column]) -> BustubxResult<()> {
@@ -73,3 +492,19 @@ fn check_column_name_conflict(columns: &[Column]) -> BustubxResult<()> {
     }
     Ok(())
 }
+
+/// The integer type behind the `serial` pseudo types.
+fn serial_data_type(data_type: &sqlparser::ast::DataType) -> Option<DataType> {
+    let sqlparser::ast::DataType::Custom(name, modifiers) = data_type else {
+        return None;
+    };
+    if !modifiers.is_empty() {
+        return None;
+    }
+    match name.to_string().to_lowercase().as_str() {
+        "smallserial" | "serial2" => Some(DataType::Int16),
+        "serial" | "serial4" => Some(DataType::Int32),
+        "bigserial" | "serial8" => Some(DataType::Int64),
+        _ => None,
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::catalog::{CatalogView, DEFAULT_CATALOG_NAME};
+use crate::common::TableReference;
+use crate::planner::logical_plan::{CreateView, LogicalPlan, RefreshMaterializedView};
+use crate::{BustubxError, BustubxResult};
+
+use super::LogicalPlanner;
+
+impl<'a> LogicalPlanner<'a> {
+    pub fn plan_create_view(
+        &self,
+        or_replace: bool,
+        materialized: bool,
+        name: &sqlparser::ast::ObjectName,
+        columns: &[sqlparser::ast::Ident],
+        query: &sqlparser::ast::Query,
+    ) -> BustubxResult<LogicalPlan> {
+        if or_replace {
+            return Err(BustubxError::NotSupport(
+                "CREATE OR REPLACE VIEW not supported".to_string(),
+            ));
+        }
+        let name = self.bind_table_name(name)?;
+        let columns = columns
+            .iter()
+            .map(|ident| ident.value.clone())
+            .collect::<Vec<String>>();
+        let view = CatalogView {
+            name: name.table().to_string(),
+            definition: query.to_string(),
+            columns,
+            materialized,
+        };
+        // planning the stored text validates it the same way it is expanded later
+        let input = self.plan_view(&name, &view)?;
+        Ok(LogicalPlan::CreateView(CreateView {
+            name,
+            definition: view.definition,
+            columns: view.columns,
+            materialized,
+            input: Arc::new(input),
+        }))
+    }
+
+    pub fn plan_refresh_materialized_view(
+        &self,
+        name: &sqlparser::ast::ObjectName,
+    ) -> BustubxResult<LogicalPlan> {
+        let name = self.bind_table_name(name)?;
+        let Some(view) = self
+            .context
+            .catalog
+            .view(&name)
+            .filter(|view| view.materialized)
+        else {
+            return Err(BustubxError::Plan(format!(
+                "\"{}\" is not a materialized view",
+                name.table()
+            )));
+        };
+        let input = self.plan_view(&name, &view)?;
+        Ok(LogicalPlan::RefreshMaterializedView(
+            RefreshMaterializedView {
+                name,
+                input: Arc::new(input),
+            },
+        ))
+    }
+
+    /// Plans the query of a view, its columns take the view name as relation.
+    /// Unqualified names in the query resolve in the schema of the view only.
+    pub fn plan_view(
+        &self,
+        view_ref: &TableReference,
+        view: &CatalogView,
+    ) -> BustubxResult<LogicalPlan> {
+        let stmts = crate::parser::parse_sql(&view.definition)?;
+        let [sqlparser::ast::Statement::Query(query)] = stmts.as_slice() else {
+            return Err(BustubxError::Internal(format!(
+                "view {} is not defined by a query: {}",
+                view.name, view.definition
+            )));
+        };
+
+        // common table expressions of the referencing query are not visible inside the view
+        let outer_ctes = self.context.ctes.replace(HashMap::new());
+        let outer_search_path = self.context.search_path.replace(
+            view_ref
+                .schema()
+                .map(|schema| vec![schema.to_string()])
+                .unwrap_or_else(|| self.context.search_path.borrow().clone()),
+        );
+        // a view of an attached database only sees that database
+        let outer_catalog = self.context.current_catalog.replace(
+            view_ref
+                .catalog()
+                .filter(|catalog| *catalog != DEFAULT_CATALOG_NAME)
+                .map(|catalog| catalog.to_string()),
+        );
+        let plan = self.plan_query(query);
+        self.context.ctes.replace(outer_ctes);
+        self.context.search_path.replace(outer_search_path);
+        self.context.current_catalog.replace(outer_catalog);
+
+        let alias = sqlparser::ast::TableAlias {
+            name: sqlparser::ast::Ident::new(view.name.clone()),
+            columns: view
+                .columns
+                .iter()
+                .map(|column| sqlparser::ast::Ident::new(column.clone()))
+                .collect(),
+        };
+        self.plan_subquery_alias(plan?, &alias)
+    }
+}
@@ -0,0 +1,258 @@
+use crate::catalog::Schema;
+use crate::common::TableReference;
+use crate::expression::{Cast, ColumnExpr, Expr};
+use crate::planner::logical_plan::{CteScan, LogicalPlan, Project, RecursiveQuery, WorkTableScan};
+use crate::planner::LogicalPlanner;
+use crate::{BustubxError, BustubxResult};
+use std::sync::Arc;
+
+impl LogicalPlanner<'_> {
+    /// Plans the CTEs of a `WITH` clause and registers them in the planner context,
+    /// returns the ids of the registered CTEs.
+    pub fn plan_with(&self, with: &sqlparser::ast::With) -> BustubxResult<Vec<usize>> {
+        let mut cte_ids = vec![];
+        let mut cte_names = vec![];
+        for cte in with.cte_tables.iter() {
+            let name = cte.alias.name.value.clone();
+            if cte_names.contains(&name) {
+                return Err(BustubxError::Plan(format!(
+                    "WITH query name \"{}\" specified more than once",
+                    name
+                )));
+            }
+
+            let plan = if with.recursive {
+                self.plan_recursive_cte(cte)?
+            } else {
+                self.plan_cte(cte)?
+            };
+
+            let cte_id = self.context.next_cte_id.get();
+            self.context.next_cte_id.set(cte_id + 1);
+            self.context.ctes.borrow_mut().insert(
+                name.clone(),
+                LogicalPlan::CteScan(CteScan::new(cte_id, name.clone(), Arc::new(plan))),
+            );
+            cte_ids.push(cte_id);
+            cte_names.push(name);
+        }
+        Ok(cte_ids)
+    }
+
+    fn plan_cte(&self, cte: &sqlparser::ast::Cte) -> BustubxResult<LogicalPlan> {
+        let plan = self.plan_query(&cte.query)?;
+        self.plan_subquery_alias(plan, &cte.alias)
+    }
+
+    fn plan_recursive_cte(&self, cte: &sqlparser::ast::Cte) -> BustubxResult<LogicalPlan> {
+        let name = cte.alias.name.value.clone();
+        let query = cte.query.as_ref();
+        let sqlparser::ast::SetExpr::SetOperation {
+            op: sqlparser::ast::SetOperator::Union,
+            set_quantifier,
+            left,
+            right,
+        } = query.body.as_ref()
+        else {
+            return self.plan_cte(cte);
+        };
+        if query.with.is_some()
+            || !query.order_by.is_empty()
+            || query.limit.is_some()
+            || query.offset.is_some()
+        {
+            return Err(BustubxError::Plan(format!(
+                "recursive query \"{}\" does not support WITH, ORDER BY, LIMIT or OFFSET",
+                name
+            )));
+        }
+
+        let static_term = self.plan_set_expr(left)?;
+        let static_term = self.plan_subquery_alias(static_term, &cte.alias)?;
+
+        // while planning the recursive term, the CTE name refers to the working table
+        let work_table = LogicalPlan::WorkTableScan(WorkTableScan::new(
+            name.clone(),
+            static_term.schema().clone(),
+        ));
+        let shadowed = self
+            .context
+            .ctes
+            .borrow_mut()
+            .insert(name.clone(), work_table);
+        let recursive_term = self.plan_set_expr(right);
+        match shadowed {
+            Some(plan) => self.context.ctes.borrow_mut().insert(name.clone(), plan),
+            None => self.context.ctes.borrow_mut().remove(&name),
+        };
+        let recursive_term = recursive_term?;
+
+        if !contains_work_table_scan(&recursive_term, &name) {
+            return self.plan_cte(cte);
+        }
+        let (static_term, recursive_term) =
+            coerce_recursive_terms(static_term, recursive_term, &name)?;
+
+        Ok(LogicalPlan::RecursiveQuery(RecursiveQuery {
+            name,
+            static_term: Arc::new(static_term),
+            recursive_term: Arc::new(recursive_term),
+            is_distinct: !matches!(set_quantifier, sqlparser::ast::SetQuantifier::All),
+        }))
+    }
+
+    /// Renames the output of `input` to `alias`, optionally renaming its columns too.
+    pub fn plan_subquery_alias(
+        &self,
+        input: LogicalPlan,
+        alias: &sqlparser::ast::TableAlias,
+    ) -> BustubxResult<LogicalPlan> {
+        let input_schema = input.schema().clone();
+        if alias.columns.len() > input_schema.column_count() {
+            return Err(BustubxError::Plan(format!(
+                "table \"{}\" has {} columns available but {} columns specified",
+                alias.name.value,
+                input_schema.column_count(),
+                alias.columns.len()
+            )));
+        }
+
+        let relation = TableReference::bare(alias.name.value.clone());
+        let mut exprs = vec![];
+        let mut columns = vec![];
+        for (idx, col) in input_schema.columns.iter().enumerate() {
+            let name = alias
+                .columns
+                .get(idx)
+                .map(|ident| ident.value.clone())
+                .unwrap_or_else(|| col.name.clone());
+            exprs.push(Expr::Column(ColumnExpr {
+                relation: col.relation.clone(),
+                name: col.name.clone(),
+            }));
+            columns.push(
+                col.as_ref()
+                    .clone()
+                    .with_name(name)
+                    .with_relation(Some(relation.clone())),
+            );
+        }
+        Ok(LogicalPlan::Project(Project {
+            exprs,
+            input: Arc::new(input),
+            schema: Arc::new(Schema::new(columns)),
+        }))
+    }
+
+    /// Inlines the CTEs which are referenced at most once, others remain as [`CteScan`]
+    /// and will be materialized once during execution.
+    pub fn inline_ctes(&self, plan: LogicalPlan, cte_ids: &[usize]) -> BustubxResult<LogicalPlan> {
+        let mut plan = plan;
+        for cte_id in cte_ids {
+            if count_cte_scans(&plan, *cte_id) <= 1 {
+                plan = inline_cte_scan(&plan, *cte_id)?;
+            }
+        }
+        Ok(plan)
+    }
+}
+
+fn contains_work_table_scan(plan: &LogicalPlan, name: &str) -> bool {
+    match plan {
+        LogicalPlan::WorkTableScan(scan) => scan.name == name,
+        _ => plan
+            .inputs()
+            .into_iter()
+            .any(|input| contains_work_table_scan(input, name)),
+    }
+}
+
+fn count_cte_scans(plan: &LogicalPlan, cte_id: usize) -> usize {
+    let count = match plan {
+        LogicalPlan::CteScan(scan) if scan.cte_id == cte_id => 1,
+        _ => 0,
+    };
+    count
+        + plan
+            .inputs()
+            .into_iter()
+            .map(|input| count_cte_scans(input, cte_id))
+            .sum::<usize>()
+}
+
+fn inline_cte_scan(plan: &LogicalPlan, cte_id: usize) -> BustubxResult<LogicalPlan> {
+    if let LogicalPlan::CteScan(scan) = plan {
+        if scan.cte_id == cte_id {
+            return inline_cte_scan(&scan.input, cte_id);
+        }
+    }
+    let inputs = plan.inputs();
+    if inputs.is_empty() {
+        return Ok(plan.clone());
+    }
+    let new_inputs = inputs
+        .into_iter()
+        .map(|input| inline_cte_scan(input, cte_id))
+        .collect::<BustubxResult<Vec<LogicalPlan>>>()?;
+    plan.with_new_inputs(&new_inputs)
+}
+
+/// Makes the recursive term produce the same schema as the static term.
+fn coerce_recursive_terms(
+    static_term: LogicalPlan,
+    recursive_term: LogicalPlan,
+    name: &str,
+) -> BustubxResult<(LogicalPlan, LogicalPlan)> {
+    let static_schema = static_term.schema().clone();
+    let recursive_schema = recursive_term.schema().clone();
+    if static_schema.column_count() != recursive_schema.column_count() {
+        return Err(BustubxError::Plan(format!(
+            "recursive query \"{}\" has {} columns in non-recursive term but {} columns overall",
+            name,
+            static_schema.column_count(),
+            recursive_schema.column_count()
+        )));
+    }
+
+    let mut exprs = vec![];
+    let mut columns = vec![];
+    for (static_col, recursive_col) in static_schema
+        .columns
+        .iter()
+        .zip(recursive_schema.columns.iter())
+    {
+        let column_expr = Expr::Column(ColumnExpr {
+            relation: recursive_col.relation.clone(),
+            name: recursive_col.name.clone(),
+        });
+        exprs.push(if static_col.data_type == recursive_col.data_type {
+            column_expr
+        } else {
+            Expr::Cast(Cast {
+                expr: Box::new(column_expr),
+                data_type: static_col.data_type,
+            })
+        });
+        columns.push(
+            static_col
+                .as_ref()
+                .clone()
+                .with_nullable(static_col.nullable || recursive_col.nullable),
+        );
+    }
+    let schema = Arc::new(Schema::new(columns));
+
+    let static_term = match static_term {
+        LogicalPlan::Project(project) => LogicalPlan::Project(Project {
+            schema: schema.clone(),
+            ..project
+        }),
+        _ => static_term,
+    };
+    let recursive_term = LogicalPlan::Project(Project {
+        exprs,
+        input: Arc::new(recursive_term),
+        schema,
+    });
+    Ok((static_term, recursive_term))
+}
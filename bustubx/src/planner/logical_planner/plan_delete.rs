@@ -0,0 +1,49 @@
+use crate::planner::logical_plan::{Delete, LogicalPlan};
+use crate::planner::LogicalPlanner;
+use crate::{BustubxError, BustubxResult};
+
+impl<'a> LogicalPlanner<'a> {
+    pub fn plan_delete(
+        &self,
+        from: &[sqlparser::ast::TableWithJoins],
+        selection: &Option<sqlparser::ast::Expr>,
+        returning: &Option<Vec<sqlparser::ast::SelectItem>>,
+    ) -> BustubxResult<LogicalPlan> {
+        let table_ref = match from {
+            [sqlparser::ast::TableWithJoins { relation, joins }] if joins.is_empty() => {
+                match relation {
+                    sqlparser::ast::TableFactor::Table { name, .. } => {
+                        self.bind_table_name(name)?
+                    }
+                    _ => {
+                        return Err(BustubxError::Plan(format!(
+                            "table {} is not supported",
+                            relation
+                        )))
+                    }
+                }
+            }
+            _ => {
+                return Err(BustubxError::Plan(
+                    "delete from multiple tables is not supported".to_string(),
+                ))
+            }
+        };
+
+        self.check_table_writable(&table_ref)?;
+        let table_schema = self.context.catalog.table_heap(&table_ref)?.schema.clone();
+
+        let selection = match selection {
+            Some(e) => Some(self.bind_expr(e)?),
+            None => None,
+        };
+        let returning = self.bind_returning(&table_schema, returning)?;
+
+        Ok(LogicalPlan::Delete(Delete {
+            table: table_ref,
+            table_schema,
+            selection,
+            returning,
+        }))
+    }
+}
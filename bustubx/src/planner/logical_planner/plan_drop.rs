@@ -0,0 +1,29 @@
+use crate::planner::logical_plan::{DropSchema, LogicalPlan};
+use crate::{BustubxError, BustubxResult};
+
+use super::LogicalPlanner;
+
+impl<'a> LogicalPlanner<'a> {
+    pub fn plan_drop(
+        &self,
+        object_type: &sqlparser::ast::ObjectType,
+        if_exists: bool,
+        names: &[sqlparser::ast::ObjectName],
+        cascade: bool,
+    ) -> BustubxResult<LogicalPlan> {
+        match object_type {
+            sqlparser::ast::ObjectType::Schema => Ok(LogicalPlan::DropSchema(DropSchema {
+                names: names
+                    .iter()
+                    .map(|name| self.bind_schema_name(name))
+                    .collect::<BustubxResult<Vec<String>>>()?,
+                if_exists,
+                cascade,
+            })),
+            _ => Err(BustubxError::NotSupport(format!(
+                "DROP {} not supported",
+                object_type
+            ))),
+        }
+    }
+}
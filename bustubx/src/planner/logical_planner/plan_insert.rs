@@ -1,7 +1,9 @@
-use crate::BustubxResult;
+use crate::catalog::{Constraint, IdentityGeneration, SchemaRef};
+use crate::common::TableReference;
+use crate::{BustubxError, BustubxResult};
 use std::sync::Arc;
 
-use crate::planner::logical_plan::{Insert, LogicalPlan, Values};
+use crate::planner::logical_plan::{Insert, LogicalPlan, OnConflict, OnConflictAction, Values};
 
 use super::LogicalPlanner;
 
@@ -11,9 +13,12 @@ impl<'a> LogicalPlanner<'a> {
         table_name: &sqlparser::ast::ObjectName,
         columns_ident: &Vec<sqlparser::ast::Ident>,
         source: &sqlparser::ast::Query,
+        on: &Option<sqlparser::ast::OnInsert>,
+        returning: &Option<Vec<sqlparser::ast::SelectItem>>,
     ) -> BustubxResult<LogicalPlan> {
         let mut input = self.plan_set_expr(source.body.as_ref())?;
         let table = self.bind_table_name(table_name)?;
+        self.check_table_writable(&table)?;
         let table_schema = self.context.catalog.table_heap(&table)?.schema.clone();
 
         let projected_schema = if columns_ident.is_empty() {
@@ -25,12 +30,43 @@ impl<'a> LogicalPlanner<'a> {
                 .collect();
             let indices = columns
                 .iter()
-                .map(|name| table_schema.index_of(Some(&table), name.as_str()))
+                .map(|name| table_schema.index_of(None, name.as_str()))
                 .collect::<BustubxResult<Vec<usize>>>()?;
 
             Arc::new(table_schema.project(&indices)?)
         };
 
+        // values of identity columns generated always come from their sequence only
+        if let Some(col) = projected_schema
+            .columns
+            .iter()
+            .find(|col| col.identity == Some(IdentityGeneration::Always))
+        {
+            return Err(BustubxError::Plan(format!(
+                "cannot insert a non-DEFAULT value into column \"{}\"",
+                col.name
+            )));
+        }
+
+        let source_widths = match &input {
+            LogicalPlan::Values(Values { values, .. }) => {
+                values.iter().map(|row| row.len()).collect()
+            }
+            input => vec![input.schema().column_count()],
+        };
+        for width in source_widths {
+            if width > projected_schema.column_count() {
+                return Err(BustubxError::Plan(
+                    "INSERT has more expressions than target columns".to_string(),
+                ));
+            }
+            if width < projected_schema.column_count() {
+                return Err(BustubxError::Plan(
+                    "INSERT has more target columns than expressions".to_string(),
+                ));
+            }
+        }
+
         if let LogicalPlan::Values(Values { values, .. }) = input {
             input = LogicalPlan::Values(Values {
                 values,
@@ -38,11 +74,100 @@ impl<'a> LogicalPlanner<'a> {
             })
         }
 
+        let on_conflict = match on {
+            Some(sqlparser::ast::OnInsert::OnConflict(on_conflict)) => {
+                Some(self.bind_on_conflict(&table, &table_schema, on_conflict)?)
+            }
+            Some(on) => {
+                return Err(BustubxError::NotSupport(format!(
+                    "insert {} not supported",
+                    on
+                )))
+            }
+            None => None,
+        };
+        let returning = self.bind_returning(&table_schema, returning)?;
+
         Ok(LogicalPlan::Insert(Insert {
             table,
             table_schema,
             projected_schema,
             input: Arc::new(input),
+            on_conflict,
+            returning,
         }))
     }
+
+    /// Resolves the conflict target to unique constraints of the table, no target means
+    /// any unique constraint.
+    fn bind_on_conflict(
+        &self,
+        table: &TableReference,
+        table_schema: &SchemaRef,
+        on_conflict: &sqlparser::ast::OnConflict,
+    ) -> BustubxResult<OnConflict> {
+        let unique_constraints = self
+            .context
+            .catalog
+            .table_constraints(table)?
+            .into_iter()
+            .filter(|c| c.kind.is_unique())
+            .collect::<Vec<Constraint>>();
+        let constraints = match &on_conflict.conflict_target {
+            Some(sqlparser::ast::ConflictTarget::Columns(idents)) => {
+                let columns = idents
+                    .iter()
+                    .map(|ident| ident.value.clone())
+                    .collect::<Vec<String>>();
+                let Some(constraint) = unique_constraints.into_iter().find(|c| {
+                    c.columns.len() == columns.len()
+                        && c.columns.iter().all(|col| columns.contains(col))
+                }) else {
+                    return Err(BustubxError::Plan(
+                        "there is no unique constraint matching the ON CONFLICT specification"
+                            .to_string(),
+                    ));
+                };
+                vec![constraint]
+            }
+            Some(sqlparser::ast::ConflictTarget::OnConstraint(name)) => {
+                let name = name.to_string();
+                let Some(constraint) = unique_constraints.into_iter().find(|c| c.name == name)
+                else {
+                    return Err(BustubxError::Plan(format!(
+                        "unique constraint \"{}\" for table \"{}\" does not exist",
+                        name,
+                        table.table()
+                    )));
+                };
+                vec![constraint]
+            }
+            None => {
+                if matches!(
+                    on_conflict.action,
+                    sqlparser::ast::OnConflictAction::DoUpdate(_)
+                ) {
+                    return Err(BustubxError::Plan(
+                        "ON CONFLICT DO UPDATE requires a conflict target".to_string(),
+                    ));
+                }
+                unique_constraints
+            }
+        };
+
+        let action = match &on_conflict.action {
+            sqlparser::ast::OnConflictAction::DoNothing => OnConflictAction::DoNothing,
+            sqlparser::ast::OnConflictAction::DoUpdate(do_update) => OnConflictAction::DoUpdate {
+                assignments: self.bind_assignments(table_schema, &do_update.assignments)?,
+                selection: match &do_update.selection {
+                    Some(e) => Some(self.bind_expr(e)?),
+                    None => None,
+                },
+            },
+        };
+        Ok(OnConflict {
+            constraints,
+            action,
+        })
+    }
 }
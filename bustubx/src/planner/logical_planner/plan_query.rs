@@ -9,6 +9,21 @@ use super::LogicalPlanner;
 
 impl<'a> LogicalPlanner<'a> {
     pub fn plan_query(&self, query: &sqlparser::ast::Query) -> BustubxResult<LogicalPlan> {
+        let Some(with) = &query.with else {
+            return self.plan_query_body(query);
+        };
+
+        // CTEs are only visible inside this query
+        let outer_ctes = self.context.ctes.borrow().clone();
+        let plan = self.plan_with(with).and_then(|cte_ids| {
+            let plan = self.plan_query_body(query)?;
+            self.inline_ctes(plan, &cte_ids)
+        });
+        *self.context.ctes.borrow_mut() = outer_ctes;
+        plan
+    }
+
+    fn plan_query_body(&self, query: &sqlparser::ast::Query) -> BustubxResult<LogicalPlan> {
         let plan = self.plan_set_expr(&query.body)?;
         let plan = self.plan_order_by(plan, &query.order_by)?;
         self.plan_limit(plan, &query.limit, &query.offset)
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use crate::catalog::{Schema, SchemaRef};
+use crate::expression::ExprTrait;
+use crate::planner::logical_plan::{EmptyRelation, LogicalPlan, Returning};
+use crate::BustubxResult;
+
+use super::LogicalPlanner;
+
+impl<'a> LogicalPlanner<'a> {
+    /// Binds the RETURNING list of a statement modifying a table with `table_schema`.
+    pub fn bind_returning(
+        &self,
+        table_schema: &SchemaRef,
+        returning: &Option<Vec<sqlparser::ast::SelectItem>>,
+    ) -> BustubxResult<Option<Returning>> {
+        let Some(returning) = returning else {
+            return Ok(None);
+        };
+        let input = LogicalPlan::EmptyRelation(EmptyRelation {
+            produce_one_row: false,
+            schema: table_schema.clone(),
+        });
+        let mut exprs = vec![];
+        for item in returning {
+            exprs.extend(self.bind_select_item(&input, item)?);
+        }
+        let columns = exprs
+            .iter()
+            .map(|expr| expr.to_column(table_schema))
+            .collect::<BustubxResult<Vec<_>>>()?;
+        Ok(Some(Returning {
+            exprs,
+            schema: Arc::new(Schema::new(columns)),
+        }))
+    }
+}
@@ -1,8 +1,10 @@
-use crate::catalog::{Column, Schema};
-use crate::expression::{columnize_expr, Alias, ColumnExpr, Expr, ExprTrait};
+use crate::catalog::{Column, DataType, Schema, SchemaRef, DEFAULT_CATALOG_NAME};
+use crate::expression::{
+    columnize_expr, find_window_exprs, Alias, Cast, ColumnExpr, Expr, ExprTrait, WindowFunction,
+};
 use crate::planner::logical_plan::{
-    build_join_schema, project_schema, EmptyRelation, Filter, Join, LogicalPlan, Project,
-    TableScan, Values,
+    build_join_schema, project_schema, EmptyRelation, Except, Filter, Intersect, Join, LogicalPlan,
+    OrderByExpr, Project, Sort, TableScan, Union, Values, Window,
 };
 use crate::planner::logical_plan::{Aggregate, JoinType};
 use crate::planner::LogicalPlanner;
@@ -15,6 +17,13 @@ impl LogicalPlanner<'_> {
         match set_expr {
             sqlparser::ast::SetExpr::Select(select) => self.plan_select(select),
             sqlparser::ast::SetExpr::Values(values) => self.plan_values(values),
+            sqlparser::ast::SetExpr::Query(query) => self.plan_query(query),
+            sqlparser::ast::SetExpr::SetOperation {
+                op,
+                set_quantifier,
+                left,
+                right,
+            } => self.plan_set_operation(op, set_quantifier, left, right),
             _ => Err(BustubxError::Plan(format!(
                 "Failed to plan set expr: {}",
                 set_expr
@@ -22,11 +31,117 @@ impl LogicalPlanner<'_> {
         }
     }
 
+    pub fn plan_set_operation(
+        &self,
+        op: &sqlparser::ast::SetOperator,
+        set_quantifier: &sqlparser::ast::SetQuantifier,
+        left: &sqlparser::ast::SetExpr,
+        right: &sqlparser::ast::SetExpr,
+    ) -> BustubxResult<LogicalPlan> {
+        let is_all = match set_quantifier {
+            sqlparser::ast::SetQuantifier::All => true,
+            sqlparser::ast::SetQuantifier::Distinct | sqlparser::ast::SetQuantifier::None => false,
+        };
+        let left = self.plan_set_expr(left)?;
+        let right = self.plan_set_expr(right)?;
+        let (left, right, schema) = coerce_set_operation_inputs(left, right, op)?;
+        let left = Arc::new(left);
+        let right = Arc::new(right);
+        match op {
+            sqlparser::ast::SetOperator::Union => Ok(LogicalPlan::Union(Union {
+                left,
+                right,
+                is_all,
+                schema,
+            })),
+            sqlparser::ast::SetOperator::Intersect => Ok(LogicalPlan::Intersect(Intersect {
+                left,
+                right,
+                is_all,
+                schema,
+            })),
+            sqlparser::ast::SetOperator::Except => Ok(LogicalPlan::Except(Except {
+                left,
+                right,
+                is_all,
+                schema,
+            })),
+        }
+    }
+
     pub fn plan_select(&self, select: &sqlparser::ast::Select) -> BustubxResult<LogicalPlan> {
         let table_scan = self.plan_from_tables(&select.from)?;
         let selection = self.plan_selection(table_scan, &select.selection)?;
         let aggregate = self.plan_aggregate(selection, &select.projection, &select.group_by)?;
-        self.plan_project(aggregate, &select.projection)
+        let window = self.plan_window(aggregate, &select.projection)?;
+        self.plan_project(window, &select.projection)
+    }
+
+    pub fn plan_window(
+        &self,
+        input: LogicalPlan,
+        project: &[sqlparser::ast::SelectItem],
+    ) -> BustubxResult<LogicalPlan> {
+        let mut exprs = vec![];
+        for select_item in project {
+            exprs.extend(self.bind_select_item(&input, select_item)?);
+        }
+
+        // window functions with the same PARTITION BY and ORDER BY share one sort
+        let mut groups: Vec<(Vec<Expr>, Vec<OrderByExpr>, Vec<Expr>)> = vec![];
+        for expr in find_window_exprs(&exprs) {
+            let Expr::WindowFunction(WindowFunction {
+                partition_by,
+                order_by,
+                ..
+            }) = &expr
+            else {
+                continue;
+            };
+            match groups
+                .iter_mut()
+                .find(|(p, o, _)| p == partition_by && o == order_by)
+            {
+                Some((_, _, window_exprs)) => window_exprs.push(expr.clone()),
+                None => groups.push((partition_by.clone(), order_by.clone(), vec![expr.clone()])),
+            }
+        }
+
+        let mut plan = input;
+        for (partition_by, order_by, window_exprs) in groups {
+            let mut sort_exprs = partition_by
+                .into_iter()
+                .map(|expr| OrderByExpr {
+                    expr: Box::new(expr),
+                    asc: true,
+                    nulls_first: false,
+                })
+                .collect::<Vec<OrderByExpr>>();
+            sort_exprs.extend(order_by);
+            if !sort_exprs.is_empty() {
+                plan = LogicalPlan::Sort(Sort {
+                    order_by: sort_exprs,
+                    input: Arc::new(plan),
+                    limit: None,
+                });
+            }
+
+            let mut columns = plan
+                .schema()
+                .columns
+                .iter()
+                .map(|col| col.as_ref().clone())
+                .collect::<Vec<Column>>();
+            for expr in window_exprs.iter() {
+                columns.push(expr.to_column(plan.schema())?);
+            }
+            plan = LogicalPlan::Window(Window {
+                input: Arc::new(plan),
+                window_exprs,
+                schema: Arc::new(Schema::new(columns)),
+            });
+        }
+        Ok(plan)
     }
 
     pub fn plan_aggregate(
@@ -265,15 +380,63 @@ impl LogicalPlanner<'_> {
         relation: &sqlparser::ast::TableFactor,
     ) -> BustubxResult<LogicalPlan> {
         match relation {
-            sqlparser::ast::TableFactor::Table { name, .. } => {
+            sqlparser::ast::TableFactor::Table { name, alias, .. } => {
+                if let [ident] = name.0.as_slice() {
+                    let cte = self.context.ctes.borrow().get(&ident.value).cloned();
+                    if let Some(cte) = cte {
+                        return match alias {
+                            Some(alias) => self.plan_subquery_alias(cte, alias),
+                            None => Ok(cte),
+                        };
+                    }
+                }
                 // TODO handle alias
                 let table_ref = self.bind_table_name(name)?;
+                if let Some(view) = self.context.catalog.view(&table_ref) {
+                    // a materialized view is scanned like its table
+                    if !view.materialized {
+                        let plan = self.plan_view(&table_ref, &view)?;
+                        return match alias {
+                            Some(alias) => self.plan_subquery_alias(plan, alias),
+                            None => Ok(plan),
+                        };
+                    }
+                }
                 let schema = self.context.catalog.table_heap(&table_ref)?.schema.clone();
-                Ok(LogicalPlan::TableScan(TableScan {
-                    table_ref,
-                    table_schema: schema,
+                let scan = LogicalPlan::TableScan(TableScan {
+                    table_ref: table_ref.clone(),
+                    table_schema: schema.clone(),
                     filters: vec![],
                     limit: None,
+                    projection: None,
+                    projected_schema: schema.clone(),
+                });
+                if table_ref
+                    .catalog()
+                    .is_none_or(|catalog| catalog == DEFAULT_CATALOG_NAME)
+                {
+                    return Ok(scan);
+                }
+                // columns of an attached table are qualified by the database they are reached through
+                let exprs = schema
+                    .columns
+                    .iter()
+                    .map(|col| {
+                        Expr::Column(ColumnExpr {
+                            relation: col.relation.clone(),
+                            name: col.name.clone(),
+                        })
+                    })
+                    .collect();
+                let columns = schema
+                    .columns
+                    .iter()
+                    .map(|col| col.as_ref().clone().with_relation(Some(table_ref.clone())))
+                    .collect();
+                Ok(LogicalPlan::Project(Project {
+                    exprs,
+                    input: Arc::new(scan),
+                    schema: Arc::new(Schema::new(columns)),
                 }))
             }
             sqlparser::ast::TableFactor::NestedJoin {
@@ -283,7 +446,15 @@ impl LogicalPlanner<'_> {
                 // TODO handle alias
                 self.plan_table_with_joins(table_with_joins)
             }
-            sqlparser::ast::TableFactor::Derived { subquery, .. } => self.plan_query(subquery),
+            sqlparser::ast::TableFactor::Derived {
+                subquery, alias, ..
+            } => {
+                let plan = self.plan_query(subquery)?;
+                match alias {
+                    Some(alias) => self.plan_subquery_alias(plan, alias),
+                    None => Ok(plan),
+                }
+            }
             _ => Err(BustubxError::Plan(format!(
                 "sqlparser relation {} not supported",
                 relation
@@ -308,3 +479,81 @@ impl LogicalPlanner<'_> {
         }))
     }
 }
+
+/// Casts the columns of both inputs to their common types, the output takes
+/// column names from the left input.
+fn coerce_set_operation_inputs(
+    left: LogicalPlan,
+    right: LogicalPlan,
+    op: &sqlparser::ast::SetOperator,
+) -> BustubxResult<(LogicalPlan, LogicalPlan, SchemaRef)> {
+    let left_schema = left.schema().clone();
+    let right_schema = right.schema().clone();
+    if left_schema.column_count() != right_schema.column_count() {
+        return Err(BustubxError::Plan(format!(
+            "each {} query must have the same number of columns, {} vs {}",
+            op,
+            left_schema.column_count(),
+            right_schema.column_count()
+        )));
+    }
+
+    let mut data_types = vec![];
+    let mut columns = vec![];
+    for (left_col, right_col) in left_schema.columns.iter().zip(right_schema.columns.iter()) {
+        let data_type = DataType::set_operation_coercion(&left_col.data_type, &right_col.data_type)
+            .map_err(|_| {
+                BustubxError::Plan(format!(
+                    "{} types {} and {} cannot be matched",
+                    op, left_col.data_type, right_col.data_type
+                ))
+            })?;
+        data_types.push(data_type);
+        columns.push(
+            left_col
+                .as_ref()
+                .clone()
+                .with_data_type(data_type)
+                .with_nullable(left_col.nullable || right_col.nullable),
+        );
+    }
+
+    let left = cast_to_types(left, &data_types);
+    let right = cast_to_types(right, &data_types);
+    Ok((left, right, Arc::new(Schema::new(columns))))
+}
+
+fn cast_to_types(input: LogicalPlan, data_types: &[DataType]) -> LogicalPlan {
+    let input_schema = input.schema().clone();
+    if input_schema
+        .columns
+        .iter()
+        .zip(data_types.iter())
+        .all(|(col, data_type)| col.data_type == *data_type)
+    {
+        return input;
+    }
+
+    let mut exprs = vec![];
+    let mut columns = vec![];
+    for (col, data_type) in input_schema.columns.iter().zip(data_types.iter()) {
+        let column_expr = Expr::Column(ColumnExpr {
+            relation: col.relation.clone(),
+            name: col.name.clone(),
+        });
+        exprs.push(if col.data_type == *data_type {
+            column_expr
+        } else {
+            Expr::Cast(Cast {
+                expr: Box::new(column_expr),
+                data_type: *data_type,
+            })
+        });
+        columns.push(col.as_ref().clone().with_data_type(*data_type));
+    }
+    LogicalPlan::Project(Project {
+        exprs,
+        input: Arc::new(input),
+        schema: Arc::new(Schema::new(columns)),
+    })
+}
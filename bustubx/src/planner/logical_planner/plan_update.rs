@@ -1,3 +1,5 @@
+use crate::catalog::{IdentityGeneration, SchemaRef};
+use crate::expression::Expr;
 use crate::planner::logical_plan::{LogicalPlan, Update};
 use crate::planner::LogicalPlanner;
 use crate::{BustubxError, BustubxResult};
@@ -9,6 +11,7 @@ impl<'a> LogicalPlanner<'a> {
         table: &sqlparser::ast::TableWithJoins,
         assignments: &[sqlparser::ast::Assignment],
         selection: &Option<sqlparser::ast::Expr>,
+        returning: &Option<Vec<sqlparser::ast::SelectItem>>,
     ) -> BustubxResult<LogicalPlan> {
         let table_ref = match &table.relation {
             sqlparser::ast::TableFactor::Table { name, .. } => self.bind_table_name(name)?,
@@ -20,8 +23,31 @@ impl<'a> LogicalPlanner<'a> {
             }
         };
 
+        self.check_table_writable(&table_ref)?;
         let table_schema = self.context.catalog.table_heap(&table_ref)?.schema.clone();
 
+        let assignment_map = self.bind_assignments(&table_schema, assignments)?;
+
+        let selection = match selection {
+            Some(e) => Some(self.bind_expr(e)?),
+            None => None,
+        };
+        let returning = self.bind_returning(&table_schema, returning)?;
+
+        Ok(LogicalPlan::Update(Update {
+            table: table_ref,
+            table_schema,
+            assignments: assignment_map,
+            selection,
+            returning,
+        }))
+    }
+
+    pub fn bind_assignments(
+        &self,
+        table_schema: &SchemaRef,
+        assignments: &[sqlparser::ast::Assignment],
+    ) -> BustubxResult<HashMap<String, Expr>> {
         let mut assignment_map = HashMap::new();
         for assign in assignments {
             let column_name = assign
@@ -33,20 +59,17 @@ impl<'a> LogicalPlanner<'a> {
                 )))?
                 .value
                 .clone();
+            if table_schema.column_with_name(None, &column_name)?.identity
+                == Some(IdentityGeneration::Always)
+            {
+                return Err(BustubxError::Plan(format!(
+                    "column \"{}\" can only be updated to DEFAULT",
+                    column_name
+                )));
+            }
             let value = self.bind_expr(&assign.value)?;
             assignment_map.insert(column_name, value);
         }
-
-        let selection = match selection {
-            Some(e) => Some(self.bind_expr(e)?),
-            None => None,
-        };
-
-        Ok(LogicalPlan::Update(Update {
-            table: table_ref,
-            table_schema,
-            assignments: assignment_map,
-            selection,
-        }))
+        Ok(assignment_map)
     }
 }
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use crate::catalog::{Column, DataType, Schema, DEFAULT_SCHEMA_NAME};
+use crate::common::ScalarValue;
+use crate::expression::{Expr, Literal};
+use crate::planner::logical_plan::{LogicalPlan, SetVariable, Values};
+use crate::storage::BPLUS_DEFAULT_FILL_FACTOR;
+use crate::{BustubxError, BustubxResult};
+
+use super::LogicalPlanner;
+
+static SEARCH_PATH: &str = "search_path";
+static INDEX_FILL_FACTOR: &str = "index_fill_factor";
+
+impl<'a> LogicalPlanner<'a> {
+    pub fn plan_set_variable(
+        &self,
+        variable: &sqlparser::ast::ObjectName,
+        value: &[sqlparser::ast::Expr],
+    ) -> BustubxResult<LogicalPlan> {
+        let variable = variable.to_string().to_lowercase();
+        if variable != SEARCH_PATH && variable != INDEX_FILL_FACTOR {
+            return Err(BustubxError::NotSupport(format!(
+                "SET {} not supported",
+                variable
+            )));
+        }
+        let mut values = vec![];
+        for expr in value {
+            match expr {
+                sqlparser::ast::Expr::Identifier(ident)
+                    if ident.quote_style.is_none()
+                        && ident.value.eq_ignore_ascii_case("default") =>
+                {
+                    if variable == SEARCH_PATH {
+                        values.push(DEFAULT_SCHEMA_NAME.to_string())
+                    } else {
+                        values.push(BPLUS_DEFAULT_FILL_FACTOR.to_string())
+                    }
+                }
+                sqlparser::ast::Expr::Identifier(ident) => values.push(ident.value.clone()),
+                sqlparser::ast::Expr::Value(sqlparser::ast::Value::Number(n, _))
+                    if variable == INDEX_FILL_FACTOR =>
+                {
+                    values.push(n.clone())
+                }
+                sqlparser::ast::Expr::Value(sqlparser::ast::Value::SingleQuotedString(s)) => {
+                    values.push(s.clone())
+                }
+                _ => {
+                    return Err(BustubxError::Plan(format!(
+                        "invalid value for parameter \"{}\": {}",
+                        variable, expr
+                    )))
+                }
+            }
+        }
+        Ok(LogicalPlan::SetVariable(SetVariable { variable, values }))
+    }
+
+    pub fn plan_show_variable(
+        &self,
+        variable: &[sqlparser::ast::Ident],
+    ) -> BustubxResult<LogicalPlan> {
+        let variable = variable
+            .iter()
+            .map(|ident| ident.value.to_lowercase())
+            .collect::<Vec<String>>()
+            .join(" ");
+        let value = if variable == SEARCH_PATH {
+            self.context.search_path.borrow().join(", ")
+        } else if variable == INDEX_FILL_FACTOR {
+            self.context.catalog.index_fill_factor.to_string()
+        } else {
+            return Err(BustubxError::NotSupport(format!(
+                "SHOW {} not supported",
+                variable
+            )));
+        };
+        Ok(LogicalPlan::Values(Values {
+            schema: Arc::new(Schema::new(vec![Column::new(
+                variable,
+                DataType::Varchar(None),
+                false,
+            )])),
+            values: vec![vec![Expr::Literal(Literal {
+                value: ScalarValue::Varchar(Some(value)),
+            })]],
+        }))
+    }
+}
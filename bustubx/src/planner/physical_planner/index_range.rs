@@ -0,0 +1,254 @@
+use std::cmp::Ordering;
+use std::ops::Bound;
+use std::sync::Arc;
+
+use crate::catalog::{CatalogTable, Column, Schema};
+use crate::common::{ScalarValue, TableReference};
+use crate::expression::{Between, BinaryExpr, BinaryOp, ColumnExpr, Expr, Literal};
+use crate::storage::Tuple;
+
+/// A key range of an index to scan a table with.
+pub struct IndexRange {
+    pub index_name: String,
+    pub start_bound: Bound<Tuple>,
+    pub end_bound: Bound<Tuple>,
+}
+
+/// Picks the index whose leading key columns are bound by equality predicates of the
+/// filters, followed by at most one range-bound column, preferring more bound columns.
+/// `None` if no index key starts with a bound column.
+pub fn select_index_range(
+    table_ref: &TableReference,
+    catalog_table: &CatalogTable,
+    filters: &[Expr],
+) -> Option<IndexRange> {
+    let predicates = filters
+        .iter()
+        .flat_map(|expr| column_predicates(table_ref, expr))
+        .collect::<Vec<(String, BinaryOp, ScalarValue)>>();
+    if predicates.is_empty() {
+        return None;
+    }
+
+    let mut index_names = catalog_table.indexes.keys().collect::<Vec<&String>>();
+    index_names.sort();
+    let mut best: Option<((usize, bool), IndexRange)> = None;
+    for index_name in index_names {
+        let key_schema = &catalog_table.indexes[index_name].key_schema;
+        let mut eq_values = vec![];
+        let mut range = None;
+        for column in key_schema.columns.iter() {
+            let column_predicates = predicates
+                .iter()
+                .filter(|(name, _, _)| name == &column.name)
+                .filter_map(|(_, op, value)| Some((op, exact_cast(value, column)?)))
+                .collect::<Vec<(&BinaryOp, ScalarValue)>>();
+            if let Some((_, value)) = column_predicates
+                .iter()
+                .find(|(op, _)| **op == BinaryOp::Eq)
+            {
+                eq_values.push(value.clone());
+                continue;
+            }
+            let mut lower = None;
+            let mut upper = None;
+            for (op, value) in column_predicates {
+                match op {
+                    BinaryOp::Gt => lower = tighter(lower, (value, false), Ordering::Greater),
+                    BinaryOp::GtEq => lower = tighter(lower, (value, true), Ordering::Greater),
+                    BinaryOp::Lt => upper = tighter(upper, (value, false), Ordering::Less),
+                    BinaryOp::LtEq => upper = tighter(upper, (value, true), Ordering::Less),
+                    _ => {}
+                }
+            }
+            if lower.is_some() || upper.is_some() {
+                // larger values come first in a descending column
+                range = Some(if column.descending {
+                    (upper, lower)
+                } else {
+                    (lower, upper)
+                });
+            }
+            break;
+        }
+        let score = (eq_values.len(), range.is_some());
+        if score == (0, false)
+            || best
+                .as_ref()
+                .is_some_and(|(best_score, _)| *best_score >= score)
+        {
+            continue;
+        }
+
+        let (start, end) = range.unwrap_or((None, None));
+        let index_range = IndexRange {
+            index_name: index_name.clone(),
+            start_bound: key_bound(key_schema, &eq_values, start),
+            end_bound: key_bound(key_schema, &eq_values, end),
+        };
+        best = Some((score, index_range));
+    }
+    best.map(|(_, index_range)| index_range)
+}
+
+/// Picks the hash index whose every key column is bound by an equality predicate of the
+/// filters, preferring longer keys, along with the key to look up.
+pub fn select_hash_index(
+    table_ref: &TableReference,
+    catalog_table: &CatalogTable,
+    filters: &[Expr],
+) -> Option<(String, Tuple)> {
+    let predicates = filters
+        .iter()
+        .flat_map(|expr| column_predicates(table_ref, expr))
+        .filter(|(_, op, _)| *op == BinaryOp::Eq)
+        .collect::<Vec<(String, BinaryOp, ScalarValue)>>();
+    if predicates.is_empty() {
+        return None;
+    }
+
+    let mut index_names = catalog_table.hash_indexes.keys().collect::<Vec<&String>>();
+    index_names.sort();
+    let mut best: Option<(String, Tuple)> = None;
+    for index_name in index_names {
+        let key_schema = &catalog_table.hash_indexes[index_name].key_schema;
+        let Some(key) = key_schema
+            .columns
+            .iter()
+            .map(|column| {
+                predicates
+                    .iter()
+                    .filter(|(name, _, _)| name == &column.name)
+                    .find_map(|(_, _, value)| exact_cast(value, column))
+            })
+            .collect::<Option<Vec<ScalarValue>>>()
+        else {
+            continue;
+        };
+        if best
+            .as_ref()
+            .is_some_and(|(_, best_key)| best_key.data.len() >= key.len())
+        {
+            continue;
+        }
+        best = Some((index_name.clone(), Tuple::new(key_schema.clone(), key)));
+    }
+    best
+}
+
+/// Positions of the columns of `schema` in an index key, `None` if the key lacks one.
+pub fn key_positions(key_schema: &Schema, schema: &Schema) -> Option<Vec<usize>> {
+    schema
+        .columns
+        .iter()
+        .map(|col| {
+            key_schema
+                .columns
+                .iter()
+                .position(|key_col| key_col.name == col.name)
+        })
+        .collect()
+}
+
+/// Bound of the keys starting with `eq_values` and then `value` if given.
+fn key_bound(
+    key_schema: &Schema,
+    eq_values: &[ScalarValue],
+    value: Option<(ScalarValue, bool)>,
+) -> Bound<Tuple> {
+    let mut data = eq_values.to_vec();
+    let included = match value {
+        Some((value, included)) => {
+            data.push(value);
+            included
+        }
+        None if data.is_empty() => return Bound::Unbounded,
+        None => true,
+    };
+    let prefix_schema = Schema {
+        columns: key_schema.columns[..data.len()].to_vec(),
+    };
+    let tuple = Tuple::new(Arc::new(prefix_schema), data);
+    if included {
+        Bound::Included(tuple)
+    } else {
+        Bound::Excluded(tuple)
+    }
+}
+
+/// Keeps the bound whose value compares `preferred` to the other, the exclusive one if equal.
+fn tighter(
+    current: Option<(ScalarValue, bool)>,
+    candidate: (ScalarValue, bool),
+    preferred: Ordering,
+) -> Option<(ScalarValue, bool)> {
+    let Some(current) = current else {
+        return Some(candidate);
+    };
+    match candidate.0.partial_cmp(&current.0) {
+        Some(order) if order == preferred => Some(candidate),
+        Some(Ordering::Equal) => Some((current.0, current.1 && candidate.1)),
+        _ => Some(current),
+    }
+}
+
+/// The literal in the type of the key column, `None` if the cast would change its value.
+fn exact_cast(value: &ScalarValue, column: &Column) -> Option<ScalarValue> {
+    if value.is_null() {
+        return None;
+    }
+    let casted = value.cast_to(&column.data_type).ok()?;
+    (casted.cast_to(&value.data_type()).ok()? == *value).then_some(casted)
+}
+
+/// `column op literal` comparisons on the scanned table an expression implies.
+fn column_predicates(
+    table_ref: &TableReference,
+    expr: &Expr,
+) -> Vec<(String, BinaryOp, ScalarValue)> {
+    let is_scanned_column = |column: &ColumnExpr| {
+        column
+            .relation
+            .as_ref()
+            .is_none_or(|relation| relation.resolved_eq(table_ref))
+    };
+    match expr {
+        Expr::Binary(BinaryExpr { left, op, right }) => match (left.as_ref(), right.as_ref()) {
+            (Expr::Column(column), Expr::Literal(Literal { value }))
+                if is_scanned_column(column) =>
+            {
+                vec![(column.name.clone(), *op, value.clone())]
+            }
+            (Expr::Literal(Literal { value }), Expr::Column(column))
+                if is_scanned_column(column) =>
+            {
+                let op = match op {
+                    BinaryOp::Gt => BinaryOp::Lt,
+                    BinaryOp::GtEq => BinaryOp::LtEq,
+                    BinaryOp::Lt => BinaryOp::Gt,
+                    BinaryOp::LtEq => BinaryOp::GtEq,
+                    op => *op,
+                };
+                vec![(column.name.clone(), op, value.clone())]
+            }
+            _ => vec![],
+        },
+        Expr::Between(Between {
+            expr,
+            negated: false,
+            low,
+            high,
+        }) => match (expr.as_ref(), low.as_ref(), high.as_ref()) {
+            (
+                Expr::Column(column),
+                Expr::Literal(Literal { value: low }),
+                Expr::Literal(Literal { value: high }),
+            ) if is_scanned_column(column) => vec![
+                (column.name.clone(), BinaryOp::GtEq, low.clone()),
+                (column.name.clone(), BinaryOp::LtEq, high.clone()),
+            ],
+            _ => vec![],
+        },
+        _ => vec![],
+    }
+}
@@ -1,3 +1,4 @@
+mod index_range;
 mod physical_planner;
 
 pub use physical_planner::PhysicalPlanner;
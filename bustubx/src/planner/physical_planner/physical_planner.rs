@@ -1,9 +1,18 @@
-use crate::catalog::{Catalog, Schema, DEFAULT_SCHEMA_NAME};
-use std::sync::Arc;
+use crate::catalog::{Catalog, Schema};
+use crate::expression::{ColumnExpr, Expr};
+use crate::planner::physical_planner::index_range::{
+    key_positions, select_hash_index, select_index_range,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Bound;
+use std::sync::{Arc, Mutex};
 
 use crate::planner::logical_plan::{
-    Aggregate, CreateIndex, CreateTable, EmptyRelation, Filter, Insert, Join, Limit, LogicalPlan,
-    Project, Sort, TableScan, Update, Values,
+    Aggregate, AttachDatabase, CreateIndex, CreateSchema, CreateSequence, CreateTable, CreateView,
+    CteScan, Delete, DetachDatabase, DropSchema, EmptyRelation, Except, Filter, Insert, Intersect,
+    Join, Limit, LogicalPlan, Project, RecursiveQuery, RefreshMaterializedView, SetVariable, Sort,
+    TableScan, Union, Update, Values, Window, WorkTableScan,
 };
 
 use crate::execution::physical_plan::PhysicalLimit;
@@ -13,42 +22,231 @@ use crate::execution::physical_plan::PhysicalProject;
 use crate::execution::physical_plan::PhysicalSeqScan;
 use crate::execution::physical_plan::PhysicalSort;
 use crate::execution::physical_plan::PhysicalValues;
+use crate::execution::physical_plan::PhysicalWindow;
 use crate::execution::physical_plan::{PhysicalAggregate, PhysicalCreateTable};
-use crate::execution::physical_plan::{PhysicalCreateIndex, PhysicalEmpty};
-use crate::execution::physical_plan::{PhysicalFilter, PhysicalIndexScan};
-use crate::execution::physical_plan::{PhysicalInsert, PhysicalUpdate};
+use crate::execution::physical_plan::{
+    PhysicalAttachDatabase, PhysicalCreateSchema, PhysicalDetachDatabase, PhysicalDropSchema,
+    PhysicalSetVariable,
+};
+use crate::execution::physical_plan::{PhysicalCreateIndex, PhysicalCreateSequence, PhysicalEmpty};
+use crate::execution::physical_plan::{PhysicalCreateView, PhysicalRefreshMaterializedView};
+use crate::execution::physical_plan::{
+    PhysicalCteScan, PhysicalRecursiveQuery, PhysicalWorkTableScan, WorkTable,
+};
+use crate::execution::physical_plan::{PhysicalDelete, PhysicalInsert, PhysicalUpdate};
+use crate::execution::physical_plan::{PhysicalExcept, PhysicalIntersect, PhysicalUnion};
+use crate::execution::physical_plan::{
+    PhysicalFilter, PhysicalHashIndexScan, PhysicalIndexOnlyScan, PhysicalIndexScan,
+};
 
 pub struct PhysicalPlanner<'a> {
     pub catalog: &'a Catalog,
+    /// Scans of the same CTE share its physical plan and materialized result
+    cte_scans: RefCell<HashMap<usize, PhysicalCteScan>>,
+    /// Work tables of the recursive queries being built, keyed by CTE name
+    work_tables: RefCell<HashMap<String, WorkTable>>,
 }
 
-impl PhysicalPlanner<'_> {
+impl<'a> PhysicalPlanner<'a> {
+    pub fn new(catalog: &'a Catalog) -> Self {
+        Self {
+            catalog,
+            cte_scans: RefCell::new(HashMap::new()),
+            work_tables: RefCell::new(HashMap::new()),
+        }
+    }
+
     pub fn create_physical_plan(&self, logical_plan: LogicalPlan) -> PhysicalPlan {
         let logical_plan = Arc::new(logical_plan);
         self.build_plan(logical_plan)
     }
 
+    fn build_table_scan(&self, scan: &TableScan) -> PhysicalPlan {
+        let TableScan {
+            table_ref,
+            table_schema,
+            filters,
+            projection,
+            projected_schema,
+            ..
+        } = scan;
+        // a full key bound by equalities is looked up in a hash index
+        let hash_lookup = self
+            .catalog
+            .catalog_table(table_ref)
+            .and_then(|catalog_table| select_hash_index(table_ref, catalog_table, filters));
+        // TODO fix testing
+        let index_scan = self
+            .catalog
+            .catalog_table(table_ref)
+            .and_then(|catalog_table| {
+                if let Some(index_range) = select_index_range(table_ref, catalog_table, filters) {
+                    return Some((
+                        index_range.index_name,
+                        (index_range.start_bound, index_range.end_bound),
+                    ));
+                }
+                // without a usable range prefer an index holding every output column
+                let mut index_names = catalog_table.indexes.keys().collect::<Vec<&String>>();
+                index_names.sort();
+                let index_name = index_names
+                    .iter()
+                    .find(|name| {
+                        projection.is_some()
+                            && key_positions(
+                                &catalog_table.indexes[**name].key_schema,
+                                projected_schema,
+                            )
+                            .is_some()
+                    })
+                    .or(index_names.first())?;
+                Some(((*index_name).clone(), (Bound::Unbounded, Bound::Unbounded)))
+            });
+
+        let plan = match (hash_lookup, index_scan) {
+            (Some((index_name, key)), _) => {
+                PhysicalPlan::HashIndexScan(PhysicalHashIndexScan::new(
+                    table_ref.clone(),
+                    index_name,
+                    table_schema.clone(),
+                    key,
+                ))
+            }
+            (None, Some((index_name, range))) => {
+                let index = self.catalog.index(table_ref, &index_name).ok().flatten();
+                if let Some(key_positions) = index
+                    .filter(|_| projection.is_some())
+                    .and_then(|index| key_positions(&index.key_schema, projected_schema))
+                {
+                    return PhysicalPlan::IndexOnlyScan(PhysicalIndexOnlyScan::new(
+                        table_ref.clone(),
+                        index_name,
+                        projected_schema.clone(),
+                        key_positions,
+                        range,
+                    ));
+                }
+                PhysicalPlan::IndexScan(PhysicalIndexScan::new(
+                    table_ref.clone(),
+                    index_name,
+                    table_schema.clone(),
+                    range,
+                ))
+            }
+            (None, None) => PhysicalPlan::SeqScan(PhysicalSeqScan::new(
+                table_ref.clone(),
+                table_schema.clone(),
+            )),
+        };
+        if projection.is_none() {
+            return plan;
+        }
+        let exprs = projected_schema
+            .columns
+            .iter()
+            .map(|col| {
+                Expr::Column(ColumnExpr {
+                    relation: col.relation.clone(),
+                    name: col.name.clone(),
+                })
+            })
+            .collect();
+        PhysicalPlan::Project(PhysicalProject::new(
+            exprs,
+            projected_schema.clone(),
+            Arc::new(plan),
+        ))
+    }
+
     fn build_plan(&self, logical_plan: Arc<LogicalPlan>) -> PhysicalPlan {
         let plan = match logical_plan.as_ref() {
-            LogicalPlan::CreateTable(CreateTable { name, columns }) => PhysicalPlan::CreateTable(
-                PhysicalCreateTable::new(name.clone(), Schema::new(columns.clone())),
-            ),
+            LogicalPlan::CreateSchema(CreateSchema {
+                name,
+                if_not_exists,
+            }) => {
+                PhysicalPlan::CreateSchema(PhysicalCreateSchema::new(name.clone(), *if_not_exists))
+            }
+            LogicalPlan::DropSchema(DropSchema {
+                names,
+                if_exists,
+                cascade,
+            }) => PhysicalPlan::DropSchema(PhysicalDropSchema::new(
+                names.clone(),
+                *if_exists,
+                *cascade,
+            )),
+            LogicalPlan::SetVariable(SetVariable { variable, values }) => {
+                PhysicalPlan::SetVariable(PhysicalSetVariable::new(
+                    variable.clone(),
+                    values.clone(),
+                ))
+            }
+            LogicalPlan::AttachDatabase(AttachDatabase { path, name }) => {
+                PhysicalPlan::AttachDatabase(PhysicalAttachDatabase::new(
+                    path.clone(),
+                    name.clone(),
+                ))
+            }
+            LogicalPlan::DetachDatabase(DetachDatabase { name }) => {
+                PhysicalPlan::DetachDatabase(PhysicalDetachDatabase::new(name.clone()))
+            }
+            LogicalPlan::CreateTable(CreateTable {
+                name,
+                columns,
+                constraints,
+                sequences,
+            }) => PhysicalPlan::CreateTable(PhysicalCreateTable::new(
+                name.clone(),
+                Schema::new(columns.clone()),
+                constraints.clone(),
+                sequences.clone(),
+            )),
             LogicalPlan::CreateIndex(CreateIndex {
                 index_name,
                 table,
                 table_schema,
                 columns,
+                index_type,
             }) => PhysicalPlan::CreateIndex(PhysicalCreateIndex::new(
                 index_name.clone(),
                 table.clone(),
                 table_schema.clone(),
                 columns.clone(),
+                *index_type,
             )),
+            LogicalPlan::CreateSequence(CreateSequence { name, options }) => {
+                PhysicalPlan::CreateSequence(PhysicalCreateSequence::new(name.clone(), *options))
+            }
+            LogicalPlan::CreateView(CreateView {
+                name,
+                definition,
+                columns,
+                materialized,
+                input,
+            }) => {
+                let input_physical_plan = self.build_plan(input.clone());
+                PhysicalPlan::CreateView(PhysicalCreateView::new(
+                    name.clone(),
+                    definition.clone(),
+                    columns.clone(),
+                    *materialized,
+                    Arc::new(input_physical_plan),
+                ))
+            }
+            LogicalPlan::RefreshMaterializedView(RefreshMaterializedView { name, input }) => {
+                let input_physical_plan = self.build_plan(input.clone());
+                PhysicalPlan::RefreshMaterializedView(PhysicalRefreshMaterializedView::new(
+                    name.clone(),
+                    Arc::new(input_physical_plan),
+                ))
+            }
             LogicalPlan::Insert(Insert {
                 table,
                 table_schema,
                 projected_schema,
                 input,
+                on_conflict,
+                returning,
             }) => {
                 let input_physical_plan = self.build_plan(input.clone());
                 PhysicalPlan::Insert(PhysicalInsert::new(
@@ -56,6 +254,8 @@ impl PhysicalPlanner<'_> {
                     table_schema.clone(),
                     projected_schema.clone(),
                     Arc::new(input_physical_plan),
+                    on_conflict.clone(),
+                    returning.clone(),
                 ))
             }
             LogicalPlan::Values(Values { schema, values }) => {
@@ -80,41 +280,7 @@ impl PhysicalPlanner<'_> {
                     Arc::new(input_physical_plan),
                 ))
             }
-            LogicalPlan::TableScan(TableScan {
-                table_ref,
-                table_schema,
-                filters: _,
-                limit: _,
-            }) => {
-                // TODO fix testing
-                if let Some(catalog_table) = self
-                    .catalog
-                    .schemas
-                    .get(table_ref.schema().unwrap_or(DEFAULT_SCHEMA_NAME))
-                    .unwrap()
-                    .tables
-                    .get(table_ref.table())
-                {
-                    if !catalog_table.indexes.is_empty() {
-                        PhysicalPlan::IndexScan(PhysicalIndexScan::new(
-                            table_ref.clone(),
-                            catalog_table.indexes.keys().next().unwrap().clone(),
-                            table_schema.clone(),
-                            ..,
-                        ))
-                    } else {
-                        PhysicalPlan::SeqScan(PhysicalSeqScan::new(
-                            table_ref.clone(),
-                            table_schema.clone(),
-                        ))
-                    }
-                } else {
-                    PhysicalPlan::SeqScan(PhysicalSeqScan::new(
-                        table_ref.clone(),
-                        table_schema.clone(),
-                    ))
-                }
-            }
+            LogicalPlan::TableScan(scan) => self.build_table_scan(scan),
             LogicalPlan::Limit(Limit {
                 limit,
                 offset,
@@ -182,12 +348,151 @@ impl PhysicalPlanner<'_> {
                 table_schema,
                 assignments,
                 selection,
+                returning,
             }) => PhysicalPlan::Update(PhysicalUpdate::new(
                 table.clone(),
                 table_schema.clone(),
                 assignments.clone(),
                 selection.clone(),
+                returning.clone(),
             )),
+            LogicalPlan::Delete(Delete {
+                table,
+                selection,
+                returning,
+                ..
+            }) => PhysicalPlan::Delete(PhysicalDelete::new(
+                table.clone(),
+                selection.clone(),
+                returning.clone(),
+            )),
+            LogicalPlan::CteScan(CteScan {
+                cte_id,
+                name,
+                input,
+            }) => {
+                let shared = self
+                    .cte_scans
+                    .borrow()
+                    .get(cte_id)
+                    .map(|scan| (scan.input.clone(), scan.result.clone()));
+                let (input_physical_plan, result) = match shared {
+                    Some(shared) => shared,
+                    None => {
+                        let input_physical_plan = Arc::new(self.build_plan(input.clone()));
+                        let result = Arc::new(Mutex::new(None));
+                        self.cte_scans.borrow_mut().insert(
+                            *cte_id,
+                            PhysicalCteScan::new(
+                                name.clone(),
+                                input_physical_plan.clone(),
+                                result.clone(),
+                            ),
+                        );
+                        (input_physical_plan, result)
+                    }
+                };
+                PhysicalPlan::CteScan(PhysicalCteScan::new(
+                    name.clone(),
+                    input_physical_plan,
+                    result,
+                ))
+            }
+            LogicalPlan::RecursiveQuery(RecursiveQuery {
+                name,
+                static_term,
+                recursive_term,
+                is_distinct,
+            }) => {
+                let work_table: WorkTable = Arc::new(Mutex::new(vec![]));
+                let static_physical_plan = self.build_plan(static_term.clone());
+                let shadowed = self
+                    .work_tables
+                    .borrow_mut()
+                    .insert(name.clone(), work_table.clone());
+                let recursive_physical_plan = self.build_plan(recursive_term.clone());
+                match shadowed {
+                    Some(shadowed) => self.work_tables.borrow_mut().insert(name.clone(), shadowed),
+                    None => self.work_tables.borrow_mut().remove(name),
+                };
+                PhysicalPlan::RecursiveQuery(PhysicalRecursiveQuery::new(
+                    name.clone(),
+                    Arc::new(static_physical_plan),
+                    Arc::new(recursive_physical_plan),
+                    *is_distinct,
+                    work_table,
+                ))
+            }
+            LogicalPlan::WorkTableScan(WorkTableScan { name, schema }) => {
+                let work_table = self
+                    .work_tables
+                    .borrow()
+                    .get(name)
+                    .cloned()
+                    .expect("work table scan should be inside its recursive query");
+                PhysicalPlan::WorkTableScan(PhysicalWorkTableScan::new(
+                    name.clone(),
+                    schema.clone(),
+                    work_table,
+                ))
+            }
+            LogicalPlan::Union(Union {
+                left,
+                right,
+                is_all,
+                schema,
+            }) => {
+                let left_physical_plan = self.build_plan(left.clone());
+                let right_physical_plan = self.build_plan(right.clone());
+                PhysicalPlan::Union(PhysicalUnion::new(
+                    Arc::new(left_physical_plan),
+                    Arc::new(right_physical_plan),
+                    *is_all,
+                    schema.clone(),
+                ))
+            }
+            LogicalPlan::Intersect(Intersect {
+                left,
+                right,
+                is_all,
+                schema,
+            }) => {
+                let left_physical_plan = self.build_plan(left.clone());
+                let right_physical_plan = self.build_plan(right.clone());
+                PhysicalPlan::Intersect(PhysicalIntersect::new(
+                    Arc::new(left_physical_plan),
+                    Arc::new(right_physical_plan),
+                    *is_all,
+                    schema.clone(),
+                ))
+            }
+            LogicalPlan::Except(Except {
+                left,
+                right,
+                is_all,
+                schema,
+            }) => {
+                let left_physical_plan = self.build_plan(left.clone());
+                let right_physical_plan = self.build_plan(right.clone());
+                PhysicalPlan::Except(PhysicalExcept::new(
+                    Arc::new(left_physical_plan),
+                    Arc::new(right_physical_plan),
+                    *is_all,
+                    schema.clone(),
+                ))
+            }
+            LogicalPlan::Window(Window {
+                input,
+                window_exprs,
+                schema,
+            }) => {
+                let input_physical_plan = self.build_plan(input.clone());
+                PhysicalPlan::Window(PhysicalWindow::new(
+                    Arc::new(input_physical_plan),
+                    window_exprs.clone(),
+                    schema.clone(),
+                ))
+            }
         };
         plan
     }
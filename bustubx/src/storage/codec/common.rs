@@ -155,6 +155,23 @@ impl CommonCodec {
         Ok((i64::from_be_bytes(data), 8))
     }
 
+    pub fn encode_i128(data: i128) -> Vec<u8> {
+        data.to_be_bytes().to_vec()
+    }
+
+    pub fn decode_i128(bytes: &[u8]) -> BustubxResult<DecodedData<i128>> {
+        if bytes.len() < 16 {
+            return Err(BustubxError::Storage(format!(
+                "bytes length {} is less than {}",
+                bytes.len(),
+                16
+            )));
+        }
+        let mut data = [0u8; 16];
+        data.copy_from_slice(&bytes[0..16]);
+        Ok((i128::from_be_bytes(data), 16))
+    }
+
     pub fn encode_f32(data: f32) -> Vec<u8> {
         data.to_be_bytes().to_vec()
     }
@@ -265,6 +282,12 @@ mod tests {
                 .unwrap()
                 .0
         );
+        assert_eq!(
+            -5i128,
+            CommonCodec::decode_i128(&CommonCodec::encode_i128(-5i128))
+                .unwrap()
+                .0
+        );
         assert_eq!(
             5.0f32,
             CommonCodec::decode_f32(&CommonCodec::encode_f32(5.0f32))
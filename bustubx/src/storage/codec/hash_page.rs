@@ -0,0 +1,215 @@
+use crate::buffer::BUSTUBX_PAGE_SIZE;
+use crate::catalog::SchemaRef;
+use crate::storage::codec::{CommonCodec, DecodedData, RidCodec, TupleCodec};
+use crate::storage::{
+    HashBucketPage, HashBucketPageHeader, HashDirectoryPage, HashDirectoryPageHeader,
+};
+use crate::{BustubxError, BustubxResult};
+
+pub struct HashDirectoryPageCodec;
+
+impl HashDirectoryPageCodec {
+    pub fn encode(page: &HashDirectoryPage) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend(HashDirectoryPageHeaderCodec::encode(&page.header));
+        for (page_id, local_depth) in page.bucket_page_ids.iter().zip(page.local_depths.iter()) {
+            bytes.extend(CommonCodec::encode_u32(*page_id));
+            bytes.extend(CommonCodec::encode_u32(*local_depth));
+        }
+        // make sure length of bytes is BUSTUBX_PAGE_SIZE
+        assert!(bytes.len() <= BUSTUBX_PAGE_SIZE);
+        bytes.extend(vec![0; BUSTUBX_PAGE_SIZE - bytes.len()]);
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> BustubxResult<DecodedData<HashDirectoryPage>> {
+        if bytes.len() != BUSTUBX_PAGE_SIZE {
+            return Err(BustubxError::Storage(format!(
+                "Hash directory page size is not {} instead of {}",
+                BUSTUBX_PAGE_SIZE,
+                bytes.len()
+            )));
+        }
+        let mut left_bytes = bytes;
+
+        let (header, offset) = HashDirectoryPageHeaderCodec::decode(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
+
+        let size = 1usize << header.global_depth;
+        let mut bucket_page_ids = Vec::with_capacity(size);
+        let mut local_depths = Vec::with_capacity(size);
+        for _ in 0..size {
+            let (page_id, offset) = CommonCodec::decode_u32(left_bytes)?;
+            left_bytes = &left_bytes[offset..];
+
+            let (local_depth, offset) = CommonCodec::decode_u32(left_bytes)?;
+            left_bytes = &left_bytes[offset..];
+
+            bucket_page_ids.push(page_id);
+            local_depths.push(local_depth);
+        }
+
+        Ok((
+            HashDirectoryPage {
+                header,
+                bucket_page_ids,
+                local_depths,
+            },
+            BUSTUBX_PAGE_SIZE,
+        ))
+    }
+}
+
+pub struct HashDirectoryPageHeaderCodec;
+
+impl HashDirectoryPageHeaderCodec {
+    pub fn encode(header: &HashDirectoryPageHeader) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend(CommonCodec::encode_u32(header.global_depth));
+        bytes.extend(CommonCodec::encode_u32(header.max_depth));
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> BustubxResult<DecodedData<HashDirectoryPageHeader>> {
+        let mut left_bytes = bytes;
+
+        let (global_depth, offset) = CommonCodec::decode_u32(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
+
+        let (max_depth, offset) = CommonCodec::decode_u32(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
+
+        Ok((
+            HashDirectoryPageHeader {
+                global_depth,
+                max_depth,
+            },
+            bytes.len() - left_bytes.len(),
+        ))
+    }
+}
+
+pub struct HashBucketPageCodec;
+
+impl HashBucketPageCodec {
+    pub fn encode(page: &HashBucketPage) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend(HashBucketPageHeaderCodec::encode(&page.header));
+        for (tuple, rid) in page.array.iter() {
+            bytes.extend(TupleCodec::encode(tuple));
+            bytes.extend(RidCodec::encode(rid));
+        }
+        // make sure length of bytes is BUSTUBX_PAGE_SIZE
+        assert!(bytes.len() <= BUSTUBX_PAGE_SIZE);
+        bytes.extend(vec![0; BUSTUBX_PAGE_SIZE - bytes.len()]);
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8], schema: SchemaRef) -> BustubxResult<DecodedData<HashBucketPage>> {
+        if bytes.len() != BUSTUBX_PAGE_SIZE {
+            return Err(BustubxError::Storage(format!(
+                "Hash bucket page size is not {} instead of {}",
+                BUSTUBX_PAGE_SIZE,
+                bytes.len()
+            )));
+        }
+        let mut left_bytes = bytes;
+
+        let (header, offset) = HashBucketPageHeaderCodec::decode(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
+
+        let mut array = vec![];
+        for _ in 0..header.current_size {
+            let (tuple, offset) = TupleCodec::decode(left_bytes, schema.clone())?;
+            left_bytes = &left_bytes[offset..];
+
+            let (rid, offset) = RidCodec::decode(left_bytes)?;
+            left_bytes = &left_bytes[offset..];
+
+            array.push((tuple, rid));
+        }
+
+        Ok((
+            HashBucketPage {
+                schema,
+                header,
+                array,
+            },
+            BUSTUBX_PAGE_SIZE,
+        ))
+    }
+}
+
+pub struct HashBucketPageHeaderCodec;
+
+impl HashBucketPageHeaderCodec {
+    pub fn encode(header: &HashBucketPageHeader) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend(CommonCodec::encode_u32(header.current_size));
+        bytes.extend(CommonCodec::encode_u32(header.max_size));
+        bytes.extend(CommonCodec::encode_u32(header.next_page_id));
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> BustubxResult<DecodedData<HashBucketPageHeader>> {
+        let mut left_bytes = bytes;
+
+        let (current_size, offset) = CommonCodec::decode_u32(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
+
+        let (max_size, offset) = CommonCodec::decode_u32(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
+
+        let (next_page_id, offset) = CommonCodec::decode_u32(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
+
+        Ok((
+            HashBucketPageHeader {
+                current_size,
+                max_size,
+                next_page_id,
+            },
+            bytes.len() - left_bytes.len(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::catalog::{Column, DataType, Schema};
+    use crate::storage::codec::{HashBucketPageCodec, HashDirectoryPageCodec};
+    use crate::storage::{HashBucketPage, HashDirectoryPage, RecordId};
+    use crate::Tuple;
+    use std::sync::Arc;
+
+    #[test]
+    fn hash_page_codec() {
+        let mut directory_page = HashDirectoryPage::new(3, 8);
+        directory_page.grow();
+        directory_page.bucket_page_ids[1] = 4;
+        directory_page.local_depths = vec![1, 1];
+        let (new_page, _) =
+            HashDirectoryPageCodec::decode(&HashDirectoryPageCodec::encode(&directory_page))
+                .unwrap();
+        assert_eq!(new_page, directory_page);
+
+        let schema = Arc::new(Schema::new(vec![
+            Column::new("a", DataType::Int8, true),
+            Column::new("b", DataType::Varchar(None), true),
+        ]));
+        let mut bucket_page = HashBucketPage::new(schema.clone(), 10);
+        bucket_page.insert(
+            Tuple::new(schema.clone(), vec![1i8.into(), "a".to_string().into()]),
+            RecordId::new(1, 1),
+        );
+        bucket_page.insert(
+            Tuple::new(schema.clone(), vec![2i8.into(), "b".to_string().into()]),
+            RecordId::new(2, 2),
+        );
+        bucket_page.header.next_page_id = 5;
+        let (new_page, _) =
+            HashBucketPageCodec::decode(&HashBucketPageCodec::encode(&bucket_page), schema.clone())
+                .unwrap();
+        assert_eq!(new_page, bucket_page);
+    }
+}
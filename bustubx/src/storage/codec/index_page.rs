@@ -1,11 +1,13 @@
 use crate::buffer::BUSTUBX_PAGE_SIZE;
+use crate::catalog::DataType;
 use crate::catalog::SchemaRef;
-use crate::storage::codec::{CommonCodec, DecodedData, RidCodec, TupleCodec};
+use crate::common::{DynamicBitmap, ScalarValue};
+use crate::storage::codec::{CommonCodec, DecodedData, RidCodec, ScalarValueCodec};
 use crate::storage::{
-    BPlusTreeInternalPage, BPlusTreeInternalPageHeader, BPlusTreeLeafPage, BPlusTreeLeafPageHeader,
-    BPlusTreePage, BPlusTreePageType,
+    BPlusTreeHeaderPage, BPlusTreeInternalPage, BPlusTreeInternalPageHeader, BPlusTreeLeafPage,
+    BPlusTreeLeafPageHeader, BPlusTreePage, BPlusTreePageType, InternalKV, LeafKV,
 };
-use crate::{BustubxError, BustubxResult};
+use crate::{BustubxError, BustubxResult, Tuple};
 
 pub struct BPlusTreePageCodec;
 
@@ -42,15 +44,39 @@ impl BPlusTreePageCodec {
     }
 }
 
+pub struct BPlusTreeHeaderPageCodec;
+
+impl BPlusTreeHeaderPageCodec {
+    pub fn encode(page: &BPlusTreeHeaderPage) -> Vec<u8> {
+        let mut bytes = CommonCodec::encode_u32(page.root_page_id);
+        bytes.extend(vec![0; BUSTUBX_PAGE_SIZE - bytes.len()]);
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> BustubxResult<DecodedData<BPlusTreeHeaderPage>> {
+        if bytes.len() != BUSTUBX_PAGE_SIZE {
+            return Err(BustubxError::Storage(format!(
+                "Index header page size is not {} instead of {}",
+                BUSTUBX_PAGE_SIZE,
+                bytes.len()
+            )));
+        }
+        let (root_page_id, _) = CommonCodec::decode_u32(bytes)?;
+        Ok((BPlusTreeHeaderPage { root_page_id }, BUSTUBX_PAGE_SIZE))
+    }
+}
+
 pub struct BPlusTreeLeafPageCodec;
 
 impl BPlusTreeLeafPageCodec {
     pub fn encode(page: &BPlusTreeLeafPage) -> Vec<u8> {
         let mut bytes = vec![];
         bytes.extend(BPlusTreeLeafPageHeaderCodec::encode(&page.header));
+        let mut prev_key = None;
         for (tuple, rid) in page.array.iter() {
-            bytes.extend(TupleCodec::encode(tuple));
+            bytes.extend(IndexKeyCodec::encode(tuple, prev_key));
             bytes.extend(RidCodec::encode(rid));
+            prev_key = Some(tuple);
         }
         // make sure length of bytes is BUSTUBX_PAGE_SIZE
         assert!(bytes.len() <= BUSTUBX_PAGE_SIZE);
@@ -78,9 +104,10 @@ impl BPlusTreeLeafPageCodec {
             let (header, offset) = BPlusTreeLeafPageHeaderCodec::decode(left_bytes)?;
             left_bytes = &left_bytes[offset..];
 
-            let mut array = vec![];
+            let mut array: Vec<LeafKV> = vec![];
             for _ in 0..header.current_size {
-                let (tuple, offset) = TupleCodec::decode(left_bytes, schema.clone())?;
+                let prev_key = array.last().map(|kv| &kv.0);
+                let (tuple, offset) = IndexKeyCodec::decode(left_bytes, schema.clone(), prev_key)?;
                 left_bytes = &left_bytes[offset..];
 
                 let (rid, offset) = RidCodec::decode(left_bytes)?;
@@ -111,9 +138,11 @@ impl BPlusTreeInternalPageCodec {
     pub fn encode(page: &BPlusTreeInternalPage) -> Vec<u8> {
         let mut bytes = vec![];
         bytes.extend(BPlusTreeInternalPageHeaderCodec::encode(&page.header));
+        let mut prev_key = None;
         for (tuple, page_id) in page.array.iter() {
-            bytes.extend(TupleCodec::encode(tuple));
+            bytes.extend(IndexKeyCodec::encode(tuple, prev_key));
             bytes.extend(CommonCodec::encode_u32(*page_id));
+            prev_key = Some(tuple);
         }
         // make sure length of bytes is BUSTUBX_PAGE_SIZE
         assert!(bytes.len() <= BUSTUBX_PAGE_SIZE);
@@ -141,9 +170,10 @@ impl BPlusTreeInternalPageCodec {
             let (header, offset) = BPlusTreeInternalPageHeaderCodec::decode(left_bytes)?;
             left_bytes = &left_bytes[offset..];
 
-            let mut array = vec![];
+            let mut array: Vec<InternalKV> = vec![];
             for _ in 0..header.current_size {
-                let (tuple, offset) = TupleCodec::decode(left_bytes, schema.clone())?;
+                let prev_key = array.last().map(|kv| &kv.0);
+                let (tuple, offset) = IndexKeyCodec::decode(left_bytes, schema.clone(), prev_key)?;
                 left_bytes = &left_bytes[offset..];
 
                 let (page_id, offset) = CommonCodec::decode_u32(left_bytes)?;
@@ -168,6 +198,138 @@ impl BPlusTreeInternalPageCodec {
     }
 }
 
+/// Encodes the keys of an index page one after another, a varchar value only stores the
+/// part after the prefix it shares with the same column of the previous key when the
+/// columns before it equal those of the previous key. The column is then in order among
+/// the neighbouring keys, so inserting a key never makes the next key longer and deleting
+/// one never makes the page longer.
+///
+/// Key format:
+/// | NullMap | Value(1) | ... | Value(n) |
+/// where a non-null varchar value is | PrefixLength (2) | SuffixLength (4) | Suffix |
+/// and other values are encoded as in tuples.
+pub struct IndexKeyCodec;
+
+impl IndexKeyCodec {
+    pub fn encode(key: &Tuple, prev_key: Option<&Tuple>) -> Vec<u8> {
+        let mut null_map = DynamicBitmap::new();
+        let mut attributes = vec![];
+        for (idx, value) in key.data.iter().enumerate() {
+            null_map.set(idx, value.is_null());
+            match value {
+                ScalarValue::Varchar(Some(v)) => {
+                    let prefix_len = Self::prefix_len(key, prev_key, idx, v);
+                    attributes.extend(CommonCodec::encode_u16(prefix_len as u16));
+                    attributes.extend(ScalarValueCodec::encode(&ScalarValue::Varchar(Some(
+                        v[prefix_len..].to_string(),
+                    ))));
+                }
+                _ if value.is_null() => {}
+                _ => attributes.extend(ScalarValueCodec::encode(value)),
+            }
+        }
+        let mut bytes = null_map.to_bytes();
+        bytes.extend(attributes);
+        bytes
+    }
+
+    /// Bytes `encode` takes for the key, without encoding it.
+    pub fn encoded_len(key: &Tuple, prev_key: Option<&Tuple>) -> usize {
+        let mut len = key.data.len().div_ceil(8);
+        for (idx, value) in key.data.iter().enumerate() {
+            len += match value {
+                ScalarValue::Varchar(Some(v)) => {
+                    std::mem::size_of::<u16>() + std::mem::size_of::<u32>() + v.len()
+                        - Self::prefix_len(key, prev_key, idx, v)
+                }
+                _ => ScalarValueCodec::encode(value).len(),
+            };
+        }
+        len
+    }
+
+    /// Bytes of `value` shared with the previous key, which is only compressed against when
+    /// the columns before it are equal.
+    fn prefix_len(key: &Tuple, prev_key: Option<&Tuple>, idx: usize, value: &str) -> usize {
+        match prev_key {
+            Some(prev) if prev.data[..idx] == key.data[..idx] => match &prev.data[idx] {
+                ScalarValue::Varchar(Some(prev)) => shared_prefix_len(prev, value),
+                _ => 0,
+            },
+            _ => 0,
+        }
+    }
+
+    pub fn decode(
+        bytes: &[u8],
+        schema: SchemaRef,
+        prev_key: Option<&Tuple>,
+    ) -> BustubxResult<DecodedData<Tuple>> {
+        let map_bytes = schema.column_count().div_ceil(8);
+        let null_map = DynamicBitmap::from_bytes(&bytes[0..map_bytes]);
+        let mut left_bytes = &bytes[map_bytes..];
+
+        let mut data = vec![];
+        for (idx, col) in schema.columns.iter().enumerate() {
+            let null = null_map.get(idx).ok_or_else(|| {
+                BustubxError::Internal(
+                    "null map size should be greater than or equal to col count".to_string(),
+                )
+            })?;
+            if null {
+                data.push(ScalarValue::new_empty(col.data_type));
+                continue;
+            }
+            if !matches!(col.data_type, DataType::Varchar(_)) {
+                let (value, offset) = ScalarValueCodec::decode(left_bytes, col.data_type)?;
+                left_bytes = &left_bytes[offset..];
+                data.push(value);
+                continue;
+            }
+
+            let (prefix_len, offset) = CommonCodec::decode_u16(left_bytes)?;
+            left_bytes = &left_bytes[offset..];
+            let (suffix, offset) = ScalarValueCodec::decode(left_bytes, col.data_type)?;
+            left_bytes = &left_bytes[offset..];
+            let ScalarValue::Varchar(Some(suffix)) = suffix else {
+                return Err(BustubxError::Storage(format!(
+                    "Failed to decode varchar suffix {}",
+                    suffix
+                )));
+            };
+            let prefix = match prev_key.map(|prev| &prev.data[idx]) {
+                Some(ScalarValue::Varchar(Some(prev))) if prev.len() >= prefix_len as usize => {
+                    &prev[..prefix_len as usize]
+                }
+                _ if prefix_len == 0 => "",
+                _ => {
+                    return Err(BustubxError::Storage(format!(
+                        "Index key shares a prefix of {} bytes with no previous key",
+                        prefix_len
+                    )))
+                }
+            };
+            data.push(ScalarValue::Varchar(Some(format!("{prefix}{suffix}"))));
+        }
+
+        Ok((Tuple::new(schema, data), bytes.len() - left_bytes.len()))
+    }
+}
+
+/// Bytes `value` shares at its start with `prev`, cut back to a char boundary.
+fn shared_prefix_len(prev: &str, value: &str) -> usize {
+    let mut len = prev
+        .bytes()
+        .zip(value.bytes())
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(u16::MAX as usize);
+    while !value.is_char_boundary(len) {
+        len -= 1;
+    }
+    len
+}
+
 pub struct BPlusTreePageTypeCodec;
 
 impl BPlusTreePageTypeCodec {
@@ -264,8 +426,13 @@ impl BPlusTreeInternalPageHeaderCodec {
 #[cfg(test)]
 mod tests {
     use crate::catalog::{Column, DataType, Schema};
-    use crate::storage::codec::index_page::BPlusTreePageCodec;
-    use crate::storage::{BPlusTreeInternalPage, BPlusTreeLeafPage, BPlusTreePage, RecordId};
+    use crate::common::ScalarValue;
+    use crate::storage::codec::index_page::{
+        BPlusTreeHeaderPageCodec, BPlusTreePageCodec, IndexKeyCodec,
+    };
+    use crate::storage::{
+        BPlusTreeHeaderPage, BPlusTreeInternalPage, BPlusTreeLeafPage, BPlusTreePage, RecordId,
+    };
     use crate::Tuple;
     use std::sync::Arc;
 
@@ -296,5 +463,63 @@ mod tests {
         let (new_page, _) =
             BPlusTreePageCodec::decode(&BPlusTreePageCodec::encode(&page), schema.clone()).unwrap();
         assert_eq!(new_page, page);
+
+        let header_page = BPlusTreeHeaderPage { root_page_id: 5 };
+        let (new_page, _) =
+            BPlusTreeHeaderPageCodec::decode(&BPlusTreeHeaderPageCodec::encode(&header_page))
+                .unwrap();
+        assert_eq!(new_page, header_page);
+    }
+
+    #[test]
+    fn index_key_codec() {
+        let schema = Arc::new(Schema::new(vec![
+            Column::new("url", DataType::Varchar(None), true),
+            Column::new("n", DataType::Int32, true),
+        ]));
+        let key = |url: Option<&str>, n: i32| {
+            Tuple::new(
+                schema.clone(),
+                vec![ScalarValue::Varchar(url.map(String::from)), n.into()],
+            )
+        };
+        let keys = vec![
+            key(Some("https://example.com/a/b"), 1),
+            key(Some("https://example.com/a/c"), 2),
+            key(Some("https://example.com/é"), 3),
+            key(Some("https://example.com/ê"), 4),
+            key(None, 5),
+            key(Some("https://example.com/a"), 6),
+        ];
+
+        // 与前一个key共享的前缀不再存储
+        // null map + prefix length + suffix length + "c" + n
+        assert_eq!(
+            IndexKeyCodec::encode(&keys[1], Some(&keys[0])).len(),
+            1 + 2 + 4 + 1 + 4
+        );
+        assert_eq!(
+            IndexKeyCodec::encode(&keys[1], None).len(),
+            1 + 2 + 4 + "https://example.com/a/c".len() + 4
+        );
+        for (idx, key) in keys.iter().enumerate() {
+            let prev_key = idx.checked_sub(1).map(|prev| &keys[prev]);
+            assert_eq!(
+                IndexKeyCodec::encoded_len(key, prev_key),
+                IndexKeyCodec::encode(key, prev_key).len()
+            );
+        }
+
+        let mut leaf_page = BPlusTreeLeafPage::new(schema.clone(), 100);
+        for (i, key) in keys.iter().enumerate() {
+            leaf_page
+                .array
+                .push((key.clone(), RecordId::new(i as u32, 0)));
+            leaf_page.header.current_size += 1;
+        }
+        let page = BPlusTreePage::Leaf(leaf_page);
+        let (new_page, _) =
+            BPlusTreePageCodec::decode(&BPlusTreePageCodec::encode(&page), schema.clone()).unwrap();
+        assert_eq!(new_page, page);
     }
 }
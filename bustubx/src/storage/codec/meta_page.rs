@@ -1,6 +1,6 @@
 use crate::storage::codec::{CommonCodec, DecodedData};
-use crate::storage::MetaPage;
-use crate::BustubxResult;
+use crate::storage::{MetaPage, META_PAGE_SIZE};
+use crate::{BustubxError, BustubxResult};
 
 pub struct MetaPageCodec;
 
@@ -9,6 +9,7 @@ impl MetaPageCodec {
         let mut bytes = Vec::new();
         bytes.extend(CommonCodec::encode_u32(page.major_version));
         bytes.extend(CommonCodec::encode_u32(page.minor_version));
+        bytes.extend(CommonCodec::encode_u32(page.format_version));
         bytes.extend(CommonCodec::encode_u32(page.freelist_page_id));
         bytes.extend(CommonCodec::encode_u32(
             page.information_schema_schemas_first_page_id,
@@ -22,16 +23,37 @@ impl MetaPageCodec {
         bytes.extend(CommonCodec::encode_u32(
             page.information_schema_indexes_first_page_id,
         ));
+        bytes.extend(CommonCodec::encode_u32(
+            page.information_schema_constraints_first_page_id,
+        ));
+        bytes.extend(CommonCodec::encode_u32(
+            page.information_schema_sequences_first_page_id,
+        ));
+        bytes.extend(CommonCodec::encode_u32(
+            page.information_schema_views_first_page_id,
+        ));
+        // make sure length of bytes is META_PAGE_SIZE
+        assert!(bytes.len() <= META_PAGE_SIZE);
+        bytes.extend(vec![0; META_PAGE_SIZE - bytes.len()]);
         bytes
     }
 
     pub fn decode(bytes: &[u8]) -> BustubxResult<DecodedData<MetaPage>> {
+        if bytes.len() != META_PAGE_SIZE {
+            return Err(BustubxError::Storage(format!(
+                "Meta page size is not {} instead of {}",
+                META_PAGE_SIZE,
+                bytes.len()
+            )));
+        }
         let mut left_bytes = bytes;
 
         let (major_version, offset) = CommonCodec::decode_u32(left_bytes)?;
         left_bytes = &left_bytes[offset..];
         let (minor_version, offset) = CommonCodec::decode_u32(left_bytes)?;
         left_bytes = &left_bytes[offset..];
+        let (format_version, offset) = CommonCodec::decode_u32(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
         let (freelist_page_id, offset) = CommonCodec::decode_u32(left_bytes)?;
         left_bytes = &left_bytes[offset..];
         let (information_schema_schemas_first_page_id, offset) =
@@ -46,18 +68,29 @@ impl MetaPageCodec {
         let (information_schema_indexes_first_page_id, offset) =
             CommonCodec::decode_u32(left_bytes)?;
         left_bytes = &left_bytes[offset..];
+        let (information_schema_constraints_first_page_id, offset) =
+            CommonCodec::decode_u32(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
+        let (information_schema_sequences_first_page_id, offset) =
+            CommonCodec::decode_u32(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
+        let (information_schema_views_first_page_id, _) = CommonCodec::decode_u32(left_bytes)?;
 
         Ok((
             MetaPage {
                 major_version,
                 minor_version,
+                format_version,
                 freelist_page_id,
                 information_schema_schemas_first_page_id,
                 information_schema_tables_first_page_id,
                 information_schema_columns_first_page_id,
                 information_schema_indexes_first_page_id,
+                information_schema_constraints_first_page_id,
+                information_schema_sequences_first_page_id,
+                information_schema_views_first_page_id,
             },
-            bytes.len() - left_bytes.len(),
+            META_PAGE_SIZE,
         ))
     }
 }
@@ -1,15 +1,19 @@
 mod common;
 mod freelist_page;
+mod hash_page;
 mod index_page;
 mod meta_page;
+mod overflow_page;
 mod scalar;
 mod table_page;
 mod tuple;
 
 pub use common::CommonCodec;
 pub use freelist_page::{FreelistPageCodec, FreelistPageHeaderCodec};
+pub use hash_page::*;
 pub use index_page::*;
 pub use meta_page::MetaPageCodec;
+pub use overflow_page::*;
 pub use scalar::ScalarValueCodec;
 pub use table_page::*;
 pub use tuple::TupleCodec;
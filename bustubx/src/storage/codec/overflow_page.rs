@@ -0,0 +1,108 @@
+use crate::buffer::BUSTUBX_PAGE_SIZE;
+use crate::storage::codec::{CommonCodec, DecodedData};
+use crate::storage::{OverflowPage, OverflowPageHeader, OverflowPointer};
+use crate::{BustubxError, BustubxResult};
+
+pub struct OverflowPageHeaderCodec;
+
+impl OverflowPageHeaderCodec {
+    pub fn encode(header: &OverflowPageHeader) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(CommonCodec::encode_u32(header.next_page_id));
+        bytes.extend(CommonCodec::encode_u16(header.data_size));
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> BustubxResult<DecodedData<OverflowPageHeader>> {
+        let mut left_bytes = bytes;
+
+        let (next_page_id, offset) = CommonCodec::decode_u32(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
+
+        let (data_size, offset) = CommonCodec::decode_u16(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
+
+        Ok((
+            OverflowPageHeader {
+                next_page_id,
+                data_size,
+            },
+            bytes.len() - left_bytes.len(),
+        ))
+    }
+}
+
+pub struct OverflowPageCodec;
+
+impl OverflowPageCodec {
+    pub fn encode(page: &OverflowPage) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(OverflowPageHeaderCodec::encode(&page.header));
+        bytes.extend(&page.data);
+        // make sure length of bytes is BUSTUBX_PAGE_SIZE
+        assert!(bytes.len() <= BUSTUBX_PAGE_SIZE);
+        bytes.extend(vec![0; BUSTUBX_PAGE_SIZE - bytes.len()]);
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> BustubxResult<DecodedData<OverflowPage>> {
+        let (header, offset) = OverflowPageHeaderCodec::decode(bytes)?;
+        let end = offset + header.data_size as usize;
+        if bytes.len() < end {
+            return Err(BustubxError::Storage(format!(
+                "bytes length {} is less than {}",
+                bytes.len(),
+                end
+            )));
+        }
+        let data = bytes[offset..end].to_vec();
+        Ok((OverflowPage { header, data }, BUSTUBX_PAGE_SIZE))
+    }
+}
+
+pub struct OverflowPointerCodec;
+
+impl OverflowPointerCodec {
+    pub fn encode(pointer: &OverflowPointer) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(CommonCodec::encode_u32(pointer.first_page_id));
+        bytes.extend(CommonCodec::encode_u32(pointer.size));
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> BustubxResult<DecodedData<OverflowPointer>> {
+        let mut left_bytes = bytes;
+
+        let (first_page_id, offset) = CommonCodec::decode_u32(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
+
+        let (size, offset) = CommonCodec::decode_u32(left_bytes)?;
+        left_bytes = &left_bytes[offset..];
+
+        Ok((
+            OverflowPointer {
+                first_page_id,
+                size,
+            },
+            bytes.len() - left_bytes.len(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::codec::OverflowPageCodec;
+    use crate::storage::{OverflowPage, OVERFLOW_PAGE_MAX_DATA_SIZE};
+
+    #[test]
+    fn overflow_page_codec() {
+        let mut page = OverflowPage::new(vec![1, 2, 3]);
+        page.header.next_page_id = 5;
+        let (new_page, _) = OverflowPageCodec::decode(&OverflowPageCodec::encode(&page)).unwrap();
+        assert_eq!(page, new_page);
+
+        let page = OverflowPage::new(vec![7; *OVERFLOW_PAGE_MAX_DATA_SIZE]);
+        let (new_page, _) = OverflowPageCodec::decode(&OverflowPageCodec::encode(&page)).unwrap();
+        assert_eq!(page, new_page);
+    }
+}
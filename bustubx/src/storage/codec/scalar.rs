@@ -1,7 +1,9 @@
 use crate::catalog::DataType;
+use crate::common::datetime::Interval;
+use crate::common::json::JsonValue;
 use crate::common::ScalarValue;
 use crate::storage::codec::{CommonCodec, DecodedData};
-use crate::BustubxResult;
+use crate::{BustubxError, BustubxResult};
 
 pub struct ScalarValueCodec;
 
@@ -19,15 +21,41 @@ impl ScalarValueCodec {
             ScalarValue::UInt64(Some(v)) => CommonCodec::encode_u64(*v),
             ScalarValue::Float32(Some(v)) => CommonCodec::encode_f32(*v),
             ScalarValue::Float64(Some(v)) => CommonCodec::encode_f64(*v),
+            ScalarValue::Decimal(Some(v), _, _) => CommonCodec::encode_i128(*v),
             ScalarValue::Varchar(Some(v)) => {
-                if v.len() > u16::MAX as usize {
-                    panic!("Varchar length is greater than u16::Max")
+                if v.len() > u32::MAX as usize {
+                    panic!("Varchar length is greater than u32::Max")
                 }
                 let mut bytes = vec![];
-                bytes.extend(CommonCodec::encode_u16(v.len() as u16));
+                bytes.extend(CommonCodec::encode_u32(v.len() as u32));
                 bytes.extend(CommonCodec::encode_string(v));
                 bytes
             }
+            ScalarValue::Binary(Some(v)) => {
+                if v.len() > u32::MAX as usize {
+                    panic!("Binary length is greater than u32::Max")
+                }
+                let mut bytes = vec![];
+                bytes.extend(CommonCodec::encode_u32(v.len() as u32));
+                bytes.extend(v);
+                bytes
+            }
+            ScalarValue::Json(Some(v)) => {
+                let mut bytes = vec![];
+                Self::encode_json(v, &mut bytes);
+                bytes
+            }
+            ScalarValue::Date(Some(v)) => CommonCodec::encode_i32(*v),
+            ScalarValue::Time(Some(v))
+            | ScalarValue::Timestamp(Some(v))
+            | ScalarValue::TimestampTz(Some(v)) => CommonCodec::encode_i64(*v),
+            ScalarValue::Interval(Some(v)) => {
+                let mut bytes = vec![];
+                bytes.extend(CommonCodec::encode_i32(v.months));
+                bytes.extend(CommonCodec::encode_i32(v.days));
+                bytes.extend(CommonCodec::encode_i64(v.micros));
+                bytes
+            }
             // null
             ScalarValue::Boolean(None)
             | ScalarValue::Int8(None)
@@ -40,7 +68,15 @@ impl ScalarValueCodec {
             | ScalarValue::UInt64(None)
             | ScalarValue::Float32(None)
             | ScalarValue::Float64(None)
-            | ScalarValue::Varchar(None) => vec![],
+            | ScalarValue::Decimal(None, _, _)
+            | ScalarValue::Varchar(None)
+            | ScalarValue::Binary(None)
+            | ScalarValue::Json(None)
+            | ScalarValue::Date(None)
+            | ScalarValue::Time(None)
+            | ScalarValue::Timestamp(None)
+            | ScalarValue::TimestampTz(None)
+            | ScalarValue::Interval(None) => vec![],
         }
     }
 
@@ -90,11 +126,22 @@ impl ScalarValueCodec {
                 let (value, offset) = CommonCodec::decode_f64(bytes)?;
                 Ok((ScalarValue::Float64(Some(value)), offset))
             }
+            DataType::Decimal(precision, scale) => {
+                let (value, offset) = CommonCodec::decode_i128(bytes)?;
+                Ok((ScalarValue::Decimal(Some(value), precision, scale), offset))
+            }
             DataType::Varchar(_) => {
                 let mut left_bytes = bytes;
 
-                let (length, offset) = CommonCodec::decode_u16(left_bytes)?;
+                let (length, offset) = CommonCodec::decode_u32(left_bytes)?;
                 left_bytes = &left_bytes[offset..];
+                if left_bytes.len() < length as usize {
+                    return Err(BustubxError::Storage(format!(
+                        "bytes length {} is less than {}",
+                        left_bytes.len(),
+                        length
+                    )));
+                }
 
                 let (value, offset) = CommonCodec::decode_string(&left_bytes[0..length as usize])?;
                 left_bytes = &left_bytes[offset..];
@@ -104,6 +151,159 @@ impl ScalarValueCodec {
                     bytes.len() - left_bytes.len(),
                 ))
             }
+            DataType::Binary => {
+                let (length, offset) = CommonCodec::decode_u32(bytes)?;
+                let end = offset + length as usize;
+                if bytes.len() < end {
+                    return Err(BustubxError::Storage(format!(
+                        "bytes length {} is less than {}",
+                        bytes.len(),
+                        end
+                    )));
+                }
+                Ok((ScalarValue::Binary(Some(bytes[offset..end].to_vec())), end))
+            }
+            DataType::Json => {
+                let (value, offset) = Self::decode_json(bytes, 0)?;
+                Ok((ScalarValue::Json(Some(value)), offset))
+            }
+            DataType::Date => {
+                let (value, offset) = CommonCodec::decode_i32(bytes)?;
+                Ok((ScalarValue::Date(Some(value)), offset))
+            }
+            DataType::Time => {
+                let (value, offset) = CommonCodec::decode_i64(bytes)?;
+                Ok((ScalarValue::Time(Some(value)), offset))
+            }
+            DataType::Timestamp => {
+                let (value, offset) = CommonCodec::decode_i64(bytes)?;
+                Ok((ScalarValue::Timestamp(Some(value)), offset))
+            }
+            DataType::TimestampTz => {
+                let (value, offset) = CommonCodec::decode_i64(bytes)?;
+                Ok((ScalarValue::TimestampTz(Some(value)), offset))
+            }
+            DataType::Interval => {
+                let mut left_bytes = bytes;
+
+                let (months, offset) = CommonCodec::decode_i32(left_bytes)?;
+                left_bytes = &left_bytes[offset..];
+                let (days, offset) = CommonCodec::decode_i32(left_bytes)?;
+                left_bytes = &left_bytes[offset..];
+                let (micros, offset) = CommonCodec::decode_i64(left_bytes)?;
+                left_bytes = &left_bytes[offset..];
+
+                Ok((
+                    ScalarValue::Interval(Some(Interval::new(months, days, micros))),
+                    bytes.len() - left_bytes.len(),
+                ))
+            }
         }
     }
+
+    /// JSON is stored as a tagged tree so reading doesn't need to parse text:
+    /// a tag byte followed by a u32 length prefixed string or u32 element count.
+    fn encode_json(value: &JsonValue, bytes: &mut Vec<u8>) {
+        let encode_string = |s: &str, bytes: &mut Vec<u8>| {
+            bytes.extend(CommonCodec::encode_u32(s.len() as u32));
+            bytes.extend(s.as_bytes());
+        };
+        match value {
+            JsonValue::Null => bytes.push(JSON_NULL),
+            JsonValue::Bool(false) => bytes.push(JSON_FALSE),
+            JsonValue::Bool(true) => bytes.push(JSON_TRUE),
+            JsonValue::Number(v) => {
+                bytes.push(JSON_NUMBER);
+                encode_string(v, bytes);
+            }
+            JsonValue::String(v) => {
+                bytes.push(JSON_STRING);
+                encode_string(v, bytes);
+            }
+            JsonValue::Array(elements) => {
+                bytes.push(JSON_ARRAY);
+                bytes.extend(CommonCodec::encode_u32(elements.len() as u32));
+                for element in elements {
+                    Self::encode_json(element, bytes);
+                }
+            }
+            JsonValue::Object(fields) => {
+                bytes.push(JSON_OBJECT);
+                bytes.extend(CommonCodec::encode_u32(fields.len() as u32));
+                for (key, value) in fields {
+                    encode_string(key, bytes);
+                    Self::encode_json(value, bytes);
+                }
+            }
+        }
+    }
+
+    fn decode_json(bytes: &[u8], depth: usize) -> BustubxResult<DecodedData<JsonValue>> {
+        let decode_string = |bytes: &[u8]| -> BustubxResult<DecodedData<String>> {
+            let (length, offset) = CommonCodec::decode_u32(bytes)?;
+            let end = offset + length as usize;
+            if bytes.len() < end {
+                return Err(BustubxError::Storage(format!(
+                    "bytes length {} is less than {}",
+                    bytes.len(),
+                    end
+                )));
+            }
+            let (value, _) = CommonCodec::decode_string(&bytes[offset..end])?;
+            Ok((value, end))
+        };
+        if depth > JSON_MAX_DEPTH {
+            return Err(BustubxError::Storage("json nesting too deep".to_string()));
+        }
+        let (tag, mut offset) = CommonCodec::decode_u8(bytes)?;
+        let value = match tag {
+            JSON_NULL => JsonValue::Null,
+            JSON_FALSE => JsonValue::Bool(false),
+            JSON_TRUE => JsonValue::Bool(true),
+            JSON_NUMBER | JSON_STRING => {
+                let (v, len) = decode_string(&bytes[offset..])?;
+                offset += len;
+                if tag == JSON_NUMBER {
+                    JsonValue::Number(v)
+                } else {
+                    JsonValue::String(v)
+                }
+            }
+            JSON_ARRAY => {
+                let (count, len) = CommonCodec::decode_u32(&bytes[offset..])?;
+                offset += len;
+                let mut elements = vec![];
+                for _ in 0..count {
+                    let (element, len) = Self::decode_json(&bytes[offset..], depth + 1)?;
+                    offset += len;
+                    elements.push(element);
+                }
+                JsonValue::Array(elements)
+            }
+            JSON_OBJECT => {
+                let (count, len) = CommonCodec::decode_u32(&bytes[offset..])?;
+                offset += len;
+                let mut fields = vec![];
+                for _ in 0..count {
+                    let (key, len) = decode_string(&bytes[offset..])?;
+                    offset += len;
+                    let (value, len) = Self::decode_json(&bytes[offset..], depth + 1)?;
+                    offset += len;
+                    fields.push((key, value));
+                }
+                JsonValue::Object(fields)
+            }
+            _ => return Err(BustubxError::Storage(format!("invalid json tag {}", tag))),
+        };
+        Ok((value, offset))
+    }
 }
+
+const JSON_NULL: u8 = 0;
+const JSON_FALSE: u8 = 1;
+const JSON_TRUE: u8 = 2;
+const JSON_NUMBER: u8 = 3;
+const JSON_STRING: u8 = 4;
+const JSON_ARRAY: u8 = 5;
+const JSON_OBJECT: u8 = 6;
+const JSON_MAX_DEPTH: usize = 256;
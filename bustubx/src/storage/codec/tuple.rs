@@ -1,51 +1,93 @@
 use crate::catalog::SchemaRef;
 use crate::common::{DynamicBitmap, ScalarValue};
-use crate::storage::codec::{DecodedData, ScalarValueCodec};
+use crate::storage::codec::{DecodedData, OverflowPointerCodec, ScalarValueCodec};
+use crate::storage::OverflowPointer;
 use crate::{BustubxError, BustubxResult, Tuple};
 
 pub struct TupleCodec;
 
 impl TupleCodec {
     pub fn encode(tuple: &Tuple) -> Vec<u8> {
-        // null map
+        Self::encode_with_overflow(tuple, &[])
+    }
+
+    /// Encodes a tuple whose values at `overflow[idx]` are stored out of line, only their
+    /// pointers are written.
+    pub fn encode_with_overflow(tuple: &Tuple, overflow: &[Option<OverflowPointer>]) -> Vec<u8> {
+        // null map and overflow map
         let mut null_map = DynamicBitmap::new();
+        let mut overflow_map = DynamicBitmap::new();
         let mut attributes = Vec::new();
         for (idx, value) in tuple.data.iter().enumerate() {
-            null_map.set(idx, value.is_null());
-            if !value.is_null() {
+            let pointer = overflow.get(idx).copied().flatten();
+            null_map.set(idx, value.is_null() && pointer.is_none());
+            overflow_map.set(idx, pointer.is_some());
+            if let Some(pointer) = pointer {
+                attributes.extend(OverflowPointerCodec::encode(&pointer));
+            } else if !value.is_null() {
                 attributes.extend(ScalarValueCodec::encode(value));
             }
         }
 
         let mut bytes = null_map.to_bytes();
+        bytes.extend(overflow_map.to_bytes());
         bytes.extend(attributes);
         bytes
     }
 
     pub fn decode(bytes: &[u8], schema: SchemaRef) -> BustubxResult<DecodedData<Tuple>> {
+        let ((tuple, overflow), offset) = Self::decode_with_overflow(bytes, schema)?;
+        if overflow.iter().any(|pointer| pointer.is_some()) {
+            return Err(BustubxError::Internal(
+                "tuple has values stored in overflow pages".to_string(),
+            ));
+        }
+        Ok((tuple, offset))
+    }
+
+    /// Decodes a tuple along with the pointers of its out of line values, those values
+    /// are NULL in the returned tuple.
+    pub fn decode_with_overflow(
+        bytes: &[u8],
+        schema: SchemaRef,
+    ) -> BustubxResult<DecodedData<(Tuple, Vec<Option<OverflowPointer>>)>> {
         let mut total_offset = 0;
 
-        let null_map_bytes = schema.column_count().div_ceil(8);
-        let null_map = DynamicBitmap::from_bytes(&bytes[0..null_map_bytes]);
-        total_offset += null_map_bytes;
-        let mut bytes = &bytes[null_map_bytes..];
+        let map_bytes = schema.column_count().div_ceil(8);
+        let null_map = DynamicBitmap::from_bytes(&bytes[0..map_bytes]);
+        let overflow_map = DynamicBitmap::from_bytes(&bytes[map_bytes..2 * map_bytes]);
+        total_offset += 2 * map_bytes;
+        let mut bytes = &bytes[2 * map_bytes..];
 
         let mut data = vec![];
+        let mut overflow = vec![];
         for (idx, col) in schema.columns.iter().enumerate() {
-            let null = null_map.get(idx).ok_or(BustubxError::Internal(
-                "null map size should be greater than or equal to col count".to_string(),
-            ))?;
-            if null {
+            let map_error = || {
+                BustubxError::Internal(
+                    "null map size should be greater than or equal to col count".to_string(),
+                )
+            };
+            let null = null_map.get(idx).ok_or_else(map_error)?;
+            let overflowed = overflow_map.get(idx).ok_or_else(map_error)?;
+            if overflowed {
+                let (pointer, offset) = OverflowPointerCodec::decode(bytes)?;
                 data.push(ScalarValue::new_empty(col.data_type));
+                overflow.push(Some(pointer));
+                total_offset += offset;
+                bytes = &bytes[offset..];
+            } else if null {
+                data.push(ScalarValue::new_empty(col.data_type));
+                overflow.push(None);
             } else {
                 let (value, offset) = ScalarValueCodec::decode(bytes, col.data_type)?;
                 data.push(value);
+                overflow.push(None);
                 total_offset += offset;
                 bytes = &bytes[offset..];
             }
         }
 
-        Ok((Tuple::new(schema, data), total_offset))
+        Ok(((Tuple::new(schema, data), overflow), total_offset))
     }
 }
 
@@ -54,6 +96,7 @@ mod tests {
     use crate::catalog::{Column, DataType, Schema};
     use crate::common::ScalarValue;
     use crate::storage::codec::TupleCodec;
+    use crate::storage::OverflowPointer;
     use crate::Tuple;
     use std::sync::Arc;
 
@@ -79,4 +122,26 @@ mod tests {
             .0;
         assert_eq!(new_tuple, tuple);
     }
+
+    #[test]
+    fn tuple_codec_with_overflow() {
+        let schema = Arc::new(Schema::new(vec![
+            Column::new("a", DataType::Int32, true),
+            Column::new("b", DataType::Varchar(None), true),
+        ]));
+        let tuple = Tuple::new(
+            schema.clone(),
+            vec![1i32.into(), ScalarValue::Varchar(None)],
+        );
+        let pointer = OverflowPointer {
+            first_page_id: 3,
+            size: 10000,
+        };
+        let bytes = TupleCodec::encode_with_overflow(&tuple, &[None, Some(pointer)]);
+        let ((new_tuple, overflow), _) =
+            TupleCodec::decode_with_overflow(&bytes, schema.clone()).unwrap();
+        assert_eq!(new_tuple, tuple);
+        assert_eq!(overflow, vec![None, Some(pointer)]);
+        assert!(TupleCodec::decode(&bytes, schema).is_err());
+    }
 }
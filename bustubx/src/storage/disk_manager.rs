@@ -1,6 +1,6 @@
 use log::debug;
 use std::fs::File;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering;
 use std::sync::RwLock;
 use std::{
@@ -12,15 +12,19 @@ use crate::error::{BustubxError, BustubxResult};
 
 use crate::buffer::{PageId, BUSTUBX_PAGE_SIZE, INVALID_PAGE_ID};
 use crate::storage::codec::{FreelistPageCodec, MetaPageCodec};
-use crate::storage::{FreelistPage, MetaPage, META_PAGE_SIZE};
+use crate::storage::{FreelistPage, MetaPage, FORMAT_VERSION, META_PAGE_SIZE};
 
 static EMPTY_PAGE: [u8; BUSTUBX_PAGE_SIZE] = [0; BUSTUBX_PAGE_SIZE];
 
 #[derive(Debug)]
 pub struct DiskManager {
+    /// Canonical path of the db file
+    pub path: PathBuf,
     next_page_id: AtomicU32,
     db_file: Mutex<File>,
     pub meta: RwLock<MetaPage>,
+    // 串行化freelist的读改写，避免同一页被并发分配两次
+    freelist_latch: Mutex<()>,
 }
 
 impl DiskManager {
@@ -30,10 +34,26 @@ impl DiskManager {
             let mut db_file = std::fs::OpenOptions::new()
                 .read(true)
                 .write(true)
-                .open(db_path)?;
-            let mut buf = vec![0; *META_PAGE_SIZE];
+                .open(db_path.as_ref())?;
+            let db_file_len = db_file.metadata()?.len();
+            if db_file_len < META_PAGE_SIZE as u64
+                || !(db_file_len - META_PAGE_SIZE as u64).is_multiple_of(BUSTUBX_PAGE_SIZE as u64)
+            {
+                // files written before the format version was recorded have a shorter meta page
+                return Err(BustubxError::Storage(format!(
+                    "db file is not of format version {}, it was written by an older version",
+                    FORMAT_VERSION
+                )));
+            }
+            let mut buf = vec![0; META_PAGE_SIZE];
             db_file.read_exact(&mut buf)?;
             let (meta_page, _) = MetaPageCodec::decode(&buf)?;
+            if meta_page.format_version != FORMAT_VERSION {
+                return Err(BustubxError::Storage(format!(
+                    "db file format version {} is not supported, expected {}",
+                    meta_page.format_version, FORMAT_VERSION
+                )));
+            }
             (db_file, meta_page)
         } else {
             is_new_file = true;
@@ -41,7 +61,7 @@ impl DiskManager {
                 .read(true)
                 .write(true)
                 .create(true)
-                .open(db_path)?;
+                .open(db_path.as_ref())?;
             let meta_page = MetaPage::try_new()?;
             #[allow(clippy::unused_io_amount)]
             db_file.write(&MetaPageCodec::encode(&meta_page))?;
@@ -50,22 +70,24 @@ impl DiskManager {
 
         // calculate next page id
         let db_file_len = db_file.metadata()?.len();
-        if (db_file_len - *META_PAGE_SIZE as u64) % BUSTUBX_PAGE_SIZE as u64 != 0 {
+        if (db_file_len - META_PAGE_SIZE as u64) % BUSTUBX_PAGE_SIZE as u64 != 0 {
             return Err(BustubxError::Internal(format!(
                 "db file size not a multiple of {} + meta page size {}",
-                BUSTUBX_PAGE_SIZE, *META_PAGE_SIZE,
+                BUSTUBX_PAGE_SIZE, META_PAGE_SIZE,
             )));
         }
         let next_page_id =
-            (((db_file_len - *META_PAGE_SIZE as u64) / BUSTUBX_PAGE_SIZE as u64) + 1) as PageId;
+            (((db_file_len - META_PAGE_SIZE as u64) / BUSTUBX_PAGE_SIZE as u64) + 1) as PageId;
         debug!("Initialized disk_manager next_page_id: {}", next_page_id);
 
         let disk_manager = Self {
+            path: std::fs::canonicalize(db_path.as_ref())?,
             next_page_id: AtomicU32::new(next_page_id),
             // Use a mutex to wrap the file handle to ensure that only one thread
             // can access the file at the same time among multiple threads.
             db_file: Mutex::new(db_file),
             meta: RwLock::new(meta),
+            freelist_latch: Mutex::new(()),
         };
 
         // new pages
@@ -75,6 +97,9 @@ impl DiskManager {
             let information_schema_tables_first_page_id = disk_manager.allocate_page()?;
             let information_schema_columns_first_page_id = disk_manager.allocate_page()?;
             let information_schema_indexes_first_page_id = disk_manager.allocate_page()?;
+            let information_schema_constraints_first_page_id = disk_manager.allocate_page()?;
+            let information_schema_sequences_first_page_id = disk_manager.allocate_page()?;
+            let information_schema_views_first_page_id = disk_manager.allocate_page()?;
 
             let mut meta = disk_manager.meta.write().unwrap();
             meta.freelist_page_id = freelist_page_id;
@@ -85,6 +110,11 @@ impl DiskManager {
                 information_schema_columns_first_page_id;
             meta.information_schema_indexes_first_page_id =
                 information_schema_indexes_first_page_id;
+            meta.information_schema_constraints_first_page_id =
+                information_schema_constraints_first_page_id;
+            meta.information_schema_sequences_first_page_id =
+                information_schema_sequences_first_page_id;
+            meta.information_schema_views_first_page_id = information_schema_views_first_page_id;
             drop(meta);
             disk_manager.write_meta_page()?;
         }
@@ -102,7 +132,7 @@ impl DiskManager {
 
         // set offset and read page data
         guard.seek(std::io::SeekFrom::Start(
-            (*META_PAGE_SIZE + (page_id - 1) as usize * BUSTUBX_PAGE_SIZE) as u64,
+            (META_PAGE_SIZE + (page_id - 1) as usize * BUSTUBX_PAGE_SIZE) as u64,
         ))?;
         // Read buf.len() bytes of data from the file, and store the data in the buf array.
         guard.read_exact(&mut buf)?;
@@ -122,23 +152,30 @@ impl DiskManager {
     }
 
     pub fn allocate_page(&self) -> BustubxResult<PageId> {
+        let _freelist_guard = self.freelist_latch.lock().unwrap();
         if let Some(page_id) = self.freelist_pop()? {
             Ok(page_id)
         } else {
-            let mut guard = self.db_file.lock().unwrap();
+            self.allocate_new_page()
+        }
+    }
 
-            // fetch current value and increment page id
-            let page_id = self.next_page_id.fetch_add(1, Ordering::SeqCst);
+    /// Appends a page to the end of the db file.
+    fn allocate_new_page(&self) -> BustubxResult<PageId> {
+        let mut guard = self.db_file.lock().unwrap();
 
-            // Write an empty page (all zeros) to the allocated page.
-            Self::write_page_internal(&mut guard, page_id, &EMPTY_PAGE)?;
+        // fetch current value and increment page id
+        let page_id = self.next_page_id.fetch_add(1, Ordering::SeqCst);
 
-            Ok(page_id)
-        }
+        // Write an empty page (all zeros) to the allocated page.
+        Self::write_page_internal(&mut guard, page_id, &EMPTY_PAGE)?;
+
+        Ok(page_id)
     }
 
     pub fn allocate_freelist_page(&self) -> BustubxResult<PageId> {
-        let page_id = self.allocate_page()?;
+        // the freelist is being changed, so do not pop a page from it
+        let page_id = self.allocate_new_page()?;
         let freelist_page = FreelistPage::new();
         self.write_page(page_id, &FreelistPageCodec::encode(&freelist_page))?;
         Ok(page_id)
@@ -151,6 +188,7 @@ impl DiskManager {
         Self::write_page_internal(&mut guard, page_id, &EMPTY_PAGE)?;
         drop(guard);
 
+        let _freelist_guard = self.freelist_latch.lock().unwrap();
         self.freelist_push(page_id)?;
         Ok(())
     }
@@ -223,7 +261,7 @@ impl DiskManager {
     ) -> BustubxResult<()> {
         // Seek to the start of the page in the database file and write the data.
         guard.seek(std::io::SeekFrom::Start(
-            (*META_PAGE_SIZE + (page_id - 1) as usize * BUSTUBX_PAGE_SIZE) as u64,
+            (META_PAGE_SIZE + (page_id - 1) as usize * BUSTUBX_PAGE_SIZE) as u64,
         ))?;
         guard.write_all(data)?;
         guard.flush()?;
@@ -241,7 +279,8 @@ impl DiskManager {
 mod tests {
     use crate::buffer::BUSTUBX_PAGE_SIZE;
     use crate::storage::codec::MetaPageCodec;
-    use crate::storage::EMPTY_META_PAGE;
+    use crate::storage::{MetaPage, META_PAGE_SIZE};
+    use std::io::Write;
     use tempfile::TempDir;
 
     #[test]
@@ -252,7 +291,7 @@ mod tests {
         let disk_manager = super::DiskManager::try_new(temp_path).unwrap();
 
         let page_id1 = disk_manager.allocate_page().unwrap();
-        assert_eq!(page_id1, 6);
+        assert_eq!(page_id1, 9);
         let mut page1 = vec![1, 2, 3];
         page1.extend(vec![0; BUSTUBX_PAGE_SIZE - 3]);
         disk_manager.write_page(page_id1, &page1).unwrap();
@@ -260,7 +299,7 @@ mod tests {
         assert_eq!(page, page1.as_slice());
 
         let page_id2 = disk_manager.allocate_page().unwrap();
-        assert_eq!(page_id2, 7);
+        assert_eq!(page_id2, 10);
         let mut page2 = vec![0; BUSTUBX_PAGE_SIZE - 3];
         page2.extend(vec![4, 5, 6]);
         disk_manager.write_page(page_id2, &page2).unwrap();
@@ -270,7 +309,7 @@ mod tests {
         let db_file_len = disk_manager.db_file_len().unwrap();
         assert_eq!(
             db_file_len as usize,
-            BUSTUBX_PAGE_SIZE * 7 + MetaPageCodec::encode(&EMPTY_META_PAGE).len()
+            BUSTUBX_PAGE_SIZE * 10 + META_PAGE_SIZE
         );
     }
 
@@ -290,4 +329,27 @@ mod tests {
         let page_id4 = disk_manager.allocate_page().unwrap();
         assert_eq!(page_id1, page_id4);
     }
+
+    #[test]
+    pub fn test_disk_manager_format_version() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // meta page of 40 bytes written before the format version was recorded
+        let legacy_path = temp_dir.path().join("legacy.db");
+        let mut legacy_file = std::fs::File::create(&legacy_path).unwrap();
+        legacy_file.write_all(&[0; 40]).unwrap();
+        legacy_file.write_all(&[0; BUSTUBX_PAGE_SIZE]).unwrap();
+        drop(legacy_file);
+        assert!(super::DiskManager::try_new(&legacy_path).is_err());
+
+        let newer_path = temp_dir.path().join("newer.db");
+        let mut meta_page = MetaPage::try_new().unwrap();
+        meta_page.format_version += 1;
+        std::fs::write(&newer_path, MetaPageCodec::encode(&meta_page)).unwrap();
+        assert!(super::DiskManager::try_new(&newer_path).is_err());
+
+        let temp_path = temp_dir.path().join("test.db");
+        drop(super::DiskManager::try_new(&temp_path).unwrap());
+        assert!(super::DiskManager::try_new(&temp_path).is_ok());
+    }
 }
@@ -0,0 +1,359 @@
+use std::sync::{Arc, RwLock};
+
+use crate::buffer::{BufferPoolManager, PageId, INVALID_PAGE_ID};
+use crate::catalog::SchemaRef;
+use crate::common::util::page_bytes_to_array;
+use crate::storage::codec::{HashBucketPageCodec, HashDirectoryPageCodec, TupleCodec};
+use crate::storage::{
+    HashBucketPage, HashDirectoryPage, RecordId, HASH_BUCKET_MAX_KV_SIZE, HASH_DIRECTORY_MAX_DEPTH,
+};
+use crate::{BustubxError, BustubxResult, Tuple};
+
+/// Extendible hash index, answers equality lookups of full keys only.
+#[derive(Debug)]
+pub struct HashIndex {
+    pub key_schema: SchemaRef,
+    pub buffer_pool: Arc<BufferPoolManager>,
+    pub bucket_max_size: u32,
+    /// The directory page is allocated with the index and never moves
+    pub directory_page_id: PageId,
+    /// Latch of the whole index, lookups share it while inserts and deletes hold it
+    /// exclusively
+    latch: RwLock<()>,
+}
+
+impl HashIndex {
+    pub fn new(
+        key_schema: SchemaRef,
+        buffer_pool: Arc<BufferPoolManager>,
+        bucket_max_size: u32,
+        directory_page_id: PageId,
+    ) -> Self {
+        Self {
+            key_schema,
+            buffer_pool,
+            bucket_max_size,
+            directory_page_id,
+            latch: RwLock::new(()),
+        }
+    }
+
+    /// Allocates the directory page of a new index pointing to a single empty bucket.
+    pub fn try_new(
+        key_schema: SchemaRef,
+        buffer_pool: Arc<BufferPoolManager>,
+        bucket_max_size: u32,
+    ) -> BustubxResult<Self> {
+        let mut index = Self::new(key_schema, buffer_pool, bucket_max_size, INVALID_PAGE_ID);
+        let bucket_page_id = index.new_bucket_page(&index.empty_bucket())?;
+
+        let directory_page = index.buffer_pool.new_page()?;
+        let directory_page_id = directory_page.read().unwrap().page_id;
+        directory_page
+            .write()
+            .unwrap()
+            .set_data(page_bytes_to_array(&HashDirectoryPageCodec::encode(
+                &HashDirectoryPage::new(bucket_page_id, HASH_DIRECTORY_MAX_DEPTH),
+            )));
+        index.directory_page_id = directory_page_id;
+        Ok(index)
+    }
+
+    /// Refuses a key too large to be indexed, checked before a row is written or an index
+    /// is created over the rows of a table.
+    pub fn check_key_size(key: &Tuple) -> BustubxResult<()> {
+        let size = HashBucketPage::kv_size(&(key.clone(), RecordId::new(0, 0)));
+        if size > HASH_BUCKET_MAX_KV_SIZE {
+            return Err(BustubxError::Storage(format!(
+                "index key of {} bytes is too large, the max is {} bytes",
+                size, HASH_BUCKET_MAX_KV_SIZE
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn insert(&self, key: &Tuple, rid: RecordId) -> BustubxResult<()> {
+        Self::check_key_size(key)?;
+        let _latch = self.latch.write().unwrap();
+        let kv = (key.clone(), rid);
+        let hash = hash_key(key);
+        let mut directory = self.read_directory()?;
+        loop {
+            let slot = directory.slot_of(hash);
+            let bucket_page_id = directory.bucket_page_ids[slot];
+            let mut bucket = self.read_bucket(bucket_page_id)?;
+            if bucket.has_room(&kv) {
+                bucket.insert(key.clone(), rid);
+                return self.write_bucket(bucket_page_id, &bucket);
+            }
+
+            // 分裂无法分开全部hash相同的key，或目录已达最大深度时使用溢出页
+            let local_depth = directory.local_depths[slot];
+            let can_split = local_depth < directory.header.max_depth
+                && bucket.header.next_page_id == INVALID_PAGE_ID
+                && bucket.array.iter().any(|(k, _)| hash_key(k) != hash);
+            if !can_split {
+                return self.insert_overflow(bucket_page_id, bucket, key, rid);
+            }
+
+            if local_depth == directory.header.global_depth {
+                directory.grow();
+            }
+            // 按hash的第local_depth位将桶一分为二
+            let split_bit = 1u32 << local_depth;
+            let mut image = self.empty_bucket();
+            for (k, r) in bucket.drain(|(k, _)| hash_key(k) & split_bit != 0) {
+                image.insert(k, r);
+            }
+            let image_page_id = self.new_bucket_page(&image)?;
+            for i in 0..directory.size() {
+                if directory.bucket_page_ids[i] == bucket_page_id {
+                    directory.local_depths[i] = local_depth + 1;
+                    if i as u32 & split_bit != 0 {
+                        directory.bucket_page_ids[i] = image_page_id;
+                    }
+                }
+            }
+            self.write_bucket(bucket_page_id, &bucket)?;
+            self.write_directory(&directory)?;
+        }
+    }
+
+    /// Deletes the entry of `rid` under `key`, other entries with an equal key are kept.
+    pub fn delete(&self, key: &Tuple, rid: RecordId) -> BustubxResult<()> {
+        let _latch = self.latch.write().unwrap();
+        let mut directory = self.read_directory()?;
+        let slot = directory.slot_of(hash_key(key));
+
+        let mut prev: Option<(PageId, HashBucketPage)> = None;
+        let mut page_id = directory.bucket_page_ids[slot];
+        while page_id != INVALID_PAGE_ID {
+            let mut bucket = self.read_bucket(page_id)?;
+            if !bucket.remove(key, rid) {
+                let next_page_id = bucket.header.next_page_id;
+                prev = Some((page_id, bucket));
+                page_id = next_page_id;
+                continue;
+            }
+            match prev {
+                // 空的溢出页从链上摘除
+                Some((prev_page_id, mut prev_bucket)) if bucket.is_empty() => {
+                    prev_bucket.header.next_page_id = bucket.header.next_page_id;
+                    self.write_bucket(prev_page_id, &prev_bucket)?;
+                    self.buffer_pool.delete_page(page_id)?;
+                }
+                None if bucket.is_empty() && bucket.header.next_page_id == INVALID_PAGE_ID => {
+                    self.write_bucket(page_id, &bucket)?;
+                    self.merge(&mut directory, slot)?;
+                }
+                _ => self.write_bucket(page_id, &bucket)?,
+            }
+            return Ok(());
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, key: &Tuple) -> BustubxResult<Vec<RecordId>> {
+        let _latch = self.latch.read().unwrap();
+        let directory = self.read_directory()?;
+        let mut page_id = directory.bucket_page_ids[directory.slot_of(hash_key(key))];
+        let mut rids = vec![];
+        while page_id != INVALID_PAGE_ID {
+            let bucket = self.read_bucket(page_id)?;
+            rids.extend(
+                bucket
+                    .array
+                    .iter()
+                    .filter(|(k, _)| k.data == key.data)
+                    .map(|(_, rid)| *rid),
+            );
+            page_id = bucket.header.next_page_id;
+        }
+        Ok(rids)
+    }
+
+    /// Appends the entry to the first page with room in the overflow chain of a bucket.
+    fn insert_overflow(
+        &self,
+        mut page_id: PageId,
+        mut bucket: HashBucketPage,
+        key: &Tuple,
+        rid: RecordId,
+    ) -> BustubxResult<()> {
+        loop {
+            if bucket.has_room(&(key.clone(), rid)) {
+                bucket.insert(key.clone(), rid);
+                return self.write_bucket(page_id, &bucket);
+            }
+            if bucket.header.next_page_id == INVALID_PAGE_ID {
+                let mut overflow = self.empty_bucket();
+                overflow.insert(key.clone(), rid);
+                bucket.header.next_page_id = self.new_bucket_page(&overflow)?;
+                return self.write_bucket(page_id, &bucket);
+            }
+            page_id = bucket.header.next_page_id;
+            bucket = self.read_bucket(page_id)?;
+        }
+    }
+
+    /// Merges the empty bucket of `slot` into its split image if both have the same local
+    /// depth, then halves the directory while no bucket needs all of its slots.
+    fn merge(&self, directory: &mut HashDirectoryPage, slot: usize) -> BustubxResult<()> {
+        let local_depth = directory.local_depths[slot];
+        if local_depth == 0 {
+            return Ok(());
+        }
+        let image_slot = slot ^ (1 << (local_depth - 1));
+        if directory.local_depths[image_slot] != local_depth {
+            return Ok(());
+        }
+        let bucket_page_id = directory.bucket_page_ids[slot];
+        let image_page_id = directory.bucket_page_ids[image_slot];
+        for i in 0..directory.size() {
+            if directory.bucket_page_ids[i] == bucket_page_id
+                || directory.bucket_page_ids[i] == image_page_id
+            {
+                directory.bucket_page_ids[i] = image_page_id;
+                directory.local_depths[i] = local_depth - 1;
+            }
+        }
+        while directory.can_shrink() {
+            directory.shrink();
+        }
+        self.write_directory(directory)?;
+        self.buffer_pool.delete_page(bucket_page_id)?;
+        Ok(())
+    }
+
+    fn empty_bucket(&self) -> HashBucketPage {
+        HashBucketPage::new(self.key_schema.clone(), self.bucket_max_size)
+    }
+
+    fn new_bucket_page(&self, bucket: &HashBucketPage) -> BustubxResult<PageId> {
+        let page = self.buffer_pool.new_page()?;
+        let page_id = page.read().unwrap().page_id;
+        page.write()
+            .unwrap()
+            .set_data(page_bytes_to_array(&HashBucketPageCodec::encode(bucket)));
+        Ok(page_id)
+    }
+
+    fn read_directory(&self) -> BustubxResult<HashDirectoryPage> {
+        let guard = self.buffer_pool.fetch_page_read(self.directory_page_id)?;
+        Ok(HashDirectoryPageCodec::decode(guard.data())?.0)
+    }
+
+    fn write_directory(&self, directory: &HashDirectoryPage) -> BustubxResult<()> {
+        let mut guard = self.buffer_pool.fetch_page_write(self.directory_page_id)?;
+        guard.set_data(page_bytes_to_array(&HashDirectoryPageCodec::encode(
+            directory,
+        )));
+        Ok(())
+    }
+
+    fn read_bucket(&self, page_id: PageId) -> BustubxResult<HashBucketPage> {
+        let guard = self.buffer_pool.fetch_page_read(page_id)?;
+        Ok(HashBucketPageCodec::decode(guard.data(), self.key_schema.clone())?.0)
+    }
+
+    fn write_bucket(&self, page_id: PageId, bucket: &HashBucketPage) -> BustubxResult<()> {
+        let mut guard = self.buffer_pool.fetch_page_write(page_id)?;
+        guard.set_data(page_bytes_to_array(&HashBucketPageCodec::encode(bucket)));
+        Ok(())
+    }
+}
+
+/// FNV-1a of the encoded key, stable across restarts since buckets are persisted.
+fn hash_key(key: &Tuple) -> u32 {
+    TupleCodec::encode(key)
+        .iter()
+        .fold(0x811c9dc5, |hash, byte| {
+            (hash ^ *byte as u32).wrapping_mul(0x01000193)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::BufferPoolManager;
+    use crate::catalog::{Column, DataType, Schema};
+    use crate::storage::hash_index::HashIndex;
+    use crate::storage::{DiskManager, RecordId};
+    use crate::Tuple;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[test]
+    pub fn test_hash_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let key_schema = Arc::new(Schema::new(vec![Column::new("a", DataType::Int32, false)]));
+        let disk_manager = DiskManager::try_new(temp_dir.path().join("test.db")).unwrap();
+        let buffer_pool = Arc::new(BufferPoolManager::new(1000, Arc::new(disk_manager)));
+        let index = HashIndex::try_new(key_schema.clone(), buffer_pool.clone(), 4).unwrap();
+        let key = |a: i32| Tuple::new(key_schema.clone(), vec![a.into()]);
+        let rid = |a: i32| RecordId::new(a as u32 + 1, a as u32);
+
+        for a in 0..500 {
+            index.insert(&key(a), rid(a)).unwrap();
+        }
+        // 重复的key落入同一个桶的溢出页
+        for slot in 0..20 {
+            index.insert(&key(7), RecordId::new(1000, slot)).unwrap();
+        }
+        assert_eq!(index.read_directory().unwrap().header.global_depth, 8);
+        assert_eq!(index.get(&key(7)).unwrap().len(), 21);
+        for a in 0..500 {
+            if a != 7 {
+                assert_eq!(index.get(&key(a)).unwrap(), vec![rid(a)]);
+            }
+        }
+        assert!(index.get(&key(500)).unwrap().is_empty());
+
+        for a in 0..500 {
+            index.delete(&key(a), rid(a)).unwrap();
+        }
+        for slot in 0..20 {
+            index.delete(&key(7), RecordId::new(1000, slot)).unwrap();
+        }
+        for a in 0..500 {
+            assert!(index.get(&key(a)).unwrap().is_empty());
+        }
+        // 空桶合并后目录收缩
+        let directory = index.read_directory().unwrap();
+        assert!(directory.header.global_depth < 8);
+
+        // 重新打开的索引读取同一个目录页
+        let index = HashIndex::new(key_schema.clone(), buffer_pool, 4, index.directory_page_id);
+        index.insert(&key(1), rid(1)).unwrap();
+        assert_eq!(index.get(&key(1)).unwrap(), vec![rid(1)]);
+    }
+
+    #[test]
+    pub fn test_hash_index_large_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let key_schema = Arc::new(Schema::new(vec![Column::new(
+            "a",
+            DataType::Varchar(None),
+            false,
+        )]));
+        let disk_manager = DiskManager::try_new(temp_dir.path().join("test.db")).unwrap();
+        let buffer_pool = Arc::new(BufferPoolManager::new(1000, Arc::new(disk_manager)));
+        let index = HashIndex::try_new(key_schema.clone(), buffer_pool, 10).unwrap();
+        let key = |a: u32, len: usize| {
+            Tuple::new(
+                key_schema.clone(),
+                vec![format!("{:04}{}", a, "x".repeat(len)).into()],
+            )
+        };
+        let rid = |a: u32| RecordId::new(a + 1, a);
+
+        // 桶按字节放不下时分裂
+        for a in 0..100 {
+            index.insert(&key(a, 900), rid(a)).unwrap();
+        }
+        for a in 0..100 {
+            assert_eq!(index.get(&key(a, 900)).unwrap(), vec![rid(a)]);
+        }
+        assert!(index.insert(&key(100, 2000), rid(100)).is_err());
+        assert!(index.get(&key(100, 2000)).unwrap().is_empty());
+    }
+}
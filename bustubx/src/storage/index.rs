@@ -1,15 +1,21 @@
 use std::collections::VecDeque;
 use std::ops::{Bound, RangeBounds};
 use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
 
-use crate::buffer::{AtomicPageId, PageId, PageRef, INVALID_PAGE_ID};
-use crate::catalog::SchemaRef;
+use crate::buffer::{
+    AtomicPageId, PageId, ReadPageGuard, WritePageGuard, BUSTUBX_PAGE_SIZE, INVALID_PAGE_ID,
+};
+use crate::catalog::{Column, DataType, Schema, SchemaRef};
 use crate::common::util::page_bytes_to_array;
+use crate::common::ScalarValue;
 use crate::storage::codec::{
-    BPlusTreeInternalPageCodec, BPlusTreeLeafPageCodec, BPlusTreePageCodec,
+    BPlusTreeHeaderPageCodec, BPlusTreeInternalPageCodec, BPlusTreeLeafPageCodec,
+    BPlusTreePageCodec,
+};
+use crate::storage::{
+    byte_midpoint, BPlusTreeHeaderPage, InternalKV, LeafKV, BPLUS_MAX_KV_SIZE, BPLUS_UNDERFLOW_SIZE,
 };
-use crate::storage::{InternalKV, LeafKV};
 use crate::{
     buffer::BufferPoolManager,
     storage::{BPlusTreeInternalPage, BPlusTreeLeafPage, BPlusTreePage, RecordId},
@@ -18,29 +24,44 @@ use crate::{
 
 use super::tuple::Tuple;
 
-struct Context {
-    pub root_page_id: PageId,
-    pub write_set: VecDeque<PageId>,
-    pub read_set: VecDeque<PageId>,
+/// Latches held by an insert or delete, from the highest page a split or merge may reach
+/// down to the current page.
+struct Context<'a> {
+    /// Held while the root page may split or collapse
+    pub root_latch: Option<RwLockWriteGuard<'a, ()>>,
+    pub write_set: VecDeque<WritePageGuard<'a>>,
 }
-impl Context {
-    pub fn new(root_page_id: PageId) -> Self {
+impl<'a> Context<'a> {
+    pub fn new(root_latch: RwLockWriteGuard<'a, ()>) -> Self {
         Self {
-            root_page_id,
+            root_latch: Some(root_latch),
             write_set: VecDeque::new(),
-            read_set: VecDeque::new(),
         }
     }
+
+    /// Releases the latches of the ancestors once the current page is safe.
+    pub fn release_ancestors(&mut self) {
+        self.root_latch = None;
+        self.write_set.clear();
+    }
 }
 
 // B+树索引
 #[derive(Debug)]
 pub struct BPlusTreeIndex {
     pub key_schema: SchemaRef,
+    /// Schema of the keys stored in tree pages, the key columns followed by the record id
+    /// which orders equal keys so that every entry is unique.
+    pub entry_schema: SchemaRef,
     pub buffer_pool: Arc<BufferPoolManager>,
     pub internal_max_size: u32,
     pub leaf_max_size: u32,
+    /// The header page is allocated with the index and never moves, it persists the root
+    /// page id on every change of the root
+    pub header_page_id: PageId,
     pub root_page_id: AtomicPageId,
+    /// Latch above the root page, held while reading the root page id or changing it
+    root_latch: RwLock<()>,
 }
 
 impl BPlusTreeIndex {
@@ -49,74 +70,138 @@ impl BPlusTreeIndex {
         buffer_pool: Arc<BufferPoolManager>,
         internal_max_size: u32,
         leaf_max_size: u32,
+        header_page_id: PageId,
     ) -> Self {
         Self {
+            entry_schema: entry_schema(&key_schema),
             key_schema,
             buffer_pool,
             internal_max_size,
             leaf_max_size,
+            header_page_id,
             root_page_id: AtomicPageId::new(INVALID_PAGE_ID),
+            root_latch: RwLock::new(()),
         }
     }
 
+    /// Allocates the header page of a new empty index.
+    pub fn try_new(
+        key_schema: SchemaRef,
+        buffer_pool: Arc<BufferPoolManager>,
+        internal_max_size: u32,
+        leaf_max_size: u32,
+    ) -> BustubxResult<Self> {
+        let header_page = buffer_pool.new_page()?;
+        let header_page_id = header_page.read().unwrap().page_id;
+        header_page.write().unwrap().set_data(page_bytes_to_array(
+            &BPlusTreeHeaderPageCodec::encode(&BPlusTreeHeaderPage {
+                root_page_id: INVALID_PAGE_ID,
+            }),
+        ));
+        Ok(Self::new(
+            key_schema,
+            buffer_pool,
+            internal_max_size,
+            leaf_max_size,
+            header_page_id,
+        ))
+    }
+
+    /// Opens an existing index, reading its root page id from the header page.
+    pub fn load(
+        key_schema: SchemaRef,
+        buffer_pool: Arc<BufferPoolManager>,
+        internal_max_size: u32,
+        leaf_max_size: u32,
+        header_page_id: PageId,
+    ) -> BustubxResult<Self> {
+        let index = Self::new(
+            key_schema,
+            buffer_pool,
+            internal_max_size,
+            leaf_max_size,
+            header_page_id,
+        );
+        let header_guard = index.buffer_pool.fetch_page_read(header_page_id)?;
+        let (header_page, _) = BPlusTreeHeaderPageCodec::decode(header_guard.data())?;
+        drop(header_guard);
+        index
+            .root_page_id
+            .store(header_page.root_page_id, Ordering::SeqCst);
+        Ok(index)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.root_page_id.load(Ordering::SeqCst) == INVALID_PAGE_ID
     }
 
+    /// Refuses a key too large to be indexed, checked before a row is written or an index
+    /// is created over the rows of a table.
+    pub fn check_key_size(key: &Tuple) -> BustubxResult<()> {
+        let rid = RecordId::new(0, 0);
+        check_kv_size(&(entry(entry_schema(&key.schema), key, rid), rid))
+    }
+
     pub fn insert(&self, key: &Tuple, rid: RecordId) -> BustubxResult<()> {
+        let entry = self.entry(key, rid);
+        check_kv_size(&(entry.clone(), rid))?;
+        let mut context = Context::new(self.root_latch.write().unwrap());
         if self.is_empty() {
-            self.start_new_tree(key, rid)?;
+            self.start_new_tree(entry, rid)?;
             return Ok(());
         }
-        let mut context = Context::new(self.root_page_id.load(Ordering::SeqCst));
-        // 找到leaf page
-        let Some(leaf_page) = self.find_leaf_page(key, &mut context)? else {
+        // 找到leaf page，不会分裂的节点释放祖先节点的latch
+        let mut leaf_tree_page =
+            self.latch_leaf_page(&entry, &mut context, |page, _| page.is_insert_safe())?;
+        let Some(curr_guard) = context.write_set.pop_back() else {
             return Err(BustubxError::Storage(
                 "Cannot find leaf page to insert".to_string(),
             ));
         };
+        leaf_tree_page.insert(entry, rid);
+        self.split_up(
+            &mut context,
+            curr_guard,
+            BPlusTreePage::Leaf(leaf_tree_page),
+        )
+    }
 
-        let (mut leaf_tree_page, _) = BPlusTreeLeafPageCodec::decode(
-            leaf_page.read().unwrap().data(),
-            self.key_schema.clone(),
-        )?;
-        leaf_tree_page.insert(key.clone(), rid);
-
-        let mut curr_page = leaf_page;
-        let mut curr_tree_page = BPlusTreePage::Leaf(leaf_tree_page);
-
-        // leaf page已满则分裂
+    /// Splits a page and then its ancestors while they are full, and writes back the last
+    /// page changed. The ancestors a split may reach are latched in the context.
+    fn split_up<'a>(
+        &'a self,
+        context: &mut Context<'a>,
+        mut curr_guard: WritePageGuard<'a>,
+        mut curr_tree_page: BPlusTreePage,
+    ) -> BustubxResult<()> {
+        // 页已满则分裂
         while curr_tree_page.is_full() {
             // 向右分裂出一个新page
             let internalkv = self.split(&mut curr_tree_page)?;
 
-            curr_page
-                .write()
-                .unwrap()
-                .set_data(page_bytes_to_array(&BPlusTreePageCodec::encode(
-                    &curr_tree_page,
-                )));
+            curr_guard.set_data(page_bytes_to_array(&BPlusTreePageCodec::encode(
+                &curr_tree_page,
+            )));
 
-            let curr_page_id = curr_page.read().unwrap().page_id;
-            if let Some(parent_page_id) = context.read_set.pop_back() {
+            let curr_page_id = curr_guard.page_id;
+            if let Some(parent_guard) = context.write_set.pop_back() {
                 // 更新父节点
-                let (parent_page, mut parent_tree_page) = self
-                    .buffer_pool
-                    .fetch_tree_page(parent_page_id, self.key_schema.clone())?;
+                let (mut parent_tree_page, _) =
+                    BPlusTreePageCodec::decode(parent_guard.data(), self.entry_schema.clone())?;
                 parent_tree_page.insert_internalkv(internalkv);
 
-                curr_page = parent_page;
+                curr_guard = parent_guard;
                 curr_tree_page = parent_tree_page;
             } else if curr_page_id == self.root_page_id.load(Ordering::SeqCst) {
                 // new 一个新的root page
                 let new_root_page = self.buffer_pool.new_page()?;
                 let new_root_page_id = new_root_page.read().unwrap().page_id;
                 let mut new_root_internal_page =
-                    BPlusTreeInternalPage::new(self.key_schema.clone(), self.internal_max_size);
+                    BPlusTreeInternalPage::new(self.entry_schema.clone(), self.internal_max_size);
 
                 // internal page第一个kv对的key为空
                 new_root_internal_page.insert(
-                    Tuple::empty(self.key_schema.clone()),
+                    Tuple::empty(self.entry_schema.clone()),
                     self.root_page_id.load(Ordering::SeqCst),
                 );
                 new_root_internal_page.insert(internalkv.0, internalkv.1);
@@ -126,100 +211,178 @@ impl BPlusTreeIndex {
                 ));
 
                 // 更新root page id
-                self.root_page_id.store(new_root_page_id, Ordering::SeqCst);
-
-                curr_page = new_root_page;
-                curr_tree_page = BPlusTreePage::Internal(new_root_internal_page);
+                self.set_root_page_id(new_root_page_id)?;
+                return Ok(());
+            } else {
+                return Err(BustubxError::Storage("Cannot find parent page".to_string()));
             }
         }
 
-        curr_page
-            .write()
-            .unwrap()
-            .set_data(page_bytes_to_array(&BPlusTreePageCodec::encode(
-                &curr_tree_page,
-            )));
+        curr_guard.set_data(page_bytes_to_array(&BPlusTreePageCodec::encode(
+            &curr_tree_page,
+        )));
 
         Ok(())
     }
 
-    pub fn delete(&self, key: &Tuple) -> BustubxResult<()> {
+    /// Deletes the entry of `rid` under `key`, other entries with an equal key are kept.
+    pub fn delete(&self, key: &Tuple, rid: RecordId) -> BustubxResult<()> {
+        let entry = self.entry(key, rid);
+        let mut context = Context::new(self.root_latch.write().unwrap());
         if self.is_empty() {
             return Ok(());
         }
-        let mut context = Context::new(self.root_page_id.load(Ordering::SeqCst));
-        // 找到leaf page
-        let Some(leaf_page) = self.find_leaf_page(key, &mut context)? else {
+        // 找到leaf page，不会合并的节点释放祖先节点的latch
+        let mut leaf_tree_page = self.latch_leaf_page(&entry, &mut context, |page, is_root| {
+            page.is_delete_safe(is_root)
+        })?;
+        let Some(mut curr_guard) = context.write_set.pop_back() else {
             return Err(BustubxError::Storage(
                 "Cannot find leaf page to delete".to_string(),
             ));
         };
-        let (mut leaf_tree_page, _) = BPlusTreeLeafPageCodec::decode(
-            leaf_page.read().unwrap().data(),
-            self.key_schema.clone(),
-        )?;
-        leaf_tree_page.delete(key);
-        leaf_page
-            .write()
-            .unwrap()
-            .set_data(page_bytes_to_array(&BPlusTreeLeafPageCodec::encode(
-                &leaf_tree_page,
-            )));
+        leaf_tree_page.delete(&entry);
+        curr_guard.set_data(page_bytes_to_array(&BPlusTreeLeafPageCodec::encode(
+            &leaf_tree_page,
+        )));
 
         let mut curr_tree_page = BPlusTreePage::Leaf(leaf_tree_page);
-        let mut curr_page_id = leaf_page.read().unwrap().page_id;
 
         // leaf page未达到半满则从兄弟节点借一个或合并
-        while curr_tree_page.is_underflow(self.root_page_id.load(Ordering::SeqCst) == curr_page_id)
+        while curr_tree_page
+            .is_underflow(self.root_page_id.load(Ordering::SeqCst) == curr_guard.page_id)
         {
-            let Some(parent_page_id) = context.read_set.pop_back() else {
+            let Some(mut parent_guard) = context.write_set.pop_back() else {
                 return Err(BustubxError::Storage("Cannot find parent page".to_string()));
             };
+            let (mut parent_internal_page, _) =
+                BPlusTreeInternalPageCodec::decode(parent_guard.data(), self.entry_schema.clone())?;
+            let curr_page_id = curr_guard.page_id;
             let (left_sibling_page_id, right_sibling_page_id) =
-                self.find_sibling_pages(parent_page_id, curr_page_id)?;
+                parent_internal_page.sibling_page_ids(curr_page_id);
+            let Some(curr_index) = parent_internal_page
+                .values()
+                .iter()
+                .position(|page_id| *page_id == curr_page_id)
+            else {
+                return Err(BustubxError::Storage(
+                    "Cannot find page in parent page".to_string(),
+                ));
+            };
+
+            // 同一层的节点从左到右加锁，与沿leaf page链表读取的顺序一致
+            drop(curr_guard);
+            let mut left_sibling = left_sibling_page_id
+                .map(|page_id| self.latch_tree_page(page_id))
+                .transpose()?;
+            curr_guard = self.buffer_pool.fetch_page_write(curr_page_id)?;
+            let mut right_sibling = right_sibling_page_id
+                .map(|page_id| self.latch_tree_page(page_id))
+                .transpose()?;
 
             // 尝试从左兄弟借一个
-            if let Some(left_sibling_page_id) = left_sibling_page_id {
-                if self.borrow_max_kv(parent_page_id, curr_page_id, left_sibling_page_id)? {
-                    break;
+            if let Some((left_guard, left_tree_page)) = left_sibling.as_mut() {
+                if self.borrow_max_kv(
+                    &mut parent_internal_page,
+                    &mut curr_tree_page,
+                    left_tree_page,
+                    curr_index,
+                )? {
+                    left_guard.set_data(page_bytes_to_array(&BPlusTreePageCodec::encode(
+                        left_tree_page,
+                    )));
+                    curr_guard.set_data(page_bytes_to_array(&BPlusTreePageCodec::encode(
+                        &curr_tree_page,
+                    )));
+                    // 替换的分隔key可能更长，父节点放不下时分裂
+                    drop((left_sibling, curr_guard, right_sibling));
+                    return self.split_up(
+                        &mut context,
+                        parent_guard,
+                        BPlusTreePage::Internal(parent_internal_page),
+                    );
                 }
             }
 
             // 尝试从右兄弟借一个
-            if let Some(right_sibling_page_id) = right_sibling_page_id {
-                if self.borrow_min_kv(parent_page_id, curr_page_id, right_sibling_page_id)? {
-                    break;
+            if let Some((right_guard, right_tree_page)) = right_sibling.as_mut() {
+                if self.borrow_min_kv(
+                    &mut parent_internal_page,
+                    &mut curr_tree_page,
+                    right_tree_page,
+                    curr_index + 1,
+                )? {
+                    right_guard.set_data(page_bytes_to_array(&BPlusTreePageCodec::encode(
+                        right_tree_page,
+                    )));
+                    curr_guard.set_data(page_bytes_to_array(&BPlusTreePageCodec::encode(
+                        &curr_tree_page,
+                    )));
+                    drop((left_sibling, curr_guard, right_sibling));
+                    return self.split_up(
+                        &mut context,
+                        parent_guard,
+                        BPlusTreePage::Internal(parent_internal_page),
+                    );
                 }
             }
 
-            let new_parent_page_id = if let Some(left_sibling_page_id) = left_sibling_page_id {
-                // 跟左兄弟合并
-                self.merge(parent_page_id, left_sibling_page_id, curr_page_id)?
-            } else if let Some(right_sibling_page_id) = right_sibling_page_id {
-                // 跟右兄弟合并
-                self.merge(parent_page_id, curr_page_id, right_sibling_page_id)?
-            } else {
-                return Err(BustubxError::Storage(
-                    "Cannot process index page borrow or merge".to_string(),
-                ));
-            };
-            let (_, new_parent_tree_page) = self
-                .buffer_pool
-                .fetch_tree_page(new_parent_page_id, self.key_schema.clone())?;
+            let ((mut left_guard, mut left_tree_page), (right_guard, right_tree_page), right_index) =
+                if let Some(left_sibling) = left_sibling {
+                    // 跟左兄弟合并
+                    (left_sibling, (curr_guard, curr_tree_page), curr_index)
+                } else if let Some(right_sibling) = right_sibling {
+                    // 跟右兄弟合并
+                    ((curr_guard, curr_tree_page), right_sibling, curr_index + 1)
+                } else {
+                    return Err(BustubxError::Storage(
+                        "Cannot process index page borrow or merge".to_string(),
+                    ));
+                };
+            let left_page_id = left_guard.page_id;
+            let right_page_id = right_guard.page_id;
+            self.merge(
+                &mut parent_internal_page,
+                &mut left_tree_page,
+                &right_tree_page,
+                right_index,
+            )?;
+            left_guard.set_data(page_bytes_to_array(&BPlusTreePageCodec::encode(
+                &left_tree_page,
+            )));
+
+            // 删除右边页
+            drop(right_guard);
+            self.buffer_pool.delete_page(right_page_id)?;
+
+            // 根节点只有一个子节点时，则该子节点成为新的根节点
+            if parent_guard.page_id == self.root_page_id.load(Ordering::SeqCst)
+                && parent_internal_page.header.current_size == 1
+            {
+                self.set_root_page_id(left_page_id)?;
+                // 删除旧的根节点
+                let parent_page_id = parent_guard.page_id;
+                drop(parent_guard);
+                self.buffer_pool.delete_page(parent_page_id)?;
+                break;
+            }
+            parent_guard.set_data(page_bytes_to_array(&BPlusTreeInternalPageCodec::encode(
+                &parent_internal_page,
+            )));
 
-            curr_page_id = new_parent_page_id;
-            curr_tree_page = new_parent_tree_page;
+            curr_guard = parent_guard;
+            curr_tree_page = BPlusTreePage::Internal(parent_internal_page);
         }
 
         Ok(())
     }
 
-    fn start_new_tree(&self, key: &Tuple, rid: RecordId) -> BustubxResult<()> {
+    fn start_new_tree(&self, entry: Tuple, rid: RecordId) -> BustubxResult<()> {
         let new_page = self.buffer_pool.new_page()?;
         let new_page_id = new_page.read().unwrap().page_id;
 
-        let mut leaf_page = BPlusTreeLeafPage::new(self.key_schema.clone(), self.leaf_max_size);
-        leaf_page.insert(key.clone(), rid);
+        let mut leaf_page = BPlusTreeLeafPage::new(self.entry_schema.clone(), self.leaf_max_size);
+        leaf_page.insert(entry, rid);
 
         new_page
             .write()
@@ -229,61 +392,229 @@ impl BPlusTreeIndex {
             )));
 
         // 更新root page id
-        self.root_page_id.store(new_page_id, Ordering::SeqCst);
+        self.set_root_page_id(new_page_id)
+    }
 
+    /// Changes the root page id and persists it in the header page, the caller holds the
+    /// root latch exclusively.
+    fn set_root_page_id(&self, page_id: PageId) -> BustubxResult<()> {
+        self.root_page_id.store(page_id, Ordering::SeqCst);
+        let mut header_guard = self.buffer_pool.fetch_page_write(self.header_page_id)?;
+        header_guard.set_data(page_bytes_to_array(&BPlusTreeHeaderPageCodec::encode(
+            &BPlusTreeHeaderPage {
+                root_page_id: page_id,
+            },
+        )));
         Ok(())
     }
 
-    // 找到叶子节点上对应的Value
-    pub fn get(&self, key: &Tuple) -> BustubxResult<Option<RecordId>> {
-        if self.is_empty() {
-            return Ok(None);
+    /// Builds the tree of an empty index bottom-up from `kvs` in one pass instead of
+    /// splitting pages entry by entry. Pages are filled to `fill_factor` percent of their
+    /// slots and bytes to leave room for later inserts. The entries are sorted in memory.
+    pub fn bulk_load(&self, kvs: Vec<(Tuple, RecordId)>, fill_factor: u8) -> BustubxResult<()> {
+        let _root_latch = self.root_latch.write().unwrap();
+        if !self.is_empty() {
+            return Err(BustubxError::Storage(
+                "Cannot bulk load a non-empty index".to_string(),
+            ));
+        }
+        if kvs.is_empty() {
+            return Ok(());
+        }
+        let mut entries = kvs
+            .into_iter()
+            .map(|(key, rid)| (self.entry(&key, rid), rid))
+            .collect::<Vec<LeafKV>>();
+        entries.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        for entry in entries.iter() {
+            check_kv_size(entry)?;
         }
 
-        // 找到leaf page
-        let mut context = Context::new(self.root_page_id.load(Ordering::SeqCst));
-        let Some(leaf_page) = self.find_leaf_page(key, &mut context)? else {
-            return Ok(None);
-        };
-        let (leaf_tree_page, _) = BPlusTreeLeafPageCodec::decode(
-            leaf_page.read().unwrap().data(),
-            self.key_schema.clone(),
-        )?;
-        let result = leaf_tree_page.look_up(key);
-        Ok(result)
+        // 叶子节点，记录每个page与前一个page的分隔key
+        let mut leaf_pages = vec![];
+        let leaf_sizes = fill_sizes(
+            &kv_sizes(&entries, BPlusTreeLeafPage::kv_size),
+            self.leaf_max_size as usize,
+            BPlusTreeLeafPage::new(self.entry_schema.clone(), 0).byte_size(),
+            fill_factor,
+        );
+        let mut entries = entries.into_iter();
+        for size in leaf_sizes {
+            let mut leaf_page =
+                BPlusTreeLeafPage::new(self.entry_schema.clone(), self.leaf_max_size);
+            leaf_page.array = entries.by_ref().take(size).collect();
+            leaf_page.header.current_size = size as u32;
+            leaf_pages.push((self.buffer_pool.new_page()?, leaf_page));
+        }
+        let mut children = vec![];
+        let mut prev_max_key: Option<Tuple> = None;
+        for i in 0..leaf_pages.len() {
+            let next_page_id = leaf_pages
+                .get(i + 1)
+                .map(|(page, _)| page.read().unwrap().page_id)
+                .unwrap_or(INVALID_PAGE_ID);
+            let (page, leaf_page) = &mut leaf_pages[i];
+            leaf_page.header.next_page_id = next_page_id;
+            let mut page = page.write().unwrap();
+            page.set_data(page_bytes_to_array(&BPlusTreeLeafPageCodec::encode(
+                leaf_page,
+            )));
+            let separator = match prev_max_key {
+                Some(prev_max_key) => shortest_separator(&prev_max_key, &leaf_page.array[0].0),
+                None => leaf_page.array[0].0.clone(),
+            };
+            prev_max_key = leaf_page.array.last().map(|kv| kv.0.clone());
+            children.push((separator, page.page_id));
+        }
+
+        // 逐层构建内部节点，直到只剩根节点
+        while children.len() > 1 {
+            let mut parents = vec![];
+            let internal_sizes = fill_sizes(
+                &kv_sizes(&children, BPlusTreeInternalPage::kv_size),
+                self.internal_max_size as usize,
+                BPlusTreeInternalPage::new(self.entry_schema.clone(), 0).byte_size(),
+                fill_factor,
+            );
+            let mut children_iter = children.into_iter();
+            for size in internal_sizes {
+                let mut internal_page =
+                    BPlusTreeInternalPage::new(self.entry_schema.clone(), self.internal_max_size);
+                internal_page.array = children_iter.by_ref().take(size).collect();
+                internal_page.header.current_size = size as u32;
+                // 与分裂出的节点一致，只有每层最左侧节点的第一个key为空
+                let min_key = internal_page.array[0].0.clone();
+                if parents.is_empty() {
+                    internal_page.array[0].0 = Tuple::empty(self.entry_schema.clone());
+                }
+                let new_page = self.buffer_pool.new_page()?;
+                let mut new_page = new_page.write().unwrap();
+                new_page.set_data(page_bytes_to_array(&BPlusTreeInternalPageCodec::encode(
+                    &internal_page,
+                )));
+                parents.push((min_key, new_page.page_id));
+            }
+            children = parents;
+        }
+
+        self.set_root_page_id(children[0].1)
     }
 
-    fn find_leaf_page(&self, key: &Tuple, context: &mut Context) -> BustubxResult<Option<PageRef>> {
+    // 找到叶子节点上key对应的所有Value
+    pub fn get(&self, key: &Tuple) -> BustubxResult<Vec<RecordId>> {
+        let mut rids = vec![];
         if self.is_empty() {
-            return Ok(None);
+            return Ok(rids);
+        }
+
+        // 相等的key可能跨越多个leaf page
+        let (mut _leaf_guard, mut leaf_page) = self.find_first_leaf_page(key, true)?;
+        let mut cursor = leaf_page
+            .next_closest(key, true)
+            .unwrap_or(leaf_page.header.current_size as usize);
+        loop {
+            while cursor < leaf_page.header.current_size as usize {
+                let (entry, rid) = &leaf_page.array[cursor];
+                if entry.partial_cmp(key) != Some(std::cmp::Ordering::Equal) {
+                    return Ok(rids);
+                }
+                rids.push(*rid);
+                cursor += 1;
+            }
+            if leaf_page.header.next_page_id == INVALID_PAGE_ID {
+                return Ok(rids);
+            }
+            // 先获取下一页的latch再释放当前页的
+            let next_guard = self
+                .buffer_pool
+                .fetch_page_read(leaf_page.header.next_page_id)?;
+            leaf_page =
+                BPlusTreeLeafPageCodec::decode(next_guard.data(), self.entry_schema.clone())?.0;
+            _leaf_guard = next_guard;
+            cursor = 0;
+        }
+    }
+
+    fn entry(&self, key: &Tuple, rid: RecordId) -> Tuple {
+        entry(self.entry_schema.clone(), key, rid)
+    }
+
+    /// Finds the leaf page holding the first entry within a start bound, `key` may be a
+    /// prefix of the entry keys. The leaf page stays latched until the guard is dropped.
+    fn find_first_leaf_page(
+        &self,
+        key: &Tuple,
+        included: bool,
+    ) -> BustubxResult<(ReadPageGuard<'_>, BPlusTreeLeafPage)> {
+        let root_latch = self.root_latch.read().unwrap();
+        let mut curr_guard = self
+            .buffer_pool
+            .fetch_page_read(self.root_page_id.load(Ordering::SeqCst))?;
+        drop(root_latch);
+        loop {
+            let (curr_tree_page, _) =
+                BPlusTreePageCodec::decode(curr_guard.data(), self.entry_schema.clone())?;
+            match curr_tree_page {
+                BPlusTreePage::Internal(internal_page) => {
+                    // a prefix may equal keys on both sides of a separator,
+                    // so descend left of the first separator not before it
+                    let mut next_page_id = internal_page.value_at(0);
+                    for idx in 1..internal_page.header.current_size as usize {
+                        let separator = internal_page.key_at(idx);
+                        if separator < key || (!included && separator <= key) {
+                            next_page_id = internal_page.value_at(idx);
+                        } else {
+                            break;
+                        }
+                    }
+                    // 先获取子节点的latch再释放父节点的
+                    curr_guard = self.buffer_pool.fetch_page_read(next_page_id)?;
+                }
+                BPlusTreePage::Leaf(leaf_page) => {
+                    return Ok((curr_guard, leaf_page));
+                }
+            }
         }
-        let (mut curr_page, mut curr_tree_page) = self.buffer_pool.fetch_tree_page(
-            self.root_page_id.load(Ordering::SeqCst),
-            self.key_schema.clone(),
-        )?;
+    }
 
-        // 找到leaf page
+    /// Descends to the leaf page of `entry` latching pages for writing. Latches of the
+    /// ancestors are released once a page is safe, so that a split or merge can't reach
+    /// them. The latched pages are left in the write set, the leaf page last.
+    fn latch_leaf_page<'a>(
+        &'a self,
+        entry: &Tuple,
+        context: &mut Context<'a>,
+        is_safe: impl Fn(&BPlusTreePage, bool) -> bool,
+    ) -> BustubxResult<BPlusTreeLeafPage> {
+        let root_page_id = self.root_page_id.load(Ordering::SeqCst);
+        let mut curr_page_id = root_page_id;
         loop {
+            let (curr_guard, curr_tree_page) = self.latch_tree_page(curr_page_id)?;
+            if is_safe(&curr_tree_page, curr_page_id == root_page_id) {
+                context.release_ancestors();
+            }
+            context.write_set.push_back(curr_guard);
             match curr_tree_page {
                 BPlusTreePage::Internal(internal_page) => {
-                    context
-                        .read_set
-                        .push_back(curr_page.read().unwrap().page_id);
                     // 查找下一页
-                    let next_page_id = internal_page.look_up(key);
-                    let (next_page, next_tree_page) = self
-                        .buffer_pool
-                        .fetch_tree_page(next_page_id, self.key_schema.clone())?;
-                    curr_page = next_page;
-                    curr_tree_page = next_tree_page;
+                    curr_page_id = internal_page.look_up(entry);
                 }
-                BPlusTreePage::Leaf(_leaf_page) => {
-                    return Ok(Some(curr_page));
+                BPlusTreePage::Leaf(leaf_page) => {
+                    return Ok(leaf_page);
                 }
             }
         }
     }
 
+    fn latch_tree_page(
+        &self,
+        page_id: PageId,
+    ) -> BustubxResult<(WritePageGuard<'_>, BPlusTreePage)> {
+        let guard = self.buffer_pool.fetch_page_write(page_id)?;
+        let (tree_page, _) = BPlusTreePageCodec::decode(guard.data(), self.entry_schema.clone())?;
+        Ok((guard, tree_page))
+    }
+
     // 分裂page
     fn split(&self, tree_page: &mut BPlusTreePage) -> BustubxResult<InternalKV> {
         let new_page = self.buffer_pool.new_page()?;
@@ -293,9 +624,8 @@ impl BPlusTreeIndex {
             BPlusTreePage::Leaf(leaf_page) => {
                 // 拆分kv对
                 let mut new_leaf_page =
-                    BPlusTreeLeafPage::new(self.key_schema.clone(), self.leaf_max_size);
-                new_leaf_page
-                    .batch_insert(leaf_page.split_off(leaf_page.header.current_size as usize / 2));
+                    BPlusTreeLeafPage::new(self.entry_schema.clone(), self.leaf_max_size);
+                new_leaf_page.batch_insert(leaf_page.split_off(leaf_page.split_index()));
 
                 // 更新next page id
                 new_leaf_page.header.next_page_id = leaf_page.header.next_page_id;
@@ -305,216 +635,163 @@ impl BPlusTreeIndex {
                     &BPlusTreeLeafPageCodec::encode(&new_leaf_page),
                 ));
 
-                Ok((new_leaf_page.key_at(0).clone(), new_page_id))
+                // 父节点只需能区分两侧的最短key
+                let separator = shortest_separator(
+                    leaf_page.key_at(leaf_page.header.current_size as usize - 1),
+                    new_leaf_page.key_at(0),
+                );
+                Ok((separator, new_page_id))
             }
             BPlusTreePage::Internal(internal_page) => {
                 // 拆分kv对
                 let mut new_internal_page =
-                    BPlusTreeInternalPage::new(self.key_schema.clone(), self.internal_max_size);
-                new_internal_page.batch_insert(
-                    internal_page.split_off(internal_page.header.current_size as usize / 2),
-                );
+                    BPlusTreeInternalPage::new(self.entry_schema.clone(), self.internal_max_size);
+                new_internal_page
+                    .batch_insert(internal_page.split_off(internal_page.split_index()));
 
                 new_page.write().unwrap().set_data(page_bytes_to_array(
                     &BPlusTreeInternalPageCodec::encode(&new_internal_page),
                 ));
 
+                let max_leafkv = self.find_subtree_max_leafkv(
+                    internal_page.value_at(internal_page.header.current_size as usize - 1),
+                )?;
                 let min_leafkv = self.find_subtree_min_leafkv(new_page_id)?;
-                Ok((min_leafkv.0, new_page_id))
+                Ok((
+                    shortest_separator(&max_leafkv.0, &min_leafkv.0),
+                    new_page_id,
+                ))
             }
         }
     }
 
     fn borrow_min_kv(
         &self,
-        parent_page_id: PageId,
-        page_id: PageId,
-        borrowed_page_id: PageId,
+        parent_internal_page: &mut BPlusTreeInternalPage,
+        tree_page: &mut BPlusTreePage,
+        borrowed_tree_page: &mut BPlusTreePage,
+        separator_index: usize,
     ) -> BustubxResult<bool> {
-        self.borrow(parent_page_id, page_id, borrowed_page_id, true)
+        self.borrow(
+            parent_internal_page,
+            tree_page,
+            borrowed_tree_page,
+            separator_index,
+            true,
+        )
     }
 
     fn borrow_max_kv(
         &self,
-        parent_page_id: PageId,
-        page_id: PageId,
-        borrowed_page_id: PageId,
+        parent_internal_page: &mut BPlusTreeInternalPage,
+        tree_page: &mut BPlusTreePage,
+        borrowed_tree_page: &mut BPlusTreePage,
+        separator_index: usize,
     ) -> BustubxResult<bool> {
-        self.borrow(parent_page_id, page_id, borrowed_page_id, false)
+        self.borrow(
+            parent_internal_page,
+            tree_page,
+            borrowed_tree_page,
+            separator_index,
+            false,
+        )
     }
 
+    // 页都已被调用方加锁，由调用方写回
+    // separator_index为父节点中两页之间分隔key的位置，页的最小key可能已被删除，不能按key查找
     fn borrow(
         &self,
-        parent_page_id: PageId,
-        page_id: PageId,
-        borrowed_page_id: PageId,
+        parent_internal_page: &mut BPlusTreeInternalPage,
+        tree_page: &mut BPlusTreePage,
+        borrowed_tree_page: &mut BPlusTreePage,
+        separator_index: usize,
         min_max: bool,
     ) -> BustubxResult<bool> {
-        let (borrowed_page, mut borrowed_tree_page) = self
-            .buffer_pool
-            .fetch_tree_page(borrowed_page_id, self.key_schema.clone())?;
         if !borrowed_tree_page.can_borrow() {
             return Ok(false);
         }
 
-        let (page, mut tree_page) = self
-            .buffer_pool
-            .fetch_tree_page(page_id, self.key_schema.clone())?;
-
-        let (old_internal_key, new_internal_key) = match borrowed_tree_page {
-            BPlusTreePage::Internal(ref mut borrowed_internal_page) => {
-                let BPlusTreePage::Internal(ref mut internal_page) = tree_page else {
-                    return Err(BustubxError::Storage(
-                        "Leaf page can not borrow from internal page".to_string(),
-                    ));
-                };
+        let separator = &mut parent_internal_page.array[separator_index].0;
+        match (tree_page, borrowed_tree_page) {
+            (
+                BPlusTreePage::Internal(internal_page),
+                BPlusTreePage::Internal(borrowed_internal_page),
+            ) => {
                 if min_max {
+                    // 分隔key下移到借来的子节点，右兄弟新的第一个key上移
                     let kv = borrowed_internal_page.reverse_split_off(0).remove(0);
-                    internal_page.insert(kv.0.clone(), kv.1);
-                    (
-                        kv.0,
-                        self.find_subtree_min_leafkv(borrowed_internal_page.value_at(0))?
-                            .0,
-                    )
+                    internal_page.array.push((separator.clone(), kv.1));
+                    internal_page.header.current_size += 1;
+                    *separator = borrowed_internal_page.key_at(0).clone();
                 } else {
+                    // 借来的kv成为第一个子节点，分隔key下移到原来的第一个子节点
                     let kv = borrowed_internal_page
                         .split_off(borrowed_internal_page.header.current_size as usize - 1)
                         .remove(0);
-                    let min_key = internal_page.key_at(0).clone();
-                    internal_page.insert(kv.0.clone(), kv.1);
-                    (
-                        min_key,
-                        self.find_subtree_min_leafkv(borrowed_internal_page.value_at(0))?
-                            .0,
-                    )
+                    internal_page.array[0].0 = separator.clone();
+                    internal_page.array.insert(0, kv.clone());
+                    internal_page.header.current_size += 1;
+                    *separator = kv.0;
                 }
             }
-            BPlusTreePage::Leaf(ref mut borrowed_leaf_page) => {
-                let BPlusTreePage::Leaf(ref mut leaf_page) = tree_page else {
-                    return Err(BustubxError::Storage(
-                        "Internal page can not borrow from leaf page".to_string(),
-                    ));
-                };
+            (BPlusTreePage::Leaf(leaf_page), BPlusTreePage::Leaf(borrowed_leaf_page)) => {
                 if min_max {
                     let kv = borrowed_leaf_page.reverse_split_off(0).remove(0);
-                    leaf_page.insert(kv.0.clone(), kv.1);
-                    (kv.0, borrowed_leaf_page.key_at(0).clone())
+                    leaf_page.insert(kv.0, kv.1);
+                    *separator = borrowed_leaf_page.key_at(0).clone();
                 } else {
                     let kv = borrowed_leaf_page
                         .split_off(borrowed_leaf_page.header.current_size as usize - 1)
                         .remove(0);
-                    leaf_page.insert(kv.0.clone(), kv.1);
-                    (leaf_page.key_at(1).clone(), leaf_page.key_at(0).clone())
+                    *separator = kv.0.clone();
+                    leaf_page.insert(kv.0, kv.1);
                 }
             }
-        };
-
-        page.write()
-            .unwrap()
-            .set_data(page_bytes_to_array(&BPlusTreePageCodec::encode(&tree_page)));
-
-        borrowed_page
-            .write()
-            .unwrap()
-            .set_data(page_bytes_to_array(&BPlusTreePageCodec::encode(
-                &borrowed_tree_page,
-            )));
-
-        // 更新父节点
-        let (parent_page, mut parent_internal_page) = self
-            .buffer_pool
-            .fetch_tree_internal_page(parent_page_id, self.key_schema.clone())?;
-        parent_internal_page.replace_key(&old_internal_key, new_internal_key);
-
-        parent_page.write().unwrap().set_data(page_bytes_to_array(
-            &BPlusTreeInternalPageCodec::encode(&parent_internal_page),
-        ));
+            _ => {
+                return Err(BustubxError::Storage(
+                    "Cannot borrow between leaf page and internal page".to_string(),
+                ))
+            }
+        }
         Ok(true)
     }
 
-    fn find_sibling_pages(
-        &self,
-        parent_page_id: PageId,
-        child_page_id: PageId,
-    ) -> BustubxResult<(Option<PageId>, Option<PageId>)> {
-        let (_, parent_internal_page) = self
-            .buffer_pool
-            .fetch_tree_internal_page(parent_page_id, self.key_schema.clone())?;
-        Ok(parent_internal_page.sibling_page_ids(child_page_id))
-    }
-
+    // 页都已被调用方加锁，由调用方写回左边页并删除右边页
     fn merge(
         &self,
-        parent_page_id: PageId,
-        left_page_id: PageId,
-        right_page_id: PageId,
-    ) -> BustubxResult<PageId> {
-        let (left_page, mut left_tree_page) = self
-            .buffer_pool
-            .fetch_tree_page(left_page_id, self.key_schema.clone())?;
-        let (_, mut right_tree_page) = self
-            .buffer_pool
-            .fetch_tree_page(right_page_id, self.key_schema.clone())?;
-
+        parent_internal_page: &mut BPlusTreeInternalPage,
+        left_tree_page: &mut BPlusTreePage,
+        right_tree_page: &BPlusTreePage,
+        separator_index: usize,
+    ) -> BustubxResult<()> {
         // 向左合入
-        match left_tree_page {
-            BPlusTreePage::Internal(ref mut left_internal_page) => {
-                if let BPlusTreePage::Internal(ref mut right_internal_page) = right_tree_page {
-                    // 空key处理
-                    let mut kvs = right_internal_page.array.clone();
-                    let min_leaf_kv =
-                        self.find_subtree_min_leafkv(right_internal_page.value_at(0))?;
-                    kvs[0].0 = min_leaf_kv.0;
-                    left_internal_page.batch_insert(kvs);
-                } else {
-                    return Err(BustubxError::Storage(
-                        "Leaf page can not merge from internal page".to_string(),
-                    ));
-                }
+        match (left_tree_page, right_tree_page) {
+            (
+                BPlusTreePage::Internal(left_internal_page),
+                BPlusTreePage::Internal(right_internal_page),
+            ) => {
+                // 分隔key下移作为右边页第一个子节点的key
+                let mut kvs = right_internal_page.array.clone();
+                kvs[0].0 = parent_internal_page.key_at(separator_index).clone();
+                left_internal_page.header.current_size += kvs.len() as u32;
+                left_internal_page.array.extend(kvs);
             }
-            BPlusTreePage::Leaf(ref mut left_leaf_page) => {
-                if let BPlusTreePage::Leaf(ref mut right_leaf_page) = right_tree_page {
-                    left_leaf_page.batch_insert(right_leaf_page.array.clone());
-                    // 更新next page id
-                    left_leaf_page.header.next_page_id = right_leaf_page.header.next_page_id;
-                } else {
-                    return Err(BustubxError::Storage(
-                        "Internal page can not merge from leaf page".to_string(),
-                    ));
-                }
+            (BPlusTreePage::Leaf(left_leaf_page), BPlusTreePage::Leaf(right_leaf_page)) => {
+                left_leaf_page.batch_insert(right_leaf_page.array.clone());
+                // 更新next page id
+                left_leaf_page.header.next_page_id = right_leaf_page.header.next_page_id;
+            }
+            _ => {
+                return Err(BustubxError::Storage(
+                    "Cannot merge leaf page and internal page".to_string(),
+                ))
             }
         };
 
-        left_page
-            .write()
-            .unwrap()
-            .set_data(page_bytes_to_array(&BPlusTreePageCodec::encode(
-                &left_tree_page,
-            )));
-
-        // 删除右边页
-        self.buffer_pool.delete_page(right_page_id)?;
-
         // 更新父节点
-        let (parent_page, mut parent_internal_page) = self
-            .buffer_pool
-            .fetch_tree_internal_page(parent_page_id, self.key_schema.clone())?;
-        parent_internal_page.delete_page_id(right_page_id);
-
-        // 根节点只有一个子节点（叶子）时，则叶子节点成为新的根节点
-        if parent_page_id == self.root_page_id.load(Ordering::SeqCst)
-            && parent_internal_page.header.current_size == 1
-        {
-            self.root_page_id.store(left_page_id, Ordering::SeqCst);
-            // 删除旧的根节点
-            self.buffer_pool.delete_page(parent_page_id)?;
-            Ok(left_page_id)
-        } else {
-            parent_page.write().unwrap().set_data(page_bytes_to_array(
-                &BPlusTreeInternalPageCodec::encode(&parent_internal_page),
-            ));
-            Ok(parent_page_id)
-        }
+        parent_internal_page.array.remove(separator_index);
+        parent_internal_page.header.current_size -= 1;
+        Ok(())
     }
 
     // 查找子树最小的leafKV
@@ -530,7 +807,7 @@ impl BPlusTreeIndex {
     fn find_subtree_leafkv(&self, page_id: PageId, min_or_max: bool) -> BustubxResult<LeafKV> {
         let (_, mut curr_tree_page) = self
             .buffer_pool
-            .fetch_tree_page(page_id, self.key_schema.clone())?;
+            .fetch_tree_page(page_id, self.entry_schema.clone())?;
         loop {
             match curr_tree_page {
                 BPlusTreePage::Internal(internal_page) => {
@@ -542,7 +819,7 @@ impl BPlusTreeIndex {
                     let next_page_id = internal_page.value_at(index);
                     let (_, next_tree_page) = self
                         .buffer_pool
-                        .fetch_tree_page(next_page_id, self.key_schema.clone())?;
+                        .fetch_tree_page(next_page_id, self.entry_schema.clone())?;
                     curr_tree_page = next_tree_page;
                 }
                 BPlusTreePage::Leaf(leaf_page) => {
@@ -557,28 +834,54 @@ impl BPlusTreeIndex {
         }
     }
 
-    pub fn get_first_leaf_page(&self) -> BustubxResult<BPlusTreeLeafPage> {
-        let (_, mut curr_tree_page) = self.buffer_pool.fetch_tree_page(
-            self.root_page_id.load(Ordering::SeqCst),
-            self.key_schema.clone(),
-        )?;
+    /// Finds the leftmost leaf page, it stays latched until the guard is dropped.
+    pub fn get_first_leaf_page(&self) -> BustubxResult<(ReadPageGuard<'_>, BPlusTreeLeafPage)> {
+        let root_latch = self.root_latch.read().unwrap();
+        let mut curr_guard = self
+            .buffer_pool
+            .fetch_page_read(self.root_page_id.load(Ordering::SeqCst))?;
+        drop(root_latch);
         loop {
+            let (curr_tree_page, _) =
+                BPlusTreePageCodec::decode(curr_guard.data(), self.entry_schema.clone())?;
             match curr_tree_page {
                 BPlusTreePage::Internal(internal_page) => {
-                    let next_page_id = internal_page.value_at(0);
-                    let (_, next_tree_page) = self
+                    curr_guard = self
                         .buffer_pool
-                        .fetch_tree_page(next_page_id, self.key_schema.clone())?;
-                    curr_tree_page = next_tree_page;
+                        .fetch_page_read(internal_page.value_at(0))?;
                 }
                 BPlusTreePage::Leaf(leaf_page) => {
-                    return Ok(leaf_page);
+                    return Ok((curr_guard, leaf_page));
                 }
             }
         }
     }
 }
 
+/// Shortest key ordered after `left` and not after `right`, the first varchar column telling
+/// them apart is cut to the prefix of `right` needed, so varchar keys make short separators.
+fn shortest_separator(left: &Tuple, right: &Tuple) -> Tuple {
+    let mut separator = right.clone();
+    for (idx, column) in right.schema.columns.iter().enumerate() {
+        if left.data[idx] == right.data[idx] {
+            continue;
+        }
+        // 降序列的前缀会排在原值之后，不能截断
+        if let (ScalarValue::Varchar(Some(l)), ScalarValue::Varchar(Some(r)), false) =
+            (&left.data[idx], &right.data[idx], column.descending)
+        {
+            let shared = l.bytes().zip(r.bytes()).take_while(|(a, b)| a == b).count();
+            let mut end = (shared + 1).min(r.len());
+            while !r.is_char_boundary(end) {
+                end += 1;
+            }
+            separator.data[idx] = ScalarValue::Varchar(Some(r[..end].to_string()));
+        }
+        break;
+    }
+    separator
+}
+
 #[derive(Debug)]
 pub struct TreeIndexIterator {
     index: Arc<BPlusTreeIndex>,
@@ -601,128 +904,193 @@ impl TreeIndexIterator {
         }
     }
 
-    pub fn load_next_leaf_page(&mut self) -> BustubxResult<bool> {
-        let next_page_id = self.leaf_page.header.next_page_id;
-        if next_page_id == INVALID_PAGE_ID {
-            Ok(false)
-        } else {
-            let (_, next_leaf_page) = self
-                .index
-                .buffer_pool
-                .fetch_tree_leaf_page(next_page_id, self.index.key_schema.clone())?;
-            self.leaf_page = next_leaf_page;
-            Ok(true)
-        }
+    pub fn next(&mut self) -> BustubxResult<Option<RecordId>> {
+        Ok(self.next_entry()?.map(|(_, rid)| rid))
     }
 
-    pub fn next(&mut self) -> BustubxResult<Option<RecordId>> {
+    /// Returns the next entry, its key is followed by the record id columns, see
+    /// [`BPlusTreeIndex::entry_schema`].
+    pub fn next_entry(&mut self) -> BustubxResult<Option<(Tuple, RecordId)>> {
         if self.started {
-            match self.end_bound.as_ref() {
-                Bound::Included(end_tuple) => {
-                    self.cursor += 1;
-                    let end_tuple = end_tuple.clone();
-                    let kv = if self.cursor >= self.leaf_page.header.current_size as usize {
-                        if self.load_next_leaf_page()? {
-                            self.cursor = 0;
-                            self.leaf_page.array[self.cursor].clone()
-                        } else {
-                            return Ok(None);
-                        }
-                    } else {
-                        self.leaf_page.array[self.cursor].clone()
-                    };
-                    if kv.0 <= end_tuple {
-                        Ok(Some(kv.1))
-                    } else {
-                        Ok(None)
-                    }
-                }
-                Bound::Excluded(end_tuple) => {
-                    self.cursor += 1;
-                    let end_tuple = end_tuple.clone();
-                    let kv = if self.cursor >= self.leaf_page.header.current_size as usize {
-                        if self.load_next_leaf_page()? {
-                            self.cursor = 0;
-                            self.leaf_page.array[self.cursor].clone()
-                        } else {
-                            return Ok(None);
-                        }
-                    } else {
-                        self.leaf_page.array[self.cursor].clone()
-                    };
-                    if kv.0 < end_tuple {
-                        Ok(Some(kv.1))
-                    } else {
-                        Ok(None)
-                    }
-                }
-                Bound::Unbounded => {
-                    self.cursor += 1;
-                    if self.cursor >= self.leaf_page.header.current_size as usize {
-                        if self.load_next_leaf_page()? {
-                            self.cursor = 0;
-                            Ok(Some(self.leaf_page.array[self.cursor].1))
-                        } else {
-                            Ok(None)
-                        }
-                    } else {
-                        Ok(Some(self.leaf_page.array[self.cursor].1))
-                    }
-                }
-            }
+            self.cursor += 1;
         } else {
             self.started = true;
-            match self.start_bound.as_ref() {
-                Bound::Included(start_tuple) => {
-                    let mut context = Context::new(self.index.root_page_id.load(Ordering::SeqCst));
-                    let Some(leaf_page) = self.index.find_leaf_page(start_tuple, &mut context)?
-                    else {
-                        return Ok(None);
-                    };
-                    self.leaf_page = BPlusTreeLeafPageCodec::decode(
-                        leaf_page.read().unwrap().data(),
-                        self.index.key_schema.clone(),
-                    )?
-                    .0;
-                    if let Some(idx) = self.leaf_page.next_closest(start_tuple, true) {
-                        self.cursor = idx;
-                        Ok(Some(self.leaf_page.array[self.cursor].1))
-                    } else if self.load_next_leaf_page()? {
-                        self.cursor = 0;
-                        Ok(Some(self.leaf_page.array[self.cursor].1))
-                    } else {
-                        Ok(None)
-                    }
-                }
-                Bound::Excluded(start_tuple) => {
-                    let mut context = Context::new(self.index.root_page_id.load(Ordering::SeqCst));
-                    let Some(leaf_page) = self.index.find_leaf_page(start_tuple, &mut context)?
-                    else {
-                        return Ok(None);
-                    };
-                    self.leaf_page = BPlusTreeLeafPageCodec::decode(
-                        leaf_page.read().unwrap().data(),
-                        self.index.key_schema.clone(),
-                    )?
-                    .0;
-                    if let Some(idx) = self.leaf_page.next_closest(start_tuple, false) {
-                        self.cursor = idx;
-                        Ok(Some(self.leaf_page.array[self.cursor].1))
-                    } else if self.load_next_leaf_page()? {
-                        self.cursor = 0;
-                        Ok(Some(self.leaf_page.array[self.cursor].1))
-                    } else {
-                        Ok(None)
-                    }
-                }
-                Bound::Unbounded => {
-                    self.leaf_page = self.index.get_first_leaf_page()?;
-                    self.cursor = 0;
-                    Ok(Some(self.leaf_page.array[self.cursor].1))
-                }
+            self.seek(self.start_bound.clone())?;
+        }
+        if self.cursor >= self.leaf_page.header.current_size as usize {
+            // the copied page may be merged away meanwhile, so descend again to the entry
+            // after its last one instead of following its next page id
+            let Some((last_entry, _)) = self.leaf_page.array.last() else {
+                return Ok(None);
+            };
+            self.seek(Bound::Excluded(last_entry.clone()))?;
+            if self.cursor >= self.leaf_page.header.current_size as usize {
+                return Ok(None);
             }
         }
+        let (key, rid) = &self.leaf_page.array[self.cursor];
+        let in_range = match self.end_bound.as_ref() {
+            Bound::Included(end_tuple) => key <= end_tuple,
+            Bound::Excluded(end_tuple) => key < end_tuple,
+            Bound::Unbounded => true,
+        };
+        Ok(in_range.then(|| (key.clone(), *rid)))
     }
+
+    /// Copies the leaf page holding the first entry within `start_bound` and positions the
+    /// cursor at it, or past the end of the last leaf page when there is none.
+    fn seek(&mut self, start_bound: Bound<Tuple>) -> BustubxResult<()> {
+        let index = self.index.clone();
+        if index.is_empty() {
+            self.leaf_page = BPlusTreeLeafPage::empty();
+            self.cursor = 0;
+            return Ok(());
+        }
+        let (mut leaf_guard, mut leaf_page) = match start_bound.as_ref() {
+            Bound::Included(start_tuple) => index.find_first_leaf_page(start_tuple, true)?,
+            Bound::Excluded(start_tuple) => index.find_first_leaf_page(start_tuple, false)?,
+            Bound::Unbounded => index.get_first_leaf_page()?,
+        };
+        let mut cursor = match start_bound.as_ref() {
+            Bound::Included(start_tuple) => leaf_page.next_closest(start_tuple, true),
+            Bound::Excluded(start_tuple) => leaf_page.next_closest(start_tuple, false),
+            Bound::Unbounded => Some(0),
+        }
+        .unwrap_or(leaf_page.header.current_size as usize);
+        while cursor >= leaf_page.header.current_size as usize
+            && leaf_page.header.next_page_id != INVALID_PAGE_ID
+        {
+            // 先获取下一页的latch再释放当前页的，下一页不会在读到之前被合并删除
+            let next_guard = index
+                .buffer_pool
+                .fetch_page_read(leaf_page.header.next_page_id)?;
+            leaf_page =
+                BPlusTreeLeafPageCodec::decode(next_guard.data(), index.entry_schema.clone())?.0;
+            leaf_guard = next_guard;
+            cursor = 0;
+        }
+        drop(leaf_guard);
+        self.leaf_page = leaf_page;
+        self.cursor = cursor;
+        Ok(())
+    }
+}
+
+/// Schema of the tree keys of an index on `key_schema`, see [`BPlusTreeIndex::entry_schema`].
+fn entry_schema(key_schema: &SchemaRef) -> SchemaRef {
+    let mut entry_columns = key_schema.columns.clone();
+    entry_columns.push(Arc::new(Column::new("__page_id", DataType::UInt32, false)));
+    entry_columns.push(Arc::new(Column::new("__slot_num", DataType::UInt32, false)));
+    Arc::new(Schema {
+        columns: entry_columns,
+    })
+}
+
+/// Tree key of the entry of `rid` under `key`.
+fn entry(entry_schema: SchemaRef, key: &Tuple, rid: RecordId) -> Tuple {
+    let mut data = key.data.clone();
+    data.push(ScalarValue::UInt32(Some(rid.page_id)));
+    data.push(ScalarValue::UInt32(Some(rid.slot_num)));
+    Tuple::new(entry_schema, data)
+}
+
+/// Refuses a kv too large for a page to keep its invariants, see [`BPLUS_MAX_KV_SIZE`].
+fn check_kv_size(kv: &LeafKV) -> BustubxResult<()> {
+    let size = BPlusTreeLeafPage::kv_size(kv, None);
+    if size > BPLUS_MAX_KV_SIZE {
+        return Err(BustubxError::Storage(format!(
+            "index key of {} bytes is too large, the max is {} bytes",
+            size, BPLUS_MAX_KV_SIZE
+        )));
+    }
+    Ok(())
+}
+
+/// Encoded bytes of each of the sorted `kvs` when it starts a page and when it follows the
+/// kv before it.
+fn kv_sizes<V>(
+    kvs: &[(Tuple, V)],
+    kv_size: impl Fn(&(Tuple, V), Option<&Tuple>) -> usize,
+) -> Vec<(usize, usize)> {
+    kvs.iter()
+        .enumerate()
+        .map(|(idx, kv)| {
+            let prev_key = idx.checked_sub(1).map(|prev| &kvs[prev].0);
+            (kv_size(kv, None), kv_size(kv, prev_key))
+        })
+        .collect()
+}
+
+/// Sizes of the pages holding sorted kvs of `kv_sizes` bytes, each filled to `fill_factor`
+/// percent of `max_size` kvs and of the page bytes after the header, but never below half
+/// of them so that no page but the root underflows.
+fn fill_sizes(
+    kv_sizes: &[(usize, usize)],
+    max_size: usize,
+    header_size: usize,
+    fill_factor: u8,
+) -> Vec<usize> {
+    let min_size = (max_size / 2).max(2).min(max_size);
+    let per_page = (max_size * fill_factor as usize / 100).clamp(min_size, max_size);
+    let max_bytes = BUSTUBX_PAGE_SIZE - header_size;
+    let bytes_per_page = (max_bytes * fill_factor as usize / 100).clamp(max_bytes / 2, max_bytes);
+    // 每页的第一个kv不做前缀压缩
+    let page_kv_sizes = |start: usize, end: usize| {
+        (start..end)
+            .map(|idx| {
+                if idx == start {
+                    kv_sizes[idx].0
+                } else {
+                    kv_sizes[idx].1
+                }
+            })
+            .collect::<Vec<usize>>()
+    };
+
+    let mut sizes = vec![];
+    let mut start = 0;
+    while start < kv_sizes.len() {
+        let mut end = start + 1;
+        let mut bytes = kv_sizes[start].0;
+        while end < kv_sizes.len()
+            && end - start < per_page
+            && bytes + kv_sizes[end].1 <= bytes_per_page
+        {
+            bytes += kv_sizes[end].1;
+            end += 1;
+        }
+        sizes.push(end - start);
+        start = end;
+    }
+    if sizes.len() < 2 {
+        return sizes;
+    }
+
+    // 最后一页未半满则合入前一页，放不下则两页均分
+    let last = sizes[sizes.len() - 1];
+    let last_bytes = page_kv_sizes(kv_sizes.len() - last, kv_sizes.len())
+        .iter()
+        .sum::<usize>();
+    if last >= min_size || header_size + last_bytes >= BPLUS_UNDERFLOW_SIZE {
+        return sizes;
+    }
+    let combined = page_kv_sizes(
+        kv_sizes.len() - last - sizes[sizes.len() - 2],
+        kv_sizes.len(),
+    );
+    let combined_bytes = header_size + combined.iter().sum::<usize>();
+    sizes.truncate(sizes.len() - 2);
+    if combined.len() <= max_size && combined_bytes <= BUSTUBX_PAGE_SIZE {
+        sizes.push(combined.len());
+    } else {
+        let at = if combined_bytes > BUSTUBX_PAGE_SIZE {
+            byte_midpoint(&combined)
+        } else {
+            combined.len() - combined.len() / 2
+        };
+        sizes.extend([at, combined.len() - at]);
+    }
+    sizes
 }
 
 #[cfg(test)]
@@ -731,9 +1099,15 @@ mod tests {
     use std::sync::Arc;
     use tempfile::TempDir;
 
+    use crate::buffer::{BUSTUBX_PAGE_SIZE, INVALID_PAGE_ID};
     use crate::catalog::SchemaRef;
     use crate::common::util::pretty_format_index_tree;
-    use crate::storage::index::TreeIndexIterator;
+    use crate::common::ScalarValue;
+    use crate::storage::index::{fill_sizes, shortest_separator, TreeIndexIterator};
+    use crate::storage::{
+        BPlusTreeLeafPage, BPLUS_INTERNAL_PAGE_MAX_SIZE, BPLUS_LEAF_PAGE_MAX_SIZE,
+        BPLUS_MAX_KV_SIZE,
+    };
     use crate::{
         buffer::BufferPoolManager,
         catalog::{Column, DataType, Schema},
@@ -752,7 +1126,7 @@ mod tests {
         ]));
         let disk_manager = DiskManager::try_new(temp_path).unwrap();
         let buffer_pool = Arc::new(BufferPoolManager::new(1000, Arc::new(disk_manager)));
-        let index = BPlusTreeIndex::new(key_schema.clone(), buffer_pool, 4, 4);
+        let index = BPlusTreeIndex::try_new(key_schema.clone(), buffer_pool, 4, 4).unwrap();
 
         index
             .insert(
@@ -830,34 +1204,34 @@ mod tests {
         println!("{display}");
         assert_eq!(display, "B+ Tree Level No.1:
 +-----------------------+
-| page_id=13, size: 2/4 |
+| page_id=17, size: 2/4 |
 +-----------------------+
 | +------------+------+ |
 | | NULL, NULL | 5, 5 | |
 | +------------+------+ |
-| | 8          | 12   | |
+| | 12         | 16   | |
 | +------------+------+ |
 +-----------------------+
 B+ Tree Level No.2:
 +-----------------------+------------------------+
-| page_id=8, size: 2/4  | page_id=12, size: 3/4  |
+| page_id=12, size: 2/4 | page_id=16, size: 3/4  |
 +-----------------------+------------------------+
 | +------------+------+ | +------+------+------+ |
 | | NULL, NULL | 3, 3 | | | 5, 5 | 7, 7 | 9, 9 | |
 | +------------+------+ | +------+------+------+ |
-| | 6          | 7    | | | 9    | 10   | 11   | |
+| | 10         | 11   | | | 13   | 14   | 15   | |
 | +------------+------+ | +------+------+------+ |
 +-----------------------+------------------------+
 B+ Tree Level No.3:
-+--------------------------------------+--------------------------------------+---------------------------------------+----------------------------------------+---------------------------------------+
-| page_id=6, size: 2/4, next_page_id=7 | page_id=7, size: 2/4, next_page_id=9 | page_id=9, size: 2/4, next_page_id=10 | page_id=10, size: 2/4, next_page_id=11 | page_id=11, size: 3/4, next_page_id=0 |
-+--------------------------------------+--------------------------------------+---------------------------------------+----------------------------------------+---------------------------------------+
-| +------+------+                      | +------+------+                      | +------+------+                       | +------+------+                        | +------+--------+--------+            |
-| | 1, 1 | 2, 2 |                      | | 3, 3 | 4, 4 |                      | | 5, 5 | 6, 6 |                       | | 7, 7 | 8, 8 |                        | | 9, 9 | 10, 10 | 11, 11 |            |
-| +------+------+                      | +------+------+                      | +------+------+                       | +------+------+                        | +------+--------+--------+            |
-| | 1-1  | 2-2  |                      | | 3-3  | 4-4  |                      | | 5-5  | 6-6  |                       | | 7-7  | 8-8  |                        | | 9-9  | 10-10  | 11-11  |            |
-| +------+------+                      | +------+------+                      | +------+------+                       | +------+------+                        | +------+--------+--------+            |
-+--------------------------------------+--------------------------------------+---------------------------------------+----------------------------------------+---------------------------------------+
++----------------------------------------+----------------------------------------+----------------------------------------+----------------------------------------+---------------------------------------+
+| page_id=10, size: 2/4, next_page_id=11 | page_id=11, size: 2/4, next_page_id=13 | page_id=13, size: 2/4, next_page_id=14 | page_id=14, size: 2/4, next_page_id=15 | page_id=15, size: 3/4, next_page_id=0 |
++----------------------------------------+----------------------------------------+----------------------------------------+----------------------------------------+---------------------------------------+
+| +------+------+                        | +------+------+                        | +------+------+                        | +------+------+                        | +------+--------+--------+            |
+| | 1, 1 | 2, 2 |                        | | 3, 3 | 4, 4 |                        | | 5, 5 | 6, 6 |                        | | 7, 7 | 8, 8 |                        | | 9, 9 | 10, 10 | 11, 11 |            |
+| +------+------+                        | +------+------+                        | +------+------+                        | +------+------+                        | +------+--------+--------+            |
+| | 1-1  | 2-2  |                        | | 3-3  | 4-4  |                        | | 5-5  | 6-6  |                        | | 7-7  | 8-8  |                        | | 9-9  | 10-10  | 11-11  |            |
+| +------+------+                        | +------+------+                        | +------+------+                        | +------+------+                        | +------+--------+--------+            |
++----------------------------------------+----------------------------------------+----------------------------------------+----------------------------------------+---------------------------------------+
 ");
     }
 
@@ -866,48 +1240,48 @@ B+ Tree Level No.3:
         let (index, key_schema) = build_index();
 
         index
-            .delete(&Tuple::new(
-                key_schema.clone(),
-                vec![3i8.into(), 3i16.into()],
-            ))
+            .delete(
+                &Tuple::new(key_schema.clone(), vec![3i8.into(), 3i16.into()]),
+                RecordId::new(3, 3),
+            )
             .unwrap();
         println!("{}", pretty_format_index_tree(&index).unwrap());
         index
-            .delete(&Tuple::new(
-                key_schema.clone(),
-                vec![10i8.into(), 10i16.into()],
-            ))
+            .delete(
+                &Tuple::new(key_schema.clone(), vec![10i8.into(), 10i16.into()]),
+                RecordId::new(10, 10),
+            )
             .unwrap();
         println!("{}", pretty_format_index_tree(&index).unwrap());
         index
-            .delete(&Tuple::new(
-                key_schema.clone(),
-                vec![8i8.into(), 8i16.into()],
-            ))
+            .delete(
+                &Tuple::new(key_schema.clone(), vec![8i8.into(), 8i16.into()]),
+                RecordId::new(8, 8),
+            )
             .unwrap();
         println!("{}", pretty_format_index_tree(&index).unwrap());
 
         assert_eq!(pretty_format_index_tree(&index).unwrap(),
                    "B+ Tree Level No.1:
 +------------------------------+
-| page_id=8, size: 3/4         |
+| page_id=12, size: 3/4        |
 +------------------------------+
 | +------------+------+------+ |
 | | NULL, NULL | 5, 5 | 7, 7 | |
 | +------------+------+------+ |
-| | 6          | 9    | 10   | |
+| | 10         | 13   | 14   | |
 | +------------+------+------+ |
 +------------------------------+
 B+ Tree Level No.2:
-+--------------------------------------+---------------------------------------+---------------------------------------+
-| page_id=6, size: 3/4, next_page_id=9 | page_id=9, size: 2/4, next_page_id=10 | page_id=10, size: 3/4, next_page_id=0 |
-+--------------------------------------+---------------------------------------+---------------------------------------+
-| +------+------+------+               | +------+------+                       | +------+------+--------+              |
-| | 1, 1 | 2, 2 | 4, 4 |               | | 5, 5 | 6, 6 |                       | | 7, 7 | 9, 9 | 11, 11 |              |
-| +------+------+------+               | +------+------+                       | +------+------+--------+              |
-| | 1-1  | 2-2  | 4-4  |               | | 5-5  | 6-6  |                       | | 7-7  | 9-9  | 11-11  |              |
-| +------+------+------+               | +------+------+                       | +------+------+--------+              |
-+--------------------------------------+---------------------------------------+---------------------------------------+
++----------------------------------------+----------------------------------------+---------------------------------------+
+| page_id=10, size: 3/4, next_page_id=13 | page_id=13, size: 2/4, next_page_id=14 | page_id=14, size: 3/4, next_page_id=0 |
++----------------------------------------+----------------------------------------+---------------------------------------+
+| +------+------+------+                 | +------+------+                        | +------+------+--------+              |
+| | 1, 1 | 2, 2 | 4, 4 |                 | | 5, 5 | 6, 6 |                        | | 7, 7 | 9, 9 | 11, 11 |              |
+| +------+------+------+                 | +------+------+                        | +------+------+--------+              |
+| | 1-1  | 2-2  | 4-4  |                 | | 5-5  | 6-6  |                        | | 7-7  | 9-9  | 11-11  |              |
+| +------+------+------+                 | +------+------+                        | +------+------+--------+              |
++----------------------------------------+----------------------------------------+---------------------------------------+
 ");
     }
 
@@ -921,7 +1295,7 @@ B+ Tree Level No.2:
                     vec![3i8.into(), 3i16.into()],
                 ))
                 .unwrap(),
-            Some(RecordId::new(3, 3))
+            vec![RecordId::new(3, 3)]
         );
         assert_eq!(
             index
@@ -930,7 +1304,474 @@ B+ Tree Level No.2:
                     vec![10i8.into(), 10i16.into()],
                 ))
                 .unwrap(),
-            Some(RecordId::new(10, 10))
+            vec![RecordId::new(10, 10)]
+        );
+    }
+
+    #[test]
+    pub fn test_index_duplicate_keys() {
+        let (index, key_schema) = build_index();
+        let key = Tuple::new(key_schema.clone(), vec![6i8.into(), 6i16.into()]);
+        for slot_num in (20..30).rev() {
+            index.insert(&key, RecordId::new(20, slot_num)).unwrap();
+        }
+        let mut expected = vec![RecordId::new(6, 6)];
+        expected.extend((20..30).map(|slot_num| RecordId::new(20, slot_num)));
+        assert_eq!(index.get(&key).unwrap(), expected);
+
+        index.delete(&key, RecordId::new(20, 25)).unwrap();
+        index.delete(&key, RecordId::new(6, 6)).unwrap();
+        expected.retain(|rid| *rid != RecordId::new(20, 25) && *rid != RecordId::new(6, 6));
+        assert_eq!(index.get(&key).unwrap(), expected);
+        assert_eq!(
+            index
+                .get(&Tuple::new(
+                    key_schema.clone(),
+                    vec![7i8.into(), 7i16.into()],
+                ))
+                .unwrap(),
+            vec![RecordId::new(7, 7)]
+        );
+
+        let index = Arc::new(index);
+        let mut iterator = TreeIndexIterator::new(index.clone(), key.clone()..=key);
+        for rid in expected {
+            assert_eq!(iterator.next().unwrap(), Some(rid));
+        }
+        assert_eq!(iterator.next().unwrap(), None);
+    }
+
+    #[test]
+    pub fn test_index_bulk_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let key_schema = Arc::new(Schema::new(vec![Column::new("a", DataType::Int32, false)]));
+        let disk_manager = DiskManager::try_new(temp_dir.path().join("test.db")).unwrap();
+        let buffer_pool = Arc::new(BufferPoolManager::new(1000, Arc::new(disk_manager)));
+        let index = BPlusTreeIndex::try_new(key_schema.clone(), buffer_pool, 4, 4).unwrap();
+        let key = |a: i32| Tuple::new(key_schema.clone(), vec![a.into()]);
+
+        // 乱序输入，每个key两条记录
+        let kvs = (0..100)
+            .rev()
+            .flat_map(|i| {
+                [
+                    (key(i), RecordId::new(2, i as u32)),
+                    (key(i), RecordId::new(1, i as u32)),
+                ]
+            })
+            .collect::<Vec<_>>();
+        index.bulk_load(kvs.clone(), 70).unwrap();
+        assert!(index.bulk_load(kvs, 70).is_err());
+
+        assert_eq!(
+            index.get(&key(42)).unwrap(),
+            vec![RecordId::new(1, 42), RecordId::new(2, 42)]
+        );
+        assert!(index.get(&key(100)).unwrap().is_empty());
+
+        index.insert(&key(42), RecordId::new(3, 42)).unwrap();
+        index.delete(&key(42), RecordId::new(1, 42)).unwrap();
+        for i in 0..40 {
+            index.delete(&key(i), RecordId::new(2, i as u32)).unwrap();
+        }
+        assert_eq!(
+            index.get(&key(42)).unwrap(),
+            vec![RecordId::new(2, 42), RecordId::new(3, 42)]
+        );
+
+        let index = Arc::new(index);
+        let mut iterator = TreeIndexIterator::new(index.clone(), key(38)..key(41));
+        for rid in [
+            RecordId::new(1, 38),
+            RecordId::new(1, 39),
+            RecordId::new(1, 40),
+            RecordId::new(2, 40),
+        ] {
+            assert_eq!(iterator.next().unwrap(), Some(rid));
+        }
+        assert_eq!(iterator.next().unwrap(), None);
+
+        let mut iterator = TreeIndexIterator::new(index.clone(), ..);
+        let mut entries = vec![];
+        while let Some(entry) = iterator.next_entry().unwrap() {
+            entries.push(entry);
+        }
+        assert_eq!(entries.len(), 160);
+        assert!(entries.windows(2).all(|w| w[0].0 <= w[1].0));
+    }
+
+    #[test]
+    pub fn test_index_concurrent_insert_delete() {
+        const THREADS: i32 = 8;
+        const KEYS_PER_THREAD: i32 = 300;
+
+        let temp_dir = TempDir::new().unwrap();
+        let key_schema = Arc::new(Schema::new(vec![Column::new("a", DataType::Int32, false)]));
+        let disk_manager = DiskManager::try_new(temp_dir.path().join("test.db")).unwrap();
+        let buffer_pool = Arc::new(BufferPoolManager::new(64, Arc::new(disk_manager)));
+        let index = BPlusTreeIndex::try_new(key_schema.clone(), buffer_pool, 4, 4).unwrap();
+        let key = |a: i32| Tuple::new(key_schema.clone(), vec![a.into()]);
+        let rid = |a: i32| RecordId::new(a as u32 + 1, a as u32);
+        // 每个线程乱序处理自己的一组key
+        let thread_keys =
+            |t: i32| (0..KEYS_PER_THREAD).map(move |i| (i * 37 % KEYS_PER_THREAD) * THREADS + t);
+
+        std::thread::scope(|scope| {
+            for t in 0..THREADS {
+                let (index, key) = (&index, &key);
+                scope.spawn(move || {
+                    for a in thread_keys(t) {
+                        index.insert(&key(a), rid(a)).unwrap();
+                        assert_eq!(index.get(&key(a)).unwrap(), vec![rid(a)]);
+                    }
+                });
+            }
+        });
+        for a in 0..THREADS * KEYS_PER_THREAD {
+            assert_eq!(index.get(&key(a)).unwrap(), vec![rid(a)]);
+        }
+
+        // 删除奇数key，同时读取的偶数key必须一直可见
+        std::thread::scope(|scope| {
+            for t in 0..THREADS {
+                let (index, key) = (&index, &key);
+                scope.spawn(move || {
+                    for a in thread_keys(t) {
+                        if a % 2 == 1 {
+                            index.delete(&key(a), rid(a)).unwrap();
+                            assert!(index.get(&key(a)).unwrap().is_empty());
+                        } else {
+                            assert_eq!(index.get(&key(a)).unwrap(), vec![rid(a)]);
+                        }
+                    }
+                });
+            }
+        });
+
+        let index = Arc::new(index);
+        let mut iterator = TreeIndexIterator::new(index.clone(), ..);
+        for a in (0..THREADS * KEYS_PER_THREAD).step_by(2) {
+            assert_eq!(iterator.next().unwrap(), Some(rid(a)));
+        }
+        assert_eq!(iterator.next().unwrap(), None);
+    }
+
+    #[test]
+    pub fn test_index_concurrent_scan_delete() {
+        const KEYS: i32 = 2000;
+
+        let temp_dir = TempDir::new().unwrap();
+        let key_schema = Arc::new(Schema::new(vec![Column::new("a", DataType::Int32, false)]));
+        let disk_manager = DiskManager::try_new(temp_dir.path().join("test.db")).unwrap();
+        let buffer_pool = Arc::new(BufferPoolManager::new(64, Arc::new(disk_manager)));
+        let index =
+            Arc::new(BPlusTreeIndex::try_new(key_schema.clone(), buffer_pool, 4, 4).unwrap());
+        let key = |a: i32| Tuple::new(key_schema.clone(), vec![a.into()]);
+        let rid = |a: i32| RecordId::new(a as u32 + 1, a as u32);
+        for a in 0..KEYS {
+            index.insert(&key(a), rid(a)).unwrap();
+        }
+
+        // 删除奇数key使leaf page不断合并，同时扫描的结果有序且包含所有偶数key
+        std::thread::scope(|scope| {
+            let (index, key) = (&index, &key);
+            scope.spawn(move || {
+                for a in (1..KEYS).step_by(2).rev() {
+                    index.delete(&key(a), rid(a)).unwrap();
+                }
+            });
+            for _ in 0..4 {
+                scope.spawn(move || {
+                    for _ in 0..5 {
+                        let mut iterator = TreeIndexIterator::new(index.clone(), ..);
+                        let mut rids = vec![];
+                        while let Some(rid) = iterator.next().unwrap() {
+                            rids.push(rid.slot_num as i32);
+                        }
+                        assert!(rids.windows(2).all(|pair| pair[0] < pair[1]));
+                        let evens = rids.iter().filter(|a| *a % 2 == 0).count();
+                        assert_eq!(evens as i32, KEYS / 2);
+                    }
+                });
+            }
+        });
+
+        let mut iterator = TreeIndexIterator::new(index.clone(), ..);
+        for a in (0..KEYS).step_by(2) {
+            assert_eq!(iterator.next().unwrap(), Some(rid(a)));
+        }
+        assert_eq!(iterator.next().unwrap(), None);
+    }
+
+    #[test]
+    fn test_shortest_separator() {
+        let schema = Arc::new(Schema::new(vec![
+            Column::new("a", DataType::Varchar(None), false),
+            Column::new("b", DataType::Int32, false),
+        ]));
+        let key =
+            |a: &str, b: i32| Tuple::new(schema.clone(), vec![a.to_string().into(), b.into()]);
+        let separator = |left: Tuple, right: Tuple| shortest_separator(&left, &right).data;
+
+        assert_eq!(
+            separator(key("/usr/bin/cat", 1), key("/usr/lib/x", 2)),
+            vec!["/usr/l".to_string().into(), 2.into()]
+        );
+        assert_eq!(
+            separator(key("/usr", 1), key("/usr/lib", 2)),
+            vec!["/usr/".to_string().into(), 2.into()]
+        );
+        assert_eq!(
+            separator(key("/café", 1), key("/caféa", 2)),
+            vec!["/caféa".to_string().into(), 2.into()]
+        );
+        assert_eq!(
+            separator(key("/caf", 1), key("/café", 2)),
+            vec!["/café".to_string().into(), 2.into()]
+        );
+        // 相同的key只能由后面的列区分
+        assert_eq!(
+            separator(key("/usr", 1), key("/usr", 2)),
+            vec!["/usr".to_string().into(), 2.into()]
+        );
+
+        let descending = Arc::new(Schema::new(vec![Column::new(
+            "a",
+            DataType::Varchar(None),
+            false,
+        )
+        .with_descending(true)]));
+        let left = Tuple::new(descending.clone(), vec!["/usr/lib".to_string().into()]);
+        let right = Tuple::new(descending.clone(), vec!["/usr/bin".to_string().into()]);
+        assert_eq!(shortest_separator(&left, &right), right);
+    }
+
+    #[test]
+    pub fn test_index_varchar_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let key_schema = Arc::new(Schema::new(vec![Column::new(
+            "url",
+            DataType::Varchar(None),
+            false,
+        )]));
+        let disk_manager = DiskManager::try_new(temp_dir.path().join("test.db")).unwrap();
+        let buffer_pool = Arc::new(BufferPoolManager::new(1000, Arc::new(disk_manager)));
+        let key = |i: u32| {
+            Tuple::new(
+                key_schema.clone(),
+                vec![ScalarValue::Varchar(Some(format!(
+                    "https://example.com/articles/{:03}/comments",
+                    i
+                )))],
+            )
+        };
+        let rid = |i: u32| RecordId::new(i, i);
+
+        let index = BPlusTreeIndex::try_new(key_schema.clone(), buffer_pool.clone(), 4, 4).unwrap();
+        for i in (0..200).map(|i| i * 37 % 200) {
+            index.insert(&key(i), rid(i)).unwrap();
+        }
+        for i in (0..200).step_by(2) {
+            index.delete(&key(i), rid(i)).unwrap();
+        }
+        let bulk_loaded = BPlusTreeIndex::try_new(key_schema.clone(), buffer_pool, 4, 4).unwrap();
+        bulk_loaded
+            .bulk_load((1..200).step_by(2).map(|i| (key(i), rid(i))).collect(), 90)
+            .unwrap();
+
+        for index in [Arc::new(index), Arc::new(bulk_loaded)] {
+            for i in 0..200 {
+                let expected = if i % 2 == 1 { vec![rid(i)] } else { vec![] };
+                assert_eq!(index.get(&key(i)).unwrap(), expected);
+            }
+            let mut iterator = TreeIndexIterator::new(index.clone(), key(51)..key(60));
+            for i in (51..60).step_by(2) {
+                assert_eq!(iterator.next().unwrap(), Some(rid(i)));
+            }
+            assert_eq!(iterator.next().unwrap(), None);
+        }
+    }
+
+    #[test]
+    pub fn test_index_prefix_compression_fanout() {
+        let temp_dir = TempDir::new().unwrap();
+        let key_schema = Arc::new(Schema::new(vec![Column::new(
+            "url",
+            DataType::Varchar(None),
+            false,
+        )]));
+        let disk_manager = DiskManager::try_new(temp_dir.path().join("test.db")).unwrap();
+        let buffer_pool = Arc::new(BufferPoolManager::new(1000, Arc::new(disk_manager)));
+        let key = |i: u32| {
+            Tuple::new(
+                key_schema.clone(),
+                vec![ScalarValue::Varchar(Some(format!(
+                    "https://example.com/{}/articles/{:04}",
+                    "archive/".repeat(20),
+                    i
+                )))],
+            )
+        };
+        let rid = |i: u32| RecordId::new(i, i);
+
+        let index = BPlusTreeIndex::try_new(
+            key_schema.clone(),
+            buffer_pool.clone(),
+            BPLUS_INTERNAL_PAGE_MAX_SIZE as u32,
+            BPLUS_LEAF_PAGE_MAX_SIZE as u32,
+        )
+        .unwrap();
+        for i in (0..500).map(|i| i * 37 % 500) {
+            index.insert(&key(i), rid(i)).unwrap();
+        }
+        let bulk_loaded = BPlusTreeIndex::try_new(
+            key_schema.clone(),
+            buffer_pool.clone(),
+            BPLUS_INTERNAL_PAGE_MAX_SIZE as u32,
+            BPLUS_LEAF_PAGE_MAX_SIZE as u32,
+        )
+        .unwrap();
+        bulk_loaded
+            .bulk_load((0..500).map(|i| (key(i), rid(i))).collect(), 90)
+            .unwrap();
+
+        // 不压缩时一页最多放下的kv数
+        let uncompressed_kv_size =
+            BPlusTreeLeafPage::kv_size(&(index.entry(&key(0), rid(0)), rid(0)), None);
+        let uncompressed_per_page = BUSTUBX_PAGE_SIZE / uncompressed_kv_size;
+        for index in [index, bulk_loaded] {
+            let mut leaf_page = index.get_first_leaf_page().unwrap().1;
+            let mut leaf_count = 1;
+            while leaf_page.header.next_page_id != INVALID_PAGE_ID {
+                leaf_page = buffer_pool
+                    .fetch_tree_leaf_page(leaf_page.header.next_page_id, index.entry_schema.clone())
+                    .unwrap()
+                    .1;
+                leaf_count += 1;
+            }
+            assert!(500 / leaf_count > uncompressed_per_page * 2);
+            for i in (0..500).step_by(7) {
+                assert_eq!(index.get(&key(i)).unwrap(), vec![rid(i)]);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_index_large_keys() {
+        let temp_dir = TempDir::new().unwrap();
+        let key_schema = Arc::new(Schema::new(vec![Column::new(
+            "name",
+            DataType::Varchar(None),
+            false,
+        )]));
+        let disk_manager = DiskManager::try_new(temp_dir.path().join("test.db")).unwrap();
+        let buffer_pool = Arc::new(BufferPoolManager::new(1000, Arc::new(disk_manager)));
+        // 长度不一的key，前缀各不相同
+        let key = |i: u32| {
+            Tuple::new(
+                key_schema.clone(),
+                vec![ScalarValue::Varchar(Some(format!(
+                    "{:04}{}",
+                    i * 7919 % 10000,
+                    "x".repeat((i * 131 % 900) as usize)
+                )))],
+            )
+        };
+        let rid = |i: u32| RecordId::new(i, i);
+
+        let index = BPlusTreeIndex::try_new(
+            key_schema.clone(),
+            buffer_pool.clone(),
+            BPLUS_INTERNAL_PAGE_MAX_SIZE as u32,
+            BPLUS_LEAF_PAGE_MAX_SIZE as u32,
+        )
+        .unwrap();
+        for i in 0..500 {
+            index.insert(&key(i), rid(i)).unwrap();
+        }
+        for i in (0..500).step_by(3) {
+            index.delete(&key(i), rid(i)).unwrap();
+        }
+        let bulk_loaded = BPlusTreeIndex::try_new(
+            key_schema.clone(),
+            buffer_pool.clone(),
+            BPLUS_INTERNAL_PAGE_MAX_SIZE as u32,
+            BPLUS_LEAF_PAGE_MAX_SIZE as u32,
+        )
+        .unwrap();
+        bulk_loaded
+            .bulk_load(
+                (0..500)
+                    .filter(|i| i % 3 != 0)
+                    .map(|i| (key(i), rid(i)))
+                    .collect(),
+                90,
+            )
+            .unwrap();
+
+        for index in [Arc::new(index), Arc::new(bulk_loaded)] {
+            for i in 0..500 {
+                let expected = if i % 3 != 0 { vec![rid(i)] } else { vec![] };
+                assert_eq!(index.get(&key(i)).unwrap(), expected);
+            }
+            let mut iterator = TreeIndexIterator::new(index.clone(), ..);
+            let mut count = 0;
+            let mut prev_key = None;
+            while let Some((key, _)) = iterator.next_entry().unwrap() {
+                assert!(prev_key.as_ref().map_or(true, |prev_key| prev_key < &key));
+                prev_key = Some(key);
+                count += 1;
+            }
+            assert_eq!(count, 333);
+            for i in (0..500).filter(|i| i % 3 != 0) {
+                index.delete(&key(i), rid(i)).unwrap();
+            }
+            assert!(TreeIndexIterator::new(index.clone(), ..)
+                .next()
+                .unwrap()
+                .is_none());
+        }
+
+        // key过大时返回错误
+        let too_large = Tuple::new(
+            key_schema.clone(),
+            vec![ScalarValue::Varchar(Some("x".repeat(BPLUS_MAX_KV_SIZE)))],
+        );
+        let index = BPlusTreeIndex::try_new(key_schema.clone(), buffer_pool.clone(), 4, 4).unwrap();
+        assert!(index.insert(&too_large, rid(1)).is_err());
+        assert!(index
+            .bulk_load(vec![(key(1), rid(1)), (too_large, rid(2))], 90)
+            .is_err());
+        assert!(index.is_empty());
+    }
+
+    #[test]
+    fn test_fill_sizes() {
+        let kv_sizes = |total: usize, size: (usize, usize)| vec![size; total];
+        assert_eq!(fill_sizes(&kv_sizes(3, (1, 1)), 10, 0, 90), vec![3]);
+        assert_eq!(fill_sizes(&kv_sizes(27, (1, 1)), 10, 0, 90), vec![9, 9, 9]);
+        assert_eq!(fill_sizes(&kv_sizes(24, (1, 1)), 10, 0, 90), vec![9, 9, 6]);
+        assert_eq!(fill_sizes(&kv_sizes(19, (1, 1)), 10, 0, 90), vec![9, 10]);
+        assert_eq!(
+            fill_sizes(&kv_sizes(29, (1, 1)), 10, 0, 90),
+            vec![9, 9, 6, 5]
+        );
+        assert_eq!(fill_sizes(&kv_sizes(12, (1, 1)), 10, 0, 10), vec![5, 7]);
+
+        // 按字节填满，90%的页放得下9个400字节的kv
+        assert_eq!(
+            fill_sizes(&kv_sizes(27, (400, 400)), 256, 0, 90),
+            vec![9, 9, 9]
+        );
+        // 前缀压缩后放得下更多kv，最后一页过小，合并后放不下则按字节均分
+        assert_eq!(
+            fill_sizes(&kv_sizes(40, (400, 100)), 256, 0, 90),
+            vec![19, 21]
+        );
+        assert_eq!(fill_sizes(&kv_sizes(10, (400, 400)), 256, 0, 90), vec![10]);
+        assert_eq!(
+            fill_sizes(&kv_sizes(11, (400, 400)), 256, 0, 90),
+            vec![6, 5]
         );
     }
 
@@ -968,5 +1809,22 @@ B+ Tree Level No.2:
         assert_eq!(iterator4.next().unwrap(), Some(RecordId::new(11, 11)));
         assert_eq!(iterator4.next().unwrap(), None);
         assert_eq!(iterator4.next().unwrap(), None);
+
+        // bounds on a key prefix
+        let prefix_schema = Arc::new(key_schema.project(&[0]).unwrap());
+        let start_tuple5 = Tuple::new(prefix_schema.clone(), vec![4i8.into()]);
+        let end_tuple5 = Tuple::new(prefix_schema.clone(), vec![5i8.into()]);
+        let mut iterator5 = TreeIndexIterator::new(index.clone(), start_tuple5..=end_tuple5);
+        assert_eq!(iterator5.next().unwrap(), Some(RecordId::new(4, 4)));
+        assert_eq!(iterator5.next().unwrap(), Some(RecordId::new(5, 5)));
+        assert_eq!(iterator5.next().unwrap(), None);
+
+        let start_tuple6 = Tuple::new(prefix_schema.clone(), vec![10i8.into()]);
+        let mut iterator6 = TreeIndexIterator::new(
+            index.clone(),
+            (Bound::Excluded(start_tuple6), Bound::Unbounded),
+        );
+        assert_eq!(iterator6.next().unwrap(), Some(RecordId::new(11, 11)));
+        assert_eq!(iterator6.next().unwrap(), None);
     }
 }
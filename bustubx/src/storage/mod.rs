@@ -1,5 +1,6 @@
 pub mod codec;
 mod disk_manager;
+pub mod hash_index;
 pub mod index;
 mod page;
 mod table_heap;
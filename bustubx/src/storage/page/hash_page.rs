@@ -0,0 +1,165 @@
+use crate::buffer::{PageId, BUSTUBX_PAGE_SIZE, INVALID_PAGE_ID};
+use crate::catalog::SchemaRef;
+use crate::storage::codec::{HashBucketPageHeaderCodec, RidCodec, TupleCodec};
+use crate::storage::RecordId;
+use crate::Tuple;
+
+pub const HASH_BUCKET_PAGE_MAX_SIZE: usize = 10;
+/// Max encoded bytes of a kv, larger keys are refused so that a bucket holds several kvs
+pub const HASH_BUCKET_MAX_KV_SIZE: usize = BUSTUBX_PAGE_SIZE / 4;
+/// Directory slots of the max depth take 2^8 * 8 bytes, which fits in one page
+pub const HASH_DIRECTORY_MAX_DEPTH: u32 = 8;
+
+pub type HashKV = (Tuple, RecordId);
+
+/**
+ * Directory page format:
+ *  -----------------------------------------------------------------------------------------
+ * | HEADER | BUCKET_PAGE_ID(0)+LOCAL_DEPTH(0) | ... | BUCKET_PAGE_ID(n-1)+LOCAL_DEPTH(n-1) |
+ *  -----------------------------------------------------------------------------------------
+ * where n is 2^global_depth, slot i holds the bucket of keys whose hash ends with bits of i.
+ *
+ * Header format (size in byte, 8 bytes in total):
+ * ----------------------------------------------------------------------------
+ * | GlobalDepth (4) | MaxDepth (4) |
+ * ----------------------------------------------------------------------------
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HashDirectoryPage {
+    pub header: HashDirectoryPageHeader,
+    pub bucket_page_ids: Vec<PageId>,
+    pub local_depths: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HashDirectoryPageHeader {
+    pub global_depth: u32,
+    pub max_depth: u32,
+}
+
+impl HashDirectoryPage {
+    pub fn new(bucket_page_id: PageId, max_depth: u32) -> Self {
+        Self {
+            header: HashDirectoryPageHeader {
+                global_depth: 0,
+                max_depth,
+            },
+            bucket_page_ids: vec![bucket_page_id],
+            local_depths: vec![0],
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        1 << self.header.global_depth
+    }
+
+    pub fn slot_of(&self, hash: u32) -> usize {
+        (hash as usize) & (self.size() - 1)
+    }
+
+    /// Doubles the slots, the new half points to the same buckets as the old half.
+    pub fn grow(&mut self) {
+        self.bucket_page_ids.extend_from_within(..);
+        self.local_depths.extend_from_within(..);
+        self.header.global_depth += 1;
+    }
+
+    pub fn can_shrink(&self) -> bool {
+        self.header.global_depth > 0
+            && self
+                .local_depths
+                .iter()
+                .all(|depth| *depth < self.header.global_depth)
+    }
+
+    /// Halves the slots, only valid when `can_shrink`.
+    pub fn shrink(&mut self) {
+        self.header.global_depth -= 1;
+        let size = self.size();
+        self.bucket_page_ids.truncate(size);
+        self.local_depths.truncate(size);
+    }
+}
+
+/**
+ * Bucket page format:
+ *  ----------------------------------------------------------------
+ * | HEADER | KEY(1)+RID(1) | KEY(2)+RID(2) | ... | KEY(n)+RID(n) |
+ *  ----------------------------------------------------------------
+ *
+ * Header format (size in byte, 12 bytes in total):
+ * ----------------------------------------------------------------------------
+ * | CurrentSize (4) | MaxSize (4) | NextPageId (4) |
+ * ----------------------------------------------------------------------------
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HashBucketPage {
+    pub schema: SchemaRef,
+    pub header: HashBucketPageHeader,
+    pub array: Vec<HashKV>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HashBucketPageHeader {
+    pub current_size: u32,
+    // max kv size can be stored
+    pub max_size: u32,
+    // overflow page of a bucket which cannot split any more
+    pub next_page_id: PageId,
+}
+
+impl HashBucketPage {
+    pub fn new(schema: SchemaRef, max_size: u32) -> Self {
+        Self {
+            schema,
+            header: HashBucketPageHeader {
+                current_size: 0,
+                max_size,
+                next_page_id: INVALID_PAGE_ID,
+            },
+            array: Vec::with_capacity(max_size as usize),
+        }
+    }
+
+    /// Whether `kv` fits in the bucket, which is limited in both kvs and bytes.
+    pub fn has_room(&self, kv: &HashKV) -> bool {
+        self.header.current_size < self.header.max_size
+            && self.byte_size() + Self::kv_size(kv) <= BUSTUBX_PAGE_SIZE
+    }
+
+    pub fn kv_size(kv: &HashKV) -> usize {
+        TupleCodec::encode(&kv.0).len() + RidCodec::encode(&kv.1).len()
+    }
+
+    pub fn byte_size(&self) -> usize {
+        HashBucketPageHeaderCodec::encode(&self.header).len()
+            + self.array.iter().map(Self::kv_size).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.header.current_size == 0
+    }
+
+    pub fn insert(&mut self, key: Tuple, rid: RecordId) {
+        self.array.push((key, rid));
+        self.header.current_size += 1;
+    }
+
+    /// Removes the entry of `rid` under `key`, returns whether it was found.
+    pub fn remove(&mut self, key: &Tuple, rid: RecordId) -> bool {
+        let Some(index) = self.array.iter().position(|kv| &kv.0 == key && kv.1 == rid) else {
+            return false;
+        };
+        self.array.remove(index);
+        self.header.current_size -= 1;
+        true
+    }
+
+    /// Removes the entries `pred` holds for and returns them.
+    pub fn drain(&mut self, pred: impl Fn(&HashKV) -> bool) -> Vec<HashKV> {
+        let (drained, kept) = self.array.drain(..).partition(pred);
+        self.array = kept;
+        self.header.current_size = self.array.len() as u32;
+        drained
+    }
+}
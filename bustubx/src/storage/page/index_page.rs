@@ -1,11 +1,24 @@
-use crate::buffer::{PageId, INVALID_PAGE_ID};
+use crate::buffer::{PageId, BUSTUBX_PAGE_SIZE, INVALID_PAGE_ID};
 use crate::catalog::{Schema, SchemaRef};
+use crate::storage::codec::{
+    BPlusTreeInternalPageHeaderCodec, BPlusTreeLeafPageHeaderCodec, CommonCodec, IndexKeyCodec,
+    RidCodec,
+};
 use crate::storage::RecordId;
 use crate::Tuple;
 use std::sync::Arc;
 
-pub const BPLUS_INTERNAL_PAGE_MAX_SIZE: usize = 10;
-pub const BPLUS_LEAF_PAGE_MAX_SIZE: usize = 10;
+/// Max kvs of a page, pages usually fill up by bytes before reaching it
+pub const BPLUS_INTERNAL_PAGE_MAX_SIZE: usize = 256;
+pub const BPLUS_LEAF_PAGE_MAX_SIZE: usize = 256;
+/// A page below half its max kvs underflows once its encoded bytes drop below this
+pub const BPLUS_UNDERFLOW_SIZE: usize = BUSTUBX_PAGE_SIZE / 4;
+/// Max encoded bytes of a kv not prefix compressed, larger keys are refused. A page holding
+/// a single kv then underflows, and an underflowing page fits into a sibling not able to
+/// lend it a kv.
+pub const BPLUS_MAX_KV_SIZE: usize = BPLUS_UNDERFLOW_SIZE - 64;
+/// Percentage of the slots of a page filled when an index is bulk loaded
+pub const BPLUS_DEFAULT_FILL_FACTOR: u8 = 90;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum BPlusTreePage {
@@ -24,9 +37,30 @@ impl BPlusTreePage {
         if is_root {
             return false;
         }
+        let (current_size, min_size) = match self {
+            Self::Internal(page) => (page.header.current_size, page.min_size()),
+            Self::Leaf(page) => (page.header.current_size, page.min_size()),
+        };
+        current_size < min_size && self.byte_size() < BPLUS_UNDERFLOW_SIZE
+    }
+    /// Whether inserting a kv cannot split the page.
+    pub fn is_insert_safe(&self) -> bool {
+        let (current_size, max_size) = match self {
+            Self::Internal(page) => (page.header.current_size, page.header.max_size),
+            Self::Leaf(page) => (page.header.current_size, page.header.max_size),
+        };
+        current_size < max_size && self.has_room_for_kv()
+    }
+    /// Whether deleting a kv cannot make the page underflow, or collapse the root. An
+    /// internal page also needs room for a separator key replaced by a borrow below it.
+    pub fn is_delete_safe(&self, is_root: bool) -> bool {
         match self {
-            Self::Internal(page) => page.header.current_size < page.min_size(),
-            Self::Leaf(page) => page.header.current_size < page.min_size(),
+            Self::Internal(page) if is_root => {
+                page.header.current_size > 2 && self.has_room_for_kv()
+            }
+            Self::Leaf(_) if is_root => true,
+            Self::Internal(_) => self.can_borrow() && self.has_room_for_kv(),
+            Self::Leaf(_) => self.can_borrow(),
         }
     }
     pub fn insert_internalkv(&mut self, internalkv: InternalKV) {
@@ -35,12 +69,36 @@ impl BPlusTreePage {
             Self::Leaf(_) => panic!("Leaf page cannot insert InternalKV"),
         }
     }
+    /// Whether the page can lend a kv to a sibling without underflowing.
     pub fn can_borrow(&self) -> bool {
+        let (current_size, min_size) = match self {
+            Self::Internal(page) => (page.header.current_size, page.min_size()),
+            Self::Leaf(page) => (page.header.current_size, page.min_size()),
+        };
+        current_size > min_size || self.byte_size() >= BPLUS_UNDERFLOW_SIZE + BPLUS_MAX_KV_SIZE
+    }
+    pub fn byte_size(&self) -> usize {
         match self {
-            Self::Internal(page) => page.header.current_size > page.min_size(),
-            Self::Leaf(page) => page.header.current_size > page.min_size(),
+            Self::Internal(page) => page.byte_size(),
+            Self::Leaf(page) => page.byte_size(),
         }
     }
+    fn has_room_for_kv(&self) -> bool {
+        self.byte_size() + BPLUS_MAX_KV_SIZE <= BUSTUBX_PAGE_SIZE
+    }
+}
+
+/// Index splitting `kv_sizes` into two halves of about the same bytes.
+pub fn byte_midpoint(kv_sizes: &[usize]) -> usize {
+    let total = kv_sizes.iter().sum::<usize>();
+    let mut bytes = 0;
+    for (idx, size) in kv_sizes.iter().enumerate() {
+        if bytes * 2 >= total {
+            return idx.clamp(1, kv_sizes.len() - 1);
+        }
+        bytes += size;
+    }
+    kv_sizes.len() - 1
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -52,12 +110,30 @@ pub enum BPlusTreePageType {
 pub type InternalKV = (Tuple, PageId);
 pub type LeafKV = (Tuple, RecordId);
 
+/**
+ * Header page format (size in byte, 4 bytes in total):
+ * ----------------------------------------------------------------------------
+ * | RootPageId (4) |
+ * ----------------------------------------------------------------------------
+ *
+ * The header page is allocated with the index and never moves, the system table records
+ * it instead of the root page which changes as the tree grows and shrinks.
+ */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BPlusTreeHeaderPage {
+    pub root_page_id: PageId,
+}
+
 /**
  * Internal page format (keys are stored in increasing order):
  *  --------------------------------------------------------------------------
  * | HEADER | KEY(1)+PAGE_ID(1) | KEY(2)+PAGE_ID(2) | ... | KEY(n)+PAGE_ID(n) |
  *  --------------------------------------------------------------------------
  *
+ * A varchar key column only stores the suffix after the prefix shared with the previous
+ * key, see `IndexKeyCodec`. Separator keys are truncated to the shortest prefix telling
+ * apart the two sides of a split.
+ *
  * Header format (size in byte, 12 bytes in total):
  * ----------------------------------------------------------------------------
  * | PageType (4) | CurrentSize (4) | MaxSize (4) |
@@ -186,7 +262,37 @@ impl BPlusTreeInternalPage {
     }
 
     pub fn is_full(&self) -> bool {
-        self.header.current_size > self.header.max_size
+        self.header.current_size > self.header.max_size || self.byte_size() > BUSTUBX_PAGE_SIZE
+    }
+
+    /// Encoded bytes of `kv` following a kv of `prev_key` on a page.
+    pub fn kv_size(kv: &InternalKV, prev_key: Option<&Tuple>) -> usize {
+        IndexKeyCodec::encoded_len(&kv.0, prev_key) + CommonCodec::encode_u32(kv.1).len()
+    }
+
+    pub fn kv_sizes(&self) -> Vec<usize> {
+        let mut prev_key = None;
+        let mut sizes = vec![];
+        for kv in self.array.iter() {
+            sizes.push(Self::kv_size(kv, prev_key));
+            prev_key = Some(&kv.0);
+        }
+        sizes
+    }
+
+    pub fn byte_size(&self) -> usize {
+        BPlusTreeInternalPageHeaderCodec::encode(&self.header).len()
+            + self.kv_sizes().iter().sum::<usize>()
+    }
+
+    /// Index to split a full page at, halving its bytes if it overflows the page size and
+    /// its kvs otherwise.
+    pub fn split_index(&self) -> usize {
+        if self.byte_size() > BUSTUBX_PAGE_SIZE {
+            byte_midpoint(&self.kv_sizes())
+        } else {
+            self.header.current_size as usize / 2
+        }
     }
 
     pub fn split_off(&mut self, at: usize) -> Vec<InternalKV> {
@@ -269,6 +375,8 @@ impl BPlusTreeInternalPage {
  * | HEADER | KEY(1) + RID(1) | KEY(2) + RID(2) | ... | KEY(n) + RID(n)
  *  ----------------------------------------------------------------------
  *
+ * Keys are prefix compressed as in internal pages.
+ *
  *  Header format (size in byte, 16 bytes in total):
  *  ---------------------------------------------------------------------
  * | PageType (4) | CurrentSize (4) | MaxSize (4) | NextPageId (4)
@@ -330,7 +438,37 @@ impl BPlusTreeLeafPage {
     }
 
     pub fn is_full(&self) -> bool {
-        self.header.current_size > self.header.max_size
+        self.header.current_size > self.header.max_size || self.byte_size() > BUSTUBX_PAGE_SIZE
+    }
+
+    /// Encoded bytes of `kv` following a kv of `prev_key` on a page.
+    pub fn kv_size(kv: &LeafKV, prev_key: Option<&Tuple>) -> usize {
+        IndexKeyCodec::encoded_len(&kv.0, prev_key) + RidCodec::encode(&kv.1).len()
+    }
+
+    pub fn kv_sizes(&self) -> Vec<usize> {
+        let mut prev_key = None;
+        let mut sizes = vec![];
+        for kv in self.array.iter() {
+            sizes.push(Self::kv_size(kv, prev_key));
+            prev_key = Some(&kv.0);
+        }
+        sizes
+    }
+
+    pub fn byte_size(&self) -> usize {
+        BPlusTreeLeafPageHeaderCodec::encode(&self.header).len()
+            + self.kv_sizes().iter().sum::<usize>()
+    }
+
+    /// Index to split a full page at, halving its bytes if it overflows the page size and
+    /// its kvs otherwise.
+    pub fn split_index(&self) -> usize {
+        if self.byte_size() > BUSTUBX_PAGE_SIZE {
+            byte_midpoint(&self.kv_sizes())
+        } else {
+            self.header.current_size as usize / 2
+        }
     }
 
     // TODO 可以通过二分查找来插入
@@ -400,15 +538,9 @@ impl BPlusTreeLeafPage {
     }
 
     pub fn next_closest(&self, tuple: &Tuple, included: bool) -> Option<usize> {
-        for (idx, (key, _)) in self.array.iter().enumerate() {
-            if tuple == key && included {
-                return Some(idx);
-            }
-            if key > tuple {
-                return Some(idx);
-            }
-        }
-        None
+        self.array
+            .iter()
+            .position(|(key, _)| key > tuple || (included && key >= tuple))
     }
 }
 
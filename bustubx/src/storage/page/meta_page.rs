@@ -1,30 +1,37 @@
 use crate::buffer::{PageId, INVALID_PAGE_ID};
-use crate::storage::codec::MetaPageCodec;
 use crate::{BustubxError, BustubxResult};
 
-pub static EMPTY_META_PAGE: MetaPage = MetaPage {
-    major_version: 0,
-    minor_version: 0,
-    freelist_page_id: 0,
-    information_schema_schemas_first_page_id: 0,
-    information_schema_tables_first_page_id: 0,
-    information_schema_columns_first_page_id: 0,
-    information_schema_indexes_first_page_id: 0,
-};
-
-lazy_static::lazy_static! {
-    pub static ref META_PAGE_SIZE: usize = MetaPageCodec::encode(&EMPTY_META_PAGE).len();
-}
+/// Version of the db file format, bumped whenever the layout of a page or of a system
+/// table changes. Files of another format version are refused instead of being misread.
+///
+/// Version 1 stores varchar lengths in 4 bytes and moves tuples larger than a page to
+/// overflow pages, files written before that recorded no format version.
+pub const FORMAT_VERSION: u32 = 1;
+/// Bytes reserved for the meta page at the start of the db file, meta fields added later
+/// take the unused tail so that the pages after it keep their offsets.
+pub const META_PAGE_SIZE: usize = 256;
 
+/**
+ * Meta page format (size in byte, META_PAGE_SIZE bytes in total):
+ * ----------------------------------------------------------------------------
+ * | MajorVersion (4) | MinorVersion (4) | FormatVersion (4) | FreelistPageId (4) |
+ * ----------------------------------------------------------------------------
+ * | first page ids of the information_schema tables (4 each) | Reserved |
+ * ----------------------------------------------------------------------------
+ */
 #[derive(Debug, Eq, PartialEq)]
 pub struct MetaPage {
     pub major_version: u32,
     pub minor_version: u32,
+    pub format_version: u32,
     pub freelist_page_id: PageId,
     pub information_schema_schemas_first_page_id: PageId,
     pub information_schema_tables_first_page_id: PageId,
     pub information_schema_columns_first_page_id: PageId,
     pub information_schema_indexes_first_page_id: PageId,
+    pub information_schema_constraints_first_page_id: PageId,
+    pub information_schema_sequences_first_page_id: PageId,
+    pub information_schema_views_first_page_id: PageId,
 }
 
 impl MetaPage {
@@ -47,11 +54,15 @@ impl MetaPage {
         Ok(Self {
             major_version,
             minor_version,
+            format_version: FORMAT_VERSION,
             freelist_page_id: INVALID_PAGE_ID,
             information_schema_schemas_first_page_id: INVALID_PAGE_ID,
             information_schema_tables_first_page_id: INVALID_PAGE_ID,
             information_schema_columns_first_page_id: INVALID_PAGE_ID,
             information_schema_indexes_first_page_id: INVALID_PAGE_ID,
+            information_schema_constraints_first_page_id: INVALID_PAGE_ID,
+            information_schema_sequences_first_page_id: INVALID_PAGE_ID,
+            information_schema_views_first_page_id: INVALID_PAGE_ID,
         })
     }
 }
@@ -0,0 +1,52 @@
+use crate::buffer::{PageId, BUSTUBX_PAGE_SIZE, INVALID_PAGE_ID};
+use crate::storage::codec::OverflowPageHeaderCodec;
+
+static EMPTY_OVERFLOW_PAGE_HEADER: OverflowPageHeader = OverflowPageHeader {
+    next_page_id: 0,
+    data_size: 0,
+};
+
+lazy_static::lazy_static! {
+    pub static ref OVERFLOW_PAGE_MAX_DATA_SIZE: usize =
+        BUSTUBX_PAGE_SIZE - OverflowPageHeaderCodec::encode(&EMPTY_OVERFLOW_PAGE_HEADER).len();
+}
+
+/**
+ * Overflow page format, a value too large to be stored inline in a table page is split
+ * into a chain of overflow pages:
+ *  ----------------------------------------------
+ *  | NextPageId (4) | DataSize (2) | ... DATA ... |
+ *  ----------------------------------------------
+ */
+#[derive(Debug, Eq, PartialEq)]
+pub struct OverflowPage {
+    pub header: OverflowPageHeader,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct OverflowPageHeader {
+    pub next_page_id: PageId,
+    pub data_size: u16,
+}
+
+/// Points to the overflow chain of a value which is stored out of line.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct OverflowPointer {
+    pub first_page_id: PageId,
+    /// total size of the encoded value across the chain
+    pub size: u32,
+}
+
+impl OverflowPage {
+    pub fn new(data: Vec<u8>) -> Self {
+        debug_assert!(data.len() <= *OVERFLOW_PAGE_MAX_DATA_SIZE);
+        Self {
+            header: OverflowPageHeader {
+                next_page_id: INVALID_PAGE_ID,
+                data_size: data.len() as u16,
+            },
+            data,
+        }
+    }
+}
@@ -70,7 +70,7 @@ pub const INVALID_RID: RecordId = RecordId {
     slot_num: 0,
 };
 
-#[derive(derive_new::new, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(derive_new::new, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct RecordId {
     pub page_id: PageId,
     pub slot_num: u32,
@@ -91,7 +91,7 @@ impl TablePage {
     }
 
     // Get the offset for the next tuple insertion.
-    pub fn next_tuple_offset(&self, tuple: &Tuple) -> BustubxResult<usize> {
+    pub fn next_tuple_offset(&self, tuple_size: usize) -> BustubxResult<usize> {
         // Get the ending offset of the current slot. If there are inserted tuples,
         // get the offset of the previous inserted tuple; otherwise, set it to the size of the page.
         let slot_end_offset = if self.header.num_tuples > 0 {
@@ -101,7 +101,7 @@ impl TablePage {
         };
 
         // Check if the current slot has enough space for the new tuple. Return None if not.
-        if slot_end_offset < TupleCodec::encode(tuple).len() {
+        if slot_end_offset < tuple_size {
             return Err(BustubxError::Storage(
                 "No enough space to store tuple".to_string(),
             ));
@@ -109,7 +109,7 @@ impl TablePage {
 
         // Calculate the insertion offset for the new tuple by subtracting its data length
         // from the ending offset of the current slot.
-        let tuple_offset = slot_end_offset - TupleCodec::encode(tuple).len();
+        let tuple_offset = slot_end_offset - tuple_size;
 
         // Calculate the minimum valid tuple insertion offset, including the table page header size,
         // the total size of each tuple info (existing tuple infos and newly added tuple info).
@@ -126,10 +126,18 @@ impl TablePage {
     }
 
     pub fn insert_tuple(&mut self, meta: &TupleMeta, tuple: &Tuple) -> BustubxResult<u16> {
+        self.insert_tuple_bytes(meta, &TupleCodec::encode(tuple))
+    }
+
+    /// Inserts an encoded tuple, see [`TupleCodec`].
+    pub fn insert_tuple_bytes(
+        &mut self,
+        meta: &TupleMeta,
+        tuple_bytes: &[u8],
+    ) -> BustubxResult<u16> {
         // Get the offset for the next tuple insertion.
-        let tuple_offset = self.next_tuple_offset(tuple)?;
+        let tuple_offset = self.next_tuple_offset(tuple_bytes.len())?;
         let tuple_id = self.header.num_tuples;
-        let tuple_bytes = TupleCodec::encode(tuple);
         debug_assert!(tuple_bytes.len() < u16::MAX as usize);
 
         // Store tuple information including offset, length, and metadata.
@@ -148,7 +156,7 @@ impl TablePage {
         }
 
         // Copy the tuple's data into the appropriate position within the page's data buffer.
-        self.data[tuple_offset..tuple_offset + tuple_bytes.len()].copy_from_slice(&tuple_bytes);
+        self.data[tuple_offset..tuple_offset + tuple_bytes.len()].copy_from_slice(tuple_bytes);
         Ok(tuple_id)
     }
 
@@ -168,6 +176,11 @@ impl TablePage {
     }
 
     pub fn update_tuple(&mut self, tuple: Tuple, slot_num: u16) -> BustubxResult<()> {
+        self.update_tuple_bytes(&TupleCodec::encode(&tuple), slot_num)
+    }
+
+    /// Replaces the encoded tuple at `slot_num`, see [`TupleCodec`].
+    pub fn update_tuple_bytes(&mut self, tuple_bytes: &[u8], slot_num: u16) -> BustubxResult<()> {
         if slot_num >= self.header.num_tuples {
             return Err(BustubxError::Storage(format!(
                 "tuple_id {} out of range",
@@ -176,27 +189,22 @@ impl TablePage {
         }
         let offset = self.header.tuple_infos[slot_num as usize].offset as usize;
         let size = self.header.tuple_infos[slot_num as usize].size as usize;
-        let tuple_bytes = TupleCodec::encode(&tuple);
         if tuple_bytes.len() == size {
-            self.data[offset..(offset + size)].copy_from_slice(&tuple_bytes);
+            self.data[offset..(offset + size)].copy_from_slice(tuple_bytes);
         } else {
             // need move other tuples
             let mut full_tuples = vec![];
             for info in self.header.tuple_infos.iter() {
                 full_tuples.push((
                     info.meta,
-                    TupleCodec::decode(
-                        &self.data[info.offset as usize..(info.offset + info.size) as usize],
-                        self.schema.clone(),
-                    )?
-                    .0,
+                    &self.data[info.offset as usize..(info.offset + info.size) as usize],
                 ));
             }
-            full_tuples[slot_num as usize].1 = tuple;
+            full_tuples[slot_num as usize].1 = tuple_bytes;
 
             let mut new_page = TablePage::new(self.schema.clone(), self.header.next_page_id);
-            for (meta, tuple) in full_tuples.iter() {
-                new_page.insert_tuple(meta, tuple)?;
+            for (meta, tuple_bytes) in full_tuples.iter() {
+                new_page.insert_tuple_bytes(meta, tuple_bytes)?;
             }
             self.header = new_page.header;
             self.data = new_page.data;
@@ -205,6 +213,13 @@ impl TablePage {
     }
 
     pub fn tuple(&self, slot_num: u16) -> BustubxResult<(TupleMeta, Tuple)> {
+        let (meta, tuple_bytes) = self.tuple_bytes(slot_num)?;
+        let (tuple, _) = TupleCodec::decode(tuple_bytes, self.schema.clone())?;
+        Ok((meta, tuple))
+    }
+
+    /// The encoded tuple at `slot_num`, see [`TupleCodec`].
+    pub fn tuple_bytes(&self, slot_num: u16) -> BustubxResult<(TupleMeta, &[u8])> {
         if slot_num >= self.header.num_tuples {
             return Err(BustubxError::Storage(format!(
                 "tuple_id {} out of range",
@@ -215,12 +230,7 @@ impl TablePage {
         let offset = self.header.tuple_infos[slot_num as usize].offset;
         let size = self.header.tuple_infos[slot_num as usize].size;
         let meta = self.header.tuple_infos[slot_num as usize].meta;
-        let (tuple, _) = TupleCodec::decode(
-            &self.data[offset as usize..(offset + size) as usize],
-            self.schema.clone(),
-        )?;
-
-        Ok((meta, tuple))
+        Ok((meta, &self.data[offset as usize..(offset + size) as usize]))
     }
 
     pub fn tuple_meta(&self, slot_num: u16) -> BustubxResult<TupleMeta> {
@@ -1,22 +1,32 @@
-use crate::buffer::{AtomicPageId, INVALID_PAGE_ID};
-use crate::catalog::SchemaRef;
+use crate::buffer::{AtomicPageId, PageId, BUSTUBX_PAGE_SIZE, INVALID_PAGE_ID};
+use crate::catalog::{DataType, SchemaRef};
 use crate::common::util::page_bytes_to_array;
-use crate::storage::codec::TablePageCodec;
-use crate::storage::{RecordId, TablePage, TupleMeta, INVALID_RID};
-use crate::{buffer::BufferPoolManager, BustubxResult};
-use std::collections::Bound;
+use crate::storage::codec::{OverflowPageCodec, ScalarValueCodec, TablePageCodec, TupleCodec};
+use crate::storage::{
+    OverflowPage, OverflowPointer, RecordId, TablePage, TupleMeta, INVALID_RID,
+    OVERFLOW_PAGE_MAX_DATA_SIZE,
+};
+use crate::{buffer::BufferPoolManager, BustubxError, BustubxResult};
+use std::collections::{Bound, HashMap};
 use std::ops::RangeBounds;
 use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use super::tuple::Tuple;
 
+/// Tuples encoded larger than this move their largest variable-length values to
+/// overflow pages until they fit, so a page holds at least a few tuples.
+pub const TUPLE_OVERFLOW_THRESHOLD: usize = BUSTUBX_PAGE_SIZE / 4;
+
 #[derive(Debug)]
 pub struct TableHeap {
     pub schema: SchemaRef,
     pub buffer_pool: Arc<BufferPoolManager>,
     pub first_page_id: AtomicPageId,
     pub last_page_id: AtomicPageId,
+    /// Whether all tuples of a page are visible, i.e. none is deleted. Filled from the page
+    /// headers on first use so scans reading only index keys can skip the page.
+    pub visibility_map: Mutex<HashMap<PageId, bool>>,
 }
 
 impl TableHeap {
@@ -35,6 +45,7 @@ impl TableHeap {
             buffer_pool,
             first_page_id: AtomicPageId::new(first_page_id),
             last_page_id: AtomicPageId::new(first_page_id),
+            visibility_map: Mutex::new(HashMap::new()),
         })
     }
 
@@ -51,23 +62,27 @@ impl TableHeap {
     /// Returns:
     /// An `Option` containing the `Rid` of the inserted tuple if successful, otherwise `None`.
     pub fn insert_tuple(&self, meta: &TupleMeta, tuple: &Tuple) -> BustubxResult<RecordId> {
+        let tuple_bytes = self.encode_tuple(tuple)?;
         let mut last_page_id = self.last_page_id.load(Ordering::SeqCst);
-        let (last_page, mut last_table_page) = self
+        let (mut last_page, mut last_table_page) = self
             .buffer_pool
             .fetch_table_page(last_page_id, self.schema.clone())?;
 
         // Loop until a suitable page is found for inserting the tuple
         loop {
-            if last_table_page.next_tuple_offset(tuple).is_ok() {
+            if last_table_page.next_tuple_offset(tuple_bytes.len()).is_ok() {
                 break;
             }
 
             // if there's no tuple in the page, and we can't insert the tuple,
             // then this tuple is too large.
-            assert!(
-                last_table_page.header.num_tuples > 0,
-                "tuple is too large, cannot insert"
-            );
+            if last_table_page.header.num_tuples == 0 {
+                self.free_tuple_overflow(&tuple_bytes)?;
+                return Err(BustubxError::Storage(format!(
+                    "tuple of {} bytes is too large, cannot insert",
+                    tuple_bytes.len()
+                )));
+            }
 
             // Allocate a new page if no more table pages are available.
             let next_page = self.buffer_pool.new_page()?;
@@ -91,12 +106,13 @@ impl TableHeap {
 
             // Update last_page_id.
             last_page_id = next_page_id;
+            last_page = next_page;
             last_table_page = next_table_page;
             self.last_page_id.store(last_page_id, Ordering::SeqCst);
         }
 
         // Insert the tuple into the chosen page
-        let slot_id = last_table_page.insert_tuple(meta, tuple)?;
+        let slot_id = last_table_page.insert_tuple_bytes(meta, &tuple_bytes)?;
 
         last_page
             .write()
@@ -109,36 +125,101 @@ impl TableHeap {
         Ok(RecordId::new(last_page_id, slot_id as u32))
     }
 
-    pub fn update_tuple(&self, rid: RecordId, tuple: Tuple) -> BustubxResult<()> {
+    /// Updates a tuple in place, or moves it to the end of the heap when its page can't
+    /// hold the new version. Returns the rid of the tuple afterwards.
+    #[must_use = "the tuple may have moved to another rid"]
+    pub fn update_tuple(&self, rid: RecordId, tuple: Tuple) -> BustubxResult<RecordId> {
+        let tuple_bytes = self.encode_tuple(&tuple)?;
         let (page, mut table_page) = self
             .buffer_pool
             .fetch_table_page(rid.page_id, self.schema.clone())?;
-        table_page.update_tuple(tuple, rid.slot_num as u16)?;
+        let (meta, old_tuple_bytes) = table_page.tuple_bytes(rid.slot_num as u16)?;
+        let old_tuple_bytes = old_tuple_bytes.to_vec();
+        if table_page
+            .update_tuple_bytes(&tuple_bytes, rid.slot_num as u16)
+            .is_err()
+        {
+            // no enough space left in the page
+            drop(page);
+            self.free_tuple_overflow(&tuple_bytes)?;
+            let new_rid = self.insert_tuple(&meta, &tuple)?;
+            let mut old_meta = meta;
+            old_meta.is_deleted = true;
+            self.update_tuple_meta(old_meta, rid)?;
+            return Ok(new_rid);
+        }
 
         page.write()
             .unwrap()
             .set_data(page_bytes_to_array(&TablePageCodec::encode(&table_page)));
-        Ok(())
+        drop(page);
+        self.free_tuple_overflow(&old_tuple_bytes)?;
+        Ok(rid)
     }
 
+    /// Updates the meta of a tuple, a tuple becoming deleted frees its overflow pages.
     pub fn update_tuple_meta(&self, meta: TupleMeta, rid: RecordId) -> BustubxResult<()> {
         let (page, mut table_page) = self
             .buffer_pool
             .fetch_table_page(rid.page_id, self.schema.clone())?;
+        let (old_meta, tuple_bytes) = table_page.tuple_bytes(rid.slot_num as u16)?;
+        let ((tuple, overflow), _) =
+            TupleCodec::decode_with_overflow(tuple_bytes, self.schema.clone())?;
+        let freed_overflow = if meta.is_deleted && !old_meta.is_deleted {
+            // values moved out are NULL in the deleted tuple so nothing points to freed pages
+            table_page.update_tuple_bytes(&TupleCodec::encode(&tuple), rid.slot_num as u16)?;
+            overflow
+        } else {
+            vec![]
+        };
         table_page.update_tuple_meta(meta, rid.slot_num as u16)?;
 
+        let mut visibility_map = self.visibility_map.lock().unwrap();
         page.write()
             .unwrap()
             .set_data(page_bytes_to_array(&TablePageCodec::encode(&table_page)));
+        if meta.is_deleted {
+            visibility_map.insert(rid.page_id, false);
+        }
+        drop(visibility_map);
+        drop(page);
+        for pointer in freed_overflow.into_iter().flatten() {
+            self.free_overflow(pointer)?;
+        }
         Ok(())
     }
 
+    /// Whether no tuple of a page is deleted.
+    pub fn is_all_visible(&self, page_id: PageId) -> BustubxResult<bool> {
+        // hold the map while reading the page so a concurrent delete can't be missed
+        let mut visibility_map = self.visibility_map.lock().unwrap();
+        if let Some(all_visible) = visibility_map.get(&page_id) {
+            return Ok(*all_visible);
+        }
+        let (_, table_page) = self
+            .buffer_pool
+            .fetch_table_page(page_id, self.schema.clone())?;
+        let all_visible = table_page.header.num_deleted_tuples == 0;
+        visibility_map.insert(page_id, all_visible);
+        Ok(all_visible)
+    }
+
     pub fn full_tuple(&self, rid: RecordId) -> BustubxResult<(TupleMeta, Tuple)> {
         let (_, table_page) = self
             .buffer_pool
             .fetch_table_page(rid.page_id, self.schema.clone())?;
-        let result = table_page.tuple(rid.slot_num as u16)?;
-        Ok(result)
+        let (meta, tuple_bytes) = table_page.tuple_bytes(rid.slot_num as u16)?;
+        let ((mut tuple, overflow), _) =
+            TupleCodec::decode_with_overflow(tuple_bytes, self.schema.clone())?;
+        for (idx, pointer) in overflow.into_iter().enumerate() {
+            if let Some(pointer) = pointer {
+                let bytes = self.read_overflow(pointer)?;
+                let (value, _) =
+                    ScalarValueCodec::decode(&bytes, self.schema.columns[idx].data_type)?;
+                tuple.data[idx] = value;
+            }
+        }
+        Ok((meta, tuple))
     }
 
     pub fn tuple(&self, rid: RecordId) -> BustubxResult<Tuple> {
@@ -151,6 +232,109 @@ impl TableHeap {
         Ok(meta)
     }
 
+    /// Encodes a tuple for a table page, moving the largest variable-length values to
+    /// overflow pages while the tuple is larger than [`TUPLE_OVERFLOW_THRESHOLD`].
+    fn encode_tuple(&self, tuple: &Tuple) -> BustubxResult<Vec<u8>> {
+        let mut tuple_bytes = TupleCodec::encode(tuple);
+        if tuple_bytes.len() <= TUPLE_OVERFLOW_THRESHOLD {
+            return Ok(tuple_bytes);
+        }
+
+        let mut candidates = tuple
+            .data
+            .iter()
+            .enumerate()
+            .filter(|(idx, value)| {
+                !value.is_null()
+                    && matches!(
+                        self.schema.columns[*idx].data_type,
+                        DataType::Varchar(_) | DataType::Binary | DataType::Json
+                    )
+            })
+            .map(|(idx, value)| (idx, ScalarValueCodec::encode(value)))
+            .collect::<Vec<_>>();
+        // largest last
+        candidates.sort_by_key(|(_, bytes)| bytes.len());
+
+        let mut overflow = vec![None; tuple.data.len()];
+        while tuple_bytes.len() > TUPLE_OVERFLOW_THRESHOLD {
+            let Some((idx, value_bytes)) = candidates.pop() else {
+                break;
+            };
+            overflow[idx] = Some(self.write_overflow(&value_bytes)?);
+            tuple_bytes = TupleCodec::encode_with_overflow(tuple, &overflow);
+        }
+        Ok(tuple_bytes)
+    }
+
+    /// Writes bytes to a new chain of overflow pages.
+    fn write_overflow(&self, bytes: &[u8]) -> BustubxResult<OverflowPointer> {
+        let size = u32::try_from(bytes.len()).map_err(|_| {
+            BustubxError::Storage(format!("value of {} bytes is too large", bytes.len()))
+        })?;
+        // write from the last chunk so each page knows its next page id
+        let mut next_page_id = INVALID_PAGE_ID;
+        for chunk in bytes.rchunks(*OVERFLOW_PAGE_MAX_DATA_SIZE) {
+            let mut overflow_page = OverflowPage::new(chunk.to_vec());
+            overflow_page.header.next_page_id = next_page_id;
+            let page = self.buffer_pool.new_page()?;
+            next_page_id = page.read().unwrap().page_id;
+            page.write()
+                .unwrap()
+                .set_data(page_bytes_to_array(&OverflowPageCodec::encode(
+                    &overflow_page,
+                )));
+        }
+        Ok(OverflowPointer {
+            first_page_id: next_page_id,
+            size,
+        })
+    }
+
+    /// Reassembles the bytes stored in a chain of overflow pages.
+    fn read_overflow(&self, pointer: OverflowPointer) -> BustubxResult<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(pointer.size as usize);
+        let mut page_id = pointer.first_page_id;
+        while page_id != INVALID_PAGE_ID {
+            let page = self.buffer_pool.fetch_page(page_id)?;
+            let (overflow_page, _) = OverflowPageCodec::decode(page.read().unwrap().data())?;
+            bytes.extend(overflow_page.data);
+            page_id = overflow_page.header.next_page_id;
+        }
+        if bytes.len() != pointer.size as usize {
+            return Err(BustubxError::Storage(format!(
+                "overflow chain at page {} has {} bytes instead of {}",
+                pointer.first_page_id,
+                bytes.len(),
+                pointer.size
+            )));
+        }
+        Ok(bytes)
+    }
+
+    /// Frees the overflow chains an encoded tuple points to.
+    fn free_tuple_overflow(&self, tuple_bytes: &[u8]) -> BustubxResult<()> {
+        let ((_, overflow), _) =
+            TupleCodec::decode_with_overflow(tuple_bytes, self.schema.clone())?;
+        for pointer in overflow.into_iter().flatten() {
+            self.free_overflow(pointer)?;
+        }
+        Ok(())
+    }
+
+    /// Frees every page of an overflow chain.
+    fn free_overflow(&self, pointer: OverflowPointer) -> BustubxResult<()> {
+        let mut page_id = pointer.first_page_id;
+        while page_id != INVALID_PAGE_ID {
+            let page = self.buffer_pool.fetch_page(page_id)?;
+            let (overflow_page, _) = OverflowPageCodec::decode(page.read().unwrap().data())?;
+            drop(page);
+            self.buffer_pool.delete_page(page_id)?;
+            page_id = overflow_page.header.next_page_id;
+        }
+        Ok(())
+    }
+
     pub fn get_first_rid(&self) -> BustubxResult<Option<RecordId>> {
         let first_page_id = self.first_page_id.load(Ordering::SeqCst);
         let (_, table_page) = self
@@ -210,7 +394,39 @@ impl TableIterator {
         }
     }
 
+    /// Whether the iterator went past `rid`, i.e. `rid` isn't found after the cursor.
+    pub fn is_passed(&self, rid: RecordId) -> BustubxResult<bool> {
+        if !self.started {
+            return Ok(false);
+        }
+        if rid.page_id == self.cursor.page_id {
+            return Ok(rid.slot_num <= self.cursor.slot_num);
+        }
+        let mut page_id = self.cursor.page_id;
+        while page_id != INVALID_PAGE_ID {
+            let (_, table_page) = self
+                .heap
+                .buffer_pool
+                .fetch_table_page(page_id, self.heap.schema.clone())?;
+            page_id = table_page.header.next_page_id;
+            if page_id == rid.page_id {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns the next tuple which is not deleted.
     pub fn next(&mut self) -> BustubxResult<Option<(RecordId, Tuple)>> {
+        while let Some((rid, meta, tuple)) = self.next_with_meta()? {
+            if !meta.is_deleted {
+                return Ok(Some((rid, tuple)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_with_meta(&mut self) -> BustubxResult<Option<(RecordId, TupleMeta, Tuple)>> {
         if self.ended {
             return Ok(None);
         }
@@ -225,9 +441,9 @@ impl TableIterator {
                         self.cursor = next_rid;
                         Ok(self
                             .heap
-                            .tuple(self.cursor)
+                            .full_tuple(self.cursor)
                             .ok()
-                            .map(|tuple| (self.cursor, tuple)))
+                            .map(|(meta, tuple)| (self.cursor, meta, tuple)))
                     } else {
                         Ok(None)
                     }
@@ -240,9 +456,9 @@ impl TableIterator {
                             self.cursor = next_rid;
                             Ok(self
                                 .heap
-                                .tuple(self.cursor)
+                                .full_tuple(self.cursor)
                                 .ok()
-                                .map(|tuple| (self.cursor, tuple)))
+                                .map(|(meta, tuple)| (self.cursor, meta, tuple)))
                         }
                     } else {
                         Ok(None)
@@ -253,9 +469,9 @@ impl TableIterator {
                         self.cursor = next_rid;
                         Ok(self
                             .heap
-                            .tuple(self.cursor)
+                            .full_tuple(self.cursor)
                             .ok()
-                            .map(|tuple| (self.cursor, tuple)))
+                            .map(|(meta, tuple)| (self.cursor, meta, tuple)))
                     } else {
                         Ok(None)
                     }
@@ -268,18 +484,18 @@ impl TableIterator {
                     self.cursor = rid;
                     Ok(self
                         .heap
-                        .tuple(self.cursor)
+                        .full_tuple(self.cursor)
                         .ok()
-                        .map(|tuple| (self.cursor, tuple)))
+                        .map(|(meta, tuple)| (self.cursor, meta, tuple)))
                 }
                 Bound::Excluded(rid) => {
                     if let Some(next_rid) = self.heap.get_next_rid(rid)? {
                         self.cursor = next_rid;
                         Ok(self
                             .heap
-                            .tuple(self.cursor)
+                            .full_tuple(self.cursor)
                             .ok()
-                            .map(|tuple| (self.cursor, tuple)))
+                            .map(|(meta, tuple)| (self.cursor, meta, tuple)))
                     } else {
                         self.ended = true;
                         Ok(None)
@@ -290,9 +506,9 @@ impl TableIterator {
                         self.cursor = first_rid;
                         Ok(self
                             .heap
-                            .tuple(self.cursor)
+                            .full_tuple(self.cursor)
                             .ok()
-                            .map(|tuple| (self.cursor, tuple)))
+                            .map(|(meta, tuple)| (self.cursor, meta, tuple)))
                     } else {
                         self.ended = true;
                         Ok(None)
@@ -310,6 +526,7 @@ mod tests {
     use tempfile::TempDir;
 
     use crate::catalog::{Column, DataType, Schema};
+    use crate::common::ScalarValue;
     use crate::storage::{TableIterator, EMPTY_TUPLE_META};
     use crate::{
         buffer::BufferPoolManager,
@@ -348,6 +565,7 @@ mod tests {
             )
             .unwrap();
 
+        assert!(table_heap.is_all_visible(rid2.page_id).unwrap());
         let mut meta = table_heap.tuple_meta(rid2).unwrap();
         meta.insert_txn_id = 1;
         meta.delete_txn_id = 2;
@@ -358,6 +576,47 @@ mod tests {
         assert_eq!(meta.insert_txn_id, 1);
         assert_eq!(meta.delete_txn_id, 2);
         assert!(meta.is_deleted);
+        assert!(!table_heap.is_all_visible(rid2.page_id).unwrap());
+    }
+
+    #[test]
+    pub fn test_table_heap_update_tuple() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().join("test.db");
+
+        let schema = Arc::new(Schema::new(vec![
+            Column::new("a", DataType::Int32, false),
+            Column::new("b", DataType::Varchar(None), false),
+        ]));
+        let disk_manager = DiskManager::try_new(temp_path).unwrap();
+        let buffer_pool = Arc::new(BufferPoolManager::new(1000, Arc::new(disk_manager)));
+        let table_heap = TableHeap::try_new(schema.clone(), buffer_pool).unwrap();
+
+        let tuple =
+            |a: i32, len: usize| Tuple::new(schema.clone(), vec![a.into(), "x".repeat(len).into()]);
+        let rid1 = table_heap
+            .insert_tuple(&EMPTY_TUPLE_META, &tuple(1, 100))
+            .unwrap();
+        // fill the first page
+        let mut a = 2;
+        while table_heap
+            .insert_tuple(&EMPTY_TUPLE_META, &tuple(a, 100))
+            .unwrap()
+            .page_id
+            == rid1.page_id
+        {
+            a += 1;
+        }
+
+        // same size tuple is updated in place
+        assert_eq!(table_heap.update_tuple(rid1, tuple(0, 100)).unwrap(), rid1);
+        assert_eq!(table_heap.tuple(rid1).unwrap(), tuple(0, 100));
+
+        // grown tuple no longer fits its page and moves
+        let new_rid = table_heap.update_tuple(rid1, tuple(0, 1000)).unwrap();
+        assert_ne!(new_rid.page_id, rid1.page_id);
+        assert_eq!(table_heap.tuple(new_rid).unwrap(), tuple(0, 1000));
+        assert!(table_heap.tuple_meta(rid1).unwrap().is_deleted);
     }
 
     #[test]
@@ -484,4 +743,60 @@ mod tests {
 
         assert!(iterator.next().unwrap().is_none());
     }
+
+    #[test]
+    pub fn test_table_heap_overflow_tuple() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path().join("test.db");
+
+        let schema = Arc::new(Schema::new(vec![
+            Column::new("a", DataType::Int32, false),
+            Column::new("b", DataType::Varchar(None), true),
+            Column::new("c", DataType::Binary, true),
+        ]));
+        let disk_manager = DiskManager::try_new(temp_path).unwrap();
+        let buffer_pool = Arc::new(BufferPoolManager::new(1000, Arc::new(disk_manager)));
+        let table_heap = TableHeap::try_new(schema.clone(), buffer_pool.clone()).unwrap();
+
+        let long_text = "abcdefghij".repeat(2000);
+        let long_bytes = (0..20000u32).map(|i| i as u8).collect::<Vec<u8>>();
+        let tuple = Tuple::new(
+            schema.clone(),
+            vec![
+                1i32.into(),
+                long_text.clone().into(),
+                long_bytes.clone().into(),
+            ],
+        );
+        let rid1 = table_heap.insert_tuple(&EMPTY_TUPLE_META, &tuple).unwrap();
+        let rid2 = table_heap.insert_tuple(&EMPTY_TUPLE_META, &tuple).unwrap();
+        // values moved out so both tuples fit in the first page
+        assert_eq!(rid1.page_id, rid2.page_id);
+        assert_eq!(table_heap.tuple(rid1).unwrap(), tuple);
+        assert_eq!(table_heap.tuple(rid2).unwrap(), tuple);
+
+        let small_tuple = Tuple::new(
+            schema.clone(),
+            vec![
+                2i32.into(),
+                "x".to_string().into(),
+                ScalarValue::Binary(None),
+            ],
+        );
+        assert_eq!(
+            table_heap.update_tuple(rid1, small_tuple.clone()).unwrap(),
+            rid1
+        );
+        assert_eq!(table_heap.tuple(rid1).unwrap(), small_tuple);
+        assert_eq!(table_heap.tuple(rid2).unwrap(), tuple);
+
+        // freed overflow pages are reused
+        let file_len = buffer_pool.disk_manager.db_file_len().unwrap();
+        let mut meta = table_heap.tuple_meta(rid2).unwrap();
+        meta.is_deleted = true;
+        table_heap.update_tuple_meta(meta, rid2).unwrap();
+        let rid3 = table_heap.insert_tuple(&EMPTY_TUPLE_META, &tuple).unwrap();
+        assert_eq!(table_heap.tuple(rid3).unwrap(), tuple);
+        assert_eq!(buffer_pool.disk_manager.db_file_len().unwrap(), file_len);
+    }
 }
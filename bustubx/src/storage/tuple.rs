@@ -65,10 +65,12 @@ impl Tuple {
     }
 
     pub fn value(&self, index: usize) -> BustubxResult<&ScalarValue> {
-        self.data.get(index).ok_or(BustubxError::Internal(format!(
-            "Not found column data at {} in tuple: {:?}",
-            index, self
-        )))
+        self.data.get(index).ok_or_else(|| {
+            BustubxError::Internal(format!(
+                "Not found column data at {} in tuple: {:?}",
+                index, self
+            ))
+        })
     }
     pub fn value_by_name(
         &self,
@@ -81,10 +83,15 @@ impl Tuple {
 }
 
 impl PartialOrd for Tuple {
+    /// Compares column by column in the sort order of the columns, a shorter tuple
+    /// compares equal to the tuples it is a prefix of so index scans can bound a key prefix.
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let column_count = self.schema.column_count();
+        let column_count = self.data.len().min(other.data.len());
         for idx in 0..column_count {
-            let order = self.value(idx).ok()?.partial_cmp(other.value(idx).ok()?)?;
+            let mut order = self.value(idx).ok()?.partial_cmp(other.value(idx).ok()?)?;
+            if self.schema.columns[idx].descending {
+                order = order.reverse();
+            }
             if order == Ordering::Equal {
                 continue;
             } else {
@@ -117,5 +124,18 @@ mod tests {
         assert_eq!(tuple1.partial_cmp(&tuple3).unwrap(), Ordering::Less);
         assert_eq!(tuple1.partial_cmp(&tuple4).unwrap(), Ordering::Less);
         assert_eq!(tuple1.partial_cmp(&tuple5).unwrap(), Ordering::Greater);
+
+        let prefix_schema = Arc::new(schema.project(&[0]).unwrap());
+        let prefix = super::Tuple::new(prefix_schema, vec![1i8.into()]);
+        assert_eq!(tuple1.partial_cmp(&prefix).unwrap(), Ordering::Equal);
+        assert_eq!(tuple4.partial_cmp(&prefix).unwrap(), Ordering::Greater);
+
+        let desc_schema = Arc::new(Schema::new(vec![
+            Column::new("a", DataType::Int8, false),
+            Column::new("b", DataType::Int16, false).with_descending(true),
+        ]));
+        let tuple6 = super::Tuple::new(desc_schema.clone(), vec![1i8.into(), 2i16.into()]);
+        let tuple7 = super::Tuple::new(desc_schema.clone(), vec![1i8.into(), 3i16.into()]);
+        assert_eq!(tuple6.partial_cmp(&tuple7).unwrap(), Ordering::Greater);
     }
 }
@@ -38,7 +38,7 @@ impl sqllogictest::DB for BustubxDB {
     fn run(&mut self, sql: &str) -> Result<DBOutput<Self::ColumnType>, Self::Error> {
         let is_query_sql = {
             let lower_sql = sql.trim_start().to_ascii_lowercase();
-            lower_sql.starts_with("select")
+            lower_sql.starts_with("select") || lower_sql.starts_with("with")
         };
         let tuples = self.db.run(sql)?;
         if tuples.is_empty() {
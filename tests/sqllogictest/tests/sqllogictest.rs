@@ -9,6 +9,7 @@ fn sqllogictest() {
     for file in test_files {
         let db = BustubxDB::new();
         let mut tester = sqllogictest::Runner::new(db);
+        tester.enable_testdir();
         println!(
             "======== start to run file {} ========",
             file.to_str().unwrap()